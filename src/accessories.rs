@@ -0,0 +1,346 @@
+use bevy::{prelude::*, transform::TransformSystem};
+
+use crate::{decals, profile::Profile, replay::Playback, AppState, Atlas, Player};
+
+/// Where on the bird an accessory is worn, one accessory to a slot
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Slot {
+    Hat,
+    Scarf,
+}
+
+/// A block of color an accessory is drawn with, placed from where its slot
+/// is anchored on the bird
+pub struct Part {
+    pub offset: Vec2,
+    pub size: Vec2,
+    pub color: Color,
+}
+
+pub const fn part(x: f32, y: f32, width: f32, height: f32, color: Color) -> Part {
+    Part {
+        offset: Vec2::new(x, y),
+        size: Vec2::new(width, height),
+        color,
+    }
+}
+
+pub enum Unlock {
+    Free,
+    /// Earning the award with this title in any run
+    Award(&'static str),
+    /// Claiming the career milestone with this id
+    Milestone(&'static str),
+}
+
+impl Unlock {
+    pub fn unlocked(&self, profile: &Profile) -> bool {
+        match self {
+            Unlock::Free => true,
+            Unlock::Award(title) => profile.achievements.contains(*title),
+            Unlock::Milestone(id) => profile.career.claimed.contains(*id),
+        }
+    }
+}
+
+struct Accessory {
+    /// What the profile saves it as, so it can't change once released
+    id: &'static str,
+    name: &'static str,
+    slot: Slot,
+    parts: &'static [Part],
+    unlock: Unlock,
+}
+
+impl Accessory {
+    fn unlocked(&self, profile: &Profile) -> bool {
+        self.unlock.unlocked(profile)
+    }
+}
+
+pub const INK: Color = Color::rgb(0.1, 0.1, 0.12);
+pub const RED: Color = Color::rgb(0.85, 0.15, 0.15);
+pub const GOLD: Color = Color::rgb(1., 0.8, 0.2);
+const BROWN: Color = Color::rgb(0.5, 0.3, 0.15);
+
+const ACCESSORIES: &[Accessory] = &[
+    Accessory {
+        id: "party-hat",
+        name: "Party hat",
+        slot: Slot::Hat,
+        parts: &[
+            part(0., 1., 6., 2., Color::rgb(0.3, 0.6, 1.)),
+            part(0., 3., 4., 2., Color::rgb(1., 0.4, 0.7)),
+            part(0., 5., 2., 2., Color::rgb(0.3, 0.6, 1.)),
+            part(0., 6.5, 2., 1., GOLD),
+        ],
+        unlock: Unlock::Free,
+    },
+    Accessory {
+        id: "top-hat",
+        name: "Top hat",
+        slot: Slot::Hat,
+        parts: &[
+            part(0., 0.5, 10., 1., INK),
+            part(0., 1.5, 6., 1., RED),
+            part(0., 4., 6., 4., INK),
+        ],
+        unlock: Unlock::Award("Closest call"),
+    },
+    Accessory {
+        id: "crown",
+        name: "Crown",
+        slot: Slot::Hat,
+        parts: &[
+            part(0., 1., 8., 2., GOLD),
+            part(-3., 2.5, 2., 1., GOLD),
+            part(0., 2.5, 2., 1., GOLD),
+            part(3., 2.5, 2., 1., GOLD),
+            part(0., 1., 2., 1., RED),
+        ],
+        unlock: Unlock::Award("Longest glide"),
+    },
+    Accessory {
+        id: "aviator-cap",
+        name: "Aviator cap",
+        slot: Slot::Hat,
+        parts: &[
+            part(0., 2., 8., 4., BROWN),
+            part(-4., 0., 2., 3., BROWN),
+            part(1., 2., 6., 2., Color::rgb(0.6, 0.85, 1.)),
+            part(1., 2., 2., 1., INK),
+        ],
+        unlock: Unlock::Milestone("pipes-10k"),
+    },
+    Accessory {
+        id: "halo",
+        name: "Halo",
+        slot: Slot::Hat,
+        parts: &[part(0., 4., 8., 1., GOLD), part(0., 5., 4., 1., GOLD)],
+        unlock: Unlock::Milestone("perfects-100"),
+    },
+    Accessory {
+        id: "red-scarf",
+        name: "Red scarf",
+        slot: Slot::Scarf,
+        parts: &[part(0., 0., 6., 2., RED), part(-4., -1., 3., 2., RED)],
+        unlock: Unlock::Award("Most flaps"),
+    },
+    Accessory {
+        id: "gold-scarf",
+        name: "Gold scarf",
+        slot: Slot::Scarf,
+        parts: &[part(0., 0., 6., 2., GOLD), part(-4., -1., 3., 2., GOLD)],
+        unlock: Unlock::Milestone("coins-1k"),
+    },
+    Accessory {
+        id: "racing-scarf",
+        name: "Racing scarf",
+        slot: Slot::Scarf,
+        parts: &[
+            part(-2., 0., 2., 2., Color::WHITE),
+            part(0., 0., 2., 2., INK),
+            part(2., 0., 2., 2., Color::WHITE),
+            part(-4., -1., 3., 2., INK),
+        ],
+        unlock: Unlock::Award("Fastest 10 pipes"),
+    },
+];
+
+fn find(id: Option<&str>) -> Option<&'static Accessory> {
+    ACCESSORIES
+        .iter()
+        .find(|accessory| Some(accessory.id) == id)
+}
+
+/// Ids and names of the accessories for `slot` that have been unlocked
+pub fn available(slot: Slot, profile: &Profile) -> Vec<(&'static str, &'static str)> {
+    ACCESSORIES
+        .iter()
+        .filter(|accessory| accessory.slot == slot && accessory.unlocked(profile))
+        .map(|accessory| (accessory.id, accessory.name))
+        .collect()
+}
+
+/// Something the bird has on, anchored to it every frame
+#[derive(Component)]
+struct Worn(Slot);
+
+#[derive(Component)]
+struct AccessoriesLabel;
+
+pub struct AccessoriesPlugin;
+
+impl Plugin for AccessoriesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            // After everything in Update, which might have laid out the world
+            // again and made a new bird. That also puts it after the bird has
+            // picked its frame
+            PostUpdate,
+            (dress_bird, anchor_accessories)
+                .chain()
+                .before(TransformSystem::TransformPropagate),
+        )
+        .add_systems(OnEnter(AppState::MainMenu), spawn_label)
+        .add_systems(OnExit(AppState::MainMenu), despawn_label)
+        .add_systems(
+            Update,
+            (
+                cycle_accessories.run_if(not(resource_exists::<Playback>)),
+                draw_label.run_if(resource_changed::<Profile>),
+            )
+                .chain()
+                .run_if(in_state(AppState::MainMenu)),
+        );
+    }
+}
+
+/// Dresses the bird `parent` is building in the accessory with `id`, if
+/// there is one and it's been unlocked
+pub fn wear(parent: &mut ChildBuilder, id: Option<&str>, profile: &Profile) {
+    let Some(accessory) = find(id) else {
+        return;
+    };
+    // Taken off again if it's been locked since, like by a new profile
+    if !accessory.unlocked(profile) {
+        return;
+    }
+
+    // Children of the bird, so they tilt right along with it
+    parent
+        .spawn((Worn(accessory.slot), SpatialBundle::default()))
+        .with_children(|parent| {
+            for part in accessory.parts {
+                parent.spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color: part.color,
+                        custom_size: Some(part.size),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(part.offset.extend(0.1)),
+                    ..default()
+                });
+            }
+        });
+}
+
+// Put on whatever the profile says, every time the bird is made anew
+fn dress_bird(
+    mut commands: Commands,
+    profile: Res<Profile>,
+    player: Query<Entity, With<Player>>,
+    added: Query<(), Added<Player>>,
+    worn: Query<(Entity, &Parent), With<Worn>>,
+) {
+    if !profile.is_changed() && added.is_empty() {
+        return;
+    }
+
+    // Only what the player's bird has on, any other bird is dressed by
+    // whatever made it
+    for (entity, bird) in &worn {
+        if player.contains(bird.get()) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+
+    for player in &player {
+        commands.entity(player).with_children(|parent| {
+            wear(parent, profile.hat.as_deref(), &profile);
+            wear(parent, profile.scarf.as_deref(), &profile);
+        });
+    }
+}
+
+fn anchor_accessories(
+    birds: Query<(&TextureAtlas, &Children)>,
+    mut worn: Query<(&Worn, &mut Transform, &mut Visibility)>,
+) {
+    for (atlas, children) in &birds {
+        let frame = Atlas::from_index(atlas.index);
+        let mut iter = worn.iter_many_mut(children);
+        while let Some((Worn(slot), mut transform, mut visibility)) = iter.fetch_next() {
+            match frame.and_then(|frame| frame.anchor(*slot)) {
+                Some(anchor) => {
+                    transform.translation = anchor.extend(0.1);
+                    *visibility = Visibility::Inherited;
+                }
+                None => *visibility = Visibility::Hidden,
+            }
+        }
+    }
+}
+
+/// The next unlocked accessory for `slot` after `current`, with nothing
+/// worn coming after the last one
+fn next_accessory(slot: Slot, current: Option<&str>, profile: &Profile) -> Option<String> {
+    let options = ACCESSORIES
+        .iter()
+        .filter(|accessory| accessory.slot == slot && accessory.unlocked(profile))
+        .map(|accessory| Some(accessory.id))
+        .chain([None])
+        .collect::<Vec<_>>();
+    let next = options
+        .iter()
+        .position(|option| *option == current)
+        .map_or(0, |i| (i + 1) % options.len());
+    options[next].map(str::to_string)
+}
+
+fn cycle_accessories(keys: Res<ButtonInput<KeyCode>>, mut profile: ResMut<Profile>) {
+    if keys.just_pressed(KeyCode::KeyA) {
+        profile.hat = next_accessory(Slot::Hat, profile.hat.as_deref(), &profile);
+    }
+    if keys.just_pressed(KeyCode::KeyS) {
+        profile.scarf = next_accessory(Slot::Scarf, profile.scarf.as_deref(), &profile);
+    }
+}
+
+fn label(profile: &Profile) -> String {
+    let name = |id: Option<&str>| find(id).map_or("none", |accessory| accessory.name);
+    let unlocked = ACCESSORIES
+        .iter()
+        .filter(|accessory| accessory.unlocked(profile))
+        .count();
+    format!(
+        "A hat: {}\nS scarf: {}\n{unlocked}/{} unlocked\n{}",
+        name(profile.hat.as_deref()),
+        name(profile.scarf.as_deref()),
+        ACCESSORIES.len(),
+        decals::label(profile)
+    )
+}
+
+fn spawn_label(mut commands: Commands, profile: Res<Profile>) {
+    commands.spawn((
+        AccessoriesLabel,
+        TextBundle::from_section(
+            label(&profile),
+            TextStyle {
+                font_size: 10.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_text_justify(JustifyText::Right)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.),
+            right: Val::Px(8.),
+            ..default()
+        }),
+    ));
+}
+
+fn despawn_label(mut commands: Commands, query: Query<Entity, With<AccessoriesLabel>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn draw_label(profile: Res<Profile>, mut query: Query<&mut Text, With<AccessoriesLabel>>) {
+    for mut text in &mut query {
+        text.sections[0].value = label(&profile);
+    }
+}
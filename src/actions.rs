@@ -0,0 +1,313 @@
+use bevy::{ecs::system::SystemParam, input::InputSystem, prelude::*, utils::HashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    prompts::Device,
+    touch::{track_touches, LastTouch},
+};
+
+/// Something the player wants done, whatever they pressed to ask for it
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    /// Flaps, and starts a run from the menu and goes back to it afterwards
+    Flap,
+    /// Holds the run still
+    Pause,
+    /// Throws the run away for a new one, whether it's over yet or not
+    Restart,
+}
+
+impl Action {
+    pub const ALL: [Action; 3] = [Action::Flap, Action::Pause, Action::Restart];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Flap => "Flap",
+            Action::Pause => "Pause",
+            Action::Restart => "Restart",
+        }
+    }
+}
+
+/// Something on one of the player's devices that can be pressed
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Button {
+    Key(KeyCode),
+    Mouse(MouseButton),
+    Gamepad(GamepadButtonType),
+}
+
+impl Button {
+    pub fn device(self) -> Device {
+        match self {
+            Button::Key(_) => Device::Keyboard,
+            Button::Mouse(_) => Device::Mouse,
+            Button::Gamepad(_) => Device::Gamepad,
+        }
+    }
+
+    fn just_pressed(self, input: &RawInput) -> bool {
+        match self {
+            Button::Key(key) => input.keys.just_pressed(key),
+            Button::Mouse(button) => {
+                input.mouse.just_pressed(button) && !input.last_touch.emulated_click(&input.time)
+            }
+            Button::Gamepad(button) => input
+                .gamepad
+                .get_just_pressed()
+                .any(|pressed| pressed.button_type == button),
+        }
+    }
+
+    /// What it's called when it's shown to the player, as short as it'll go
+    pub fn name(self) -> String {
+        match self {
+            Button::Key(key) => {
+                let name = format!("{key:?}");
+                ["Key", "Digit", "Arrow"]
+                    .into_iter()
+                    .find_map(|prefix| name.strip_prefix(prefix))
+                    .map_or(name.clone(), str::to_string)
+            }
+            Button::Mouse(MouseButton::Left) => "Click".to_string(),
+            Button::Mouse(button) => format!("{button:?} click"),
+            // Laid out like most pads people have, whatever they actually say
+            Button::Gamepad(button) => match button {
+                GamepadButtonType::South => "A".to_string(),
+                GamepadButtonType::East => "B".to_string(),
+                GamepadButtonType::West => "X".to_string(),
+                GamepadButtonType::North => "Y".to_string(),
+                GamepadButtonType::LeftTrigger => "LB".to_string(),
+                GamepadButtonType::RightTrigger => "RB".to_string(),
+                GamepadButtonType::LeftTrigger2 => "LT".to_string(),
+                GamepadButtonType::RightTrigger2 => "RT".to_string(),
+                button => format!("{button:?}"),
+            },
+        }
+    }
+}
+
+/// Everything on every device that asks for an action
+#[derive(Serialize, Deserialize, Clone)]
+struct Binding {
+    action: Action,
+    buttons: Vec<Button>,
+    /// Whether touching the screen anywhere asks for it too
+    #[serde(default)]
+    touch: bool,
+}
+
+impl Binding {
+    fn pressed(&self, input: &RawInput) -> bool {
+        self.buttons.iter().any(|button| button.just_pressed(input))
+            || (self.touch && input.touches.any_just_pressed())
+    }
+}
+
+/// What each action is bound to, which the player can change
+#[derive(Resource, Serialize, Deserialize, Clone)]
+pub struct ActionMap(Vec<Binding>);
+
+impl Default for ActionMap {
+    // Space and up flap as well as the mouse, for anyone who'd rather keep
+    // their hands on the keyboard. A tap does it too, for phones and browsers
+    fn default() -> Self {
+        Self(vec![
+            Binding {
+                action: Action::Flap,
+                buttons: vec![
+                    Button::Key(KeyCode::Space),
+                    Button::Key(KeyCode::ArrowUp),
+                    Button::Mouse(MouseButton::Left),
+                    Button::Gamepad(GamepadButtonType::South),
+                ],
+                touch: true,
+            },
+            Binding {
+                action: Action::Pause,
+                buttons: vec![
+                    Button::Key(KeyCode::Escape),
+                    Button::Gamepad(GamepadButtonType::Start),
+                ],
+                touch: false,
+            },
+            Binding {
+                action: Action::Restart,
+                buttons: vec![Button::Key(KeyCode::KeyR)],
+                touch: false,
+            },
+        ])
+    }
+}
+
+impl ActionMap {
+    pub fn buttons(&self, action: Action) -> &[Button] {
+        self.0
+            .iter()
+            .find(|binding| binding.action == action)
+            .map_or(&[], |binding| &binding.buttons)
+    }
+
+    /// The first thing `action` is bound to on `device`, or on anything if
+    /// there's nothing on that one
+    pub fn first(&self, action: Action, device: Device) -> Option<Button> {
+        let buttons = self.buttons(action);
+        buttons
+            .iter()
+            .find(|button| button.device() == device)
+            .or(buttons.first())
+            .copied()
+    }
+
+    pub fn touch(&self, action: Action) -> bool {
+        self.0
+            .iter()
+            .any(|binding| binding.action == action && binding.touch)
+    }
+
+    /// Binds `button` to `action` in place of whatever was bound to it on the
+    /// same device. A button only ever does one thing, so it's taken off
+    /// whatever it did before
+    pub fn bind(&mut self, action: Action, button: Button) {
+        for binding in &mut self.0 {
+            binding.buttons.retain(|bound| *bound != button);
+        }
+        if let Some(binding) = self.binding_mut(action) {
+            binding
+                .buttons
+                .retain(|bound| bound.device() != button.device());
+            binding.buttons.push(button);
+        }
+    }
+
+    /// Puts `action` back to what it's bound to out of the box
+    pub fn reset(&mut self, action: Action) {
+        let Some(default) = ActionMap::default()
+            .0
+            .into_iter()
+            .find(|binding| binding.action == action)
+        else {
+            return;
+        };
+        for binding in &mut self.0 {
+            binding
+                .buttons
+                .retain(|bound| !default.buttons.contains(bound));
+        }
+        if let Some(binding) = self.binding_mut(action) {
+            *binding = default;
+        }
+    }
+
+    fn binding_mut(&mut self, action: Action) -> Option<&mut Binding> {
+        self.0.iter_mut().find(|binding| binding.action == action)
+    }
+
+    // Actions added since the controls were saved start out bound the way
+    // they are out of the box
+    pub fn fill_in(mut self) -> Self {
+        for default in ActionMap::default().0 {
+            if !self
+                .0
+                .iter()
+                .any(|binding| binding.action == default.action)
+            {
+                self.0.push(default);
+            }
+        }
+        self
+    }
+}
+
+/// The actions asked for this frame. Whatever acts on one takes it, so a
+/// press that changes the state isn't seen again by the next one
+#[derive(Resource, Default)]
+pub struct Actions(HashSet<Action>);
+
+impl Actions {
+    /// Whether `action` was asked for this frame, which nothing else gets to
+    /// see once it's been taken
+    pub fn take(&mut self, action: Action) -> bool {
+        self.0.remove(&action)
+    }
+
+    /// Asks for `action` as if it had been pressed this frame
+    #[cfg(test)]
+    pub fn press(&mut self, action: Action) {
+        self.0.insert(action);
+    }
+}
+
+/// How long it's been since anything was pressed on any device, in real
+/// seconds
+#[derive(Resource, Default)]
+pub struct Idle(pub f32);
+
+/// Every device, for reading what's been pressed on any of them
+#[derive(SystemParam)]
+pub struct RawInput<'w> {
+    mouse: Res<'w, ButtonInput<MouseButton>>,
+    keys: Res<'w, ButtonInput<KeyCode>>,
+    gamepad: Res<'w, ButtonInput<GamepadButton>>,
+    touches: Res<'w, Touches>,
+    last_touch: Res<'w, LastTouch>,
+    time: Res<'w, Time<Real>>,
+}
+
+impl RawInput<'_> {
+    /// Whatever was pressed this frame, if anything was
+    pub fn just_pressed(&self) -> Option<Button> {
+        self.keys
+            .get_just_pressed()
+            .next()
+            .map(|&key| Button::Key(key))
+            .or_else(|| {
+                self.mouse
+                    .get_just_pressed()
+                    .next()
+                    .map(|&button| Button::Mouse(button))
+                    .filter(|button| button.just_pressed(self))
+            })
+            .or_else(|| {
+                self.gamepad
+                    .get_just_pressed()
+                    .next()
+                    .map(|button| Button::Gamepad(button.button_type))
+            })
+    }
+}
+
+pub struct ActionsPlugin;
+
+impl Plugin for ActionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Actions>()
+            .init_resource::<Idle>()
+            .add_systems(
+                PreUpdate,
+                read_actions.after(InputSystem).after(track_touches),
+            );
+    }
+}
+
+// Anything asked for last frame and not taken is dropped. Anything pressed at
+// all counts as the player being there, bound to an action or not
+fn read_actions(
+    mut actions: ResMut<Actions>,
+    mut idle: ResMut<Idle>,
+    map: Res<ActionMap>,
+    input: RawInput,
+) {
+    actions.0.clear();
+    for binding in &map.0 {
+        if binding.pressed(&input) {
+            actions.0.insert(binding.action);
+        }
+    }
+
+    if actions.0.is_empty() && input.just_pressed().is_none() && !input.touches.any_just_pressed() {
+        idle.0 += input.time.delta_seconds();
+    } else {
+        idle.0 = 0.;
+    }
+}
@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+
+use crate::{actions::Idle, pause::PauseState, save::FlushSaves, AppState};
+
+// How long the game waits on a player who's walked off before going home
+const AFK_TIMEOUT: f32 = 120.;
+// How long the screen takes to go black, and again to come back
+const FADE_DURATION: f32 = 0.6;
+
+/// Fading out to the main menu after nothing's been pressed for a while
+#[derive(Component)]
+struct AfkFade {
+    timer: Timer,
+    /// Whether it's past the black and fading back in on the menu
+    home: bool,
+}
+
+pub struct AfkPlugin;
+
+impl Plugin for AfkPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<FlushSaves>()
+            .add_systems(
+                Update,
+                go_home.run_if(in_state(AppState::GameOver).or_else(in_state(PauseState::Paused))),
+            )
+            .add_systems(Update, fade);
+    }
+}
+
+fn go_home(
+    mut commands: Commands,
+    mut flush: EventWriter<FlushSaves>,
+    idle: Res<Idle>,
+    fades: Query<(), With<AfkFade>>,
+) {
+    if idle.0 < AFK_TIMEOUT || !fades.is_empty() {
+        return;
+    }
+
+    // Nothing's left waiting to be written if the game's closed from the menu
+    flush.send(FlushSaves);
+    commands.spawn((
+        AfkFade {
+            timer: Timer::from_seconds(FADE_DURATION, TimerMode::Once),
+            home: false,
+        },
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            background_color: Color::NONE.into(),
+            z_index: ZIndex::Global(i32::MAX),
+            ..default()
+        },
+    ));
+}
+
+// Goes by real time since a paused game holds the virtual clock still
+fn fade(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut AfkFade, &mut BackgroundColor)>,
+    mut state: ResMut<NextState<AppState>>,
+    mut idle: ResMut<Idle>,
+    time: Res<Time<Real>>,
+) {
+    for (entity, mut afk, mut color) in &mut query {
+        let finished = afk.timer.tick(time.delta()).finished();
+        let fraction = afk.timer.fraction();
+        color.0 = Color::rgba(0., 0., 0., if afk.home { 1. - fraction } else { fraction });
+
+        if !finished {
+            continue;
+        }
+        if afk.home {
+            commands.entity(entity).despawn_recursive();
+        } else {
+            // Leaving the run lets go of the pause along with it
+            state.set(AppState::MainMenu);
+            idle.0 = 0.;
+            afk.home = true;
+            afk.timer.reset();
+        }
+    }
+}
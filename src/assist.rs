@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+
+use crate::{
+    difficulty::Difficulty,
+    fall,
+    main_menu::MenuRow,
+    physics::ActivePhysics,
+    replay::Playback,
+    scroll::ScrollEase,
+    tutorials::{AddTutorial, Tutorial, BETWEEN_PIPES},
+    AppState, Atlas, BirdState, Player, RunModifiers, Velocity, ASSIST, SIM_HZ,
+};
+
+// Seconds ahead the bird's path is worked out for
+const LOOKAHEAD: f32 = 1.;
+
+#[derive(Component)]
+struct AssistLabel;
+
+pub struct AssistPlugin;
+
+impl Plugin for AssistPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_tutorial(Tutorial {
+            id: ASSIST,
+            title: "Assist",
+            text: "The bird's path is drawn out ahead of it. Assisted runs are only ranked with each other",
+            art: Atlas::Bird1,
+            example: BETWEEN_PIPES,
+        })
+        .add_systems(
+            OnEnter(AppState::MainMenu),
+            spawn_label.run_if(not(resource_exists::<Playback>)),
+        )
+        .add_systems(
+            Update,
+            (
+                toggle_assist,
+                draw_label.run_if(resource_changed::<RunModifiers>),
+            )
+                .chain()
+                .run_if(in_state(AppState::MainMenu).and_then(not(resource_exists::<Playback>))),
+        )
+        .add_systems(OnExit(AppState::MainMenu), despawn_label)
+        .add_systems(
+            Update,
+            draw_path.run_if(in_state(AppState::Playing).and_then(assisted)),
+        );
+    }
+}
+
+fn assisted(modifiers: Res<RunModifiers>) -> bool {
+    modifiers.assist
+}
+
+// It's one of the run's modifiers, so an assisted run is only ever ranked
+// against other assisted runs
+fn toggle_assist(keys: Res<ButtonInput<KeyCode>>, mut modifiers: ResMut<RunModifiers>) {
+    if keys.just_pressed(KeyCode::KeyG) {
+        modifiers.assist = !modifiers.assist;
+    }
+}
+
+fn spawn_label(mut commands: Commands) {
+    commands.spawn((
+        AssistLabel,
+        MenuRow::Assist,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 14.,
+                color: Color::WHITE,
+                ..default()
+            },
+        ),
+    ));
+}
+
+fn despawn_label(mut commands: Commands, query: Query<Entity, With<AssistLabel>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn draw_label(modifiers: Res<RunModifiers>, mut query: Query<&mut Text, With<AssistLabel>>) {
+    let state = if modifiers.assist { "on" } else { "off" };
+    for mut text in &mut query {
+        text.sections[0].value = format!("G assist: {state}");
+    }
+}
+
+// Stepped the way the simulation steps it, so the line is where the bird
+// would go if it didn't flap again. The world scrolls towards the bird
+// rather than the other way around, so the line goes out ahead of it to
+// where the pipes will have got to
+fn draw_path(
+    query: Query<(&Transform, &Velocity, &BirdState), With<Player>>,
+    physics: Res<ActivePhysics>,
+    difficulty: Res<Difficulty>,
+    ease: Res<ScrollEase>,
+    mut gizmos: Gizmos,
+) {
+    let step = 1. / SIM_HZ as f32;
+    let steps = (LOOKAHEAD / step) as usize;
+    let speed = ease.speed(&difficulty);
+
+    for (transform, velocity, state) in &query {
+        if *state != BirdState::Flying {
+            continue;
+        }
+
+        let start = transform.translation.xy();
+        let mut transform = *transform;
+        let mut velocity = Velocity(velocity.0);
+        let path = (0..=steps).map(|i| {
+            if i > 0 {
+                fall(&mut transform, &mut velocity, &physics.0, step);
+            }
+            Vec2::new(start.x - speed * step * i as f32, transform.translation.y)
+        });
+        gizmos.linestrip_2d(path, Color::rgba(1., 1., 1., 0.6));
+    }
+}
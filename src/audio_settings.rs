@@ -0,0 +1,304 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    low_power::LowPower, mixer::Channel, profile::Profile, replay::Playback,
+    screenshot::ScreenshotSettings, settings::Accessibility, weather::WeatherSetting, AppState,
+};
+
+// How much a volume goes up or down with every press
+const VOLUME_STEP: f32 = 0.1;
+// Characters in a volume slider
+const SLIDER_WIDTH: usize = 10;
+
+/// How loud the player wants things, which every sound is played at
+#[derive(Resource, Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct AudioSettings {
+    /// Everything, on top of the volume of its channel. From 0 to 1 like the rest
+    pub master: f32,
+    pub music: f32,
+    /// Sound effects and menu sounds
+    pub sfx: f32,
+    pub muted: bool,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        Self {
+            master: 1.,
+            music: 0.8,
+            sfx: 1.,
+            muted: false,
+        }
+    }
+}
+
+impl AudioSettings {
+    /// How loud a sound on `channel` is played compared to its own volume
+    pub fn gain(&self, channel: Channel) -> f32 {
+        if self.muted {
+            return 0.;
+        }
+        let channel = match channel {
+            Channel::Music => self.music,
+            Channel::Effects | Channel::Ui => self.sfx,
+        };
+        self.master * channel
+    }
+}
+
+/// A line on the settings screen
+#[derive(Clone, Copy, PartialEq)]
+enum Row {
+    Master,
+    Music,
+    Sfx,
+    Mute,
+    LowPower,
+    Screenshots,
+    Weather,
+    Shake,
+    Rumble,
+    ReducedMotion,
+    Timing,
+}
+
+const ROWS: [Row; 11] = [
+    Row::Master,
+    Row::Music,
+    Row::Sfx,
+    Row::Mute,
+    Row::LowPower,
+    Row::Screenshots,
+    Row::Weather,
+    Row::Shake,
+    Row::Rumble,
+    Row::ReducedMotion,
+    Row::Timing,
+];
+
+#[derive(Resource, Default)]
+struct SettingsMenu {
+    selected: usize,
+}
+
+#[derive(Component)]
+struct SettingsScreen;
+
+pub struct AudioSettingsPlugin;
+
+impl Plugin for AudioSettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SettingsMenu>()
+            .add_systems(
+                Update,
+                open_settings.run_if(
+                    in_state(AppState::MainMenu).and_then(not(resource_exists::<Playback>)),
+                ),
+            )
+            .add_systems(OnEnter(AppState::Settings), reset_selection)
+            .add_systems(
+                Update,
+                (
+                    change_settings,
+                    draw_settings.run_if(
+                        resource_changed::<SettingsMenu>
+                            .or_else(resource_changed::<AudioSettings>)
+                            .or_else(resource_changed::<LowPower>)
+                            .or_else(resource_changed::<ScreenshotSettings>)
+                            .or_else(resource_changed::<WeatherSetting>)
+                            .or_else(resource_changed::<Accessibility>)
+                            .or_else(resource_changed::<Profile>),
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Settings)),
+            )
+            .add_systems(OnExit(AppState::Settings), close_settings);
+    }
+}
+
+fn open_settings(keys: Res<ButtonInput<KeyCode>>, mut state: ResMut<NextState<AppState>>) {
+    if keys.just_pressed(KeyCode::KeyO) {
+        state.set(AppState::Settings);
+    }
+}
+
+fn reset_selection(mut menu: ResMut<SettingsMenu>) {
+    menu.selected = 0;
+}
+
+fn close_settings(mut commands: Commands, query: Query<Entity, With<SettingsScreen>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn change_settings(
+    mut menu: ResMut<SettingsMenu>,
+    mut settings: ResMut<AudioSettings>,
+    mut power: ResMut<LowPower>,
+    mut screenshots: ResMut<ScreenshotSettings>,
+    mut weather: ResMut<WeatherSetting>,
+    mut accessibility: ResMut<Accessibility>,
+    mut state: ResMut<NextState<AppState>>,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        state.set(AppState::MainMenu);
+        return;
+    }
+    if keys.just_pressed(KeyCode::Tab) {
+        state.set(AppState::Controls);
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        menu.selected = menu.selected.saturating_sub(1);
+    }
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        menu.selected = (menu.selected + 1).min(ROWS.len() - 1);
+    }
+
+    let step = match (
+        keys.just_pressed(KeyCode::ArrowLeft),
+        keys.just_pressed(KeyCode::ArrowRight),
+    ) {
+        (true, false) => -VOLUME_STEP,
+        (false, true) => VOLUME_STEP,
+        _ => 0.,
+    };
+    // Only touched when something changes, every change is saved
+    match ROWS[menu.selected] {
+        Row::Mute if step != 0. || keys.just_pressed(KeyCode::Enter) => {
+            settings.muted = !settings.muted;
+        }
+        Row::LowPower if step != 0. || keys.just_pressed(KeyCode::Enter) => {
+            power.enabled = !power.enabled;
+        }
+        Row::Screenshots if step != 0. || keys.just_pressed(KeyCode::Enter) => {
+            screenshots.on_new_best = !screenshots.on_new_best;
+        }
+        // Enter goes forwards like Right does, there's more than on and off
+        Row::Weather if step != 0. || keys.just_pressed(KeyCode::Enter) => {
+            *weather = weather.cycle(if step < 0. { -1 } else { 1 });
+        }
+        Row::Shake if step != 0. || keys.just_pressed(KeyCode::Enter) => {
+            accessibility.screen_shake = !accessibility.screen_shake;
+        }
+        Row::Rumble if step != 0. || keys.just_pressed(KeyCode::Enter) => {
+            accessibility.rumble = !accessibility.rumble;
+        }
+        Row::ReducedMotion if step != 0. || keys.just_pressed(KeyCode::Enter) => {
+            accessibility.reduced_motion = !accessibility.reduced_motion;
+        }
+        Row::Timing if keys.just_pressed(KeyCode::Enter) => state.set(AppState::Calibration),
+        Row::Master if step != 0. => settings.master = step_volume(settings.master, step),
+        Row::Music if step != 0. => settings.music = step_volume(settings.music, step),
+        Row::Sfx if step != 0. => settings.sfx = step_volume(settings.sfx, step),
+        _ => {}
+    }
+}
+
+// Rounded so the steps land on whole percentages
+fn step_volume(volume: f32, step: f32) -> f32 {
+    ((volume + step) * 10.).round().clamp(0., 10.) / 10.
+}
+
+fn on_off(on: bool) -> &'static str {
+    if on {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+fn slider(volume: f32) -> String {
+    let filled = (volume * SLIDER_WIDTH as f32).round() as usize;
+    format!(
+        "[{}{}] {:>3}%",
+        "#".repeat(filled),
+        "-".repeat(SLIDER_WIDTH - filled),
+        (volume * 100.).round()
+    )
+}
+
+fn draw_settings(
+    mut commands: Commands,
+    menu: Res<SettingsMenu>,
+    settings: Res<AudioSettings>,
+    power: Res<LowPower>,
+    screenshots: Res<ScreenshotSettings>,
+    weather: Res<WeatherSetting>,
+    accessibility: Res<Accessibility>,
+    profile: Res<Profile>,
+    query: Query<Entity, With<SettingsScreen>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let text = |value: String, size: f32, color: Color| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size: size,
+                color,
+                ..default()
+            },
+        )
+    };
+
+    commands
+        .spawn((
+            SettingsScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(12.)),
+                    row_gap: Val::Px(4.),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.8).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("Settings".to_string(), 24., Color::WHITE));
+
+            for (i, row) in ROWS.iter().enumerate() {
+                let line = match row {
+                    Row::Master => format!("Master  {}", slider(settings.master)),
+                    Row::Music => format!("Music   {}", slider(settings.music)),
+                    Row::Sfx => format!("Effects {}", slider(settings.sfx)),
+                    Row::Mute => format!("Mute    {}", on_off(settings.muted)),
+                    Row::LowPower => format!("Low power {}", on_off(power.enabled)),
+                    Row::Screenshots => {
+                        format!("Best screenshots {}", on_off(screenshots.on_new_best))
+                    }
+                    Row::Weather => format!("Weather {}", weather.name()),
+                    Row::Shake => format!("Screen shake {}", on_off(accessibility.screen_shake)),
+                    Row::Rumble => format!("Rumble  {}", on_off(accessibility.rumble)),
+                    Row::ReducedMotion => {
+                        format!("Reduced motion {}", on_off(accessibility.reduced_motion))
+                    }
+                    Row::Timing => format!("Timing  {:+.0} ms", profile.input_offset * 1000.),
+                };
+                let color = if i == menu.selected {
+                    Color::YELLOW
+                } else {
+                    Color::WHITE
+                };
+                parent.spawn(text(line, 14., color));
+            }
+
+            parent.spawn(text(
+                "Left/Right change, Enter toggle, Tab controls, Esc back".to_string(),
+                12.,
+                Color::GRAY,
+            ));
+        });
+}
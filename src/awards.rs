@@ -0,0 +1,231 @@
+use bevy::{math::bounding::BoundingVolume, prelude::*};
+
+use crate::{
+    offset_aabb, pipe_aabb, profile::Profile, replay::Playback, AppState, Collider, Obstacle,
+    OnJumped, Pipe, PipePassed, Player, SimSet, SimTick, SIM_HZ,
+};
+
+// How many awards make it onto the game over screen
+const SHOWN_AWARDS: usize = 2;
+// Pipes in a row that count towards the fastest stretch
+const STRETCH: usize = 10;
+// Anything closer than this (in pixels) is a close call
+const CLOSE_CALL: f32 = 8.;
+// Going this long (in seconds) without flapping is a glide worth mentioning
+const GLIDE: f32 = 1.;
+
+enum RunEvent {
+    Flap,
+    /// How close the player came to either pipe going through the gap
+    Passed {
+        margin: f32,
+    },
+}
+
+/// Everything that happened in the current run, in the order it happened
+#[derive(Resource, Default)]
+struct RunLog {
+    events: Vec<(u64, RunEvent)>,
+}
+
+struct Award {
+    title: &'static str,
+    value: String,
+    /// How impressive it is next to the other awards
+    rank: f32,
+}
+
+#[derive(Component)]
+struct AwardsPanel;
+
+pub struct AwardsPlugin;
+
+impl Plugin for AwardsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunLog>()
+            .add_systems(OnEnter(AppState::Playing), clear_log)
+            .add_systems(
+                FixedUpdate,
+                (
+                    log_flaps.after(SimSet::Input).before(SimSet::Physics),
+                    log_passes.after(SimSet::Collision).before(SimSet::Rules),
+                )
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(OnEnter(AppState::GameOver), show_awards)
+            .add_systems(OnExit(AppState::GameOver), hide_awards);
+    }
+}
+
+fn clear_log(mut log: ResMut<RunLog>) {
+    log.events.clear();
+}
+
+fn log_flaps(mut log: ResMut<RunLog>, mut reader: EventReader<OnJumped>, tick: Res<SimTick>) {
+    for _ in reader.read() {
+        log.events.push((tick.0, RunEvent::Flap));
+    }
+}
+
+fn log_passes(
+    mut log: ResMut<RunLog>,
+    mut reader: EventReader<PipePassed>,
+    obstacles: Query<(&Transform, &Children), With<Obstacle>>,
+    pipes: Query<(&Transform, &Collider), With<Pipe>>,
+    player: Query<(&Transform, &Collider), With<Player>>,
+    tick: Res<SimTick>,
+) {
+    let Ok((transform, Collider(collider))) = player.get_single() else {
+        return;
+    };
+    let player = offset_aabb(collider, &transform.translation);
+
+    for passed in reader.read() {
+        let Ok((obstacle, children)) = obstacles.get(passed.obstacle) else {
+            continue;
+        };
+        // Closest of the gap to the top pipe and the gap to the bottom one
+        let margin = pipes
+            .iter_many(children)
+            .map(|(t, Collider(collider))| {
+                let pipe = pipe_aabb(collider, obstacle, t);
+                if pipe.center().y > player.center().y {
+                    pipe.min.y - player.max.y
+                } else {
+                    player.min.y - pipe.max.y
+                }
+            })
+            .fold(f32::MAX, f32::min);
+        log.events.push((tick.0, RunEvent::Passed { margin }));
+    }
+}
+
+fn seconds(ticks: u64) -> f32 {
+    ticks as f32 / SIM_HZ as f32
+}
+
+fn awards(log: &RunLog) -> Vec<Award> {
+    let flaps = log
+        .events
+        .iter()
+        .filter_map(|(tick, event)| matches!(event, RunEvent::Flap).then_some(*tick))
+        .collect::<Vec<_>>();
+    let passes = log
+        .events
+        .iter()
+        .filter_map(|(tick, event)| match event {
+            RunEvent::Passed { margin } => Some((*tick, *margin)),
+            RunEvent::Flap => None,
+        })
+        .collect::<Vec<_>>();
+
+    let mut awards = Vec::new();
+
+    let closest = passes
+        .iter()
+        .map(|(_, margin)| *margin)
+        .fold(f32::MAX, f32::min);
+    if closest < CLOSE_CALL {
+        awards.push(Award {
+            title: "Closest call",
+            value: format!("{:.1}px", closest.max(0.)),
+            rank: 2. - closest / CLOSE_CALL,
+        });
+    }
+
+    let glide = flaps
+        .windows(2)
+        .map(|pair| seconds(pair[1] - pair[0]))
+        .fold(0., f32::max);
+    if glide >= GLIDE {
+        awards.push(Award {
+            title: "Longest glide",
+            value: format!("{glide:.1}s"),
+            rank: glide / GLIDE,
+        });
+    }
+
+    let fastest = passes
+        .windows(STRETCH)
+        .map(|stretch| seconds(stretch[STRETCH - 1].0 - stretch[0].0))
+        .reduce(f32::min);
+    if let Some(fastest) = fastest {
+        awards.push(Award {
+            title: "Fastest 10 pipes",
+            value: format!("{fastest:.1}s"),
+            rank: 1.,
+        });
+    }
+
+    // Always something to show for a run, even a short one
+    if !flaps.is_empty() {
+        awards.push(Award {
+            title: "Most flaps",
+            value: flaps.len().to_string(),
+            rank: 0.,
+        });
+    }
+
+    awards.sort_by(|a, b| b.rank.total_cmp(&a.rank));
+    awards
+}
+
+fn show_awards(
+    mut commands: Commands,
+    log: Res<RunLog>,
+    mut profile: ResMut<Profile>,
+    playback: Option<Res<Playback>>,
+) {
+    let mut awards = awards(&log);
+
+    // Every award counts towards unlocks, not just the ones that are shown.
+    // Replays have already been counted when they were played
+    let earned = awards.iter().map(|award| award.title.to_string());
+    if playback.is_none()
+        && earned
+            .clone()
+            .any(|title| !profile.achievements.contains(&title))
+    {
+        profile.achievements.extend(earned);
+    }
+
+    awards.truncate(SHOWN_AWARDS);
+    if awards.is_empty() {
+        return;
+    }
+
+    commands
+        .spawn((
+            AwardsPanel,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(48.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(4.),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            for award in awards {
+                parent.spawn(TextBundle::from_section(
+                    format!("{}: {}", award.title, award.value),
+                    TextStyle {
+                        font_size: 14.,
+                        color: Color::YELLOW,
+                        ..default()
+                    },
+                ));
+            }
+        });
+}
+
+fn hide_awards(mut commands: Commands, query: Query<Entity, With<AwardsPanel>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,216 @@
+use std::f32::consts::PI;
+
+use bevy::{
+    ecs::{query::QueryData, system::EntityCommands},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{bonus::PlayPhase, scroll_pipes, AppState, Obstacle, Pipe, SimSet};
+
+// Everything here is driven by how far along an obstacle is rather than by
+// time, so it moves the same regardless of frame rate and a snapshot only
+// needs the parameters to pick it back up
+
+/// The obstacle bobs up and down around `center`
+#[derive(Component, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Oscillate {
+    pub center: f32,
+    pub amplitude: f32,
+    /// How far the obstacle scrolls for a full bob, divided by 2π
+    pub wavelength: f32,
+}
+
+impl Oscillate {
+    fn y(&self, x: f32) -> f32 {
+        self.center + (x / self.wavelength).sin() * self.amplitude
+    }
+}
+
+/// The pipes tilt back and forth around the middle of the gap
+#[derive(Component, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Rotate {
+    /// Furthest it tilts either way, in radians
+    pub angle: f32,
+    /// How far the obstacle scrolls for a full swing, divided by 2π
+    pub wavelength: f32,
+}
+
+impl Rotate {
+    fn angle(&self, x: f32) -> f32 {
+        (x / self.wavelength).sin() * self.angle
+    }
+}
+
+/// The gap stays shut until the obstacle gets close, then opens up to `gap`
+#[derive(Component, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct DelayedOpen {
+    pub gap: f32,
+    /// Where the gap starts to open
+    pub at: f32,
+    /// How far the obstacle scrolls while it opens
+    pub over: f32,
+}
+
+impl DelayedOpen {
+    fn gap(&self, x: f32) -> f32 {
+        let t = ((self.at - x) / self.over).clamp(0., 1.);
+        self.gap * t * t * (3. - 2. * t)
+    }
+}
+
+/// The bottom pipe rams up into the gap every so often
+#[derive(Component, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Crusher {
+    pub gap: f32,
+    /// How far the obstacle scrolls from one ram to the next
+    pub wavelength: f32,
+    /// How much of the way from one ram to the next is spent ramming
+    pub duty: f32,
+}
+
+impl Crusher {
+    fn gap(&self, x: f32) -> f32 {
+        let phase = (x / self.wavelength).rem_euclid(1.);
+        let closed = if phase < self.duty {
+            (phase / self.duty * PI).sin()
+        } else {
+            0.
+        };
+        self.gap * (1. - closed)
+    }
+}
+
+/// Any one of the behaviors, for putting obstacles together from data
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Behavior {
+    Oscillate(Oscillate),
+    Rotate(Rotate),
+    DelayedOpen(DelayedOpen),
+    Crusher(Crusher),
+}
+
+/// The behaviors an obstacle has
+#[derive(QueryData)]
+pub struct Behaviors {
+    oscillate: Option<&'static Oscillate>,
+    rotate: Option<&'static Rotate>,
+    delayed_open: Option<&'static DelayedOpen>,
+    crusher: Option<&'static Crusher>,
+}
+
+impl BehaviorsItem<'_> {
+    pub fn to_vec(&self) -> Vec<Behavior> {
+        let oscillate = self.oscillate.map(|b| Behavior::Oscillate(*b));
+        let rotate = self.rotate.map(|b| Behavior::Rotate(*b));
+        let delayed_open = self.delayed_open.map(|b| Behavior::DelayedOpen(*b));
+        let crusher = self.crusher.map(|b| Behavior::Crusher(*b));
+        [oscillate, rotate, delayed_open, crusher]
+            .into_iter()
+            .flatten()
+            .collect()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.oscillate.is_none()
+            && self.rotate.is_none()
+            && self.delayed_open.is_none()
+            && self.crusher.is_none()
+    }
+}
+
+/// Swaps whatever behaviors an obstacle had for `behaviors`
+pub fn set_behaviors(entity: &mut EntityCommands, behaviors: &[Behavior]) {
+    entity.remove::<(Oscillate, Rotate, DelayedOpen, Crusher)>();
+    for behavior in behaviors {
+        match *behavior {
+            Behavior::Oscillate(b) => entity.insert(b),
+            Behavior::Rotate(b) => entity.insert(b),
+            Behavior::DelayedOpen(b) => entity.insert(b),
+            Behavior::Crusher(b) => entity.insert(b),
+        };
+    }
+}
+
+/// Puts the pipes of an obstacle either side of a `pipe_space` tall gap,
+/// tilted by `angle` around the middle of it
+pub fn pose_pipes(
+    children: &Children,
+    pipes: &mut Query<(&Pipe, &mut Transform), Without<Obstacle>>,
+    pipe_space: f32,
+    angle: f32,
+) {
+    // The top pipe sits right on the obstacle when it's upright
+    let pivot = Vec3::new(0., -80. - pipe_space / 2., 0.);
+    let rotation = Quat::from_rotation_z(angle);
+    let reach = 80. + pipe_space / 2.;
+
+    let mut iter = pipes.iter_many_mut(children);
+    while let Some((pipe, mut transform)) = iter.fetch_next() {
+        let offset = match pipe {
+            Pipe::Top => Vec3::new(0., reach, 0.),
+            Pipe::Bottom => Vec3::new(0., -reach, 0.),
+        };
+        transform.translation = pivot + rotation * offset;
+        transform.rotation = rotation;
+    }
+}
+
+/// How tall the gap between the two pipes of an obstacle is
+pub fn pipe_space<'a>(pipes: impl IntoIterator<Item = &'a Transform>) -> f32 {
+    let ends = pipes
+        .into_iter()
+        .map(|transform| transform.translation)
+        .collect::<Vec<_>>();
+    match ends[..] {
+        [a, b] => a.distance(b) - 160.,
+        _ => 0.,
+    }
+}
+
+pub struct BehaviorsPlugin;
+
+impl Plugin for BehaviorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            drive_obstacles
+                .in_set(SimSet::Physics)
+                .after(scroll_pipes)
+                .run_if(in_state(AppState::Playing).and_then(in_state(PlayPhase::Normal))),
+        );
+    }
+}
+
+fn drive_obstacles(
+    mut obstacles: Query<(&mut Transform, &Children, Behaviors), With<Obstacle>>,
+    mut pipes: Query<(&Pipe, &mut Transform), Without<Obstacle>>,
+) {
+    for (mut transform, children, behaviors) in &mut obstacles {
+        if behaviors.is_empty() {
+            continue;
+        }
+
+        let x = transform.translation.x;
+        if let Some(oscillate) = behaviors.oscillate {
+            transform.translation.y = oscillate.y(x);
+        }
+
+        // Ramming shuts a gap that hasn't finished opening yet even further
+        let gap = [
+            behaviors.delayed_open.map(|b| b.gap(x)),
+            behaviors.crusher.map(|b| b.gap(x)),
+        ]
+        .into_iter()
+        .flatten()
+        .reduce(f32::min);
+        let angle = behaviors.rotate.map_or(0., |b| b.angle(x));
+
+        if gap.is_some() || behaviors.rotate.is_some() {
+            let gap = gap.unwrap_or_else(|| {
+                pipe_space(pipes.iter_many(children).map(|(_, transform)| transform))
+            });
+            pose_pipes(children, &mut pipes, gap, angle);
+        }
+    }
+}
@@ -0,0 +1,260 @@
+use std::f32::consts::PI;
+
+use bevy::{math::bounding::IntersectsVolume, prelude::*};
+use rand::Rng;
+
+use crate::{
+    behaviors::{pose_pipes, set_behaviors},
+    config::ActiveConfig,
+    decals::Repaint,
+    difficulty::Difficulty,
+    hitboxes::Hitboxes,
+    levels::LevelRun,
+    offset_aabb, random_pattern, random_pipe_height,
+    scroll::{is_scrolling, ScrollEase},
+    snapshot::OnSnapshotRestored,
+    AppState, Collider, GameRng, Obstacle, Passed, Pattern, Pipe, Player, Root, Score, Side,
+    SimSet, SpriteSheet, FIRST_PIPE_X,
+};
+
+const BONUS_EVERY: u32 = 30;
+const BONUS_DURATION: f32 = 10.;
+const ARC_INTERVAL: f32 = 0.8;
+const ARC_COINS: usize = 5;
+const ARC_HEIGHT: f32 = 24.;
+const COIN_SPACING: f32 = 12.;
+const COIN_SIZE: f32 = 6.;
+
+/// What's going on inside of `AppState::Playing`
+#[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum PlayPhase {
+    #[default]
+    Normal,
+    /// The pipes are gone and there are coins to collect instead
+    Bonus,
+}
+
+#[derive(Resource)]
+struct BonusRound {
+    next_at: u32,
+    duration: Timer,
+    arcs: Timer,
+}
+
+#[derive(Component)]
+struct Coin;
+
+#[derive(Event)]
+pub struct OnCoinCollected {
+    pub position: Vec2,
+}
+
+pub struct BonusPlugin;
+
+impl Plugin for BonusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<PlayPhase>()
+            .add_event::<OnCoinCollected>()
+            .add_systems(OnEnter(AppState::Playing), reset_bonus_round)
+            .add_systems(OnExit(AppState::Playing), end_phase)
+            .add_systems(
+                FixedUpdate,
+                (catch_up_bonus_round, start_bonus_round)
+                    .chain()
+                    .in_set(SimSet::Rules)
+                    // Levels lay out their own coins
+                    .run_if(
+                        in_state(AppState::Playing)
+                            .and_then(in_state(PlayPhase::Normal))
+                            .and_then(not(resource_exists::<LevelRun>)),
+                    ),
+            )
+            .add_systems(OnEnter(PlayPhase::Bonus), hide_obstacles)
+            .add_systems(
+                FixedUpdate,
+                (spawn_coin_arcs, end_bonus_round)
+                    .in_set(SimSet::Rules)
+                    .run_if(in_state(AppState::Playing).and_then(in_state(PlayPhase::Bonus))),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    scroll_coins.in_set(SimSet::Physics).run_if(is_scrolling),
+                    collect_coins
+                        .in_set(SimSet::Collision)
+                        .run_if(in_state(AppState::Playing)),
+                ),
+            )
+            .add_systems(OnExit(PlayPhase::Bonus), respawn_obstacles);
+    }
+}
+
+fn reset_bonus_round(mut commands: Commands) {
+    commands.insert_resource(BonusRound {
+        next_at: BONUS_EVERY,
+        duration: Timer::from_seconds(BONUS_DURATION, TimerMode::Once),
+        arcs: Timer::from_seconds(ARC_INTERVAL, TimerMode::Repeating),
+    });
+}
+
+fn end_phase(mut phase: ResMut<NextState<PlayPhase>>) {
+    phase.set(PlayPhase::Normal);
+}
+
+// After a snapshot the next round is wherever the score says it should be
+fn catch_up_bonus_round(
+    mut bonus: ResMut<BonusRound>,
+    score: Res<Score>,
+    mut reader: EventReader<OnSnapshotRestored>,
+) {
+    if reader.read().count() > 0 {
+        bonus.next_at = (score.0 / BONUS_EVERY + 1) * BONUS_EVERY;
+    }
+}
+
+fn start_bonus_round(
+    mut bonus: ResMut<BonusRound>,
+    score: Res<Score>,
+    mut phase: ResMut<NextState<PlayPhase>>,
+) {
+    if score.0 < bonus.next_at {
+        return;
+    }
+
+    bonus.next_at += BONUS_EVERY;
+    bonus.duration.reset();
+    bonus.arcs.reset();
+    phase.set(PlayPhase::Bonus);
+}
+
+fn end_bonus_round(
+    mut bonus: ResMut<BonusRound>,
+    mut phase: ResMut<NextState<PlayPhase>>,
+    time: Res<Time>,
+) {
+    if bonus.duration.tick(time.delta()).finished() {
+        phase.set(PlayPhase::Normal);
+    }
+}
+
+fn hide_obstacles(mut query: Query<&mut Visibility, With<Obstacle>>) {
+    for mut visibility in &mut query {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Bring the pipes back in from the right the same way a new world lays them out,
+// so none of them pop in on screen
+fn respawn_obstacles(
+    mut commands: Commands,
+    mut query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Pattern,
+            &mut Visibility,
+            &Children,
+        ),
+        With<Obstacle>,
+    >,
+    mut pipes: Query<(&Pipe, &mut Transform), Without<Obstacle>>,
+    difficulty: Res<Difficulty>,
+    config: Res<ActiveConfig>,
+    mut rng: ResMut<GameRng>,
+) {
+    let mut obstacles = query.iter_mut().collect::<Vec<_>>();
+    obstacles.sort_by(|(_, a, ..), (_, b, ..)| a.translation.x.total_cmp(&b.translation.x));
+
+    for (i, (entity, mut transform, mut pattern, mut visibility, children)) in
+        obstacles.into_iter().enumerate()
+    {
+        transform.translation.x = i as f32 * difficulty.pipe_to_pipe_space + FIRST_PIPE_X;
+        transform.translation.y = random_pipe_height(&mut rng);
+        *pattern = random_pattern(&difficulty, &config.0, &mut rng);
+        pose_pipes(children, &mut pipes, difficulty.pipe_space, 0.);
+        *visibility = Visibility::Inherited;
+        let mut entity = commands.entity(entity);
+        set_behaviors(&mut entity, &pattern.behaviors(&config.0));
+        entity.remove::<(Passed, Side)>().insert(Repaint);
+    }
+}
+
+fn spawn_coin_arcs(
+    mut commands: Commands,
+    mut bonus: ResMut<BonusRound>,
+    mut rng: ResMut<GameRng>,
+    root: Query<Entity, With<Root>>,
+    sheet: Res<SpriteSheet>,
+    time: Res<Time>,
+) {
+    if !bonus.arcs.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let base = rng.gen_range(-60. ..=60. - ARC_HEIGHT);
+    commands.entity(root.single()).with_children(|parent| {
+        for i in 0..ARC_COINS {
+            let t = i as f32 / (ARC_COINS - 1) as f32;
+            spawn_coin(
+                parent,
+                &sheet.hitboxes,
+                Vec2::new(
+                    90. + i as f32 * COIN_SPACING,
+                    base + (t * PI).sin() * ARC_HEIGHT,
+                ),
+            );
+        }
+    });
+}
+
+pub fn spawn_coin(parent: &mut ChildBuilder, hitboxes: &Hitboxes, position: Vec2) {
+    parent.spawn((
+        Coin,
+        Collider(hitboxes.coin.aabb()),
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::GOLD,
+                custom_size: Some(Vec2::splat(COIN_SIZE)),
+                ..default()
+            },
+            transform: Transform::from_translation(position.extend(2.)),
+            ..default()
+        },
+    ));
+}
+
+fn scroll_coins(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform), With<Coin>>,
+    difficulty: Res<Difficulty>,
+    ease: Res<ScrollEase>,
+    time: Res<Time>,
+) {
+    for (entity, mut transform) in &mut query {
+        transform.translation.x += time.delta_seconds() * ease.speed(&difficulty);
+        if transform.translation.x < -90. {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn collect_coins(
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+    mut writer: EventWriter<OnCoinCollected>,
+    player: Query<(&Transform, &Collider), With<Player>>,
+    coins: Query<(Entity, &Transform, &Collider), With<Coin>>,
+) {
+    let (transform, Collider(player_collider)) = player.single();
+    let player = offset_aabb(player_collider, &transform.translation);
+
+    for (entity, transform, Collider(coin_collider)) in &coins {
+        if offset_aabb(coin_collider, &transform.translation).intersects(&player) {
+            score.0 += 1;
+            writer.send(OnCoinCollected {
+                position: transform.translation.xy(),
+            });
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
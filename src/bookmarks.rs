@@ -0,0 +1,275 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{profile::Profile, replay::Playback, AppState, GameRng, NextSeed};
+
+const MAX_NAME_LENGTH: usize = 24;
+
+/// A seed the player wanted to come back to
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Bookmark {
+    pub name: String,
+    pub seed: u64,
+}
+
+/// The name being typed for a new bookmark on the game over screen
+#[derive(Resource, Default)]
+pub struct Draft(String);
+
+#[derive(Resource, Default)]
+struct BookmarksMenu {
+    selected: usize,
+}
+
+#[derive(Component)]
+struct DraftPrompt;
+
+#[derive(Component)]
+struct BookmarksScreen;
+
+pub struct BookmarksPlugin;
+
+impl Plugin for BookmarksPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BookmarksMenu>()
+            .add_systems(
+                Update,
+                (
+                    type_draft,
+                    start_draft,
+                    draw_draft.run_if(resource_changed_or_removed::<Draft>()),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::GameOver)),
+            )
+            .add_systems(OnEnter(AppState::GameOver), draw_draft)
+            .add_systems(OnExit(AppState::GameOver), discard_draft)
+            .add_systems(
+                Update,
+                open_bookmarks.run_if(
+                    in_state(AppState::MainMenu).and_then(not(resource_exists::<Playback>)),
+                ),
+            )
+            .add_systems(OnEnter(AppState::Bookmarks), reset_selection)
+            .add_systems(
+                Update,
+                (
+                    browse_bookmarks,
+                    draw_bookmarks.run_if(
+                        resource_changed::<BookmarksMenu>.or_else(resource_changed::<Profile>),
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Bookmarks)),
+            )
+            .add_systems(OnExit(AppState::Bookmarks), close_bookmarks);
+    }
+}
+
+fn text(value: String, size: f32, color: Color) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font_size: size,
+            color,
+            ..default()
+        },
+    )
+}
+
+fn start_draft(mut commands: Commands, draft: Option<Res<Draft>>, keys: Res<ButtonInput<KeyCode>>) {
+    if draft.is_none() && keys.just_pressed(KeyCode::KeyB) {
+        commands.init_resource::<Draft>();
+    }
+}
+
+// Reads characters even when there's no draft, otherwise the key that opened
+// it would end up as the first letter of the name
+fn type_draft(
+    mut commands: Commands,
+    draft: Option<ResMut<Draft>>,
+    mut reader: EventReader<ReceivedCharacter>,
+    keys: Res<ButtonInput<KeyCode>>,
+    rng: Res<GameRng>,
+    mut profile: ResMut<Profile>,
+) {
+    let typed = reader
+        .read()
+        .flat_map(|event| event.char.chars())
+        .filter(|c| !c.is_control())
+        .collect::<String>();
+    let Some(mut draft) = draft else {
+        return;
+    };
+
+    if keys.just_pressed(KeyCode::Escape) {
+        commands.remove_resource::<Draft>();
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Enter) {
+        let name = match draft.0.trim() {
+            "" => format!("Seed {}", rng.seed),
+            name => name.to_string(),
+        };
+        profile.bookmarks.push(Bookmark {
+            name,
+            seed: rng.seed,
+        });
+        commands.remove_resource::<Draft>();
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Backspace) {
+        draft.0.pop();
+    }
+    for c in typed.chars() {
+        if draft.0.chars().count() < MAX_NAME_LENGTH {
+            draft.0.push(c);
+        }
+    }
+}
+
+fn draw_draft(
+    mut commands: Commands,
+    draft: Option<Res<Draft>>,
+    query: Query<Entity, With<DraftPrompt>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let prompt = match draft {
+        Some(draft) => format!("Bookmark as: {}_\nEnter save, Esc cancel", draft.0),
+        None => "B bookmark this seed".to_string(),
+    };
+
+    commands
+        .spawn((
+            DraftPrompt,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(12.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(text(prompt, 14., Color::WHITE));
+        });
+}
+
+fn discard_draft(mut commands: Commands, query: Query<Entity, With<DraftPrompt>>) {
+    commands.remove_resource::<Draft>();
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn open_bookmarks(keys: Res<ButtonInput<KeyCode>>, mut state: ResMut<NextState<AppState>>) {
+    if keys.just_pressed(KeyCode::KeyK) {
+        state.set(AppState::Bookmarks);
+    }
+}
+
+fn reset_selection(mut menu: ResMut<BookmarksMenu>) {
+    menu.selected = 0;
+}
+
+fn close_bookmarks(mut commands: Commands, query: Query<Entity, With<BookmarksScreen>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn browse_bookmarks(
+    mut menu: ResMut<BookmarksMenu>,
+    mut profile: ResMut<Profile>,
+    mut next_seed: ResMut<NextSeed>,
+    mut state: ResMut<NextState<AppState>>,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        state.set(AppState::MainMenu);
+        return;
+    }
+
+    if profile.bookmarks.is_empty() {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        menu.selected = menu.selected.saturating_sub(1);
+    }
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        menu.selected = (menu.selected + 1).min(profile.bookmarks.len() - 1);
+    }
+
+    if keys.just_pressed(KeyCode::Enter) {
+        next_seed.0 = Some(profile.bookmarks[menu.selected].seed);
+        state.set(AppState::MainMenu);
+    }
+
+    if keys.just_pressed(KeyCode::Delete) {
+        profile.bookmarks.remove(menu.selected);
+        menu.selected = menu.selected.min(profile.bookmarks.len().saturating_sub(1));
+    }
+}
+
+fn draw_bookmarks(
+    mut commands: Commands,
+    menu: Res<BookmarksMenu>,
+    profile: Res<Profile>,
+    query: Query<Entity, With<BookmarksScreen>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    commands
+        .spawn((
+            BookmarksScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(12.)),
+                    row_gap: Val::Px(4.),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.8).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("Bookmarked seeds".to_string(), 24., Color::WHITE));
+
+            if profile.bookmarks.is_empty() {
+                parent.spawn(text(
+                    "Press B on the game over screen to bookmark a seed".to_string(),
+                    14.,
+                    Color::GRAY,
+                ));
+            }
+
+            for (i, bookmark) in profile.bookmarks.iter().enumerate() {
+                let color = if i == menu.selected {
+                    Color::YELLOW
+                } else {
+                    Color::WHITE
+                };
+                parent.spawn(text(bookmark.name.clone(), 14., color));
+            }
+
+            parent.spawn(text(
+                "Enter play, Delete remove, Esc back".to_string(),
+                12.,
+                Color::GRAY,
+            ));
+        });
+}
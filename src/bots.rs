@@ -0,0 +1,709 @@
+use std::collections::VecDeque;
+
+use bevy::{
+    math::bounding::{Aabb2d, BoundingVolume},
+    prelude::*,
+};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    behaviors::pipe_space,
+    ceiling::CeilingBehavior,
+    config::ActiveConfig,
+    difficulty::Difficulty,
+    fall, hit_floor,
+    main_menu::MenuRow,
+    modes::ModeRegistry,
+    offset_aabb,
+    physics::{ActivePhysics, PhysicsPreset},
+    pipe_aabb,
+    profile::Profile,
+    replay::{start_recording, Playback, Recording},
+    scroll::ScrollEase,
+    start_dying, tilt, touch_ground, touch_pipe, world_running, AppState, Atlas, BirdState,
+    Collider, GameRng, Ground, Obstacle, Pipe, Root, RunModifiers, SimSet, SimTick, SpriteSheet,
+    Velocity, PIPE_WIDTH, SIM_HZ,
+};
+
+// A little behind the player, so both birds can be seen going through the
+// same gaps
+const OPPONENT_X: f32 = -24.;
+const OPPONENT_TINT: Color = Color::rgb(0.6, 0.8, 1.);
+// How far above the bottom of a gap the middle of the bird is kept, its
+// own half height and a little more
+const CLEARANCE: f32 = 7.;
+// Where the bird is kept above when there's no gap to go by, also as low as
+// it'll go for one
+const OPEN_FLOOR: f32 = -100.;
+// How many gaps ahead a bird can see
+const GAPS_SEEN: usize = 2;
+// Steps ahead the perfect bot plays out what happens if it flaps
+const LOOKAHEAD: usize = 72;
+// How close to the pipes the perfect bot lets the bird come before it tries
+// something else
+const SAFE_CLEARANCE: f32 = 3.;
+// How many steps before the one it's reacting to it goes by to tell how the
+// gaps are moving, and how fast one has to be moving for it to tell whether
+// it's bobbing
+const SEEN_BEFORE: usize = 3;
+const BOB_DRIFT: f32 = 4.;
+// Seconds ahead it trusts its guess of which way a gap is moving, when it
+// isn't bobbing
+const MOTION_HORIZON: f32 = 0.2;
+// How far up or down the bird can go before it's out of the world
+const WORLD_EDGE: f32 = 128.;
+// Mixed into the run's seed so a bot's mistakes are its own, and the run's
+// random numbers are left alone
+const BOT_SEED: u64 = 0xB07;
+
+/// How good a bot is at flying
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BotLevel {
+    Easy,
+    Medium,
+    Perfect,
+}
+
+impl BotLevel {
+    fn name(self) -> &'static str {
+        match self {
+            BotLevel::Easy => "Easy",
+            BotLevel::Medium => "Medium",
+            BotLevel::Perfect => "Perfect",
+        }
+    }
+
+    /// How many steps late a bot sees what's going on
+    fn reaction(self) -> usize {
+        match self {
+            BotLevel::Easy => 18,
+            BotLevel::Medium => 8,
+            BotLevel::Perfect => 0,
+        }
+    }
+
+    /// How far above where it should a bot can end up keeping the bird
+    fn wobble(self) -> f32 {
+        match self {
+            BotLevel::Easy => 14.,
+            BotLevel::Medium => 6.,
+            BotLevel::Perfect => 0.,
+        }
+    }
+
+    /// No bot, then each of them from the easiest up
+    fn next(level: Option<BotLevel>) -> Option<BotLevel> {
+        match level {
+            None => Some(BotLevel::Easy),
+            Some(BotLevel::Easy) => Some(BotLevel::Medium),
+            Some(BotLevel::Medium) => Some(BotLevel::Perfect),
+            Some(BotLevel::Perfect) => None,
+        }
+    }
+}
+
+/// The bot a run was raced against, and every step it flapped on
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OpponentRecord {
+    pub level: BotLevel,
+    pub flaps: Vec<u64>,
+}
+
+/// Everything a bot gets to go on, nothing a player couldn't see for
+/// themselves
+#[derive(Clone, Debug)]
+pub struct Observation {
+    pub height: f32,
+    pub velocity: f32,
+    /// Half of how wide and tall the bird is, going by what it gets hit with
+    pub size: Vec2,
+    /// The gaps the bird has to get through next, closest first
+    pub gaps: Vec<Gap>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Gap {
+    /// How far ahead of the bird it is
+    pub ahead: f32,
+    /// How high up its middle is
+    pub height: f32,
+    /// How tall it is
+    pub size: f32,
+}
+
+/// What a bird with the hitbox `bird` moving at `velocity` can see of the
+/// pipes ahead
+pub fn observe(
+    bird: Aabb2d,
+    velocity: &Velocity,
+    obstacles: &Query<(&Transform, &Visibility, &Children), With<Obstacle>>,
+    pipes: &Query<&Transform, (With<Pipe>, Without<Obstacle>)>,
+) -> Observation {
+    // A gap is still ahead until the bird is out the other side of it
+    let mut gaps = obstacles
+        .iter()
+        .filter(|(transform, visibility, _)| {
+            **visibility != Visibility::Hidden
+                && transform.translation.x + PIPE_WIDTH / 2. > bird.min.x
+        })
+        .filter_map(|(transform, _, children)| {
+            let ends = pipes.iter_many(children).collect::<Vec<_>>();
+            // Halfway between the top and bottom pipes, wherever they've moved to
+            let middle =
+                ends.iter().map(|pipe| pipe.translation.y).sum::<f32>() / ends.len().max(1) as f32;
+            (!ends.is_empty()).then(|| Gap {
+                ahead: transform.translation.x - bird.center().x,
+                height: transform.translation.y + middle,
+                size: pipe_space(ends),
+            })
+        })
+        .collect::<Vec<_>>();
+    gaps.sort_by(|a, b| a.ahead.total_cmp(&b.ahead));
+    gaps.truncate(GAPS_SEEN);
+
+    Observation {
+        height: bird.center().y,
+        velocity: velocity.0,
+        size: bird.half_size(),
+        gaps,
+    }
+}
+
+/// How a gap's been moving, going by the last few times it was seen
+#[derive(Clone, Copy)]
+struct Motion {
+    /// How fast it's coming closer
+    speed: f32,
+    /// How fast it's moving up, and how fast that's changing
+    drift: f32,
+    turn: f32,
+    /// How fast it's bobbing up and down, in radians a second, if it is
+    bob: Option<f32>,
+}
+
+impl Motion {
+    /// How `gap` has moved over the last few steps, if it was seen on all
+    /// of them
+    fn of(gap: &Gap, seen: [&Observation; SEEN_BEFORE]) -> Option<Self> {
+        // The same gap, only a little further away than the step after
+        let find = |seen: &Observation, after: &Gap| {
+            seen.gaps
+                .iter()
+                .find(|gap| gap.ahead >= after.ahead && gap.ahead - after.ahead < PIPE_WIDTH)
+                .copied()
+        };
+        let c = find(seen[2], gap)?;
+        let b = find(seen[1], &c)?;
+        let a = find(seen[0], &b)?;
+
+        let hz = SIM_HZ as f32;
+        let drift = (gap.height - c.height) * hz;
+        let turn = (gap.height - 2. * c.height + b.height) * hz * hz;
+        let jerk = (gap.height - 3. * c.height + 3. * b.height - a.height) * hz * hz * hz;
+        // Something bobbing up and down turns back the faster it's going
+        let bob = (drift.abs() > BOB_DRIFT)
+            .then(|| -jerk / drift)
+            .filter(|&rate| rate > 0.)
+            .map(f32::sqrt);
+        Some(Self {
+            speed: (c.ahead - gap.ahead) * hz,
+            drift,
+            turn,
+            bob,
+        })
+    }
+
+    /// Where `gap` will be in `t` seconds, as far ahead and as high up. For
+    /// a gap that isn't bobbing it can only tell which way it's heading for
+    /// so long, after that it's taken to stay put
+    fn predict(&self, gap: &Gap, t: f32) -> (f32, f32) {
+        let ahead = gap.ahead - self.speed * t;
+        let height = match self.bob {
+            Some(rate) => {
+                let middle = gap.height + self.turn / (rate * rate);
+                let (sin, cos) = (rate * t).sin_cos();
+                middle + (gap.height - middle) * cos + self.drift / rate * sin
+            }
+            None => {
+                let t = t.min(MOTION_HORIZON);
+                gap.height + self.drift * t + self.turn * t * t / 2.
+            }
+        };
+        (ahead, height)
+    }
+}
+
+/// A bot flying its own bird alongside the player's
+#[derive(Component)]
+struct Opponent {
+    level: BotLevel,
+    /// What it's seen lately, oldest first. Where the gaps are in the last
+    /// few it might not have reacted to yet
+    seen: VecDeque<Observation>,
+    /// How far above the bottom of the gap it's keeping the bird, rolled
+    /// again after every flap
+    aim: f32,
+    rng: ChaCha8Rng,
+    /// Which of the recorded flaps comes next when the race is played back
+    next_flap: usize,
+    pipes: u32,
+}
+
+impl Opponent {
+    fn new(level: BotLevel, seed: u64) -> Self {
+        Self {
+            level,
+            seen: VecDeque::new(),
+            aim: 0.,
+            rng: ChaCha8Rng::seed_from_u64(seed ^ BOT_SEED),
+            next_flap: 0,
+            pipes: 0,
+        }
+    }
+
+    fn decide(&mut self, now: Observation, physics: &PhysicsPreset) -> bool {
+        // A few more than it's reacting to, to tell how the gaps are moving
+        let kept = self.level.reaction() + SEEN_BEFORE + 1;
+        self.seen.push_back(now);
+        if self.seen.len() > kept {
+            self.seen.pop_front();
+        }
+        if self.seen.len() < kept {
+            return false;
+        }
+
+        let flap = match self.level {
+            BotLevel::Perfect => self.plan(physics),
+            _ => self.keep_low(),
+        };
+        if flap {
+            self.aim = self.rng.gen_range(0. ..=self.level.wobble());
+        }
+        flap
+    }
+
+    // Keeps the bird just above the bottom of the next gap, flapping
+    // whenever it's about to drop under it, since a flap always goes up the
+    // same way however fast the bird was falling. It knows where its own
+    // bird is, but goes by where the gaps were a little while ago
+    fn keep_low(&self) -> bool {
+        let now = &self.seen[self.seen.len() - 1];
+        let floor = self.seen[SEEN_BEFORE]
+            .gaps
+            .first()
+            .map_or(OPEN_FLOOR, |gap| gap.height - gap.size / 2.)
+            .max(OPEN_FLOOR);
+        // Where it'll be by the next step if it doesn't flap
+        let next = now.height + now.velocity / SIM_HZ as f32;
+        next < floor + CLEARANCE + self.aim
+    }
+
+    // Second guesses keeping low when that's cutting it fine, playing out
+    // flapping and not flapping now to see which stays further from the pipes
+    fn plan(&self, physics: &PhysicsPreset) -> bool {
+        let keep_low = self.keep_low();
+        let seen = &self.seen[SEEN_BEFORE];
+        let before = [&self.seen[0], &self.seen[1], &self.seen[2]];
+        let gaps = seen
+            .gaps
+            .iter()
+            .map(|gap| Some((*gap, Motion::of(gap, before)?)))
+            .collect::<Option<Vec<_>>>();
+        let Some(gaps) = gaps else {
+            return keep_low;
+        };
+
+        let clearance = |flap| clearance(seen, &gaps, physics, flap);
+        match (clearance(keep_low), clearance(!keep_low)) {
+            (Some(keeping), Some(other)) if keeping < SAFE_CLEARANCE && other > keeping => {
+                !keep_low
+            }
+            _ => keep_low,
+        }
+    }
+}
+
+/// The closest the bird comes to the pipes over the next while, if it goes
+/// through any in that time, when it flaps now or doesn't. After that it
+/// keeps just above the bottom of whichever gap's next
+fn clearance(
+    seen: &Observation,
+    gaps: &[(Gap, Motion)],
+    physics: &PhysicsPreset,
+    flap: bool,
+) -> Option<f32> {
+    let step = 1. / SIM_HZ as f32;
+    let (mut height, mut velocity) = (seen.height, seen.velocity);
+    let mut closest = None::<f32>;
+
+    for i in 0..LOOKAHEAD {
+        let t = i as f32 * step;
+        let gaps = gaps
+            .iter()
+            .map(|(gap, motion)| (motion.predict(gap, t), gap.size))
+            .collect::<Vec<_>>();
+        let next = gaps
+            .iter()
+            .find(|((ahead, _), _)| *ahead > -(PIPE_WIDTH / 2. + seen.size.x));
+
+        let flaps = match next {
+            _ if i == 0 => flap,
+            Some(((_, middle), size)) => height + velocity * step < middle - size / 2. + CLEARANCE,
+            None => false,
+        };
+        if flaps {
+            velocity = physics.jump;
+        }
+        let before = velocity;
+        velocity = (velocity + physics.gravity * step).max(physics.terminal);
+        height += (before + velocity) / 2. * step;
+
+        for ((ahead, middle), size) in gaps {
+            if ahead.abs() < PIPE_WIDTH / 2. + seen.size.x {
+                let room = size / 2. - seen.size.y - (height - middle).abs();
+                closest = Some(closest.map_or(room, |closest| closest.min(room)));
+            }
+        }
+    }
+    closest
+}
+
+/// An obstacle the bot has made it past
+#[derive(Component)]
+struct Overtaken;
+
+#[derive(Component)]
+struct OpponentLabel;
+
+#[derive(Component)]
+struct OpponentResult;
+
+pub struct BotsPlugin;
+
+impl Plugin for BotsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::Playing),
+            // After the recording's started, so the bot's flaps go in it
+            spawn_opponent.after(start_recording),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                fly_opponent
+                    .in_set(SimSet::Input)
+                    .run_if(in_state(AppState::Playing)),
+                move_opponent.in_set(SimSet::Physics),
+                (crash_opponent, overtake_pipes)
+                    .chain()
+                    .in_set(SimSet::Collision),
+            )
+                .run_if(world_running),
+        )
+        .add_systems(Update, tilt_opponent.run_if(world_running))
+        .add_systems(
+            OnEnter(AppState::MainMenu),
+            spawn_label.run_if(not(resource_exists::<Playback>)),
+        )
+        .add_systems(
+            Update,
+            (
+                pick_opponent,
+                draw_label.run_if(resource_changed::<Profile>),
+            )
+                .chain()
+                .run_if(in_state(AppState::MainMenu).and_then(not(resource_exists::<Playback>))),
+        )
+        .add_systems(OnExit(AppState::MainMenu), despawn::<OpponentLabel>)
+        .add_systems(OnEnter(AppState::GameOver), spawn_result)
+        .add_systems(OnExit(AppState::GameOver), despawn::<OpponentResult>);
+    }
+}
+
+// A replay races the bot it was recorded with, whichever one's picked now
+fn spawn_opponent(
+    mut commands: Commands,
+    profile: Res<Profile>,
+    rng: Res<GameRng>,
+    playback: Option<Res<Playback>>,
+    recording: Option<ResMut<Recording>>,
+    sheet: Res<SpriteSheet>,
+    root: Query<Entity, With<Root>>,
+    opponents: Query<Entity, With<Opponent>>,
+) {
+    for entity in &opponents {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let level = match &playback {
+        Some(playback) => playback.replay.opponent.as_ref().map(|record| record.level),
+        None => profile.opponent,
+    };
+    let Some(level) = level else {
+        return;
+    };
+
+    if let (None, Some(mut recording)) = (&playback, recording) {
+        recording.0.opponent = Some(OpponentRecord {
+            level,
+            flaps: Vec::new(),
+        });
+    }
+
+    let opponent = commands
+        .spawn((
+            Opponent::new(level, rng.seed),
+            BirdState::Flying,
+            Collider(sheet.hitboxes.bird.aabb()),
+            Velocity(0.),
+            SpriteSheetBundle {
+                texture: sheet.texture.clone(),
+                atlas: TextureAtlas {
+                    layout: sheet.layout.clone(),
+                    index: Atlas::Bird1 as usize,
+                },
+                sprite: Sprite {
+                    color: OPPONENT_TINT,
+                    ..default()
+                },
+                // Between the ghost and the player
+                transform: Transform::from_translation(Vec3::new(OPPONENT_X, 0., 3.5)),
+                ..default()
+            },
+        ))
+        .id();
+    commands.entity(root.single()).add_child(opponent);
+}
+
+fn fly_opponent(
+    mut query: Query<(
+        &mut Opponent,
+        &Transform,
+        &Collider,
+        &mut Velocity,
+        &BirdState,
+    )>,
+    obstacles: Query<(&Transform, &Visibility, &Children), With<Obstacle>>,
+    pipes: Query<&Transform, (With<Pipe>, Without<Obstacle>)>,
+    playback: Option<Res<Playback>>,
+    mut recording: Option<ResMut<Recording>>,
+    physics: Res<ActivePhysics>,
+    tick: Res<SimTick>,
+) {
+    for (mut opponent, transform, Collider(collider), mut velocity, state) in &mut query {
+        if *state != BirdState::Flying {
+            continue;
+        }
+
+        let played_back = playback
+            .as_ref()
+            .and_then(|playback| playback.replay.opponent.as_ref());
+        let flap = match played_back {
+            Some(record) => {
+                let flap = record.flaps.get(opponent.next_flap) == Some(&tick.0);
+                if flap {
+                    opponent.next_flap += 1;
+                }
+                flap
+            }
+            None => opponent.decide(
+                observe(
+                    offset_aabb(collider, &transform.translation),
+                    &velocity,
+                    &obstacles,
+                    &pipes,
+                ),
+                &physics.0,
+            ),
+        };
+        if !flap {
+            continue;
+        }
+
+        velocity.0 = physics.0.jump;
+        let record = recording
+            .as_mut()
+            .and_then(|recording| recording.0.opponent.as_mut());
+        if let Some(record) = record {
+            record.flaps.push(tick.0);
+        }
+    }
+}
+
+// Only flies while the run's on, a bot that's outlasted the player waits for
+// the next one where it is
+fn move_opponent(
+    mut query: Query<(&mut Transform, &mut Velocity, &mut BirdState), With<Opponent>>,
+    physics: Res<ActivePhysics>,
+    difficulty: Res<Difficulty>,
+    ease: Res<ScrollEase>,
+    state: Res<State<AppState>>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_seconds();
+    for (mut transform, mut velocity, mut bird) in &mut query {
+        match *bird {
+            BirdState::Flying if *state.get() == AppState::Playing => {
+                fall(&mut transform, &mut velocity, &physics.0, delta);
+            }
+            // Falls back with the pipes it crashed into
+            BirdState::Dying => {
+                fall(&mut transform, &mut velocity, &physics.0, delta);
+                transform.translation.x += ease.speed(&difficulty) * delta;
+                hit_floor(&transform, &mut velocity, &mut bird);
+            }
+            _ => {}
+        }
+    }
+}
+
+fn crash_opponent(
+    mut query: Query<(&mut Transform, &Collider, &mut Velocity, &mut BirdState), With<Opponent>>,
+    pipes: Query<(&Parent, &Transform, &Collider), (With<Pipe>, Without<Opponent>)>,
+    obstacles: Query<(&Transform, &Visibility), (With<Obstacle>, Without<Opponent>)>,
+    grounds: Query<(&Transform, &Collider), (With<Ground>, Without<Opponent>)>,
+    modifiers: Res<RunModifiers>,
+    registry: Res<ModeRegistry>,
+    config: Res<ActiveConfig>,
+) {
+    let crashes_end_run = registry.current(&modifiers).rules.crashes_end_run();
+
+    for (mut transform, Collider(collider), mut velocity, mut bird) in &mut query {
+        if *bird != BirdState::Flying {
+            continue;
+        }
+
+        if transform.translation.y > WORLD_EDGE && modifiers.ceiling == CeilingBehavior::Bonk {
+            transform.translation.y = WORLD_EDGE;
+            velocity.0 = -config.0.bonk_knockdown;
+        }
+
+        let bot = offset_aabb(collider, &transform.translation);
+        let hit_pipe = || {
+            pipes.iter().any(|(parent, t, Collider(pipe_collider))| {
+                let Ok((obstacle, visibility)) = obstacles.get(parent.get()) else {
+                    return false;
+                };
+                let pipe = pipe_aabb(pipe_collider, obstacle, t);
+                visibility != Visibility::Hidden && touch_pipe(&bot, pipe, t.rotation).is_some()
+            })
+        };
+
+        let out_of_bounds = bot.center().y > WORLD_EDGE || touch_ground(&bot, &grounds).is_some();
+        if out_of_bounds || (crashes_end_run && hit_pipe()) {
+            start_dying(&mut velocity, &mut bird);
+        }
+    }
+}
+
+// Counted the way the player's pipes are, but every obstacle comes back
+// around, so it's let go of again once it's ahead of the bot
+fn overtake_pipes(
+    mut commands: Commands,
+    mut query: Query<(&mut Opponent, &Transform, &BirdState)>,
+    obstacles: Query<(Entity, &Transform, Has<Overtaken>), With<Obstacle>>,
+) {
+    for (mut opponent, bot, state) in &mut query {
+        for (entity, transform, overtaken) in &obstacles {
+            let behind = transform.translation.x < bot.translation.x;
+            if behind && !overtaken {
+                commands.entity(entity).insert(Overtaken);
+                if *state == BirdState::Flying {
+                    opponent.pipes += 1;
+                }
+            } else if !behind && overtaken {
+                commands.entity(entity).remove::<Overtaken>();
+            }
+        }
+    }
+}
+
+fn tilt_opponent(
+    mut query: Query<(&mut Transform, &Velocity, &BirdState), With<Opponent>>,
+    physics: Res<ActivePhysics>,
+) {
+    for (mut transform, velocity, state) in &mut query {
+        if state.falls() {
+            tilt(&mut transform, velocity, &physics.0);
+        }
+    }
+}
+
+fn pick_opponent(keys: Res<ButtonInput<KeyCode>>, mut profile: ResMut<Profile>) {
+    if keys.just_pressed(KeyCode::KeyI) {
+        profile.opponent = BotLevel::next(profile.opponent);
+    }
+}
+
+fn label(profile: &Profile) -> String {
+    let opponent = profile.opponent.map_or("off", BotLevel::name);
+    format!("I opponent: {opponent}")
+}
+
+fn spawn_label(mut commands: Commands, profile: Res<Profile>) {
+    commands.spawn((
+        OpponentLabel,
+        MenuRow::Opponent,
+        TextBundle::from_section(
+            label(&profile),
+            TextStyle {
+                font_size: 14.,
+                color: Color::WHITE,
+                ..default()
+            },
+        ),
+    ));
+}
+
+fn draw_label(profile: Res<Profile>, mut query: Query<&mut Text, With<OpponentLabel>>) {
+    for mut text in &mut query {
+        text.sections[0].value = label(&profile);
+    }
+}
+
+// Whoever's still in the air when the other one crashes wins, so a bot that
+// went down on the same step as the player has lost
+fn spawn_result(mut commands: Commands, query: Query<(&Opponent, &BirdState)>) {
+    let Ok((opponent, state)) = query.get_single() else {
+        return;
+    };
+
+    let name = opponent.level.name();
+    let result = if *state == BirdState::Flying {
+        format!(
+            "The {name} bot outlasted you, it's past {} and still going",
+            opponent.pipes
+        )
+    } else {
+        format!(
+            "You beat the {name} bot, it made it past {}",
+            opponent.pipes
+        )
+    };
+
+    commands.spawn((
+        OpponentResult,
+        TextBundle::from_section(
+            result,
+            TextStyle {
+                font_size: 12.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(72.),
+            left: Val::Px(8.),
+            ..default()
+        }),
+    ));
+}
+
+fn despawn<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
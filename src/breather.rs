@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+
+use crate::{
+    behaviors::{pose_pipes, set_behaviors},
+    difficulty::Difficulty,
+    main_menu::MenuRow,
+    replay::Playback,
+    snapshot::OnSnapshotRestored,
+    tutorials::{AddTutorial, Tutorial, BETWEEN_PIPES},
+    AppState, Atlas, Obstacle, Pattern, Pipe, Player, RunModifiers, SimSet, BREATHER, PIPE_WIDTH,
+};
+
+// How many columns the bird gets to find its feet again over
+const BREATHER_COLUMNS: u32 = 3;
+// How much wider than usual their gaps are
+const BREATHER_WIDENING: f32 = 24.;
+
+/// How many more columns are left with an easy gap
+#[derive(Resource, Default)]
+pub struct Breather(u32);
+
+impl Breather {
+    /// Where the next column's top pipe goes and how tall its gap is, if it's
+    /// one of the easy ones. The gap goes right in the middle of the screen
+    pub fn gap(&mut self, pipe_space: f32) -> Option<(f32, f32)> {
+        if self.0 == 0 {
+            return None;
+        }
+
+        self.0 -= 1;
+        let space = pipe_space + BREATHER_WIDENING;
+        Some((80. + space / 2., space))
+    }
+}
+
+#[derive(Component)]
+struct BreatherLabel;
+
+pub struct BreatherPlugin;
+
+impl Plugin for BreatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_tutorial(Tutorial {
+            id: BREATHER,
+            title: "Breather",
+            text: "The next few gaps after a run's picked back up are wide and centered",
+            art: Atlas::PipeTop,
+            example: BETWEEN_PIPES,
+        })
+        .init_resource::<Breather>()
+        .add_systems(
+            OnEnter(AppState::MainMenu),
+            spawn_label.run_if(not(resource_exists::<Playback>)),
+        )
+        .add_systems(
+            Update,
+            (
+                toggle_breather,
+                draw_label.run_if(resource_changed::<RunModifiers>),
+            )
+                .chain()
+                .run_if(in_state(AppState::MainMenu).and_then(not(resource_exists::<Playback>))),
+        )
+        .add_systems(OnExit(AppState::MainMenu), despawn_label)
+        .add_systems(OnEnter(AppState::Playing), reset_breather)
+        .add_systems(
+            FixedUpdate,
+            take_breather
+                .in_set(SimSet::Input)
+                .run_if(in_state(AppState::Playing).and_then(breathing)),
+        );
+    }
+}
+
+fn breathing(modifiers: Res<RunModifiers>) -> bool {
+    modifiers.breather
+}
+
+// Like the assist, it's one of the run's modifiers so it's only ever ranked
+// against other runs with it
+fn toggle_breather(keys: Res<ButtonInput<KeyCode>>, mut modifiers: ResMut<RunModifiers>) {
+    if keys.just_pressed(KeyCode::KeyU) {
+        modifiers.breather = !modifiers.breather;
+    }
+}
+
+fn spawn_label(mut commands: Commands) {
+    commands.spawn((
+        BreatherLabel,
+        MenuRow::Breather,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 14.,
+                color: Color::WHITE,
+                ..default()
+            },
+        ),
+    ));
+}
+
+fn despawn_label(mut commands: Commands, query: Query<Entity, With<BreatherLabel>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn draw_label(modifiers: Res<RunModifiers>, mut query: Query<&mut Text, With<BreatherLabel>>) {
+    let state = if modifiers.breather { "on" } else { "off" };
+    for mut text in &mut query {
+        text.sections[0].value = format!("U breather: {state}");
+    }
+}
+
+// Whatever's left over from a run before doesn't carry into the next one
+fn reset_breather(mut breather: ResMut<Breather>) {
+    breather.0 = 0;
+}
+
+// The columns the bird hasn't got to yet are eased straight away, and any
+// that are still to come get theirs as they come back around
+fn take_breather(
+    mut commands: Commands,
+    mut reader: EventReader<OnSnapshotRestored>,
+    mut breather: ResMut<Breather>,
+    mut obstacles: Query<(Entity, &mut Transform, &mut Pattern, &Children), With<Obstacle>>,
+    mut pipes: Query<(&Pipe, &mut Transform), Without<Obstacle>>,
+    player: Query<&Transform, (With<Player>, Without<Obstacle>, Without<Pipe>)>,
+    difficulty: Res<Difficulty>,
+) {
+    if reader.read().count() == 0 {
+        return;
+    }
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+
+    breather.0 = BREATHER_COLUMNS;
+    let mut ahead = obstacles
+        .iter_mut()
+        .filter(|(_, transform, ..)| {
+            transform.translation.x - PIPE_WIDTH / 2. > player.translation.x
+        })
+        .collect::<Vec<_>>();
+    ahead.sort_by(|(_, a, ..), (_, b, ..)| a.translation.x.total_cmp(&b.translation.x));
+
+    for (entity, mut transform, mut pattern, children) in ahead {
+        let Some((height, space)) = breather.gap(difficulty.pipe_space) else {
+            break;
+        };
+        transform.translation.y = height;
+        *pattern = Pattern::Regular;
+        pose_pipes(children, &mut pipes, space, 0.);
+        set_behaviors(&mut commands.entity(entity), &[]);
+    }
+}
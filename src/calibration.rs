@@ -0,0 +1,240 @@
+use bevy::prelude::*;
+
+use crate::{
+    actions::{Action, Actions},
+    mixer::{Channel, PlaySound},
+    profile::Profile,
+    AppState,
+};
+
+const TICK_SOUND: &str = "sounds/tick.wav";
+// Seconds between clicks
+const BEAT: f32 = 0.5;
+// Clicks played before taps start counting, to find the beat
+const LEAD_IN: u32 = 4;
+// Taps the offset is worked out from
+const TAPS: usize = 12;
+// Taps further than this from a click are the player losing the beat
+const MAX_OFFSET: f32 = BEAT / 2.;
+// How big the dot gets on a click, and how fast it shrinks back
+const PULSE_SIZE: f32 = 1.6;
+const PULSE_RATE: f32 = 6.;
+
+/// Tapping along to clicks to work out how much later than the sound the
+/// player's taps come in, with the time it takes to hear a sound and the
+/// time it takes for a tap to arrive rolled into one
+#[derive(Resource, Default)]
+struct Calibration {
+    /// When the first click was played, in real seconds
+    started: f32,
+    clicks: u32,
+    /// How far after its click each tap came in, in seconds
+    taps: Vec<f32>,
+}
+
+impl Calibration {
+    /// The offset worked out from the taps, once there are enough of them.
+    /// The middle one, so a few stray taps don't pull it off
+    fn offset(&self) -> Option<f32> {
+        if self.taps.len() < TAPS {
+            return None;
+        }
+        let mut taps = self.taps.clone();
+        taps.sort_by(f32::total_cmp);
+        Some(taps[taps.len() / 2])
+    }
+}
+
+#[derive(Component)]
+struct CalibrationScreen;
+
+#[derive(Component)]
+struct Pulse;
+
+pub struct CalibrationPlugin;
+
+impl Plugin for CalibrationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Calibration>()
+            .add_systems(OnEnter(AppState::Calibration), start_calibration)
+            .add_systems(
+                Update,
+                (
+                    tick,
+                    tap,
+                    pulse,
+                    draw_calibration.run_if(resource_changed::<Calibration>),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Calibration)),
+            )
+            .add_systems(OnExit(AppState::Calibration), close_calibration);
+    }
+}
+
+fn start_calibration(
+    mut commands: Commands,
+    mut calibration: ResMut<Calibration>,
+    time: Res<Time<Real>>,
+) {
+    *calibration = Calibration {
+        started: time.elapsed_seconds(),
+        ..default()
+    };
+
+    commands.spawn((
+        CalibrationScreen,
+        Pulse,
+        NodeBundle {
+            style: Style {
+                width: Val::Px(16.),
+                height: Val::Px(16.),
+                position_type: PositionType::Absolute,
+                left: Val::Percent(50.),
+                top: Val::Percent(60.),
+                ..default()
+            },
+            background_color: Color::WHITE.into(),
+            // Over the screen's backdrop
+            z_index: ZIndex::Global(1),
+            ..default()
+        },
+    ));
+}
+
+fn close_calibration(mut commands: Commands, query: Query<Entity, With<CalibrationScreen>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Counted in real time, the clicks have to keep to the beat whatever the
+// run's clock is doing
+fn tick(
+    mut calibration: ResMut<Calibration>,
+    mut sounds: EventWriter<PlaySound>,
+    mut pulse: Query<&mut Transform, With<Pulse>>,
+    time: Res<Time<Real>>,
+) {
+    // Done clicking once there's an offset
+    if calibration.offset().is_some() {
+        return;
+    }
+
+    let clock = time.elapsed_seconds() - calibration.started;
+    if clock < calibration.clicks as f32 * BEAT {
+        return;
+    }
+
+    calibration.clicks += 1;
+    sounds.send(PlaySound::new(TICK_SOUND, Channel::Ui));
+    for mut transform in &mut pulse {
+        transform.scale = Vec3::splat(PULSE_SIZE);
+    }
+}
+
+// Taps are measured from the closest click, so a tap just before one counts
+// as early rather than very late
+fn tap(
+    mut calibration: ResMut<Calibration>,
+    mut profile: ResMut<Profile>,
+    mut actions: ResMut<Actions>,
+    mut state: ResMut<NextState<AppState>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    time: Res<Time<Real>>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        state.set(AppState::Settings);
+        return;
+    }
+
+    if let Some(offset) = calibration.offset() {
+        if keys.just_pressed(KeyCode::Enter) {
+            profile.input_offset = offset;
+            state.set(AppState::Settings);
+        } else if keys.just_pressed(KeyCode::KeyR) {
+            *calibration = Calibration {
+                started: time.elapsed_seconds(),
+                ..default()
+            };
+        }
+        return;
+    }
+
+    if !actions.take(Action::Flap) || calibration.clicks <= LEAD_IN {
+        return;
+    }
+    let clock = time.elapsed_seconds() - calibration.started;
+    let offset = clock - (clock / BEAT).round() * BEAT;
+    if offset.abs() < MAX_OFFSET {
+        calibration.taps.push(offset);
+    }
+}
+
+fn pulse(mut query: Query<&mut Transform, With<Pulse>>, time: Res<Time<Real>>) {
+    for mut transform in &mut query {
+        let shrink = (PULSE_RATE * time.delta_seconds()).min(1.);
+        transform.scale = transform.scale.lerp(Vec3::ONE, shrink);
+    }
+}
+
+fn draw_calibration(
+    mut commands: Commands,
+    calibration: Res<Calibration>,
+    profile: Res<Profile>,
+    query: Query<Entity, (With<CalibrationScreen>, Without<Pulse>)>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let text = |value: String, size: f32, color: Color| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size: size,
+                color,
+                ..default()
+            },
+        )
+    };
+
+    let (status, hint) = match calibration.offset() {
+        Some(offset) => (
+            format!("Offset {:+.0} ms", offset * 1000.),
+            "Enter keep, R again, Esc back",
+        ),
+        None if calibration.clicks <= LEAD_IN => ("Listen for the beat...".to_string(), "Esc back"),
+        None => (
+            format!("Taps {}/{TAPS}", calibration.taps.len()),
+            "Flap along to the clicks, Esc back",
+        ),
+    };
+
+    commands
+        .spawn((
+            CalibrationScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(12.)),
+                    row_gap: Val::Px(4.),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.8).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("Timing".to_string(), 24., Color::WHITE));
+            parent.spawn(text(
+                format!("Now {:+.0} ms", profile.input_offset * 1000.),
+                14.,
+                Color::WHITE,
+            ));
+            parent.spawn(text(status, 14., Color::YELLOW));
+            parent.spawn(text(hint.to_string(), 12., Color::GRAY));
+        });
+}
@@ -0,0 +1,120 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    effects::{ActiveEffects, ShakeSettings},
+    settings::Accessibility,
+    AppState, Root,
+};
+
+// Half the size of the world, which is all the camera sees when it's not
+// zoomed in
+const WORLD_HALF_SIZE: Vec2 = Vec2::new(72., 128.);
+
+/// How shaken up the view is right now. Cues add to it as things happen and
+/// the game over screen starts it off at full
+#[derive(Resource)]
+pub struct Shake {
+    /// From 1 down to 0
+    trauma: f32,
+    /// How the shake that was added last moves
+    settings: ShakeSettings,
+    direction: Vec2,
+    /// Seconds until it jerks in a new direction
+    next_jerk: f32,
+}
+
+impl Default for Shake {
+    fn default() -> Self {
+        Self {
+            trauma: 0.,
+            settings: ShakeSettings::default(),
+            direction: Vec2::ZERO,
+            next_jerk: 0.,
+        }
+    }
+}
+
+impl Shake {
+    /// Shakes harder by `amount`, moving the way `settings` say from now on
+    pub fn add(&mut self, settings: ShakeSettings, amount: f32) {
+        self.settings = settings;
+        self.trauma = (self.trauma + amount).min(1.);
+    }
+}
+
+pub struct CameraShakePlugin;
+
+impl Plugin for CameraShakePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Shake>()
+            .add_systems(OnEnter(AppState::GameOver), shake_on_death)
+            .add_systems(Update, shake_world)
+            .add_systems(OnExit(AppState::GameOver), stop_shake);
+    }
+}
+
+fn shake_on_death(
+    mut shake: ResMut<Shake>,
+    effects: Res<ActiveEffects>,
+    accessibility: Res<Accessibility>,
+) {
+    if accessibility.screen_shake {
+        shake.add(effects.0.death_shake, 1.);
+    }
+}
+
+fn stop_shake(mut shake: ResMut<Shake>) {
+    shake.trauma = 0.;
+}
+
+// The world is moved rather than the camera, which the kill cam is in charge
+// of. Everything in the run goes by local positions so this is only visual.
+// It's blown up around the camera just enough to cover how far it moves, so
+// the shake never shows past the edges of the world
+pub fn shake_world(
+    mut shake: ResMut<Shake>,
+    mut root: Query<&mut Transform, With<Root>>,
+    camera: Query<(&Transform, &OrthographicProjection), (With<Camera>, Without<Root>)>,
+    time: Res<Time>,
+) {
+    let Ok(mut transform) = root.get_single_mut() else {
+        return;
+    };
+    let Ok((camera, projection)) = camera.get_single() else {
+        return;
+    };
+    let ShakeSettings {
+        amplitude,
+        frequency,
+        decay,
+    } = shake.settings;
+
+    shake.trauma = (shake.trauma - decay * time.delta_seconds()).max(0.);
+    if shake.trauma <= 0. {
+        transform.translation = Vec3::new(0., 0., transform.translation.z);
+        transform.scale = Vec3::ONE;
+        return;
+    }
+
+    shake.next_jerk -= time.delta_seconds();
+    if shake.next_jerk <= 0. {
+        let mut rng = rand::thread_rng();
+        shake.direction = Vec2::from_angle(rng.gen_range(0. ..std::f32::consts::TAU));
+        shake.next_jerk = 1. / frequency;
+    }
+
+    // Squared so small bumps stay subtle
+    let reach = shake.trauma * shake.trauma * amplitude;
+    let center = camera.translation.xy();
+    let view = WORLD_HALF_SIZE * projection.scale;
+    let zoom = 1. + reach / view.min_element();
+    // How far the world can move before its edges come into view, from the
+    // room left between the camera and the edges once it's blown up
+    let room = WORLD_HALF_SIZE - center.abs();
+    let bounds = (room * zoom - view).max(Vec2::ZERO);
+
+    let offset = (shake.direction * reach).clamp(-bounds, bounds);
+    transform.translation = (center * (1. - zoom) + offset).extend(transform.translation.z);
+    transform.scale = Vec3::new(zoom, zoom, 1.);
+}
@@ -0,0 +1,482 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    behaviors::{set_behaviors, Behavior},
+    bonus::spawn_coin,
+    create_world,
+    custom_levels::CustomLevels,
+    levels::{LevelBest, LevelGoal, StarTargets},
+    modes::CLASSIC,
+    portals::spawn_portal,
+    profile::Profile,
+    replay::Playback,
+    ron_asset::RonLoader,
+    scoring::{PipeScore, PipeTag},
+    spawn_obstacle, AppState, BuildWorld, NextSeed, Obstacle, Root, RunModifiers, SpriteSheet,
+    FIRST_PIPE_X, PIPE_SPACE, PIPE_TO_PIPE_SPACE,
+};
+
+const CAMPAIGN_FILE: &str = "campaign.levels.ron";
+// How many levels there are to a row on the map
+const MAP_COLUMNS: usize = 4;
+const MEDAL_SIZE: f32 = 8.;
+
+fn default_gap() -> f32 {
+    PIPE_SPACE
+}
+
+fn default_spacing() -> f32 {
+    PIPE_TO_PIPE_SPACE
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+/// One of the pipes of a level, in the order they come up
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LevelPipe {
+    /// Height of the top pipe, anything from 48 to 154 keeps the gap on screen
+    pub y: f32,
+    #[serde(default = "default_gap")]
+    pub gap: f32,
+    #[serde(default)]
+    pub behaviors: Vec<Behavior>,
+    /// How many times the usual points getting through it is worth
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+    #[serde(default)]
+    pub tags: Vec<PipeTag>,
+}
+
+/// Takes the player from one height to another partway through a level
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct LevelPortal {
+    /// Measured from the first pipe, like coins
+    pub x: f32,
+    pub from: f32,
+    pub to: f32,
+}
+
+/// A finite run that's laid out by hand instead of at random
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Level {
+    /// What bests and replays of the level are saved under, so it can't
+    /// change once released
+    pub id: String,
+    pub name: String,
+    /// For whatever is still left to chance, like the roulette
+    #[serde(default)]
+    pub seed: u64,
+    /// How far apart the pipes are
+    #[serde(default = "default_spacing")]
+    pub spacing: f32,
+    pub pipes: Vec<LevelPipe>,
+    /// Where coins are, with x measured from the first pipe
+    #[serde(default)]
+    pub coins: Vec<(f32, f32)>,
+    #[serde(default)]
+    pub portals: Vec<LevelPortal>,
+    pub stars: StarTargets,
+}
+
+impl Level {
+    pub fn goal(&self) -> LevelGoal {
+        // Halfway past the last pipe
+        let pipes = self.pipes.len() as f32;
+        LevelGoal {
+            id: self.id.clone(),
+            length: FIRST_PIPE_X + (pipes - 0.5) * self.spacing,
+            stars: self.stars,
+        }
+    }
+}
+
+/// The levels that come with the game, in the order they're unlocked in
+#[derive(Asset, TypePath, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Campaign {
+    pub levels: Vec<Level>,
+}
+
+#[derive(Resource)]
+struct CampaignHandle(Handle<Campaign>);
+
+/// The campaign's levels, none until the file has loaded
+#[derive(SystemParam)]
+pub struct CampaignLevels<'w> {
+    handle: Res<'w, CampaignHandle>,
+    campaigns: Res<'w, Assets<Campaign>>,
+    custom: Res<'w, CustomLevels>,
+}
+
+impl CampaignLevels<'_> {
+    pub fn all(&self) -> &[Level] {
+        self.campaigns
+            .get(&self.handle.0)
+            .map(|campaign| campaign.levels.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Looks through the player's own levels too, which are played the same way
+    pub fn get(&self, id: &str) -> Option<&Level> {
+        self.all()
+            .iter()
+            .chain(&self.custom.levels)
+            .find(|level| level.id == id)
+    }
+}
+
+/// How well a level has gone at best
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Medal {
+    Bronze,
+    Silver,
+    Gold,
+}
+
+impl Medal {
+    fn new(best: &LevelBest) -> Option<Self> {
+        match best.stars {
+            0 => None,
+            1 => Some(Medal::Bronze),
+            2 => Some(Medal::Silver),
+            _ => Some(Medal::Gold),
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            Medal::Bronze => Color::rgb(0.8, 0.5, 0.2),
+            Medal::Silver => Color::SILVER,
+            Medal::Gold => Color::GOLD,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct LevelMap {
+    selected: usize,
+}
+
+#[derive(Component)]
+struct MapScreen;
+
+pub struct CampaignPlugin;
+
+impl Plugin for CampaignPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Campaign>()
+            .register_asset_loader(RonLoader::<Campaign>::new(&["levels.ron"]));
+
+        let handle = app.world.resource::<AssetServer>().load(CAMPAIGN_FILE);
+        app.insert_resource(CampaignHandle(handle))
+            .init_resource::<LevelMap>()
+            .add_systems(
+                BuildWorld,
+                lay_out_level.after(create_world).run_if(in_level),
+            )
+            .add_systems(
+                OnExit(AppState::LevelComplete),
+                back_to_map.run_if(in_campaign.and_then(not(resource_exists::<Playback>))),
+            )
+            .add_systems(
+                Update,
+                open_map.run_if(
+                    in_state(AppState::MainMenu).and_then(not(resource_exists::<Playback>)),
+                ),
+            )
+            .add_systems(OnEnter(AppState::LevelSelect), select_current_level)
+            .add_systems(
+                Update,
+                (
+                    browse_map,
+                    draw_map.run_if(
+                        resource_changed::<LevelMap>.or_else(on_event::<AssetEvent<Campaign>>()),
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::LevelSelect)),
+            )
+            .add_systems(OnExit(AppState::LevelSelect), close_map);
+    }
+}
+
+/// Run condition for when the run to be played is one of the campaign's levels
+pub fn in_campaign(modifiers: Res<RunModifiers>) -> bool {
+    modifiers.level.is_some() && modifiers.mode == CLASSIC
+}
+
+/// Run condition for when the run to be played is laid out by hand, whether
+/// it's from the campaign or one of the player's own levels
+pub fn in_level(modifiers: Res<RunModifiers>) -> bool {
+    modifiers.level.is_some()
+}
+
+/// Whether `level` can be played yet, which takes finishing the one before it
+fn unlocked(levels: &[Level], profile: &Profile, level: usize) -> bool {
+    level == 0 || profile.level_bests.contains_key(&levels[level - 1].id)
+}
+
+// A finished level goes back to the map, where a crashed one is tried again
+fn back_to_map(mut state: ResMut<NextState<AppState>>) {
+    state.set(AppState::LevelSelect);
+}
+
+// Swaps the randomly laid out pipes of the new world for the level's own
+fn lay_out_level(
+    mut commands: Commands,
+    campaign: CampaignLevels,
+    modifiers: Res<RunModifiers>,
+    sheet: Res<SpriteSheet>,
+    obstacles: Query<Entity, With<Obstacle>>,
+    root: Query<Entity, With<Root>>,
+) {
+    let Some(level) = modifiers.level.as_deref().and_then(|id| campaign.get(id)) else {
+        return;
+    };
+
+    for entity in &obstacles {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    commands.entity(root.single()).with_children(|parent| {
+        for (i, pipe) in level.pipes.iter().enumerate() {
+            let x = FIRST_PIPE_X + i as f32 * level.spacing;
+            let mut obstacle = spawn_obstacle(parent, &sheet, Vec3::new(x, pipe.y, 1.), pipe.gap);
+            set_behaviors(&mut obstacle, &pipe.behaviors);
+            obstacle.insert(PipeScore {
+                weight: pipe.weight,
+                tags: pipe.tags.clone(),
+            });
+        }
+
+        for &(x, y) in &level.coins {
+            spawn_coin(parent, &sheet.hitboxes, Vec2::new(FIRST_PIPE_X + x, y));
+        }
+
+        for portal in &level.portals {
+            spawn_portal(
+                parent,
+                &sheet.hitboxes,
+                FIRST_PIPE_X + portal.x,
+                portal.from,
+                portal.to,
+            );
+        }
+    });
+}
+
+fn open_map(keys: Res<ButtonInput<KeyCode>>, mut state: ResMut<NextState<AppState>>) {
+    if keys.just_pressed(KeyCode::KeyM) {
+        state.set(AppState::LevelSelect);
+    }
+}
+
+// Starts on the level that was played last, or the first one that's still
+// waiting to be finished
+fn select_current_level(
+    mut map: ResMut<LevelMap>,
+    campaign: CampaignLevels,
+    modifiers: Res<RunModifiers>,
+    profile: Res<Profile>,
+) {
+    let levels = campaign.all();
+    map.selected = modifiers
+        .level
+        .as_deref()
+        .and_then(|id| levels.iter().position(|level| level.id == id))
+        .or_else(|| {
+            levels
+                .iter()
+                .position(|level| !profile.level_bests.contains_key(&level.id))
+        })
+        .unwrap_or(0);
+}
+
+fn close_map(mut commands: Commands, query: Query<Entity, With<MapScreen>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn browse_map(
+    mut map: ResMut<LevelMap>,
+    campaign: CampaignLevels,
+    profile: Res<Profile>,
+    mut modifiers: ResMut<RunModifiers>,
+    mut next_seed: ResMut<NextSeed>,
+    mut state: ResMut<NextState<AppState>>,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        modifiers.level = None;
+        modifiers.mode.clone_from(&profile.mode);
+        state.set(AppState::MainMenu);
+        return;
+    }
+
+    let levels = campaign.all();
+    if levels.is_empty() {
+        return;
+    }
+
+    let last = levels.len() - 1;
+    if keys.just_pressed(KeyCode::ArrowLeft) {
+        map.selected = map.selected.saturating_sub(1);
+    }
+    if keys.just_pressed(KeyCode::ArrowRight) {
+        map.selected = (map.selected + 1).min(last);
+    }
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        map.selected = map.selected.saturating_sub(MAP_COLUMNS);
+    }
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        map.selected = (map.selected + MAP_COLUMNS).min(last);
+    }
+
+    if keys.just_pressed(KeyCode::Enter) && unlocked(levels, &profile, map.selected) {
+        let level = &levels[map.selected];
+        // Levels are played by the classic rules, whatever mode was picked
+        modifiers.mode = CLASSIC.to_string();
+        modifiers.level = Some(level.id.clone());
+        next_seed.0 = Some(level.seed);
+        state.set(AppState::MainMenu);
+    }
+}
+
+fn draw_map(
+    mut commands: Commands,
+    map: Res<LevelMap>,
+    campaign: CampaignLevels,
+    profile: Res<Profile>,
+    query: Query<Entity, With<MapScreen>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let text = |value: String, size: f32, color: Color| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size: size,
+                color,
+                ..default()
+            },
+        )
+    };
+
+    let levels = campaign.all();
+    commands
+        .spawn((
+            MapScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(12.)),
+                    row_gap: Val::Px(8.),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.8).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("Campaign".to_string(), 24., Color::WHITE));
+
+            if levels.is_empty() {
+                parent.spawn(text("No levels yet".to_string(), 14., Color::GRAY));
+            }
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_wrap: FlexWrap::Wrap,
+                        column_gap: Val::Px(6.),
+                        row_gap: Val::Px(6.),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (i, level) in levels.iter().enumerate() {
+                        let open = unlocked(levels, &profile, i);
+                        let medal = profile.level_bests.get(&level.id).and_then(Medal::new);
+                        let border = if i == map.selected {
+                            Color::YELLOW
+                        } else {
+                            Color::NONE
+                        };
+                        let background = if open {
+                            Color::rgb(0.2, 0.5, 0.3)
+                        } else {
+                            Color::DARK_GRAY
+                        };
+
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    width: Val::Px(54.),
+                                    height: Val::Px(40.),
+                                    flex_direction: FlexDirection::Column,
+                                    align_items: AlignItems::Center,
+                                    justify_content: JustifyContent::Center,
+                                    row_gap: Val::Px(2.),
+                                    border: UiRect::all(Val::Px(2.)),
+                                    ..default()
+                                },
+                                background_color: background.into(),
+                                border_color: border.into(),
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                let label = if open {
+                                    (i + 1).to_string()
+                                } else {
+                                    "Locked".to_string()
+                                };
+                                parent.spawn(text(label, 12., Color::WHITE));
+                                if let Some(medal) = medal {
+                                    parent.spawn(NodeBundle {
+                                        style: Style {
+                                            width: Val::Px(MEDAL_SIZE),
+                                            height: Val::Px(MEDAL_SIZE),
+                                            ..default()
+                                        },
+                                        background_color: medal.color().into(),
+                                        ..default()
+                                    });
+                                }
+                            });
+                    }
+                });
+
+            if let Some(level) = levels.get(map.selected) {
+                parent.spawn(text(level.name.clone(), 16., Color::WHITE));
+                let details = match profile.level_bests.get(&level.id) {
+                    Some(best) => format!(
+                        "Best {} stars, score {}, {:.1}s, {} coins",
+                        best.stars,
+                        best.score,
+                        best.time.unwrap_or_default(),
+                        best.coins
+                    ),
+                    None if unlocked(levels, &profile, map.selected) => {
+                        "Not finished yet".to_string()
+                    }
+                    None => "Finish the level before to unlock it".to_string(),
+                };
+                parent.spawn(text(details, 12., Color::GRAY));
+            }
+
+            parent.spawn(text(
+                "Arrows pick, Enter play, Esc back".to_string(),
+                12.,
+                Color::GRAY,
+            ));
+        });
+}
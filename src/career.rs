@@ -0,0 +1,305 @@
+use std::collections::BTreeSet;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bonus::OnCoinCollected, profile::Profile, replay::Playback, AppState, Obstacle, Pipe,
+    PipePassed,
+};
+
+// How close to the middle of a gap the bird has to go through it for it to
+// count as perfect
+const PERFECT_MARGIN: f32 = 2.;
+// How wide a progress bar is when it's full
+const BAR_WIDTH: f32 = 120.;
+
+/// What the player has done over every run they've played
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Career {
+    pub pipes: u32,
+    pub coins: u32,
+    /// Pipes gone through right down the middle of the gap
+    pub perfects: u32,
+    /// Ids of the milestones whose rewards have been claimed
+    pub claimed: BTreeSet<String>,
+}
+
+/// Something to work towards across runs, with something to wear once it's
+/// reached and claimed
+struct Milestone {
+    /// What the profile saves it as, so it can't change once released
+    id: &'static str,
+    title: &'static str,
+    target: u32,
+    progress: fn(&Career) -> u32,
+    /// Name of the accessory it unlocks
+    reward: &'static str,
+}
+
+const MILESTONES: [Milestone; 3] = [
+    Milestone {
+        id: "pipes-10k",
+        title: "Fly through 10,000 pipes",
+        target: 10_000,
+        progress: |career| career.pipes,
+        reward: "Aviator cap",
+    },
+    Milestone {
+        id: "coins-1k",
+        title: "Collect 1,000 coins",
+        target: 1_000,
+        progress: |career| career.coins,
+        reward: "Gold scarf",
+    },
+    Milestone {
+        id: "perfects-100",
+        title: "Go through 100 gaps dead center",
+        target: 100,
+        progress: |career| career.perfects,
+        reward: "Halo",
+    },
+];
+
+impl Milestone {
+    fn reached(&self, career: &Career) -> bool {
+        (self.progress)(career) >= self.target
+    }
+}
+
+/// What's been done in the run so far, added to the career once it's over
+#[derive(Resource, Default)]
+struct Tally {
+    pipes: u32,
+    coins: u32,
+    perfects: u32,
+}
+
+#[derive(Resource, Default)]
+struct CareerScreen {
+    selected: usize,
+}
+
+#[derive(Component)]
+struct CareerOverlay;
+
+pub struct CareerPlugin;
+
+impl Plugin for CareerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tally>()
+            .init_resource::<CareerScreen>()
+            .add_systems(
+                Update,
+                (count_pipes, count_coins)
+                    .run_if(in_state(AppState::Playing).and_then(not(resource_exists::<Playback>))),
+            )
+            .add_systems(OnEnter(AppState::GameOver), add_up_run)
+            .add_systems(OnEnter(AppState::LevelComplete), add_up_run)
+            .add_systems(
+                Update,
+                open_career.run_if(
+                    in_state(AppState::MainMenu).and_then(not(resource_exists::<Playback>)),
+                ),
+            )
+            .add_systems(OnEnter(AppState::Career), reset_selection)
+            .add_systems(
+                Update,
+                (
+                    browse_career,
+                    draw_career.run_if(
+                        resource_changed::<CareerScreen>.or_else(resource_changed::<Profile>),
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Career)),
+            )
+            .add_systems(OnExit(AppState::Career), close_career);
+    }
+}
+
+// Right down the middle is halfway between the top and bottom pipes,
+// wherever they've moved to
+fn count_pipes(
+    mut tally: ResMut<Tally>,
+    mut reader: EventReader<PipePassed>,
+    obstacles: Query<(&Transform, &Children), With<Obstacle>>,
+    pipes: Query<&Transform, With<Pipe>>,
+) {
+    for passed in reader.read() {
+        tally.pipes += 1;
+
+        let Ok((transform, children)) = obstacles.get(passed.obstacle) else {
+            continue;
+        };
+        let ends = pipes.iter_many(children).collect::<Vec<_>>();
+        if ends.is_empty() {
+            continue;
+        }
+        let middle = transform.translation.y
+            + ends.iter().map(|pipe| pipe.translation.y).sum::<f32>() / ends.len() as f32;
+        if (passed.position.y - middle).abs() <= PERFECT_MARGIN {
+            tally.perfects += 1;
+        }
+    }
+}
+
+fn count_coins(mut tally: ResMut<Tally>, mut reader: EventReader<OnCoinCollected>) {
+    tally.coins += reader.read().count() as u32;
+}
+
+// Only once the run's over so the profile isn't saved on every pipe
+fn add_up_run(mut tally: ResMut<Tally>, mut profile: ResMut<Profile>) {
+    let Tally {
+        pipes,
+        coins,
+        perfects,
+    } = std::mem::take(tally.as_mut());
+    if pipes == 0 && coins == 0 {
+        return;
+    }
+
+    let career = &mut profile.career;
+    career.pipes += pipes;
+    career.coins += coins;
+    career.perfects += perfects;
+}
+
+fn open_career(keys: Res<ButtonInput<KeyCode>>, mut state: ResMut<NextState<AppState>>) {
+    if keys.just_pressed(KeyCode::KeyJ) {
+        state.set(AppState::Career);
+    }
+}
+
+fn reset_selection(mut screen: ResMut<CareerScreen>) {
+    screen.selected = 0;
+}
+
+fn close_career(mut commands: Commands, query: Query<Entity, With<CareerOverlay>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn browse_career(
+    mut screen: ResMut<CareerScreen>,
+    mut profile: ResMut<Profile>,
+    mut state: ResMut<NextState<AppState>>,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        state.set(AppState::MainMenu);
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        screen.selected = screen.selected.saturating_sub(1);
+    }
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        screen.selected = (screen.selected + 1).min(MILESTONES.len() - 1);
+    }
+
+    let milestone = &MILESTONES[screen.selected];
+    let claimable =
+        milestone.reached(&profile.career) && !profile.career.claimed.contains(milestone.id);
+    if keys.just_pressed(KeyCode::Enter) && claimable {
+        profile.career.claimed.insert(milestone.id.to_string());
+    }
+}
+
+fn draw_career(
+    mut commands: Commands,
+    screen: Res<CareerScreen>,
+    profile: Res<Profile>,
+    query: Query<Entity, With<CareerOverlay>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let text = |value: String, size: f32, color: Color| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size: size,
+                color,
+                ..default()
+            },
+        )
+    };
+    let career = &profile.career;
+
+    commands
+        .spawn((
+            CareerOverlay,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(12.)),
+                    row_gap: Val::Px(4.),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.8).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("Career".to_string(), 24., Color::WHITE));
+
+            for (i, milestone) in MILESTONES.iter().enumerate() {
+                let progress = (milestone.progress)(career).min(milestone.target);
+                let claimed = career.claimed.contains(milestone.id);
+                let color = if i == screen.selected {
+                    Color::YELLOW
+                } else {
+                    Color::WHITE
+                };
+                let status = if claimed {
+                    format!("{} claimed", milestone.reward)
+                } else if milestone.reached(career) {
+                    format!("Enter to claim the {}", milestone.reward.to_lowercase())
+                } else {
+                    format!(
+                        "{progress}/{} for the {}",
+                        milestone.target,
+                        milestone.reward.to_lowercase()
+                    )
+                };
+
+                parent.spawn(text(milestone.title.to_string(), 14., color));
+                parent
+                    .spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Px(BAR_WIDTH),
+                            height: Val::Px(4.),
+                            ..default()
+                        },
+                        background_color: Color::DARK_GRAY.into(),
+                        ..default()
+                    })
+                    .with_children(|parent| {
+                        parent.spawn(NodeBundle {
+                            style: Style {
+                                width: Val::Percent(
+                                    100. * progress as f32 / milestone.target as f32,
+                                ),
+                                height: Val::Percent(100.),
+                                ..default()
+                            },
+                            background_color: if claimed { Color::GOLD } else { color }.into(),
+                            ..default()
+                        });
+                    });
+                parent.spawn(text(status, 12., Color::GRAY));
+            }
+
+            parent.spawn(text(
+                "Up/Down choose, Enter claim, Esc back".to_string(),
+                12.,
+                Color::GRAY,
+            ));
+        });
+}
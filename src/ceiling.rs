@@ -0,0 +1,39 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    tutorials::{AddTutorial, Tutorial},
+    Atlas, BONK_CEILING,
+};
+
+/// What happens when the player flies out the top of the screen
+#[derive(Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CeilingBehavior {
+    /// Same as hitting a pipe
+    #[default]
+    Deadly,
+    /// The player is knocked back down and keeps going
+    Bonk,
+}
+
+#[derive(Event)]
+pub struct OnBonked {
+    pub position: Vec2,
+}
+
+// The bird knocked back off the top of the screen
+const BONK_EXAMPLE: &[(Atlas, Vec2)] = &[(Atlas::Bird2, Vec2::new(40., 50.))];
+
+pub struct CeilingPlugin;
+
+impl Plugin for CeilingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<OnBonked>().add_tutorial(Tutorial {
+            id: BONK_CEILING,
+            title: "Bonk ceiling",
+            text: "Flying out the top of the screen knocks you back down instead of ending the run",
+            art: Atlas::Bird2,
+            example: BONK_EXAMPLE,
+        });
+    }
+}
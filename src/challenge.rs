@@ -0,0 +1,448 @@
+use bevy::prelude::*;
+
+use crate::{
+    ceiling::CeilingBehavior,
+    difficulty::DifficultyPreset,
+    modes::ModeRegistry,
+    physics::{PhysicsHandle, PhysicsPresets, CLASSIC_PHYSICS},
+    replay::Playback,
+    AppState, GameRng, NextSeed, RunModifiers, Score,
+};
+
+// Crockford's base 32, which leaves out letters that look like numbers
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+// Characters between the dashes of a code
+const GROUP: usize = 7;
+const MAX_CODE_LENGTH: usize = 32;
+// Seed, mode, modifiers, score and a checksum
+const CODE_BYTES: usize = 13;
+// Stands for a physics preset that isn't in the list, played as classic
+const UNKNOWN_PHYSICS: u8 = 63;
+
+/// A run someone else played, for the player to try and beat on the same
+/// pipes
+#[derive(Resource, Clone)]
+pub struct Challenge {
+    pub seed: u64,
+    pub modifiers: RunModifiers,
+    pub score: u32,
+}
+
+impl Challenge {
+    /// A short code for the challenge that can be sent to someone else. Only
+    /// the modes and physics presets this game has can be put in one
+    fn encode(&self, registry: &ModeRegistry, physics: &[String]) -> Option<String> {
+        let mode = registry
+            .modes
+            .iter()
+            .position(|mode| mode.info.id == self.modifiers.mode)?;
+        let preset = physics
+            .iter()
+            .position(|name| *name == self.modifiers.physics)
+            .map_or(UNKNOWN_PHYSICS, |i| (i as u8).min(UNKNOWN_PHYSICS));
+        let flags = u8::from(self.modifiers.adaptive)
+            | u8::from(self.modifiers.ceiling == CeilingBehavior::Bonk) << 1
+            | preset << 2;
+
+        let mut bytes = self.seed.to_le_bytes().to_vec();
+        bytes.push(u8::try_from(mode).ok()?);
+        bytes.push(flags);
+        bytes.extend((self.score.min(u16::MAX as u32) as u16).to_le_bytes());
+        bytes.push(checksum(&bytes));
+
+        Some(grouped(&to_base32(&bytes)))
+    }
+
+    fn decode(code: &str, registry: &ModeRegistry, physics: &[String]) -> Result<Self, String> {
+        let bytes = from_base32(code).ok_or("That's not a challenge code")?;
+        if bytes.len() != CODE_BYTES || checksum(&bytes[..CODE_BYTES - 1]) != bytes[CODE_BYTES - 1]
+        {
+            return Err("That code has a typo in it".to_string());
+        }
+
+        let mut seed = [0; 8];
+        seed.copy_from_slice(&bytes[..8]);
+        let mode = registry
+            .modes
+            .get(bytes[8] as usize)
+            .ok_or("That code is for a mode this game doesn't have")?;
+        let flags = bytes[9];
+        let physics = match flags >> 2 {
+            UNKNOWN_PHYSICS => CLASSIC_PHYSICS.to_string(),
+            i => physics
+                .get(i as usize)
+                .cloned()
+                .ok_or("That code is for physics this game doesn't have")?,
+        };
+
+        Ok(Self {
+            seed: u64::from_le_bytes(seed),
+            modifiers: RunModifiers {
+                mode: mode.info.id.to_string(),
+                level: None,
+                adaptive: flags & 1 != 0,
+                ceiling: if flags & 2 != 0 {
+                    CeilingBehavior::Bonk
+                } else {
+                    CeilingBehavior::Deadly
+                },
+                physics,
+                assist: false,
+                breather: false,
+                preset: DifficultyPreset::Normal,
+            },
+            score: u16::from_le_bytes([bytes[10], bytes[11]]) as u32,
+        })
+    }
+
+    /// Whether the run that's being played is the one the challenge is for
+    fn is_for(&self, rng: &GameRng, modifiers: &RunModifiers) -> bool {
+        self.seed == rng.seed && self.modifiers == *modifiers
+    }
+}
+
+/// A seed written out the same way challenge codes are, short enough to be
+/// read off the screen
+pub fn seed_code(seed: u64) -> String {
+    grouped(&to_base32(&seed.to_le_bytes()))
+}
+
+// Dashes every few characters so a code is easier to copy out by hand
+fn grouped(code: &str) -> String {
+    let groups: Vec<&str> = code
+        .as_bytes()
+        .chunks(GROUP)
+        .map(|group| std::str::from_utf8(group).unwrap_or_default())
+        .collect();
+    groups.join("-")
+}
+
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes
+        .iter()
+        .fold(0x5a, |sum: u8, byte| sum.rotate_left(1) ^ byte)
+}
+
+fn to_base32(bytes: &[u8]) -> String {
+    let mut code = String::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for byte in bytes {
+        buffer = buffer << 8 | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            code.push(ALPHABET[(buffer >> bits & 31) as usize] as char);
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        code.push(ALPHABET[(buffer << (5 - bits) & 31) as usize] as char);
+    }
+    code
+}
+
+// Forgiving about case and dashes, and about letters that were meant to be
+// the numbers they look like
+fn from_base32(code: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let (mut buffer, mut bits) = (0u32, 0);
+    for c in code.chars().filter(|c| !matches!(c, '-' | ' ')) {
+        let c = match c.to_ascii_uppercase() {
+            'O' => '0',
+            'I' | 'L' => '1',
+            c => c,
+        };
+        let value = ALPHABET.iter().position(|a| *a as char == c)?;
+        buffer = buffer << 5 | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+    Some(bytes)
+}
+
+/// Names of the physics presets in the order they're listed, which is what a
+/// code refers to them by
+fn physics_names(handle: &PhysicsHandle, presets: &Assets<PhysicsPresets>) -> Vec<String> {
+    presets
+        .get(&handle.0)
+        .map(|presets| {
+            presets
+                .presets
+                .iter()
+                .map(|preset| preset.name.clone())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The code being typed on the challenge screen
+#[derive(Resource, Default)]
+struct CodeEntry {
+    code: String,
+    error: Option<String>,
+}
+
+#[derive(Component)]
+struct ChallengeScreen;
+
+#[derive(Component)]
+struct ChallengeBanner;
+
+#[derive(Component)]
+struct ChallengeResults;
+
+pub struct ChallengePlugin;
+
+impl Plugin for ChallengePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            open_challenge
+                .run_if(in_state(AppState::MainMenu).and_then(not(resource_exists::<Playback>))),
+        )
+        .add_systems(OnEnter(AppState::Challenge), start_entry)
+        .add_systems(
+            Update,
+            (
+                type_code,
+                draw_entry.run_if(resource_exists_and_changed::<CodeEntry>),
+            )
+                .chain(),
+        )
+        .add_systems(OnExit(AppState::Challenge), close_entry)
+        .add_systems(OnEnter(AppState::Playing), show_banner)
+        .add_systems(OnExit(AppState::Playing), hide::<ChallengeBanner>)
+        .add_systems(
+            OnEnter(AppState::GameOver),
+            show_results.run_if(not(resource_exists::<Playback>)),
+        )
+        .add_systems(OnExit(AppState::GameOver), hide::<ChallengeResults>);
+    }
+}
+
+fn text(value: String, size: f32, color: Color) -> TextBundle {
+    TextBundle::from_section(
+        value,
+        TextStyle {
+            font_size: size,
+            color,
+            ..default()
+        },
+    )
+}
+
+/// A line of text across the top or bottom of the screen
+fn banner(top: Option<f32>, bottom: Option<f32>) -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            width: Val::Percent(100.),
+            position_type: PositionType::Absolute,
+            top: top.map_or(Val::Auto, Val::Px),
+            bottom: bottom.map_or(Val::Auto, Val::Px),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        ..default()
+    }
+}
+
+fn hide<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn open_challenge(keys: Res<ButtonInput<KeyCode>>, mut state: ResMut<NextState<AppState>>) {
+    if keys.just_pressed(KeyCode::KeyX) {
+        state.set(AppState::Challenge);
+    }
+}
+
+fn start_entry(mut commands: Commands) {
+    commands.init_resource::<CodeEntry>();
+}
+
+fn close_entry(mut commands: Commands, query: Query<Entity, With<ChallengeScreen>>) {
+    commands.remove_resource::<CodeEntry>();
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Reads characters even when the screen is closed, otherwise the key that
+// opened it would end up at the start of the code
+fn type_code(
+    mut commands: Commands,
+    entry: Option<ResMut<CodeEntry>>,
+    mut reader: EventReader<ReceivedCharacter>,
+    keys: Res<ButtonInput<KeyCode>>,
+    registry: Res<ModeRegistry>,
+    handle: Res<PhysicsHandle>,
+    presets: Res<Assets<PhysicsPresets>>,
+    mut modifiers: ResMut<RunModifiers>,
+    mut next_seed: ResMut<NextSeed>,
+    mut state: ResMut<NextState<AppState>>,
+) {
+    let typed = reader
+        .read()
+        .flat_map(|event| event.char.chars())
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .collect::<String>();
+    let Some(mut entry) = entry else {
+        return;
+    };
+
+    if keys.just_pressed(KeyCode::Escape) {
+        state.set(AppState::MainMenu);
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Enter) {
+        let physics = physics_names(&handle, &presets);
+        match Challenge::decode(&entry.code, &registry, &physics) {
+            Ok(challenge) => {
+                // The menu lays out the challenge's world once it's back
+                next_seed.0 = Some(challenge.seed);
+                *modifiers = challenge.modifiers.clone();
+                commands.insert_resource(challenge);
+                state.set(AppState::MainMenu);
+            }
+            Err(error) => entry.error = Some(error),
+        }
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Backspace) {
+        entry.code.pop();
+    }
+    for c in typed.chars() {
+        if entry.code.len() < MAX_CODE_LENGTH {
+            entry.code.push(c.to_ascii_uppercase());
+        }
+    }
+}
+
+fn draw_entry(
+    mut commands: Commands,
+    entry: Res<CodeEntry>,
+    query: Query<Entity, With<ChallengeScreen>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    commands
+        .spawn((
+            ChallengeScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(12.)),
+                    row_gap: Val::Px(4.),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.8).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("Take a challenge".to_string(), 24., Color::WHITE));
+            parent.spawn(text(
+                "Type the code from someone's game over screen".to_string(),
+                12.,
+                Color::GRAY,
+            ));
+            parent.spawn(text(format!("{}_", entry.code), 14., Color::YELLOW));
+            if let Some(error) = &entry.error {
+                parent.spawn(text(error.clone(), 12., Color::ORANGE_RED));
+            }
+            parent.spawn(text("Enter accept, Esc back".to_string(), 12., Color::GRAY));
+        });
+}
+
+fn show_banner(
+    mut commands: Commands,
+    challenge: Option<Res<Challenge>>,
+    rng: Res<GameRng>,
+    modifiers: Res<RunModifiers>,
+) {
+    let Some(challenge) = challenge.filter(|challenge| challenge.is_for(&rng, &modifiers)) else {
+        return;
+    };
+
+    commands
+        .spawn((ChallengeBanner, banner(Some(4.), None)))
+        .with_children(|parent| {
+            parent.spawn(text(
+                format!("Beat {}", challenge.score),
+                12.,
+                Color::YELLOW,
+            ));
+        });
+}
+
+// A run that was played for a challenge settles it, either way. Every other
+// run gets a code to challenge someone else with
+fn show_results(
+    mut commands: Commands,
+    challenge: Option<Res<Challenge>>,
+    rng: Res<GameRng>,
+    modifiers: Res<RunModifiers>,
+    score: Res<Score>,
+    registry: Res<ModeRegistry>,
+    handle: Res<PhysicsHandle>,
+    presets: Res<Assets<PhysicsPresets>>,
+) {
+    if let Some(challenge) = challenge.filter(|challenge| challenge.is_for(&rng, &modifiers)) {
+        let (result, color) = if score.0 > challenge.score {
+            (
+                format!("Challenge won! {} beats {}", score.0, challenge.score),
+                Color::GREEN,
+            )
+        } else {
+            (
+                format!("Challenge lost, {} to beat", challenge.score),
+                Color::ORANGE_RED,
+            )
+        };
+        commands.remove_resource::<Challenge>();
+        commands
+            .spawn((ChallengeResults, banner(Some(44.), None)))
+            .with_children(|parent| {
+                parent.spawn(text(result, 14., color));
+            });
+        return;
+    }
+
+    // Levels aren't part of a code, and neither are assisted runs or
+    // ones on an easier or harder preset
+    if modifiers.level.is_some()
+        || modifiers.assist
+        || modifiers.breather
+        || modifiers.preset != DifficultyPreset::Normal
+    {
+        return;
+    }
+    let physics = physics_names(&handle, &presets);
+    let challenge = Challenge {
+        seed: rng.seed,
+        modifiers: modifiers.clone(),
+        score: score.0,
+    };
+    let Some(code) = challenge.encode(&registry, &physics) else {
+        return;
+    };
+
+    info!("Challenge code: {code}");
+    commands
+        .spawn((ChallengeResults, banner(None, Some(40.))))
+        .with_children(|parent| {
+            parent.spawn(text(format!("Challenge {code}"), 10., Color::WHITE));
+        });
+}
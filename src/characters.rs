@@ -0,0 +1,285 @@
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    difficulty::Difficulty,
+    main_menu::MenuRow,
+    profile::Profile,
+    replay::Playback,
+    ron_asset::RonLoader,
+    scroll::{is_scrolling, ScrollEase},
+    AppState, Player, Root,
+};
+
+const CHARACTERS_FILE: &str = "game.characters.ron";
+
+/// Dots left behind the bird as it flies
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Trail {
+    pub color: Color,
+    pub size: f32,
+    /// Seconds between dots
+    pub interval: f32,
+    pub lifetime: f32,
+}
+
+/// A bird to fly as, only changes how things look and sound
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct Character {
+    pub name: String,
+    /// Multiplied onto the bird's sprite
+    pub tint: Color,
+    /// How fast the flap sound plays, higher sounds higher
+    pub pitch: f32,
+    /// The color of particles that are marked as feathers
+    pub feathers: Color,
+    pub trail: Option<Trail>,
+    /// How much the bird squashes and stretches, 0 for not at all
+    pub squash: f32,
+    /// Name of the physics preset picking this bird switches to
+    pub physics: Option<String>,
+}
+
+impl Default for Character {
+    fn default() -> Self {
+        Self {
+            name: "Classic".to_string(),
+            tint: Color::WHITE,
+            pitch: 1.,
+            feathers: Color::WHITE,
+            trail: None,
+            squash: 1.,
+            physics: None,
+        }
+    }
+}
+
+#[derive(Asset, TypePath, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct CharacterList {
+    pub characters: Vec<Character>,
+}
+
+#[derive(Resource)]
+struct CharactersHandle(Handle<CharacterList>);
+
+/// Every bird there is to fly as, none until the list has loaded
+#[derive(SystemParam)]
+pub struct Characters<'w> {
+    handle: Res<'w, CharactersHandle>,
+    lists: Res<'w, Assets<CharacterList>>,
+}
+
+impl Characters<'_> {
+    pub fn all(&self) -> &[Character] {
+        self.lists
+            .get(&self.handle.0)
+            .map(|list| list.characters.as_slice())
+            .unwrap_or_default()
+    }
+}
+
+/// The bird the player picked, or the default one until the list has loaded
+#[derive(Resource, Default)]
+pub struct ActiveCharacter(pub Character);
+
+#[derive(Resource, Default)]
+struct TrailTimer(Timer);
+
+#[derive(Component)]
+struct TrailDot {
+    lifetime: Timer,
+}
+
+#[derive(Component)]
+struct CharacterLabel;
+
+pub struct CharactersPlugin;
+
+impl Plugin for CharactersPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<CharacterList>()
+            .register_asset_loader(RonLoader::<CharacterList>::new(&["characters.ron"]));
+
+        let handle = app.world.resource::<AssetServer>().load(CHARACTERS_FILE);
+        app.insert_resource(CharactersHandle(handle))
+            .init_resource::<ActiveCharacter>()
+            .init_resource::<TrailTimer>()
+            .add_systems(
+                Update,
+                (
+                    pick_character.run_if(
+                        resource_changed::<Profile>
+                            .or_else(on_event::<AssetEvent<CharacterList>>()),
+                    ),
+                    tint_bird,
+                )
+                    .chain(),
+            )
+            .add_systems(OnEnter(AppState::MainMenu), spawn_label)
+            .add_systems(OnExit(AppState::MainMenu), despawn_label)
+            .add_systems(
+                Update,
+                (
+                    cycle_character.run_if(not(resource_exists::<Playback>)),
+                    draw_label.run_if(resource_changed::<ActiveCharacter>),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::MainMenu)),
+            )
+            .add_systems(
+                Update,
+                (
+                    lay_trail.run_if(in_state(AppState::Playing)),
+                    fade_trail.run_if(is_scrolling),
+                ),
+            );
+    }
+}
+
+fn pick_character(
+    mut active: ResMut<ActiveCharacter>,
+    handle: Res<CharactersHandle>,
+    lists: Res<Assets<CharacterList>>,
+    profile: Res<Profile>,
+) {
+    let characters = lists
+        .get(&handle.0)
+        .map(|list| list.characters.as_slice())
+        .unwrap_or_default();
+
+    // Falls back to the first one when the picked bird has been taken out of the file
+    active.0 = characters
+        .iter()
+        .find(|character| character.name == profile.character)
+        .or(characters.first())
+        .cloned()
+        .unwrap_or_default();
+}
+
+fn cycle_character(
+    keys: Res<ButtonInput<KeyCode>>,
+    handle: Res<CharactersHandle>,
+    lists: Res<Assets<CharacterList>>,
+    active: Res<ActiveCharacter>,
+    mut profile: ResMut<Profile>,
+) {
+    if !keys.just_pressed(KeyCode::KeyC) {
+        return;
+    }
+    let Some(list) = lists.get(&handle.0) else {
+        return;
+    };
+    if list.characters.is_empty() {
+        return;
+    }
+
+    let current = list
+        .characters
+        .iter()
+        .position(|character| character.name == active.0.name);
+    let next = current.map_or(0, |i| (i + 1) % list.characters.len());
+    profile.character.clone_from(&list.characters[next].name);
+}
+
+fn tint_bird(
+    active: Res<ActiveCharacter>,
+    mut query: Query<&mut Sprite, With<Player>>,
+    added: Query<(), Added<Player>>,
+) {
+    if !active.is_changed() && added.is_empty() {
+        return;
+    }
+
+    for mut sprite in &mut query {
+        sprite.color = active.0.tint;
+    }
+}
+
+fn spawn_label(mut commands: Commands) {
+    commands.spawn((
+        CharacterLabel,
+        MenuRow::Bird,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 14.,
+                color: Color::WHITE,
+                ..default()
+            },
+        ),
+    ));
+}
+
+fn despawn_label(mut commands: Commands, query: Query<Entity, With<CharacterLabel>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn draw_label(active: Res<ActiveCharacter>, mut query: Query<&mut Text, With<CharacterLabel>>) {
+    for mut text in &mut query {
+        text.sections[0].value = format!("C bird: {}", active.0.name);
+    }
+}
+
+fn lay_trail(
+    mut commands: Commands,
+    mut timer: ResMut<TrailTimer>,
+    active: Res<ActiveCharacter>,
+    player: Query<&Transform, With<Player>>,
+    root: Query<Entity, With<Root>>,
+    time: Res<Time>,
+) {
+    let Some(trail) = &active.0.trail else {
+        return;
+    };
+
+    if timer.0.duration().as_secs_f32() != trail.interval {
+        timer.0 = Timer::from_seconds(trail.interval, TimerMode::Repeating);
+    }
+    if !timer.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let (Ok(player), Ok(root)) = (player.get_single(), root.get_single()) else {
+        return;
+    };
+    commands.entity(root).with_children(|parent| {
+        parent.spawn((
+            TrailDot {
+                lifetime: Timer::from_seconds(trail.lifetime, TimerMode::Once),
+            },
+            SpriteBundle {
+                sprite: Sprite {
+                    color: trail.color,
+                    custom_size: Some(Vec2::splat(trail.size)),
+                    ..default()
+                },
+                // Just behind the bird
+                transform: Transform::from_translation(player.translation.xy().extend(3.)),
+                ..default()
+            },
+        ));
+    });
+}
+
+fn fade_trail(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut TrailDot, &mut Transform, &mut Sprite)>,
+    difficulty: Res<Difficulty>,
+    ease: Res<ScrollEase>,
+    time: Res<Time>,
+) {
+    for (entity, mut dot, mut transform, mut sprite) in &mut query {
+        if dot.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        // Left where it was laid, so it scrolls away with the world
+        transform.translation.x += ease.speed(&difficulty) * time.delta_seconds();
+        sprite.color.set_a(1. - dot.lifetime.fraction());
+    }
+}
@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    behaviors::Oscillate,
+    create_world,
+    replay::{Playback, Recording},
+    ron_asset::RonLoader,
+    BuildWorld, Obstacle, Pattern, SimSet, SimTick,
+};
+
+const CONFIG_FILE: &str = "game.config.ron";
+
+/// Numbers the game is tuned with that aren't part of the difficulty curve or
+/// a physics preset. The gap, scroll speed, pipe spacing and gravity are left
+/// to those since they change with the score and the preset
+#[derive(Asset, TypePath, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default)]
+pub struct GameConfig {
+    /// How fast the bird's sent back down when it bonks the ceiling
+    pub bonk_knockdown: f32,
+    /// How far a moving gap bobs up and down from its middle
+    pub moving_gap_amplitude: f32,
+    /// How far a moving gap scrolls for every bob
+    pub moving_gap_wavelength: f32,
+    /// How far the bird bobs while it waits for a run, and how many times a
+    /// second
+    pub hover_height: f32,
+    pub hover_rate: f32,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            bonk_knockdown: 60.,
+            moving_gap_amplitude: 16.,
+            moving_gap_wavelength: 24.,
+            hover_height: 4.,
+            hover_rate: 0.8,
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct ConfigHandle(Handle<GameConfig>);
+
+/// The config the current run plays by
+#[derive(Resource, Default)]
+pub struct ActiveConfig(pub GameConfig);
+
+/// A config waiting for the next simulation step to take over, so it applies
+/// on a step a replay can put it back on
+#[derive(Resource, Default)]
+pub struct PendingConfig(pub Option<GameConfig>);
+
+pub struct ConfigPlugin;
+
+impl Plugin for ConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<GameConfig>()
+            .register_asset_loader(RonLoader::<GameConfig>::new(&["config.ron"]));
+
+        let handle = app.world.resource::<AssetServer>().load(CONFIG_FILE);
+        app.insert_resource(ConfigHandle(handle))
+            .init_resource::<ActiveConfig>()
+            .init_resource::<PendingConfig>()
+            .add_systems(BuildWorld, pick_config.before(create_world))
+            .add_systems(
+                Update,
+                reload_config.run_if(not(resource_exists::<Playback>)),
+            )
+            .add_systems(FixedUpdate, apply_config.in_set(SimSet::Input));
+    }
+}
+
+fn pick_config(
+    mut active: ResMut<ActiveConfig>,
+    handle: Res<ConfigHandle>,
+    configs: Res<Assets<GameConfig>>,
+    playback: Option<Res<Playback>>,
+) {
+    // Replays play out with the config they were recorded with
+    let config = match (playback, configs.get(&handle.0)) {
+        (Some(playback), _) => playback.replay.config,
+        (None, Some(config)) => *config,
+        (None, None) => GameConfig::default(),
+    };
+    if active.0 != config {
+        active.0 = config;
+    }
+}
+
+// Edits to the config file apply straight away, even in the middle of a run
+fn reload_config(
+    mut pending: ResMut<PendingConfig>,
+    mut reader: EventReader<AssetEvent<GameConfig>>,
+    handle: Res<ConfigHandle>,
+    configs: Res<Assets<GameConfig>>,
+) {
+    for event in reader.read() {
+        if !event.is_loaded_with_dependencies(&handle.0) && !event.is_modified(&handle.0) {
+            continue;
+        }
+
+        if let Some(config) = configs.get(&handle.0) {
+            info!("Game config loaded");
+            pending.0 = Some(*config);
+        }
+    }
+}
+
+// Kept in the recording along with the step it applied on, and moving gaps
+// that are already out there bob the new way from then on too
+fn apply_config(
+    mut pending: ResMut<PendingConfig>,
+    mut active: ResMut<ActiveConfig>,
+    recording: Option<ResMut<Recording>>,
+    tick: Res<SimTick>,
+    mut gaps: Query<(&Pattern, &mut Oscillate), With<Obstacle>>,
+) {
+    let Some(config) = pending.0.take() else {
+        return;
+    };
+    if active.0 == config {
+        return;
+    }
+
+    active.0 = config;
+    if let Some(mut recording) = recording {
+        recording.0.config_changes.push((tick.0, config));
+    }
+    for (pattern, mut oscillate) in &mut gaps {
+        if let Pattern::MovingGap { .. } = pattern {
+            oscillate.amplitude = config.moving_gap_amplitude;
+            oscillate.wavelength = config.moving_gap_wavelength;
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use bevy::prelude::*;
+
+use crate::{
+    bonus::PlayPhase,
+    pause::PauseState,
+    replay::Playback,
+    save,
+    snapshot::{QueuedRestore, SimState, Snapshot, SNAPSHOT_DIR},
+    AppState,
+};
+
+const MAX_LINE_LENGTH: usize = 40;
+const HELP: &str = "save <name>, load <name>";
+
+/// The line being typed while the console is open
+#[derive(Resource, Default)]
+pub struct Console {
+    line: String,
+    /// What came of the last command
+    output: String,
+}
+
+#[derive(Component)]
+struct ConsolePrompt;
+
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                type_line,
+                toggle_console,
+                draw_console.run_if(resource_changed_or_removed::<Console>()),
+            )
+                .chain()
+                // The pause overlay has the keys to itself while it's up
+                .run_if(in_state(AppState::Playing).and_then(in_state(PauseState::Running))),
+        )
+        .add_systems(OnExit(AppState::Playing), close_console);
+    }
+}
+
+fn toggle_console(
+    mut commands: Commands,
+    console: Option<Res<Console>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    // The run holds still while the console is up
+    match console {
+        Some(_) if keys.any_just_pressed([KeyCode::Backquote, KeyCode::Escape]) => {
+            commands.remove_resource::<Console>();
+            time.unpause();
+        }
+        None if keys.just_pressed(KeyCode::Backquote) => {
+            commands.init_resource::<Console>();
+            time.pause();
+        }
+        _ => {}
+    }
+}
+
+// Reads characters even when the console is closed, otherwise the key that
+// opened it would end up at the start of the line
+fn type_line(
+    console: Option<ResMut<Console>>,
+    mut reader: EventReader<ReceivedCharacter>,
+    keys: Res<ButtonInput<KeyCode>>,
+    sim: SimState,
+    mut queued: ResMut<QueuedRestore>,
+    playback: Option<Res<Playback>>,
+    phase: Res<State<PlayPhase>>,
+) {
+    let typed = reader
+        .read()
+        .flat_map(|event| event.char.chars())
+        .filter(|c| !c.is_control() && *c != '`')
+        .collect::<String>();
+    let Some(mut console) = console else {
+        return;
+    };
+
+    if keys.just_pressed(KeyCode::Enter) {
+        let line = std::mem::take(&mut console.line);
+        console.output = run_command(&line, &sim, &mut queued, playback.is_some(), phase.get());
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Backspace) {
+        console.line.pop();
+    }
+    for c in typed.chars() {
+        if console.line.chars().count() < MAX_LINE_LENGTH {
+            console.line.push(c);
+        }
+    }
+}
+
+fn run_command(
+    line: &str,
+    sim: &SimState,
+    queued: &mut QueuedRestore,
+    watching: bool,
+    phase: &PlayPhase,
+) -> String {
+    let mut words = line.split_whitespace();
+    let (command, name) = (words.next(), words.next());
+
+    match (command, name) {
+        (Some("save" | "load"), _) if *phase != PlayPhase::Normal => {
+            "Not during a bonus round".to_string()
+        }
+        (Some("save"), Some(name)) if is_valid_name(name) => {
+            let Some(snapshot) = sim.capture() else {
+                return "Nothing to save".to_string();
+            };
+            match save::store(&snapshot_path(name), &snapshot) {
+                Ok(()) => format!("Saved {name}"),
+                Err(error) => format!("Couldn't save {name}: {error}"),
+            }
+        }
+        // A replay has to play out the way it was recorded
+        (Some("load"), Some(_)) if watching => "Not while watching a replay".to_string(),
+        (Some("load"), Some(name)) if is_valid_name(name) => {
+            match save::load::<Snapshot>(&snapshot_path(name)) {
+                Ok(Some(snapshot)) => {
+                    queued.0 = Some(snapshot);
+                    format!("Loaded {name}")
+                }
+                Ok(None) => format!("There's no snapshot called {name}"),
+                Err(error) => format!("Couldn't load {name}: {error}"),
+            }
+        }
+        (Some("save" | "load"), _) => "Names are letters, digits, - and _".to_string(),
+        _ => HELP.to_string(),
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    name.chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn snapshot_path(name: &str) -> String {
+    format!("{SNAPSHOT_DIR}/{name}.ron")
+}
+
+fn draw_console(
+    mut commands: Commands,
+    console: Option<Res<Console>>,
+    query: Query<Entity, With<ConsolePrompt>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let Some(console) = console else {
+        return;
+    };
+
+    commands
+        .spawn((
+            ConsolePrompt,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(0.),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(8.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.8).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                format!("> {}_", console.line),
+                TextStyle {
+                    font_size: 14.,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+            parent.spawn(TextBundle::from_section(
+                console.output.clone(),
+                TextStyle {
+                    font_size: 12.,
+                    color: Color::GRAY,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn close_console(
+    mut commands: Commands,
+    query: Query<Entity, With<ConsolePrompt>>,
+    mut time: ResMut<Time<Virtual>>,
+) {
+    commands.remove_resource::<Console>();
+    time.unpause();
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,163 @@
+use bevy::prelude::*;
+
+use crate::{
+    actions::{Action, ActionMap, RawInput},
+    AppState,
+};
+
+#[derive(Resource, Default)]
+struct ControlsMenu {
+    selected: usize,
+    /// Waiting for the player to press what the selected action gets bound to
+    listening: bool,
+}
+
+#[derive(Component)]
+struct ControlsScreen;
+
+pub struct ControlsPlugin;
+
+impl Plugin for ControlsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ControlsMenu>()
+            .add_systems(OnEnter(AppState::Controls), reset_selection)
+            .add_systems(
+                Update,
+                (
+                    change_controls,
+                    draw_controls.run_if(
+                        resource_changed::<ControlsMenu>.or_else(resource_changed::<ActionMap>),
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Controls)),
+            )
+            .add_systems(OnExit(AppState::Controls), close_controls);
+    }
+}
+
+fn reset_selection(mut menu: ResMut<ControlsMenu>) {
+    *menu = ControlsMenu::default();
+}
+
+fn close_controls(mut commands: Commands, query: Query<Entity, With<ControlsScreen>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+// Escape is what gets out of listening, so it can't be bound to anything
+// from here. Resetting an action gives it back whatever it had out of the box
+fn change_controls(
+    mut menu: ResMut<ControlsMenu>,
+    mut map: ResMut<ActionMap>,
+    mut state: ResMut<NextState<AppState>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    input: RawInput,
+) {
+    let action = Action::ALL[menu.selected];
+
+    if menu.listening {
+        if keys.just_pressed(KeyCode::Escape) {
+            menu.listening = false;
+        } else if let Some(button) = input.just_pressed() {
+            map.bind(action, button);
+            menu.listening = false;
+        }
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::Escape) {
+        state.set(AppState::Settings);
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        menu.selected = menu.selected.saturating_sub(1);
+    }
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        menu.selected = (menu.selected + 1).min(Action::ALL.len() - 1);
+    }
+    if keys.just_pressed(KeyCode::Enter) {
+        menu.listening = true;
+    }
+    if keys.any_just_pressed([KeyCode::Delete, KeyCode::Backspace]) {
+        map.reset(action);
+    }
+}
+
+fn draw_controls(
+    mut commands: Commands,
+    menu: Res<ControlsMenu>,
+    map: Res<ActionMap>,
+    query: Query<Entity, With<ControlsScreen>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let text = |value: String, size: f32, color: Color| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size: size,
+                color,
+                ..default()
+            },
+        )
+    };
+
+    commands
+        .spawn((
+            ControlsScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(12.)),
+                    row_gap: Val::Px(4.),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.8).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("Controls".to_string(), 24., Color::WHITE));
+
+            for (i, action) in Action::ALL.into_iter().enumerate() {
+                let selected = i == menu.selected;
+                let bound = if selected && menu.listening {
+                    "press something...".to_string()
+                } else {
+                    let mut names = map
+                        .buttons(action)
+                        .iter()
+                        .map(|button| button.name())
+                        .collect::<Vec<_>>();
+                    if map.touch(action) {
+                        names.push("Tap".to_string());
+                    }
+                    if names.is_empty() {
+                        "nothing".to_string()
+                    } else {
+                        names.join(", ")
+                    }
+                };
+                let color = if selected {
+                    Color::YELLOW
+                } else {
+                    Color::WHITE
+                };
+                parent.spawn(text(format!("{:<8}{bound}", action.name()), 14., color));
+            }
+
+            let hint = if menu.listening {
+                "Press a key, mouse or pad button, Esc cancel"
+            } else {
+                "Enter rebind, Del reset, Esc back"
+            };
+            parent.spawn(text(hint.to_string(), 12., Color::GRAY));
+        });
+}
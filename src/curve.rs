@@ -0,0 +1,177 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    create_world, replay::Playback, ron_asset::RonLoader, BuildWorld, PIPE_COLUMNS, PIPE_SPACE,
+    PIPE_TO_PIPE_SPACE, SCROLL_SPEED,
+};
+
+const CURVE_FILE: &str = "difficulty.curve.ron";
+
+/// How likely each pattern is to be picked for a new pipe, relative to each other
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct PatternWeights {
+    pub regular: f32,
+    pub moving_gap: f32,
+}
+
+impl Default for PatternWeights {
+    fn default() -> Self {
+        Self {
+            regular: 3.,
+            moving_gap: 1.,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct CurvePoint {
+    pub score: u32,
+    /// Pixels per second, negative scrolls to the left
+    pub scroll_speed: f32,
+    pub pipe_space: f32,
+    /// How far apart the pipe columns are, applies to columns as they come back around
+    pub pipe_to_pipe_space: f32,
+    pub patterns: PatternWeights,
+    /// How hard gusts of wind push the bird, in pixels per second squared.
+    /// There aren't any when it's 0
+    pub gust_strength: f32,
+    /// Seconds from the start of one gust to the start of the next
+    pub gust_interval: f32,
+}
+
+impl Default for CurvePoint {
+    fn default() -> Self {
+        Self {
+            score: 0,
+            scroll_speed: SCROLL_SPEED,
+            pipe_space: PIPE_SPACE,
+            pipe_to_pipe_space: PIPE_TO_PIPE_SPACE,
+            patterns: PatternWeights::default(),
+            gust_strength: 0.,
+            gust_interval: 0.,
+        }
+    }
+}
+
+/// How the game gets harder as the score goes up, linear between the points
+#[derive(Asset, TypePath, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct DifficultyCurve {
+    /// How many pipe columns a world is laid out with, fixed for the whole run
+    pub pipe_columns: usize,
+    /// The score unusual patterns like moving gaps start showing up from
+    pub unusual_from: u32,
+    pub points: Vec<CurvePoint>,
+}
+
+impl Default for DifficultyCurve {
+    fn default() -> Self {
+        Self {
+            pipe_columns: PIPE_COLUMNS,
+            unusual_from: 1,
+            points: Vec::new(),
+        }
+    }
+}
+
+impl DifficultyCurve {
+    /// A copy that's ready to be sampled, the file doesn't have to list the
+    /// points in order
+    fn sorted(&self) -> Self {
+        let mut curve = self.clone();
+        curve.points.sort_by_key(|point| point.score);
+        curve
+    }
+
+    pub fn sample(&self, score: u32) -> CurvePoint {
+        let Some(first) = self.points.first() else {
+            return CurvePoint::default();
+        };
+
+        let mut sampled = *first;
+        for pair in self.points.windows(2) {
+            let (from, to) = (pair[0], pair[1]);
+            if score < from.score {
+                break;
+            }
+
+            sampled = to;
+            if score < to.score {
+                let t = (score - from.score) as f32 / (to.score - from.score) as f32;
+                let lerp = |from: f32, to: f32| from + (to - from) * t;
+                sampled = CurvePoint {
+                    score,
+                    scroll_speed: lerp(from.scroll_speed, to.scroll_speed),
+                    pipe_space: lerp(from.pipe_space, to.pipe_space),
+                    pipe_to_pipe_space: lerp(from.pipe_to_pipe_space, to.pipe_to_pipe_space),
+                    patterns: PatternWeights {
+                        regular: lerp(from.patterns.regular, to.patterns.regular),
+                        moving_gap: lerp(from.patterns.moving_gap, to.patterns.moving_gap),
+                    },
+                    gust_strength: lerp(from.gust_strength, to.gust_strength),
+                    gust_interval: lerp(from.gust_interval, to.gust_interval),
+                };
+                break;
+            }
+        }
+        sampled
+    }
+}
+
+/// The curve the current run plays by
+#[derive(Resource, Default)]
+pub struct ActiveCurve(pub DifficultyCurve);
+
+#[derive(Resource)]
+pub struct CurveHandle(Handle<DifficultyCurve>);
+
+pub struct CurvePlugin;
+
+impl Plugin for CurvePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<DifficultyCurve>()
+            .register_asset_loader(RonLoader::<DifficultyCurve>::new(&["curve.ron"]));
+
+        let handle = app.world.resource::<AssetServer>().load(CURVE_FILE);
+        app.insert_resource(CurveHandle(handle))
+            .init_resource::<ActiveCurve>()
+            .add_systems(BuildWorld, pick_curve.before(create_world))
+            .add_systems(Update, reload_curve);
+    }
+}
+
+pub fn pick_curve(
+    mut active: ResMut<ActiveCurve>,
+    handle: Res<CurveHandle>,
+    curves: Res<Assets<DifficultyCurve>>,
+    playback: Option<Res<Playback>>,
+) {
+    // Replays play out on the curve they were recorded with
+    active.0 = match (playback, curves.get(&handle.0)) {
+        (Some(playback), _) => playback.replay.curve.clone(),
+        (None, Some(curve)) => curve.sorted(),
+        (None, None) => DifficultyCurve::default(),
+    };
+}
+
+// Edits to the curve file apply straight away, even in the middle of a run
+fn reload_curve(
+    mut active: ResMut<ActiveCurve>,
+    mut reader: EventReader<AssetEvent<DifficultyCurve>>,
+    handle: Res<CurveHandle>,
+    curves: Res<Assets<DifficultyCurve>>,
+    playback: Option<Res<Playback>>,
+) {
+    for event in reader.read() {
+        if !event.is_loaded_with_dependencies(&handle.0) && !event.is_modified(&handle.0) {
+            continue;
+        }
+
+        if let (None, Some(curve)) = (&playback, curves.get(&handle.0)) {
+            info!("Difficulty curve loaded");
+            active.0 = curve.sorted();
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use bevy::prelude::*;
+
+use crate::{
+    build_world,
+    campaign::{in_campaign, Level},
+    modes::{AddGameMode, GameMode, ModeInfo},
+    replay::Playback,
+    save,
+    tutorials::{AddTutorial, Tutorial, BETWEEN_PIPES},
+    AppState, Atlas, NextSeed, RunModifiers,
+};
+
+const CUSTOM: &str = "custom";
+// Where the player puts levels of their own, in the save directory
+const LEVEL_DIR: &str = "levels";
+// Kept in front of the ids of custom levels, so their bests can't be mixed up
+// with the campaign's
+const ID_PREFIX: &str = "custom:";
+
+/// The levels in the player's level directory, read again every time the mode
+/// is picked so new ones show up without a restart
+#[derive(Resource, Default)]
+pub struct CustomLevels {
+    pub levels: Vec<Level>,
+}
+
+impl CustomLevels {
+    fn load() -> Self {
+        let paths = match save::list(LEVEL_DIR) {
+            Ok(paths) => paths,
+            Err(error) => {
+                warn!("Couldn't look for custom levels: {error}");
+                return Self::default();
+            }
+        };
+
+        let levels = paths
+            .iter()
+            .filter_map(|path| match save::load_path::<Level>(path) {
+                Ok(mut level) => {
+                    level.id = format!("{ID_PREFIX}{}", level.id);
+                    Some(level)
+                }
+                Err(error) => {
+                    warn!("Couldn't load the level {}: {error}", path.display());
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+        info!("Found {} custom levels", levels.len());
+        Self { levels }
+    }
+}
+
+struct Custom;
+
+impl GameMode for Custom {
+    fn info(&self) -> ModeInfo {
+        ModeInfo {
+            id: CUSTOM,
+            name: "Custom Levels",
+            blurb: "Levels of your own, Up/Down to pick",
+            preview: Atlas::PipeBottom,
+            color: Color::rgb(0.45, 0.3, 0.55),
+            seed: None,
+        }
+    }
+
+    fn setup(&self, app: &mut App) {
+        app.insert_resource(CustomLevels::load()).add_systems(
+            Update,
+            pick_level.run_if(
+                in_state(AppState::MainMenu)
+                    .and_then(not(resource_exists::<Playback>))
+                    .and_then(not(in_campaign)),
+            ),
+        );
+    }
+}
+
+pub struct CustomLevelsPlugin;
+
+impl Plugin for CustomLevelsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_game_mode(Custom).add_tutorial(Tutorial {
+            id: CUSTOM,
+            title: "Custom Levels",
+            text: "Put level files in the levels folder of your save directory to play them here",
+            art: Atlas::PipeBottom,
+            example: BETWEEN_PIPES,
+        });
+    }
+}
+
+// Switching to the mode starts on its first level, and Up/Down go through the
+// rest. Whichever one's picked is laid out right away, like a campaign level
+fn pick_level(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut custom: ResMut<CustomLevels>,
+    mut modifiers: ResMut<RunModifiers>,
+    mut next_seed: ResMut<NextSeed>,
+    mut was_picked: Local<bool>,
+) {
+    let picked = modifiers.mode == CUSTOM;
+    let switched = picked && !*was_picked;
+    *was_picked = picked;
+    if !picked {
+        return;
+    }
+    if switched {
+        *custom = CustomLevels::load();
+    }
+
+    let step = match (
+        keys.just_pressed(KeyCode::ArrowUp),
+        keys.just_pressed(KeyCode::ArrowDown),
+    ) {
+        (true, false) => -1,
+        (false, true) => 1,
+        _ => 0,
+    };
+    let current = modifiers
+        .level
+        .as_deref()
+        .and_then(|id| custom.levels.iter().position(|level| level.id == id));
+    let next = match (current, step) {
+        (None, _) => 0,
+        (Some(_), 0) => return,
+        (Some(current), step) => current
+            .saturating_add_signed(step)
+            .min(custom.levels.len() - 1),
+    };
+    if current == Some(next) {
+        return;
+    }
+    let Some(level) = custom.levels.get(next) else {
+        return;
+    };
+
+    modifiers.level = Some(level.id.clone());
+    next_seed.0 = Some(level.seed);
+    commands.add(build_world);
+}
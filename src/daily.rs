@@ -0,0 +1,51 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+
+use crate::{
+    modes::{AddGameMode, GameMode, ModeInfo},
+    tutorials::{AddTutorial, Tutorial, BETWEEN_PIPES},
+    Atlas,
+};
+
+const DAILY: &str = "daily";
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+struct Daily;
+
+impl GameMode for Daily {
+    fn info(&self) -> ModeInfo {
+        ModeInfo {
+            id: DAILY,
+            name: "Daily",
+            blurb: "Same pipes for everyone today",
+            preview: Atlas::PipeTop,
+            color: Color::rgb(0.6, 0.35, 0.15),
+            seed: Some(daily_seed),
+        }
+    }
+}
+
+pub struct DailyPlugin;
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_game_mode(Daily).add_tutorial(Tutorial {
+            id: DAILY,
+            title: "Daily",
+            text: "Everyone gets the same pipes today, and tomorrow there's a new set",
+            art: Atlas::PipeTop,
+            example: BETWEEN_PIPES,
+        });
+    }
+}
+
+// Changes at midnight UTC, so everyone playing on the same day gets the same world
+fn daily_seed() -> u64 {
+    let day = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() / SECONDS_PER_DAY)
+        .unwrap_or_default();
+    // Spreads neighbouring days out so they don't start off alike
+    day.wrapping_mul(0x9E37_79B9_7F4A_7C15)
+}
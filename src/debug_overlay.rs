@@ -0,0 +1,164 @@
+use std::time::{Duration, Instant};
+
+use bevy::{
+    diagnostic::{
+        Diagnostic, DiagnosticPath, Diagnostics, DiagnosticsStore, EntityCountDiagnosticsPlugin,
+        FrameTimeDiagnosticsPlugin, RegisterDiagnostic,
+    },
+    prelude::*,
+};
+
+use crate::{kill_plane::KillPlaneAudit, AppState, SimSet};
+
+// In the order they run in within a step
+const STAGES: [(SimSet, DiagnosticPath); 5] = [
+    (SimSet::Input, DiagnosticPath::const_new("sim/input")),
+    (SimSet::Physics, DiagnosticPath::const_new("sim/physics")),
+    (
+        SimSet::Collision,
+        DiagnosticPath::const_new("sim/collision"),
+    ),
+    (SimSet::Rules, DiagnosticPath::const_new("sim/rules")),
+    (
+        SimSet::Transition,
+        DiagnosticPath::const_new("sim/transition"),
+    ),
+];
+
+/// How long every stage of the simulation took over all the steps of the
+/// current frame
+#[derive(Resource, Default)]
+struct StageTimes {
+    /// When the stage that's running right now started
+    started: Option<Instant>,
+    totals: [Duration; 5],
+}
+
+#[derive(Component)]
+struct DebugOverlay;
+
+pub struct DebugOverlayPlugin;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins((FrameTimeDiagnosticsPlugin, EntityCountDiagnosticsPlugin))
+            .init_resource::<StageTimes>()
+            .add_systems(
+                Update,
+                (toggle_overlay, publish_stage_times, draw_overlay).chain(),
+            );
+
+        for (_, path) in &STAGES {
+            app.register_diagnostic(Diagnostic::new(path.clone()).with_suffix("ms"));
+        }
+
+        // Every stage is timed from the end of the one before it to the start
+        // of the one after it
+        app.add_systems(FixedUpdate, start_stage.before(STAGES[0].0.clone()));
+        for (i, (stage, _)) in STAGES.iter().enumerate() {
+            let end = end_stage(i).after(stage.clone());
+            match STAGES.get(i + 1) {
+                Some((next, _)) => app.add_systems(FixedUpdate, end.before(next.clone())),
+                None => app.add_systems(FixedUpdate, end),
+            };
+        }
+    }
+}
+
+fn start_stage(mut times: ResMut<StageTimes>) {
+    times.started = Some(Instant::now());
+}
+
+fn end_stage(stage: usize) -> impl FnMut(ResMut<StageTimes>) {
+    move |mut times| {
+        let now = Instant::now();
+        if let Some(started) = times.started.replace(now) {
+            times.totals[stage] += now - started;
+        }
+    }
+}
+
+fn publish_stage_times(mut times: ResMut<StageTimes>, mut diagnostics: Diagnostics) {
+    for (i, (_, path)) in STAGES.iter().enumerate() {
+        let total = std::mem::take(&mut times.totals[i]);
+        diagnostics.add_measurement(path, || total.as_secs_f64() * 1000.);
+    }
+}
+
+fn toggle_overlay(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    query: Query<Entity, With<DebugOverlay>>,
+) {
+    if !keys.just_pressed(KeyCode::F3) {
+        return;
+    }
+
+    if query.is_empty() {
+        commands.spawn((
+            DebugOverlay,
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 10.,
+                    color: Color::GREEN,
+                    ..default()
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(4.),
+                right: Val::Px(4.),
+                ..default()
+            })
+            .with_background_color(Color::rgba(0., 0., 0., 0.6)),
+        ));
+    } else {
+        for entity in &query {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn draw_overlay(
+    mut query: Query<&mut Text, With<DebugOverlay>>,
+    diagnostics: Res<DiagnosticsStore>,
+    state: Res<State<AppState>>,
+    audit: Res<KillPlaneAudit>,
+) {
+    let Ok(mut text) = query.get_single_mut() else {
+        return;
+    };
+    let smoothed = |path: &DiagnosticPath| {
+        diagnostics
+            .get(path)
+            .and_then(|diagnostic| diagnostic.smoothed())
+            .unwrap_or_default()
+    };
+
+    let mut lines = vec![
+        format!("{:?}", state.get()),
+        format!("fps {:.0}", smoothed(&FrameTimeDiagnosticsPlugin::FPS)),
+        format!(
+            "frame {:.2}ms",
+            smoothed(&FrameTimeDiagnosticsPlugin::FRAME_TIME)
+        ),
+        format!(
+            "entities {:.0}",
+            smoothed(&EntityCountDiagnosticsPlugin::ENTITY_COUNT)
+        ),
+    ];
+    // Summed over all the steps that fit in a frame
+    for (stage, path) in &STAGES {
+        lines.push(format!("{stage:?} {:.3}ms", smoothed(path)));
+    }
+    lines.push(format!(
+        "kill plane {} despawned {} flagged",
+        audit.despawned,
+        audit.flagged.len()
+    ));
+    if let Some(last) = &audit.last {
+        lines.push(last.clone());
+    }
+    text.sections[0].value = lines.join("\n");
+}
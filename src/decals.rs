@@ -0,0 +1,245 @@
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    accessories::{part, Part, Unlock, GOLD, INK, RED},
+    profile::Profile,
+    replay::Playback,
+    AppState, Pipe,
+};
+
+// How likely a column of pipes is to have been painted on
+const DECAL_CHANCE: f64 = 0.35;
+// Where on a pipe a decal goes, measured from its middle towards the gap, so
+// it's on the part of the pipe that's on screen
+const DECAL_Y: f32 = 48.;
+// Most a decal is nudged by, sideways, along the pipe and tilted
+const DECAL_JITTER: Vec3 = Vec3::new(3., 16., 0.25);
+
+const PINK: Color = Color::rgb(1., 0.4, 0.7);
+const BLUE: Color = Color::rgb(0.3, 0.6, 1.);
+const WHITE: Color = Color::rgb(0.95, 0.95, 0.95);
+
+struct Decal {
+    /// What the profile saves it as, so it can't change once released
+    id: &'static str,
+    name: &'static str,
+    parts: &'static [Part],
+    unlock: Unlock,
+}
+
+const DECALS: &[Decal] = &[
+    Decal {
+        id: "heart",
+        name: "Heart",
+        parts: &[
+            part(-2., 1.5, 3., 3., PINK),
+            part(2., 1.5, 3., 3., PINK),
+            part(0., -0.5, 7., 3., PINK),
+            part(0., -2.5, 3., 2., PINK),
+        ],
+        unlock: Unlock::Free,
+    },
+    Decal {
+        id: "arrow",
+        name: "Arrow",
+        parts: &[
+            part(-1., 0., 8., 2., WHITE),
+            part(3., 2., 2., 2., WHITE),
+            part(3., -2., 2., 2., WHITE),
+        ],
+        unlock: Unlock::Free,
+    },
+    Decal {
+        id: "star",
+        name: "Star",
+        parts: &[
+            part(0., 0., 3., 7., GOLD),
+            part(0., 0.5, 9., 2., GOLD),
+            part(-2., -2.5, 2., 2., GOLD),
+            part(2., -2.5, 2., 2., GOLD),
+        ],
+        unlock: Unlock::Award("Fastest 10 pipes"),
+    },
+    Decal {
+        id: "smiley",
+        name: "Smiley",
+        parts: &[
+            part(0., 0., 8., 8., GOLD),
+            part(-2., 1.5, 1., 2., INK),
+            part(2., 1.5, 1., 2., INK),
+            part(0., -2., 4., 1., INK),
+            part(-2.5, -1., 1., 1., INK),
+            part(2.5, -1., 1., 1., INK),
+        ],
+        unlock: Unlock::Award("Closest call"),
+    },
+    Decal {
+        id: "tag",
+        name: "Tag",
+        parts: &[
+            part(-4., 0., 2., 5., BLUE),
+            part(-2., 1.5, 2., 2., BLUE),
+            part(0., 0., 2., 5., RED),
+            part(2., -1.5, 2., 2., RED),
+            part(4., 0., 2., 5., BLUE),
+        ],
+        unlock: Unlock::Award("Most flaps"),
+    },
+];
+
+/// Which decals get painted on pipes
+#[derive(Serialize, Deserialize, Clone, PartialEq, Default, Debug)]
+pub enum DecalChoice {
+    Off,
+    /// Any of the ones that have been unlocked
+    #[default]
+    Random,
+    /// Just the one with this id
+    Only(String),
+}
+
+impl DecalChoice {
+    fn decals(&self, profile: &Profile) -> Vec<&'static Decal> {
+        let unlocked = DECALS.iter().filter(|decal| decal.unlock.unlocked(profile));
+        match self {
+            DecalChoice::Off => Vec::new(),
+            DecalChoice::Random => unlocked.collect(),
+            DecalChoice::Only(id) => unlocked.filter(|decal| decal.id == id).collect(),
+        }
+    }
+
+    /// Random first, then every unlocked decal on its own, then none at all
+    fn next(&self, profile: &Profile) -> Self {
+        let options = [DecalChoice::Random]
+            .into_iter()
+            .chain(
+                DECALS
+                    .iter()
+                    .filter(|decal| decal.unlock.unlocked(profile))
+                    .map(|decal| DecalChoice::Only(decal.id.to_string())),
+            )
+            .chain([DecalChoice::Off])
+            .collect::<Vec<_>>();
+        let next = options
+            .iter()
+            .position(|option| option == self)
+            .map_or(0, |i| (i + 1) % options.len());
+        options[next].clone()
+    }
+}
+
+/// The line about decals that goes in with the rest of the bird's looks
+pub fn label(profile: &Profile) -> String {
+    let choice = match &profile.decals {
+        DecalChoice::Off => "off",
+        DecalChoice::Random => "random",
+        DecalChoice::Only(id) => DECALS
+            .iter()
+            .find(|decal| decal.id == id)
+            .map_or("none", |decal| decal.name),
+    };
+    let unlocked = DECALS
+        .iter()
+        .filter(|decal| decal.unlock.unlocked(profile))
+        .count();
+    format!("W decals: {choice} {unlocked}/{}", DECALS.len())
+}
+
+/// A column of pipes that's new, or has just come back around, and gets its
+/// decals painted over
+#[derive(Component)]
+pub struct Repaint;
+
+/// Decals painted on a pipe, cleared off before it's painted again
+#[derive(Component)]
+pub struct Painted;
+
+pub struct DecalsPlugin;
+
+impl Plugin for DecalsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, paint_pipes).add_systems(
+            Update,
+            cycle_decals
+                .run_if(in_state(AppState::MainMenu).and_then(not(resource_exists::<Playback>))),
+        );
+    }
+}
+
+fn cycle_decals(keys: Res<ButtonInput<KeyCode>>, mut profile: ResMut<Profile>) {
+    if keys.just_pressed(KeyCode::KeyW) {
+        profile.decals = profile.decals.next(&profile);
+    }
+}
+
+// Only for looks, so it's rolled off to the side of the game's own random
+// numbers and a run plays out the same whatever's painted on it
+pub fn paint_pipes(
+    mut commands: Commands,
+    profile: Res<Profile>,
+    obstacles: Query<(Entity, &Children), With<Repaint>>,
+    pipes: Query<(Entity, &Pipe, Option<&Children>)>,
+    painted: Query<(), With<Painted>>,
+) {
+    if obstacles.is_empty() {
+        return;
+    }
+
+    let decals = profile.decals.decals(&profile);
+    let mut rng = rand::thread_rng();
+    for (obstacle, children) in &obstacles {
+        commands.entity(obstacle).remove::<Repaint>();
+
+        // Whatever was painted on last time around comes off first
+        let pipes = pipes.iter_many(children).collect::<Vec<_>>();
+        for (_, _, paint) in &pipes {
+            for &child in paint.iter().copied().flatten() {
+                if painted.contains(child) {
+                    commands.entity(child).despawn_recursive();
+                }
+            }
+        }
+
+        if !rng.gen_bool(DECAL_CHANCE) {
+            continue;
+        }
+        let (Some(decal), Some((pipe, side, _))) =
+            (decals.choose(&mut rng), pipes.choose(&mut rng))
+        else {
+            continue;
+        };
+
+        // Towards the gap, whichever pipe it's on
+        let toward_gap = match side {
+            Pipe::Top => -1.,
+            Pipe::Bottom => 1.,
+        };
+        let jitter = Vec3::new(
+            rng.gen_range(-DECAL_JITTER.x..=DECAL_JITTER.x),
+            rng.gen_range(-DECAL_JITTER.y..=DECAL_JITTER.y),
+            rng.gen_range(-DECAL_JITTER.z..=DECAL_JITTER.z),
+        );
+        let transform = Transform::from_xyz(jitter.x, DECAL_Y * toward_gap + jitter.y, 0.1)
+            .with_rotation(Quat::from_rotation_z(jitter.z));
+
+        commands.entity(*pipe).with_children(|parent| {
+            parent
+                .spawn((Painted, SpatialBundle::from_transform(transform)))
+                .with_children(|parent| {
+                    for part in decal.parts {
+                        parent.spawn(SpriteBundle {
+                            sprite: Sprite {
+                                color: part.color,
+                                custom_size: Some(part.size),
+                                ..default()
+                            },
+                            transform: Transform::from_translation(part.offset.extend(0.)),
+                            ..default()
+                        });
+                    }
+                });
+        });
+    }
+}
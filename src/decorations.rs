@@ -0,0 +1,187 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    difficulty::Difficulty,
+    low_power::saving_power,
+    scroll::{is_scrolling, ScrollEase},
+    AppState, BuildWorld,
+};
+
+const CLOUD_POOL: usize = 6;
+const FLOCK_POOL: usize = 3;
+const FLOCK_BIRDS: usize = 5;
+// Seconds between decorations, picked at random within these
+const CLOUD_INTERVAL: (f32, f32) = (1.5, 4.);
+const FLOCK_INTERVAL: (f32, f32) = (6., 14.);
+// How fast each layer scrolls compared to the pipes
+const CLOUD_PARALLAX: f32 = 0.3;
+const FLOCK_PARALLAX: f32 = 0.5;
+// Just off the edges of the screen
+const SPAWN_X: f32 = 100.;
+const DESPAWN_X: f32 = -100.;
+
+/// Scenery that doesn't take part in the run, put back in the pool once it's
+/// scrolled off screen
+#[derive(Component)]
+struct Decoration {
+    parallax: f32,
+    /// Moves on its own on top of scrolling, for birds that are flying
+    drift: f32,
+}
+
+#[derive(Component)]
+struct Cloud;
+
+#[derive(Component)]
+struct Flock;
+
+#[derive(Resource)]
+struct DecorationSpawner {
+    clouds: Timer,
+    flocks: Timer,
+}
+
+fn random_timer(rng: &mut impl Rng, (min, max): (f32, f32)) -> Timer {
+    Timer::from_seconds(rng.gen_range(min..max), TimerMode::Once)
+}
+
+pub struct DecorationsPlugin;
+
+impl Plugin for DecorationsPlugin {
+    fn build(&self, app: &mut App) {
+        let mut rng = rand::thread_rng();
+        app.insert_resource(DecorationSpawner {
+            clouds: random_timer(&mut rng, CLOUD_INTERVAL),
+            flocks: random_timer(&mut rng, FLOCK_INTERVAL),
+        })
+        .add_systems(Startup, spawn_pool)
+        .add_systems(BuildWorld, clear_decorations)
+        .add_systems(
+            Update,
+            (
+                // Already out on screen ones are left to scroll off
+                spawn_decorations.run_if(in_state(AppState::Playing).and_then(not(saving_power))),
+                move_decorations.run_if(is_scrolling),
+            ),
+        );
+    }
+}
+
+fn spawn_pool(mut commands: Commands) {
+    for _ in 0..CLOUD_POOL {
+        commands.spawn((
+            Cloud,
+            Decoration {
+                parallax: CLOUD_PARALLAX,
+                drift: 0.,
+            },
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(1., 1., 1., 0.7),
+                    ..default()
+                },
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ));
+    }
+
+    for _ in 0..FLOCK_POOL {
+        commands
+            .spawn((
+                Flock,
+                Decoration {
+                    parallax: FLOCK_PARALLAX,
+                    drift: 0.,
+                },
+                SpatialBundle {
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+            ))
+            .with_children(|parent| {
+                // A loose V with the leader in front
+                for i in 0..FLOCK_BIRDS {
+                    let row = i.div_ceil(2) as f32;
+                    let side = if i % 2 == 0 { 1. } else { -1. };
+                    parent.spawn(SpriteBundle {
+                        sprite: Sprite {
+                            color: Color::rgba(0.2, 0.2, 0.3, 0.8),
+                            custom_size: Some(Vec2::new(3., 1.)),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(Vec3::new(
+                            row * 4.,
+                            row * 3. * side,
+                            0.,
+                        )),
+                        ..default()
+                    });
+                }
+            });
+    }
+}
+
+fn clear_decorations(mut query: Query<&mut Visibility, With<Decoration>>) {
+    for mut visibility in &mut query {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn spawn_decorations(
+    mut spawner: ResMut<DecorationSpawner>,
+    mut clouds: Query<
+        (&mut Transform, &mut Visibility, &mut Sprite),
+        (With<Cloud>, Without<Flock>),
+    >,
+    mut flocks: Query<(&mut Transform, &mut Visibility, &mut Decoration), With<Flock>>,
+    time: Res<Time>,
+) {
+    let mut rng = rand::thread_rng();
+
+    if spawner.clouds.tick(time.delta()).finished() {
+        spawner.clouds = random_timer(&mut rng, CLOUD_INTERVAL);
+        // Nothing to do when every cloud is already out, it just skips this one
+        if let Some((mut transform, mut visibility, mut sprite)) = clouds
+            .iter_mut()
+            .find(|(_, visibility, _)| **visibility == Visibility::Hidden)
+        {
+            let size = Vec2::new(rng.gen_range(16. ..32.), rng.gen_range(5. ..9.));
+            sprite.custom_size = Some(size);
+            transform.translation = Vec3::new(SPAWN_X, rng.gen_range(40. ..110.), -0.5);
+            *visibility = Visibility::Inherited;
+        }
+    }
+
+    if spawner.flocks.tick(time.delta()).finished() {
+        spawner.flocks = random_timer(&mut rng, FLOCK_INTERVAL);
+        if let Some((mut transform, mut visibility, mut decoration)) = flocks
+            .iter_mut()
+            .find(|(_, visibility, _)| **visibility == Visibility::Hidden)
+        {
+            decoration.drift = rng.gen_range(-20. ..-5.);
+            transform.translation = Vec3::new(SPAWN_X, rng.gen_range(20. ..100.), -0.6);
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
+
+fn move_decorations(
+    mut query: Query<(&mut Transform, &mut Visibility, &Decoration)>,
+    difficulty: Res<Difficulty>,
+    ease: Res<ScrollEase>,
+    time: Res<Time>,
+) {
+    for (mut transform, mut visibility, decoration) in &mut query {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+
+        let speed = ease.speed(&difficulty) * decoration.parallax + decoration.drift;
+        transform.translation.x += speed * time.delta_seconds();
+        if transform.translation.x < DESPAWN_X {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
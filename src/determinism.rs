@@ -0,0 +1,687 @@
+use std::{
+    collections::VecDeque,
+    hash::{DefaultHasher, Hash, Hasher},
+    io::{self, BufRead, BufReader, Write},
+    process::{self, Child, ChildStdin, Command, Stdio},
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use bevy::{
+    app::{PluginGroupBuilder, ScheduleRunnerPlugin},
+    asset::AssetMode,
+    audio::{AudioPlugin, GlobalVolume},
+    prelude::*,
+    time::TimeUpdateStrategy,
+    window::ExitCondition,
+    winit::WinitPlugin,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::GameConfig,
+    physics::PhysicsPreset,
+    replay::{start_recording, Playback, Recording, Replay},
+    snapshot::{OnSnapshotRestored, SimState},
+    AppState, BuildWorld, NextSeed, RunModifiers, SimSet, SimTick, SIM_HZ,
+};
+
+const SHADOW_ARG: &str = "--determinism-shadow";
+// Marks the shadow's lines among its logs, which go to the same place
+const PREFIX: &str = "determinism: ";
+// How long the shadow can go quiet once the run's over before it's taken to
+// be stuck
+const SHADOW_TIMEOUT: Duration = Duration::from_secs(10);
+// How many steps the shadow can fit in a frame, so it can catch up on the ones
+// it's behind on
+const SHADOW_STEPS_PER_FRAME: f64 = 4.;
+
+// The stages a step is sampled after, and the ones that come next
+const STAGES: [(SimSet, SimSet); 4] = [
+    (SimSet::Input, SimSet::Physics),
+    (SimSet::Physics, SimSet::Collision),
+    (SimSet::Collision, SimSet::Rules),
+    (SimSet::Rules, SimSet::Transition),
+];
+
+/// Whether this is the copy of the game that plays every run again alongside
+/// the one being checked
+pub fn is_shadow() -> bool {
+    std::env::args().any(|arg| arg == SHADOW_ARG)
+}
+
+/// Makes the default plugins run the shadow without a window or sound, at
+/// the same frame rate as the simulation
+pub fn shadow(plugins: PluginGroupBuilder) -> PluginGroupBuilder {
+    if !is_shadow() {
+        return plugins;
+    }
+
+    plugins
+        // The game's already processing the assets, the shadow only reads them
+        .set(AssetPlugin {
+            mode: AssetMode::Unprocessed,
+            ..default()
+        })
+        .set(AudioPlugin {
+            global_volume: GlobalVolume::new(0.),
+            ..default()
+        })
+        .set(WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        })
+        .disable::<WinitPlugin>()
+        .add(ScheduleRunnerPlugin::run_loop(Duration::from_secs_f64(
+            1. / SIM_HZ,
+        )))
+}
+
+/// The parts of the simulation after one stage of a step, hashed one by one
+/// so a mismatch says which part went wrong
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+struct StateHash {
+    /// The tick, score and where the random numbers are at
+    run: u64,
+    player: u64,
+    obstacles: u64,
+    hazards: u64,
+}
+
+impl StateHash {
+    /// The names of the parts that don't match
+    fn differences(&self, other: &StateHash) -> Vec<&'static str> {
+        [
+            ("run", self.run == other.run),
+            ("player", self.player == other.player),
+            ("obstacles", self.obstacles == other.obstacles),
+            ("hazards", self.hazards == other.hazards),
+        ]
+        .into_iter()
+        .filter(|(_, same)| !same)
+        .map(|(part, _)| part)
+        .collect()
+    }
+}
+
+/// The state right after stage `stage` of step `tick`, counting from 0 in
+/// `STAGES`
+#[derive(Clone, Serialize, Deserialize)]
+struct Sample {
+    tick: u64,
+    stage: usize,
+    hash: StateHash,
+}
+
+/// Everything that went into a step from outside the simulation
+#[derive(Default, Serialize, Deserialize)]
+struct StepInputs {
+    /// How far into the step the player flapped, if they did
+    flap: Option<f32>,
+    physics: Option<PhysicsPreset>,
+    config: Option<GameConfig>,
+    opponent_flap: bool,
+}
+
+/// What the game tells its shadow, one per line
+#[derive(Serialize, Deserialize)]
+enum ToShadow {
+    /// Play this run from the start, with `run` counting the runs so far
+    Start { run: u64, replay: Box<Replay> },
+    /// Everything that happened on step `tick` of run `run`
+    Step {
+        run: u64,
+        tick: u64,
+        inputs: StepInputs,
+    },
+}
+
+/// What the shadow tells the game, one per line after `PREFIX`
+#[derive(Serialize, Deserialize)]
+enum FromShadow {
+    Sampled {
+        run: u64,
+        sample: Sample,
+    },
+    /// The shadow's run is over, after this many samples
+    Ended {
+        run: u64,
+        samples: usize,
+    },
+}
+
+/// The shadow process, and what it's said that hasn't been looked at yet
+#[derive(Resource)]
+struct Shadow {
+    input: ChildStdin,
+    output: Mutex<Receiver<FromShadow>>,
+    _process: Child,
+}
+
+impl Shadow {
+    fn spawn() -> io::Result<Self> {
+        let mut process = Command::new(std::env::current_exe()?)
+            .arg(SHADOW_ARG)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+        let input = process.stdin.take().expect("the shadow's input is piped");
+        let output = process.stdout.take().expect("the shadow's output is piped");
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(output).lines() {
+                let Ok(line) = line else {
+                    return;
+                };
+                let Some(message) = line.strip_prefix(PREFIX) else {
+                    continue;
+                };
+                match ron::from_str(message) {
+                    Ok(message) => {
+                        if sender.send(message).is_err() {
+                            return;
+                        }
+                    }
+                    Err(error) => warn!("Couldn't read the shadow's {message:?}: {error}"),
+                }
+            }
+        });
+
+        Ok(Self {
+            input,
+            output: Mutex::new(receiver),
+            _process: process,
+        })
+    }
+
+    fn send(&mut self, message: &ToShadow) -> io::Result<()> {
+        let line = ron::to_string(message).map_err(io::Error::other)?;
+        self.input.write_all(format!("{line}\n").as_bytes())
+    }
+}
+
+/// Every run is played again by a shadow copy of the game, fed the same
+/// inputs step by step, and has to come out exactly the same after every
+/// stage of every step
+#[derive(Resource, Default)]
+struct DeterminismCheck {
+    run: u64,
+    /// Whether the run that's on is being checked. Replays aren't, and
+    /// neither are runs once they're rewound
+    checking: bool,
+    /// Set until the shadow's been told about the run
+    starting: bool,
+    /// Samples of the run the shadow hasn't caught up to yet
+    ahead: VecDeque<Sample>,
+    sampled: usize,
+    last_tick: u64,
+    /// How many samples the shadow's run ended after, once it has
+    shadow_ended: Option<usize>,
+}
+
+impl DeterminismCheck {
+    fn receive(&mut self, message: FromShadow) {
+        match message {
+            FromShadow::Sampled { run, sample } if run == self.run => {
+                let Some(expected) = self.ahead.pop_front() else {
+                    panic!(
+                        "Determinism check failed: the shadow kept going after the run ended at tick {}",
+                        self.last_tick,
+                    );
+                };
+                let differences = expected.hash.differences(&sample.hash);
+                if expected.tick != sample.tick || expected.stage != sample.stage {
+                    panic!(
+                        "Determinism check failed: the shadow was at tick {} after {:?} when the run was at tick {} after {:?}",
+                        sample.tick, STAGES[sample.stage].0, expected.tick, STAGES[expected.stage].0,
+                    );
+                }
+                if !differences.is_empty() {
+                    panic!(
+                        "Determinism check failed: the shadow split from the run at tick {} after {:?}, in the {}",
+                        sample.tick,
+                        STAGES[sample.stage].0,
+                        differences.join(" and "),
+                    );
+                }
+            }
+            FromShadow::Ended { run, samples } if run == self.run => {
+                self.shadow_ended = Some(samples);
+            }
+            // Left over from a run that's no longer being checked
+            _ => {}
+        }
+    }
+}
+
+/// The run the shadow's playing, fed to it a step at a time
+#[derive(Resource, Default)]
+struct ShadowRun {
+    run: u64,
+    /// A run to start as soon as the last one's out of the way
+    start: Option<(u64, Box<Replay>)>,
+    steps: VecDeque<(u64, StepInputs)>,
+    samples: usize,
+}
+
+impl ShadowRun {
+    fn receive(&mut self, message: ToShadow) {
+        match message {
+            ToShadow::Start { run, replay } => {
+                self.start = Some((run, replay));
+                self.steps.clear();
+            }
+            ToShadow::Step { run, tick, inputs } => {
+                let latest = self.start.as_ref().map_or(self.run, |(run, _)| *run);
+                if run == latest {
+                    self.steps.push_back((tick, inputs));
+                }
+            }
+        }
+    }
+}
+
+#[derive(Resource)]
+struct ShadowInput(Mutex<Receiver<ToShadow>>);
+
+pub struct DeterminismPlugin;
+
+impl Plugin for DeterminismPlugin {
+    fn build(&self, app: &mut App) {
+        if is_shadow() {
+            build_shadow(app);
+            return;
+        }
+
+        // Only there for tracking down bugs, every run is played twice over
+        if !std::env::args().any(|arg| arg == "--check-determinism") {
+            return;
+        }
+        let shadow = match Shadow::spawn() {
+            Ok(shadow) => shadow,
+            Err(error) => {
+                error!("Couldn't start the determinism check's shadow: {error}");
+                return;
+            }
+        };
+
+        app.insert_resource(shadow)
+            .init_resource::<DeterminismCheck>()
+            .add_systems(
+                OnEnter(AppState::Playing),
+                start_check.after(start_recording),
+            )
+            .add_systems(
+                FixedUpdate,
+                send_step
+                    .after(SimSet::Rules)
+                    .before(SimSet::Transition)
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(Update, compare)
+            .add_systems(OnEnter(AppState::GameOver), finish_check)
+            .add_systems(OnEnter(AppState::LevelComplete), finish_check);
+        for (index, (stage, next)) in STAGES.into_iter().enumerate() {
+            app.add_systems(
+                FixedUpdate,
+                sample(index)
+                    .pipe(keep_sample)
+                    .after(stage)
+                    .before(next)
+                    .before(send_step)
+                    .run_if(in_state(AppState::Playing)),
+            );
+        }
+    }
+}
+
+fn build_shadow(app: &mut App) {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in io::stdin().lines() {
+            let Ok(line) = line else {
+                return;
+            };
+            match ron::from_str(&line) {
+                Ok(message) => {
+                    if sender.send(message).is_err() {
+                        return;
+                    }
+                }
+                Err(error) => warn!("Couldn't read {line:?} from the game: {error}"),
+            }
+        }
+    });
+
+    app.insert_resource(ShadowInput(Mutex::new(receiver)))
+        .init_resource::<ShadowRun>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            SHADOW_STEPS_PER_FRAME / SIM_HZ,
+        )))
+        .add_systems(Last, start_shadow_run)
+        .add_systems(
+            FixedFirst,
+            wait_for_step.run_if(in_state(AppState::Playing).and_then(resource_exists::<Playback>)),
+        )
+        .add_systems(OnEnter(AppState::GameOver), end_shadow_run)
+        .add_systems(OnEnter(AppState::LevelComplete), end_shadow_run);
+    for (index, (stage, next)) in STAGES.into_iter().enumerate() {
+        app.add_systems(
+            FixedUpdate,
+            sample(index)
+                .pipe(report_sample)
+                .after(stage)
+                .before(next)
+                .run_if(in_state(AppState::Playing).and_then(resource_exists::<Playback>)),
+        );
+    }
+}
+
+fn hash(value: &impl Serialize) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    ron::to_string(value)
+        .expect("simulation state is always serializable")
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+fn sample(stage: usize) -> impl FnMut(SimState) -> Option<Sample> {
+    move |sim| {
+        let mut snapshot = sim.capture()?;
+        // Which order entities come out of a query in isn't part of the simulation
+        snapshot.obstacles.sort_by(|a, b| a.x.total_cmp(&b.x));
+        snapshot.hazards.sort_by(|a, b| a.x.total_cmp(&b.x));
+
+        Some(Sample {
+            tick: snapshot.tick,
+            stage,
+            hash: StateHash {
+                run: hash(&(snapshot.tick, snapshot.score, snapshot.rng)),
+                player: hash(&snapshot.player),
+                obstacles: hash(&snapshot.obstacles),
+                hazards: hash(&snapshot.hazards),
+            },
+        })
+    }
+}
+
+// The inputs go out with the first step rather than when the run starts, so
+// everything that's set up as it starts is in the replay the shadow gets
+fn start_check(mut check: ResMut<DeterminismCheck>, recording: Option<Res<Recording>>) {
+    *check = DeterminismCheck {
+        run: check.run + 1,
+        // Replays themselves have nothing to check
+        checking: recording.is_some(),
+        starting: true,
+        ..default()
+    };
+}
+
+fn keep_sample(In(sample): In<Option<Sample>>, mut check: ResMut<DeterminismCheck>) {
+    let Some(sample) = sample else {
+        return;
+    };
+    if check.checking {
+        check.sampled += 1;
+        check.last_tick = sample.tick;
+        check.ahead.push_back(sample);
+    }
+}
+
+fn send_step(
+    mut check: ResMut<DeterminismCheck>,
+    mut shadow: ResMut<Shadow>,
+    recording: Option<Res<Recording>>,
+    mut restored: EventReader<OnSnapshotRestored>,
+    tick: Res<SimTick>,
+) {
+    let rewound = restored.read().count() > 0;
+    if !check.checking {
+        return;
+    }
+    let (false, Some(recording)) = (rewound, recording) else {
+        info!("Determinism check stopped, the run was rewound");
+        check.checking = false;
+        return;
+    };
+
+    let replay = &recording.0;
+    let mut sent = Ok(());
+    if check.starting {
+        check.starting = false;
+        let mut replay = replay.clone();
+        replay.flaps.clear();
+        replay.offsets.clear();
+        replay.physics_changes.clear();
+        replay.config_changes.clear();
+        if let Some(opponent) = &mut replay.opponent {
+            opponent.flaps.clear();
+        }
+        sent = shadow.send(&ToShadow::Start {
+            run: check.run,
+            replay: Box::new(replay),
+        });
+    }
+
+    // Everything's recorded in order, so this step's inputs are the last ones
+    let now = |at: Option<&u64>| at == Some(&tick.0);
+    let inputs = StepInputs {
+        flap: now(replay.flaps.last()).then(|| replay.offsets.last().copied().unwrap_or(0.)),
+        physics: replay
+            .physics_changes
+            .last()
+            .filter(|(at, _)| now(Some(at)))
+            .map(|(_, preset)| preset.clone()),
+        config: replay
+            .config_changes
+            .last()
+            .filter(|(at, _)| now(Some(at)))
+            .map(|(_, config)| *config),
+        opponent_flap: replay
+            .opponent
+            .as_ref()
+            .is_some_and(|opponent| now(opponent.flaps.last())),
+    };
+    let sent = sent.and_then(|_| {
+        shadow.send(&ToShadow::Step {
+            run: check.run,
+            tick: tick.0,
+            inputs,
+        })
+    });
+    if let Err(error) = sent {
+        error!("Determinism check stopped, couldn't reach the shadow: {error}");
+        check.checking = false;
+    }
+}
+
+fn compare(mut check: ResMut<DeterminismCheck>, shadow: Res<Shadow>) {
+    let output = shadow
+        .output
+        .lock()
+        .unwrap_or_else(|error| error.into_inner());
+    loop {
+        match output.try_recv() {
+            Ok(message) if check.checking => check.receive(message),
+            Ok(_) => {}
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                if check.checking {
+                    error!("Determinism check stopped, the shadow went away");
+                    check.checking = false;
+                }
+                return;
+            }
+        }
+    }
+}
+
+// Holds the game up until the shadow's run is over too, for as long as it's
+// still catching up
+fn finish_check(mut check: ResMut<DeterminismCheck>, shadow: Res<Shadow>) {
+    if !check.checking {
+        return;
+    }
+
+    let output = shadow
+        .output
+        .lock()
+        .unwrap_or_else(|error| error.into_inner());
+    while check.shadow_ended.is_none() {
+        match output.recv_timeout(SHADOW_TIMEOUT) {
+            Ok(message) => check.receive(message),
+            Err(_) => break,
+        }
+    }
+    check.checking = false;
+
+    match check.shadow_ended {
+        None => panic!(
+            "Determinism check failed: the shadow was still going after the run ended at tick {}",
+            check.last_tick,
+        ),
+        Some(samples) if samples != check.sampled => panic!(
+            "Determinism check failed: the shadow's run ended after {samples} samples instead of {}",
+            check.sampled,
+        ),
+        Some(_) => info!(
+            "Determinism check passed over {} steps",
+            check.sampled / STAGES.len()
+        ),
+    }
+}
+
+fn receive_all(world: &mut World) {
+    let input = world.resource::<ShadowInput>();
+    let messages = {
+        let input = input.0.lock().unwrap_or_else(|error| error.into_inner());
+        let mut messages = Vec::new();
+        loop {
+            match input.try_recv() {
+                Ok(message) => messages.push(message),
+                Err(TryRecvError::Empty) => break,
+                // The game's gone, so there's nothing left to shadow
+                Err(TryRecvError::Disconnected) => process::exit(0),
+            }
+        }
+        messages
+    };
+
+    let mut run = world.resource_mut::<ShadowRun>();
+    for message in messages {
+        run.receive(message);
+    }
+}
+
+// A run that's still going is given up on, the game's moved on from it. Done
+// last thing in the frame, once the menu's been entered and nothing's left
+// waiting on the world that's about to be rebuilt
+fn start_shadow_run(world: &mut World) {
+    receive_all(world);
+    let Some((run, replay)) = world.resource_mut::<ShadowRun>().start.take() else {
+        return;
+    };
+    {
+        let mut shadow_run = world.resource_mut::<ShadowRun>();
+        shadow_run.run = run;
+        shadow_run.samples = 0;
+    }
+
+    world.resource_scope(|world, mut next_seed: Mut<NextSeed>| {
+        let playback = Playback::watch(
+            *replay,
+            &mut world.resource_mut::<RunModifiers>(),
+            &mut next_seed,
+        );
+        world.insert_resource(playback);
+    });
+
+    // Going to the menu builds the world for the replay, unless it's there
+    // already. Then the replay takes it straight into the run
+    if *world.resource::<State<AppState>>().get() == AppState::MainMenu {
+        world.run_schedule(BuildWorld);
+    } else {
+        world
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::MainMenu);
+    }
+}
+
+// Nothing can happen on a step before the game's done it, so the shadow waits
+// for it
+fn wait_for_step(
+    mut run: ResMut<ShadowRun>,
+    input: Res<ShadowInput>,
+    mut playback: ResMut<Playback>,
+    tick: Res<SimTick>,
+) {
+    let input = input.0.lock().unwrap_or_else(|error| error.into_inner());
+    loop {
+        if run.start.is_some() {
+            return;
+        }
+        while run.steps.front().is_some_and(|(at, _)| *at < tick.0) {
+            run.steps.pop_front();
+        }
+        if let Some(&(at, _)) = run.steps.front() {
+            if at == tick.0 {
+                let (_, inputs) = run.steps.pop_front().expect("there's a step");
+                feed(&mut playback.replay, tick.0, inputs);
+            }
+            return;
+        }
+
+        match input.recv() {
+            Ok(message) => run.receive(message),
+            Err(_) => process::exit(0),
+        }
+    }
+}
+
+fn feed(replay: &mut Replay, tick: u64, inputs: StepInputs) {
+    if let Some(offset) = inputs.flap {
+        replay.flaps.push(tick);
+        replay.offsets.push(offset);
+    }
+    if let Some(preset) = inputs.physics {
+        replay.physics_changes.push((tick, preset));
+    }
+    if let Some(config) = inputs.config {
+        replay.config_changes.push((tick, config));
+    }
+    if let (true, Some(opponent)) = (inputs.opponent_flap, &mut replay.opponent) {
+        opponent.flaps.push(tick);
+    }
+}
+
+fn report(message: &FromShadow) {
+    let line = ron::to_string(message).expect("messages are always serializable");
+    if writeln!(io::stdout(), "{PREFIX}{line}").is_err() {
+        // The game's gone, so there's nothing left to shadow
+        process::exit(0);
+    }
+}
+
+fn report_sample(In(sample): In<Option<Sample>>, mut run: ResMut<ShadowRun>) {
+    let Some(sample) = sample else {
+        return;
+    };
+    run.samples += 1;
+    report(&FromShadow::Sampled {
+        run: run.run,
+        sample,
+    });
+}
+
+fn end_shadow_run(run: Res<ShadowRun>) {
+    report(&FromShadow::Ended {
+        run: run.run,
+        samples: run.samples,
+    });
+}
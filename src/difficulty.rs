@@ -0,0 +1,348 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    create_world,
+    curve::{pick_curve, ActiveCurve, DifficultyCurve, PatternWeights},
+    main_menu::MenuRow,
+    profile::Profile,
+    replay::Playback,
+    tutorials::{AddTutorial, Tutorial, BETWEEN_PIPES},
+    AppState, Atlas, BuildWorld, RunModifiers, Score, SimSet, ADAPTIVE, PIPE_COLUMNS, PIPE_SPACE,
+    PIPE_TO_PIPE_SPACE, SCROLL_SPEED,
+};
+
+// A run shorter than this (in seconds) is an early death
+const EARLY_DEATH: f32 = 5.;
+// A run longer than this (in seconds) is part of a good streak
+const LONG_RUN: f32 = 30.;
+// How many runs in a row it takes before the gap is adjusted
+const STREAK: usize = 3;
+const GAP_STEP: f32 = 4.;
+const MAX_GAP_ADJUSTMENT: f32 = 16.;
+
+/// How forgiving runs are on top of the curve, picked on the main menu and
+/// saved with the settings
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub enum DifficultyPreset {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl DifficultyPreset {
+    pub fn name(self) -> &'static str {
+        match self {
+            DifficultyPreset::Easy => "easy",
+            DifficultyPreset::Normal => "normal",
+            DifficultyPreset::Hard => "hard",
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            DifficultyPreset::Easy => DifficultyPreset::Normal,
+            DifficultyPreset::Normal => DifficultyPreset::Hard,
+            DifficultyPreset::Hard => DifficultyPreset::Easy,
+        }
+    }
+
+    /// What the physics preset's gravity is multiplied by
+    pub fn gravity(self) -> f32 {
+        match self {
+            DifficultyPreset::Easy => 0.85,
+            DifficultyPreset::Normal => 1.,
+            DifficultyPreset::Hard => 1.1,
+        }
+    }
+
+    /// Added on top of the curve's gap
+    fn gap(self) -> f32 {
+        match self {
+            DifficultyPreset::Easy => 8.,
+            DifficultyPreset::Normal => 0.,
+            DifficultyPreset::Hard => -4.,
+        }
+    }
+
+    /// What the curve's scroll speed is multiplied by
+    fn speed(self) -> f32 {
+        match self {
+            DifficultyPreset::Easy => 0.85,
+            DifficultyPreset::Normal => 1.,
+            DifficultyPreset::Hard => 1.15,
+        }
+    }
+
+    /// What the curve's space between columns is multiplied by
+    fn spacing(self) -> f32 {
+        match self {
+            DifficultyPreset::Easy => 1.1,
+            DifficultyPreset::Normal => 1.,
+            DifficultyPreset::Hard => 0.9,
+        }
+    }
+}
+
+/// Where the difficulty curve is at for the current score
+#[derive(Resource)]
+pub struct Difficulty {
+    /// The run's preset, which everything the curve gives is adjusted by
+    pub preset: DifficultyPreset,
+    /// Added on top of the curve's gap by the adaptive mode
+    pub gap_adjustment: f32,
+    pub pipe_space: f32,
+    pub pipe_to_pipe_space: f32,
+    pub pipe_columns: usize,
+    pub scroll_speed: f32,
+    pub patterns: PatternWeights,
+    pub gust_strength: f32,
+    pub gust_interval: f32,
+    /// Whether the score's made it far enough along the curve for unusual
+    /// patterns to show up
+    pub unusual: bool,
+}
+
+impl Difficulty {
+    /// Far enough along the curve for unusual patterns, which goes by the
+    /// score alone so the preset and the adaptive mode don't hold them back
+    pub fn is_high(&self) -> bool {
+        self.unusual
+    }
+
+    pub fn follow(&mut self, curve: &DifficultyCurve, score: u32) {
+        let point = curve.sample(score);
+        self.pipe_space = point.pipe_space + self.gap_adjustment + self.preset.gap();
+        self.pipe_to_pipe_space = point.pipe_to_pipe_space * self.preset.spacing();
+        self.pipe_columns = curve.pipe_columns;
+        self.scroll_speed = point.scroll_speed * self.preset.speed();
+        self.patterns = point.patterns;
+        self.gust_strength = point.gust_strength;
+        self.gust_interval = point.gust_interval;
+        self.unusual = score >= curve.unusual_from;
+    }
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Self {
+            preset: DifficultyPreset::Normal,
+            gap_adjustment: 0.,
+            pipe_space: PIPE_SPACE,
+            pipe_to_pipe_space: PIPE_TO_PIPE_SPACE,
+            pipe_columns: PIPE_COLUMNS,
+            scroll_speed: SCROLL_SPEED,
+            patterns: PatternWeights::default(),
+            gust_strength: 0.,
+            gust_interval: 0.,
+            unusual: false,
+        }
+    }
+}
+
+/// Rolling record of the latest runs that the adaptive mode nudges the gap with
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct PerformanceModel {
+    recent_runs: VecDeque<f32>,
+    gap_adjustment: f32,
+}
+
+impl PerformanceModel {
+    fn record(&mut self, duration: f32) {
+        self.recent_runs.push_back(duration);
+        while self.recent_runs.len() > STREAK {
+            self.recent_runs.pop_front();
+        }
+
+        if self.recent_runs.len() < STREAK {
+            return;
+        }
+
+        if self.recent_runs.iter().all(|&run| run < EARLY_DEATH) {
+            self.gap_adjustment += GAP_STEP;
+            self.recent_runs.clear();
+        } else if self.recent_runs.iter().all(|&run| run > LONG_RUN) {
+            self.gap_adjustment -= GAP_STEP;
+            self.recent_runs.clear();
+        }
+
+        self.gap_adjustment = self
+            .gap_adjustment
+            .clamp(-MAX_GAP_ADJUSTMENT, MAX_GAP_ADJUSTMENT);
+    }
+}
+
+#[derive(Resource, Default)]
+struct RunDuration(f32);
+
+#[derive(Component)]
+struct PresetLabel;
+
+#[derive(Component)]
+struct AdaptiveLabel;
+
+pub struct DifficultyPlugin;
+
+impl Plugin for DifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Difficulty>()
+            .init_resource::<RunDuration>()
+            .add_tutorial(Tutorial {
+                id: ADAPTIVE,
+                title: "Adaptive",
+                text:
+                    "The gaps open up when you're struggling and close in when you're flying well",
+                art: Atlas::PipeBottom,
+                example: BETWEEN_PIPES,
+            })
+            .add_systems(
+                BuildWorld,
+                apply_difficulty.after(pick_curve).before(create_world),
+            )
+            .add_systems(
+                FixedUpdate,
+                follow_curve
+                    .in_set(SimSet::Rules)
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(OnEnter(AppState::Playing), reset_run_duration)
+            .add_systems(
+                Update,
+                track_run_duration.run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(
+                OnEnter(AppState::GameOver),
+                record_run.run_if(not(resource_exists::<Playback>)),
+            )
+            .add_systems(
+                OnEnter(AppState::MainMenu),
+                spawn_label.run_if(not(resource_exists::<Playback>)),
+            )
+            .add_systems(
+                Update,
+                (
+                    cycle_preset,
+                    toggle_adaptive,
+                    follow_preset.run_if(resource_changed::<DifficultyPreset>),
+                    draw_label.run_if(resource_changed::<RunModifiers>),
+                )
+                    .chain()
+                    .run_if(
+                        in_state(AppState::MainMenu).and_then(not(resource_exists::<Playback>)),
+                    ),
+            )
+            .add_systems(OnExit(AppState::MainMenu), despawn_label);
+    }
+}
+
+fn reset_run_duration(mut duration: ResMut<RunDuration>) {
+    duration.0 = 0.;
+}
+
+fn track_run_duration(mut duration: ResMut<RunDuration>, time: Res<Time>) {
+    duration.0 += time.delta_seconds();
+}
+
+fn record_run(
+    duration: Res<RunDuration>,
+    modifiers: Res<RunModifiers>,
+    difficulty: Res<Difficulty>,
+    mut profile: ResMut<Profile>,
+) {
+    if modifiers.adaptive {
+        info!(
+            "Run over after {:.1}s [adaptive, gap {:+}]",
+            duration.0, difficulty.gap_adjustment
+        );
+    } else {
+        info!("Run over after {:.1}s", duration.0);
+    }
+
+    profile.performance.record(duration.0);
+}
+
+pub fn apply_difficulty(
+    mut difficulty: ResMut<Difficulty>,
+    curve: Res<ActiveCurve>,
+    modifiers: Res<RunModifiers>,
+    profile: Res<Profile>,
+    playback: Option<Res<Playback>>,
+) {
+    // Replays play out with the gap they were recorded with
+    difficulty.gap_adjustment = match playback {
+        Some(playback) => playback.replay.gap_adjustment,
+        None if modifiers.adaptive => profile.performance.gap_adjustment,
+        None => 0.,
+    };
+    difficulty.preset = modifiers.preset;
+    difficulty.follow(&curve.0, 0);
+}
+
+fn follow_curve(mut difficulty: ResMut<Difficulty>, curve: Res<ActiveCurve>, score: Res<Score>) {
+    difficulty.follow(&curve.0, score.0);
+}
+
+fn cycle_preset(keys: Res<ButtonInput<KeyCode>>, mut preset: ResMut<DifficultyPreset>) {
+    if keys.just_pressed(KeyCode::KeyQ) {
+        *preset = preset.next();
+    }
+}
+
+// Like the breather, it's one of the run's modifiers so it's only ever ranked
+// against other runs with it
+fn toggle_adaptive(keys: Res<ButtonInput<KeyCode>>, mut modifiers: ResMut<RunModifiers>) {
+    if keys.just_pressed(KeyCode::KeyZ) {
+        modifiers.adaptive = !modifiers.adaptive;
+    }
+}
+
+// Kept in the run's modifiers like the physics, so a run's only ever ranked
+// against others on the same preset and replays play out on the one they
+// were recorded with
+fn follow_preset(preset: Res<DifficultyPreset>, mut modifiers: ResMut<RunModifiers>) {
+    if modifiers.preset != *preset {
+        modifiers.preset = *preset;
+    }
+}
+
+fn spawn_label(mut commands: Commands) {
+    let label = || {
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 14.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+    };
+    commands.spawn((PresetLabel, MenuRow::Preset, label()));
+    commands.spawn((AdaptiveLabel, MenuRow::Adaptive, label()));
+}
+
+fn despawn_label(
+    mut commands: Commands,
+    query: Query<Entity, Or<(With<PresetLabel>, With<AdaptiveLabel>)>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn draw_label(
+    modifiers: Res<RunModifiers>,
+    mut presets: Query<&mut Text, (With<PresetLabel>, Without<AdaptiveLabel>)>,
+    mut adaptives: Query<&mut Text, With<AdaptiveLabel>>,
+) {
+    for mut text in &mut presets {
+        text.sections[0].value = format!("Q difficulty: {}", modifiers.preset.name());
+    }
+    let state = if modifiers.adaptive { "on" } else { "off" };
+    for mut text in &mut adaptives {
+        text.sections[0].value = format!("Z adaptive: {state}");
+    }
+}
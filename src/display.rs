@@ -0,0 +1,167 @@
+use bevy::{
+    prelude::*,
+    render::camera::Viewport,
+    window::{PrimaryWindow, WindowCreated, WindowResized, WindowScaleFactorChanged},
+};
+
+use crate::{console::Console, pause::PauseState, AppState};
+
+/// How big the game is on the screen at its smallest, in physical pixels
+const GAME_SIZE: Vec2 = Vec2::new(288., 512.);
+// Seconds counted down before a run that was held still goes on
+const COUNTDOWN: f32 = 3.;
+
+/// A run that's held still because the display changed under it, until it's
+/// been counted back in
+#[derive(Resource)]
+pub struct DisplayPause(Timer);
+
+#[derive(Component)]
+struct CountdownText;
+
+pub struct DisplayPlugin;
+
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            fit_viewport.run_if(
+                on_event::<WindowCreated>()
+                    .or_else(on_event::<WindowResized>())
+                    .or_else(on_event::<WindowScaleFactorChanged>()),
+            ),
+        )
+        .add_systems(
+            Update,
+            pause_run.run_if(in_state(AppState::Playing).and_then(
+                on_event::<WindowResized>().or_else(on_event::<WindowScaleFactorChanged>()),
+            )),
+        )
+        .add_systems(
+            Update,
+            // The console and the pause overlay hold the run still for
+            // themselves while they're up
+            count_in.run_if(
+                resource_exists::<DisplayPause>
+                    .and_then(not(resource_exists::<Console>))
+                    .and_then(in_state(PauseState::Running)),
+            ),
+        )
+        .add_systems(OnExit(AppState::Playing), end_pause);
+    }
+}
+
+// Docking or undocking a laptop can change the size of the window and how
+// many pixels it has, so the game is fitted to it again. Scaled up by whole
+// steps where it fits so the pixels stay sharp, and shrunk where it doesn't
+fn fit_viewport(windows: Query<&Window, With<PrimaryWindow>>, mut cameras: Query<&mut Camera>) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let window_size = Vec2::new(
+        window.physical_width() as f32,
+        window.physical_height() as f32,
+    );
+    let fit = (window_size / GAME_SIZE).min_element();
+    let zoom = if fit >= 1. { fit.floor() } else { fit };
+
+    let size = (GAME_SIZE * zoom).max(Vec2::ONE);
+    let position = ((window_size - size) / 2.).max(Vec2::ZERO);
+    for mut camera in &mut cameras {
+        camera.viewport = Some(Viewport {
+            physical_position: position.as_uvec2(),
+            physical_size: size.as_uvec2(),
+            ..default()
+        });
+    }
+}
+
+fn pause_run(
+    mut commands: Commands,
+    mut time: ResMut<Time<Virtual>>,
+    pause: Option<ResMut<DisplayPause>>,
+) {
+    // Another change while counting in starts the count over
+    if let Some(mut pause) = pause {
+        pause.0.reset();
+        return;
+    }
+    // Something else already has the run held still
+    if time.is_paused() {
+        return;
+    }
+
+    time.pause();
+    commands.insert_resource(DisplayPause(Timer::from_seconds(
+        COUNTDOWN,
+        TimerMode::Once,
+    )));
+    commands
+        .spawn((
+            CountdownText,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "",
+                TextStyle {
+                    font_size: 48.,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
+
+// Counted in real time, the run's own clock is the one that's stopped
+fn count_in(
+    mut commands: Commands,
+    mut pause: ResMut<DisplayPause>,
+    mut time: ResMut<Time<Virtual>>,
+    real: Res<Time<Real>>,
+    mut text: Query<&mut Text>,
+    countdown: Query<(Entity, &Children), With<CountdownText>>,
+) {
+    pause.0.tick(real.delta());
+    if pause.0.finished() {
+        time.unpause();
+        commands.remove_resource::<DisplayPause>();
+        for (entity, _) in &countdown {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    }
+
+    let left = pause.0.remaining_secs().ceil();
+    for (_, children) in &countdown {
+        let mut texts = text.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = format!("{left}");
+        }
+    }
+}
+
+fn end_pause(
+    mut commands: Commands,
+    pause: Option<Res<DisplayPause>>,
+    mut time: ResMut<Time<Virtual>>,
+    query: Query<Entity, With<CountdownText>>,
+) {
+    if pause.is_some() {
+        time.unpause();
+        commands.remove_resource::<DisplayPause>();
+    }
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::ron_asset::RonLoader;
+
+const EFFECTS_FILE: &str = "game.effects.ron";
+
+/// How the world shakes when a cue asks for it, or the run's over
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct ShakeSettings {
+    /// How far the world moves at full shake
+    pub amplitude: f32,
+    /// How many times a second it jerks in a new direction
+    pub frequency: f32,
+    /// How much shake wears off every second
+    pub decay: f32,
+}
+
+impl Default for ShakeSettings {
+    fn default() -> Self {
+        Self {
+            amplitude: 4.,
+            frequency: 60.,
+            decay: 2.,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Easing {
+    Linear,
+    Smoothstep,
+    /// Quick at first and slowing down towards the end
+    Out,
+}
+
+impl Easing {
+    pub fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::Smoothstep => t * t * (3. - 2. * t),
+            Easing::Out => 1. - (1. - t) * (1. - t),
+        }
+    }
+}
+
+/// How the kill cam closes in on a crash
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct DeathZoom {
+    /// Seconds the crash is held for before the game over screen
+    pub freeze: f32,
+    /// Seconds it takes to zoom all the way in
+    pub duration: f32,
+    /// How many times bigger things are at the end of the zoom
+    pub zoom: f32,
+    pub easing: Easing,
+}
+
+impl Default for DeathZoom {
+    fn default() -> Self {
+        Self {
+            freeze: 1.,
+            duration: 0.2,
+            zoom: 2.,
+            easing: Easing::Smoothstep,
+        }
+    }
+}
+
+#[derive(Asset, TypePath, Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct CameraEffects {
+    pub shake: ShakeSettings,
+    pub death_zoom: DeathZoom,
+    /// Starts off at full when the game over screen comes up
+    pub death_shake: ShakeSettings,
+}
+
+impl Default for CameraEffects {
+    fn default() -> Self {
+        Self {
+            shake: ShakeSettings::default(),
+            death_zoom: DeathZoom::default(),
+            death_shake: ShakeSettings {
+                amplitude: 3.,
+                frequency: 30.,
+                decay: 2.5,
+            },
+        }
+    }
+}
+
+/// The camera effects in use, the defaults until the file has loaded
+#[derive(Resource, Default)]
+pub struct ActiveEffects(pub CameraEffects);
+
+#[derive(Resource)]
+struct EffectsHandle(Handle<CameraEffects>);
+
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<CameraEffects>()
+            .register_asset_loader(RonLoader::<CameraEffects>::new(&["effects.ron"]));
+
+        let handle = app.world.resource::<AssetServer>().load(EFFECTS_FILE);
+        app.insert_resource(EffectsHandle(handle))
+            .init_resource::<ActiveEffects>()
+            .add_systems(Update, reload_effects);
+    }
+}
+
+// Edits to the effects file apply straight away, so the feel can be tuned
+// while playing
+fn reload_effects(
+    mut active: ResMut<ActiveEffects>,
+    mut reader: EventReader<AssetEvent<CameraEffects>>,
+    handle: Res<EffectsHandle>,
+    effects: Res<Assets<CameraEffects>>,
+) {
+    for event in reader.read() {
+        if !event.is_loaded_with_dependencies(&handle.0) && !event.is_modified(&handle.0) {
+            continue;
+        }
+
+        if let Some(effects) = effects.get(&handle.0) {
+            info!("Camera effects loaded");
+            active.0 = *effects;
+        }
+    }
+}
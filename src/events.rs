@@ -0,0 +1,292 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bevy::{
+    prelude::*,
+    tasks::{block_on, poll_once, IoTaskPool, Task},
+    time::common_conditions::on_timer,
+};
+use serde::Deserialize;
+
+use crate::{
+    paint_sky, replay::Playback, AppState, Background, RunModifiers, TimeOfDay, WorldTime,
+};
+
+/// How the world looks while an event is being played
+#[derive(Deserialize, Clone, Copy)]
+#[serde(default)]
+struct Theme {
+    /// Multiplied onto the background, as red, green and blue
+    sky: [f32; 3],
+    /// The color the event is announced in on the menu
+    banner: [f32; 3],
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            sky: [1., 1., 1.],
+            banner: [1., 0.85, 0.3],
+        }
+    }
+}
+
+/// A challenge that's only around for a while, played with its own modifiers
+#[derive(Deserialize, Clone)]
+struct Event {
+    name: String,
+    modifiers: RunModifiers,
+    #[serde(default)]
+    theme: Theme,
+    /// When it's over, in seconds since the Unix epoch
+    ends_at: u64,
+}
+
+impl Event {
+    /// How many seconds it has left, none once it's over
+    fn remaining(&self, now: u64) -> Option<u64> {
+        self.ends_at.checked_sub(now).filter(|&seconds| seconds > 0)
+    }
+}
+
+/// What the events URL serves
+#[derive(Deserialize)]
+struct Manifest {
+    events: Vec<Event>,
+}
+
+/// The manifest being downloaded in the background
+#[derive(Resource)]
+struct FetchEvents(Task<Result<Manifest, String>>);
+
+/// Events from the manifest, some of which might have ended since
+#[derive(Resource, Default)]
+struct Events(Vec<Event>);
+
+/// Present while the player has picked an event to play
+#[derive(Resource)]
+struct EventRun {
+    theme: Theme,
+    ends_at: u64,
+    /// The player's own modifiers, put back once they stop playing the event
+    modifiers: RunModifiers,
+}
+
+#[derive(Component)]
+struct EventBanner;
+
+#[derive(Component)]
+struct BannerText;
+
+pub struct EventsPlugin;
+
+impl Plugin for EventsPlugin {
+    fn build(&self, app: &mut App) {
+        let mut args = std::env::args().skip_while(|arg| arg != "--events-url");
+        let Some(url) = args.nth(1) else {
+            return;
+        };
+
+        let task = IoTaskPool::get().spawn(async move { fetch_manifest(&url) });
+        app.insert_resource(FetchEvents(task))
+            .init_resource::<Events>()
+            .add_systems(
+                Update,
+                receive_manifest.run_if(resource_exists::<FetchEvents>),
+            )
+            .add_systems(
+                OnEnter(AppState::MainMenu),
+                (spawn_banner, draw_banner).chain(),
+            )
+            .add_systems(OnExit(AppState::MainMenu), despawn_banner)
+            .add_systems(
+                Update,
+                (
+                    pick_event.run_if(not(resource_exists::<Playback>)),
+                    // Often enough to keep the time left up to date
+                    draw_banner.run_if(
+                        on_timer(Duration::from_secs(1)).or_else(
+                            resource_changed::<Events>
+                                .or_else(resource_changed_or_removed::<EventRun>()),
+                        ),
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::MainMenu)),
+            )
+            .add_systems(Update, apply_theme.after(paint_sky));
+    }
+}
+
+fn fetch_manifest(url: &str) -> Result<Manifest, String> {
+    ureq::get(url)
+        .call()
+        .map_err(|error| error.to_string())?
+        .into_json()
+        .map_err(|error| error.to_string())
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+fn receive_manifest(
+    mut commands: Commands,
+    mut fetch: ResMut<FetchEvents>,
+    mut events: ResMut<Events>,
+) {
+    let Some(result) = block_on(poll_once(&mut fetch.0)) else {
+        return;
+    };
+    commands.remove_resource::<FetchEvents>();
+
+    match result {
+        Ok(manifest) => {
+            let now = now();
+            events.0 = manifest
+                .events
+                .into_iter()
+                .filter(|event| event.remaining(now).is_some())
+                .collect();
+            info!("{} events running", events.0.len());
+        }
+        // The game is the same without them, so there's nothing to tell the player
+        Err(error) => warn!("Couldn't fetch events: {error}"),
+    }
+}
+
+/// The event the menu offers, the one that ends soonest
+fn current_event(events: &Events, now: u64) -> Option<&Event> {
+    events
+        .0
+        .iter()
+        .filter(|event| event.remaining(now).is_some())
+        .min_by_key(|event| event.ends_at)
+}
+
+fn pick_event(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    events: Res<Events>,
+    event_run: Option<Res<EventRun>>,
+    mut modifiers: ResMut<RunModifiers>,
+) {
+    let pressed = keys.just_pressed(KeyCode::KeyV);
+    match event_run {
+        // Once the event is over, runs go back to being the player's own
+        Some(event_run) if pressed || event_run.ends_at <= now() => {
+            *modifiers = event_run.modifiers.clone();
+            commands.remove_resource::<EventRun>();
+        }
+        None if pressed => {
+            let Some(event) = current_event(&events, now()) else {
+                return;
+            };
+            let own = std::mem::replace(modifiers.as_mut(), event.modifiers.clone());
+            commands.insert_resource(EventRun {
+                theme: event.theme,
+                ends_at: event.ends_at,
+                modifiers: own,
+            });
+        }
+        _ => {}
+    }
+}
+
+fn spawn_banner(mut commands: Commands) {
+    commands
+        .spawn((
+            EventBanner,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(8.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                BannerText,
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 12.,
+                        ..default()
+                    },
+                ),
+            ));
+        });
+}
+
+fn despawn_banner(mut commands: Commands, query: Query<Entity, With<EventBanner>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn draw_banner(
+    events: Res<Events>,
+    event_run: Option<Res<EventRun>>,
+    mut query: Query<&mut Text, With<BannerText>>,
+) {
+    let now = now();
+    let banner = current_event(&events, now).map(|event| {
+        let remaining = event.remaining(now).unwrap_or_default();
+        let left = match remaining / 3600 {
+            0 => format!("{}m", remaining / 60),
+            hours if hours < 48 => format!("{hours}h"),
+            hours => format!("{}d", hours / 24),
+        };
+        let action = if event_run.is_some() {
+            "V leave"
+        } else {
+            "V play"
+        };
+        let [r, g, b] = event.theme.banner;
+        (
+            format!("{} ({left} left)  {action}", event.name),
+            Color::rgb(r, g, b),
+        )
+    });
+
+    for mut text in &mut query {
+        let section = &mut text.sections[0];
+        (section.value, section.style.color) = banner.clone().unwrap_or_default();
+    }
+}
+
+// The sky takes on the event's colors for as long as it's picked, over the
+// time of day's own
+fn apply_theme(
+    event_run: Option<Res<EventRun>>,
+    time_of_day: Option<Res<TimeOfDay>>,
+    world_time: Option<Res<WorldTime>>,
+    mut themed: Local<bool>,
+    mut query: Query<(&mut Sprite, Ref<Background>)>,
+) {
+    let repaint = *themed != event_run.is_some()
+        || event_run.as_ref().is_some_and(|run| run.is_changed())
+        || time_of_day.as_ref().is_some_and(|time| time.is_changed())
+        || world_time.as_ref().is_some_and(|time| time.is_changed());
+    *themed = event_run.is_some();
+    let untinted = match (time_of_day, world_time) {
+        (Some(time_of_day), Some(world_time)) => time_of_day.sky(&world_time),
+        _ => Color::WHITE,
+    };
+    let color = event_run.map_or(untinted, |event_run| {
+        let [r, g, b] = event_run.theme.sky;
+        Color::rgb(r, g, b)
+    });
+
+    for (mut sprite, background) in &mut query {
+        if repaint || background.is_added() {
+            sprite.color = color;
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use std::time::Duration;
+
+use bevy::{
+    input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest},
+    prelude::*,
+    utils::HashMap,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bonus::OnCoinCollected,
+    camera_shake::Shake,
+    ceiling::OnBonked,
+    characters::ActiveCharacter,
+    effects::ActiveEffects,
+    low_power::LowPower,
+    mixer::{Channel, Duck, Ducking, PlaySound},
+    particles::Particles,
+    prompts::LastGamepad,
+    ron_asset::RonLoader,
+    settings::Accessibility,
+    OnCrashed, OnDied, OnJumped, PipePassed, Root,
+};
+
+const FEEDBACK_FILE: &str = "game.feedback.ron";
+
+/// Something happening in the game that the player should feel
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Cue {
+    Flap,
+    Crash,
+    /// Hitting the floor after a crash
+    Die,
+    Bonk,
+    Coin,
+    Pipe,
+}
+
+/// Game events that come with a cue, forwarded to every feedback channel
+pub trait FeedbackSource: Event {
+    /// The cue and where in the world it happened
+    fn feedback(&self) -> (Cue, Vec2);
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct Rumble {
+    pub strength: f32,
+    pub duration: f32,
+}
+
+/// Everything that happens on a cue, a channel is skipped when it's left out
+#[derive(Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Feedback {
+    /// Path to a sound in the assets folder
+    pub sound: Option<String>,
+    pub volume: Option<f32>,
+    pub particles: Option<Particles>,
+    pub rumble: Option<Rumble>,
+    /// From 0 to 1
+    pub shake: f32,
+    /// Dips the music under whatever else happens
+    pub duck: Option<Duck>,
+}
+
+#[derive(Asset, TypePath, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct FeedbackMap {
+    pub cues: HashMap<Cue, Feedback>,
+}
+
+#[derive(Resource)]
+struct FeedbackHandle(Handle<FeedbackMap>);
+
+#[derive(Event)]
+struct OnFeedback {
+    cue: Cue,
+    position: Vec2,
+}
+
+impl FeedbackSource for OnJumped {
+    fn feedback(&self) -> (Cue, Vec2) {
+        (Cue::Flap, self.position)
+    }
+}
+
+impl FeedbackSource for OnCrashed {
+    fn feedback(&self) -> (Cue, Vec2) {
+        (Cue::Crash, self.position)
+    }
+}
+
+impl FeedbackSource for OnDied {
+    fn feedback(&self) -> (Cue, Vec2) {
+        (Cue::Die, self.position)
+    }
+}
+
+impl FeedbackSource for OnBonked {
+    fn feedback(&self) -> (Cue, Vec2) {
+        (Cue::Bonk, self.position)
+    }
+}
+
+impl FeedbackSource for OnCoinCollected {
+    fn feedback(&self) -> (Cue, Vec2) {
+        (Cue::Coin, self.position)
+    }
+}
+
+impl FeedbackSource for PipePassed {
+    fn feedback(&self) -> (Cue, Vec2) {
+        (Cue::Pipe, self.position)
+    }
+}
+
+pub struct FeedbackPlugin;
+
+impl Plugin for FeedbackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<FeedbackMap>()
+            .register_asset_loader(RonLoader::<FeedbackMap>::new(&["feedback.ron"]));
+
+        let handle = app.world.resource::<AssetServer>().load(FEEDBACK_FILE);
+        app.insert_resource(FeedbackHandle(handle))
+            .add_event::<OnFeedback>()
+            .add_systems(
+                Update,
+                (
+                    (
+                        forward::<OnJumped>,
+                        forward::<OnCrashed>,
+                        forward::<OnDied>,
+                        forward::<OnBonked>,
+                        forward::<OnCoinCollected>,
+                        forward::<PipePassed>,
+                    ),
+                    play_feedback,
+                )
+                    .chain(),
+            );
+    }
+}
+
+fn forward<E: FeedbackSource>(mut reader: EventReader<E>, mut writer: EventWriter<OnFeedback>) {
+    for event in reader.read() {
+        let (cue, position) = event.feedback();
+        writer.send(OnFeedback { cue, position });
+    }
+}
+
+fn play_feedback(
+    mut commands: Commands,
+    mut reader: EventReader<OnFeedback>,
+    mut rumble: EventWriter<GamepadRumbleRequest>,
+    mut sounds: EventWriter<PlaySound>,
+    mut shake: ResMut<Shake>,
+    mut ducking: ResMut<Ducking>,
+    handle: Res<FeedbackHandle>,
+    maps: Res<Assets<FeedbackMap>>,
+    gamepads: Res<Gamepads>,
+    last_gamepad: Res<LastGamepad>,
+    character: Res<ActiveCharacter>,
+    power: Res<LowPower>,
+    accessibility: Res<Accessibility>,
+    effects: Res<ActiveEffects>,
+    root: Query<Entity, With<Root>>,
+) {
+    // Nothing to play until the map has loaded
+    let Some(map) = maps.get(&handle.0) else {
+        reader.clear();
+        return;
+    };
+
+    for event in reader.read() {
+        let Some(feedback) = map.cues.get(&event.cue) else {
+            continue;
+        };
+
+        if let Some(sound) = &feedback.sound {
+            // Every bird sounds a little different when it flaps
+            let speed = match event.cue {
+                Cue::Flap => character.0.pitch,
+                _ => 1.,
+            };
+            sounds.send(PlaySound {
+                volume: feedback.volume.unwrap_or(1.),
+                speed,
+                ..PlaySound::new(sound.clone(), Channel::Effects)
+            });
+        }
+        if let Some(duck) = feedback.duck {
+            ducking.duck(duck);
+        }
+
+        if let (Some(particles), Ok(root)) = (&feedback.particles, root.get_single()) {
+            let color = if particles.feathers {
+                character.0.feathers
+            } else {
+                particles.color
+            };
+            commands.entity(root).with_children(|parent| {
+                particles.burst(
+                    parent,
+                    event.position.extend(5.),
+                    color,
+                    power.particles(particles.count),
+                );
+            });
+        }
+
+        if let Some(Rumble { strength, duration }) =
+            feedback.rumble.filter(|_| accessibility.rumble)
+        {
+            // Only the pad that's being played with, unless it's been
+            // unplugged or none has been pressed yet
+            let rumbling = match last_gamepad.0 {
+                Some(gamepad) if gamepads.contains(gamepad) => vec![gamepad],
+                _ => gamepads.iter().collect(),
+            };
+            for gamepad in rumbling {
+                rumble.send(GamepadRumbleRequest::Add {
+                    gamepad,
+                    duration: Duration::from_secs_f32(duration),
+                    intensity: GamepadRumbleIntensity {
+                        strong_motor: strength,
+                        weak_motor: strength,
+                    },
+                });
+            }
+        }
+
+        if accessibility.screen_shake && feedback.shake > 0. {
+            shake.add(effects.0.shake, feedback.shake);
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use bevy::prelude::*;
+
+use crate::OnCrashed;
+
+// How long the screen takes to fade back from white
+const FLASH_DURATION: f32 = 0.15;
+
+/// Covers the whole screen in white for a moment when the bird hits something
+#[derive(Component)]
+struct Flash(Timer);
+
+pub struct FlashPlugin;
+
+impl Plugin for FlashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (start_flash, fade_flash).chain());
+    }
+}
+
+fn start_flash(
+    mut commands: Commands,
+    mut reader: EventReader<OnCrashed>,
+    flashes: Query<Entity, With<Flash>>,
+) {
+    if reader.read().count() == 0 {
+        return;
+    }
+
+    // A crash on top of another only starts it over
+    for entity in &flashes {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.spawn((
+        Flash(Timer::from_seconds(FLASH_DURATION, TimerMode::Once)),
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            background_color: Color::WHITE.into(),
+            // Over the score and everything else
+            z_index: ZIndex::Global(i32::MAX),
+            ..default()
+        },
+    ));
+}
+
+fn fade_flash(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Flash, &mut BackgroundColor)>,
+    time: Res<Time>,
+) {
+    for (entity, mut flash, mut color) in &mut query {
+        if flash.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        color.0.set_a(1. - flash.0.fraction());
+    }
+}
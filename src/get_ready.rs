@@ -0,0 +1,54 @@
+use bevy::prelude::*;
+
+use crate::{
+    actions::{Action, Actions},
+    replay::Playback,
+    AppState, QueuedFlap,
+};
+
+/// The "Get Ready!" sign above the bird, only shown before a run starts
+#[derive(Component)]
+pub struct GetReadySign;
+
+pub struct GetReadyPlugin;
+
+impl Plugin for GetReadyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::GetReady), show_sign)
+            .add_systems(
+                Update,
+                take_off.run_if(
+                    in_state(AppState::GetReady).and_then(not(resource_exists::<Playback>)),
+                ),
+            )
+            .add_systems(OnExit(AppState::GetReady), hide_sign);
+    }
+}
+
+fn show_sign(mut query: Query<&mut Visibility, With<GetReadySign>>) {
+    for mut visibility in &mut query {
+        *visibility = Visibility::Inherited;
+    }
+}
+
+fn hide_sign(mut query: Query<&mut Visibility, With<GetReadySign>>) {
+    for mut visibility in &mut query {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+// Gravity's off until the run starts, so the bird keeps bobbing in place for
+// as long as the player needs
+pub fn take_off(
+    mut state: ResMut<NextState<AppState>>,
+    mut queued: ResMut<QueuedFlap>,
+    mut actions: ResMut<Actions>,
+) {
+    if actions.take(Action::Flap) {
+        state.set(AppState::Playing);
+        // The run starts with this flap, right at the start of its first step
+        queued.0 = Some(0.);
+    } else if actions.take(Action::Pause) {
+        state.set(AppState::MainMenu);
+    }
+}
@@ -0,0 +1,325 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    difficulty::Difficulty, profile::Profile, replay::Playback, scroll::ScrollEase,
+    snapshot::OnSnapshotRestored, world_running, AppState, Atlas, BirdState, GameRng, Player, Root,
+    RunModifiers, Score, SimSet, SimTick, SpriteSheet,
+};
+
+// Anything further left than this has scrolled off the screen
+const OFF_SCREEN: f32 = -80.;
+
+/// When the ghost is drawn, going by how far its run got compared to this one
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ShowGhost {
+    #[default]
+    Always,
+    /// Until the player makes it past where the ghost crashed
+    WhenAhead,
+    /// Once the player has made it past where the ghost crashed
+    WhenBehind,
+}
+
+/// How the ghost of the best run on a seed is drawn, edited by hand in the
+/// profile
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct GhostSettings {
+    /// How see-through the ghost and its trail are, from 0 (not at all
+    /// visible) to 1
+    pub opacity: f32,
+    /// A breadcrumb is left where the ghost was every this many steps, none
+    /// when it's 0
+    pub breadcrumbs: u64,
+    pub show: ShowGhost,
+}
+
+impl Default for GhostSettings {
+    fn default() -> Self {
+        Self {
+            opacity: 0.4,
+            breadcrumbs: 0,
+            show: ShowGhost::Always,
+        }
+    }
+}
+
+/// Where the bird was on every step of a run
+#[derive(Clone)]
+struct Track {
+    modifiers: RunModifiers,
+    score: u32,
+    /// How far the world had scrolled and how high the bird was, one for
+    /// every step
+    points: Vec<Vec2>,
+}
+
+impl Track {
+    /// Where the bird was on `tick`, or where it crashed once the run is over
+    fn at(&self, tick: u64) -> Option<Vec2> {
+        let last = self.points.len().checked_sub(1)?;
+        self.points.get((tick as usize).min(last)).copied()
+    }
+
+    fn distance(&self) -> f32 {
+        self.points.last().map_or(0., |point| point.x)
+    }
+}
+
+/// The best run on every seed played this session
+#[derive(Resource, Default)]
+struct Ghosts(HashMap<u64, Track>);
+
+/// The run that's being played right now
+#[derive(Resource)]
+struct Tracking {
+    seed: u64,
+    /// How far the world has scrolled since the run started
+    distance: f32,
+    track: Track,
+    /// The run was taken back to an earlier step, so it doesn't count
+    rewound: bool,
+}
+
+/// The run the player is racing against
+#[derive(Resource)]
+struct Racing(Track);
+
+#[derive(Component)]
+struct Ghost;
+
+/// How far the world had scrolled when the ghost left this behind
+#[derive(Component)]
+struct Breadcrumb(f32);
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Ghosts>()
+            .add_systems(OnEnter(AppState::Playing), start_race)
+            .add_systems(OnExit(AppState::Playing), finish_track)
+            .add_systems(
+                FixedUpdate,
+                (
+                    track_player,
+                    drop_breadcrumbs.run_if(in_state(AppState::Playing)),
+                )
+                    .chain()
+                    .in_set(SimSet::Rules)
+                    .run_if(resource_exists::<Tracking>),
+            )
+            .add_systems(
+                Update,
+                (
+                    rewind_track.run_if(resource_exists::<Tracking>),
+                    move_ghost.run_if(
+                        world_running
+                            .and_then(resource_exists::<Tracking>)
+                            .and_then(resource_exists::<Racing>),
+                    ),
+                ),
+            );
+    }
+}
+
+fn start_race(
+    mut commands: Commands,
+    ghosts: Res<Ghosts>,
+    rng: Res<GameRng>,
+    modifiers: Res<RunModifiers>,
+    playback: Option<Res<Playback>>,
+    profile: Res<Profile>,
+    sheet: Res<SpriteSheet>,
+    root: Query<Entity, With<Root>>,
+) {
+    commands.remove_resource::<Racing>();
+    commands.remove_resource::<Tracking>();
+    // Watching a replay isn't a run of its own
+    if playback.is_some() {
+        return;
+    }
+
+    commands.insert_resource(Tracking {
+        seed: rng.seed,
+        distance: 0.,
+        track: Track {
+            modifiers: modifiers.clone(),
+            score: 0,
+            points: Vec::new(),
+        },
+        rewound: false,
+    });
+
+    // Only a run with the same modifiers went through the same world
+    let Some(best) = ghosts
+        .0
+        .get(&rng.seed)
+        .filter(|best| best.modifiers == *modifiers)
+    else {
+        return;
+    };
+
+    commands.insert_resource(Racing(best.clone()));
+    let ghost = commands
+        .spawn((
+            Ghost,
+            SpriteSheetBundle {
+                texture: sheet.texture.clone(),
+                atlas: TextureAtlas {
+                    layout: sheet.layout.clone(),
+                    index: Atlas::Bird1 as usize,
+                },
+                sprite: Sprite {
+                    color: Color::WHITE.with_a(profile.ghost.opacity),
+                    ..default()
+                },
+                // Behind the player, but in front of the pipes
+                transform: Transform::from_translation(Vec3::new(0., 0., 3.)),
+                ..default()
+            },
+        ))
+        .id();
+    commands.entity(root.single()).add_child(ghost);
+}
+
+fn track_player(
+    mut tracking: ResMut<Tracking>,
+    player: Query<(&Transform, &BirdState), With<Player>>,
+    difficulty: Res<Difficulty>,
+    ease: Res<ScrollEase>,
+    state: Res<State<AppState>>,
+    time: Res<Time>,
+) {
+    // Keeps counting once the run is over so the ghost slows down with the
+    // rest of the world
+    tracking.distance -= ease.speed(&difficulty) * time.delta_seconds();
+    if *state.get() != AppState::Playing {
+        return;
+    }
+
+    let flying = player
+        .iter()
+        .find(|(_, state)| **state == BirdState::Flying);
+    if let Some((transform, _)) = flying {
+        let point = Vec2::new(tracking.distance, transform.translation.y);
+        tracking.track.points.push(point);
+    }
+}
+
+fn finish_track(tracking: Option<ResMut<Tracking>>, mut ghosts: ResMut<Ghosts>, score: Res<Score>) {
+    let Some(mut tracking) = tracking.filter(|tracking| !tracking.rewound) else {
+        return;
+    };
+    tracking.track.score = score.0;
+
+    let better = ghosts
+        .0
+        .get(&tracking.seed)
+        .is_none_or(|best| best.modifiers != tracking.track.modifiers || best.score < score.0);
+    if better {
+        ghosts.0.insert(tracking.seed, tracking.track.clone());
+    }
+}
+
+// The ghost goes back along with the run, but a run that's been rewound can't
+// be raced against later
+fn rewind_track(
+    mut tracking: ResMut<Tracking>,
+    mut reader: EventReader<OnSnapshotRestored>,
+    tick: Res<SimTick>,
+) {
+    if reader.read().count() == 0 {
+        return;
+    }
+
+    tracking.rewound = true;
+    tracking.track.points.truncate(tick.0 as usize);
+    tracking.distance = tracking.track.distance();
+}
+
+fn drop_breadcrumbs(
+    mut commands: Commands,
+    racing: Option<Res<Racing>>,
+    profile: Res<Profile>,
+    tick: Res<SimTick>,
+    root: Query<Entity, With<Root>>,
+) {
+    let every = profile.ghost.breadcrumbs;
+    let Some(racing) = racing else {
+        return;
+    };
+    // None past the end of the ghost's run, it's not going anywhere anymore
+    if every == 0 || !tick.0.is_multiple_of(every) || tick.0 as usize >= racing.0.points.len() {
+        return;
+    }
+    let Some(point) = racing.0.at(tick.0) else {
+        return;
+    };
+
+    let crumb = commands
+        .spawn((
+            Breadcrumb(point.x),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::WHITE.with_a(profile.ghost.opacity),
+                    custom_size: Some(Vec2::splat(2.)),
+                    ..default()
+                },
+                transform: Transform::from_translation(Vec3::new(0., point.y, 2.)),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+        ))
+        .id();
+    commands.entity(root.single()).add_child(crumb);
+}
+
+fn move_ghost(
+    mut commands: Commands,
+    racing: Res<Racing>,
+    tracking: Res<Tracking>,
+    profile: Res<Profile>,
+    tick: Res<SimTick>,
+    player: Query<&Transform, With<Player>>,
+    mut ghost: Query<(&mut Transform, &mut Visibility), (With<Ghost>, Without<Player>)>,
+    mut crumbs: Query<
+        (Entity, &Breadcrumb, &mut Transform, &mut Visibility),
+        (Without<Ghost>, Without<Player>),
+    >,
+) {
+    let Some(player) = player.iter().next() else {
+        return;
+    };
+    // Everything the ghost did is laid out relative to where the player is
+    // in the world right now
+    let x = |distance: f32| player.translation.x + distance - tracking.distance;
+
+    let ahead = racing.0.distance() > tracking.distance;
+    let visibility = match (profile.ghost.show, ahead) {
+        (ShowGhost::Always, _) | (ShowGhost::WhenAhead, true) | (ShowGhost::WhenBehind, false) => {
+            Visibility::Inherited
+        }
+        _ => Visibility::Hidden,
+    };
+
+    // The tick has already moved on to the step that hasn't happened yet
+    if let Some(point) = racing.0.at(tick.0.saturating_sub(1)) {
+        for (mut transform, mut shown) in &mut ghost {
+            transform.translation.x = x(point.x);
+            transform.translation.y = point.y;
+            *shown = visibility;
+        }
+    }
+
+    for (entity, crumb, mut transform, mut shown) in &mut crumbs {
+        transform.translation.x = x(crumb.0);
+        *shown = visibility;
+        if transform.translation.x < OFF_SCREEN {
+            commands.entity(entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::*;
+
+use crate::{
+    apply_gravity, difficulty::Difficulty, AppState, BirdState, GameRng, Player, Root, SimSet,
+    SimTick, Velocity, SIM_HZ,
+};
+
+// How long a gust blows for, and how long the streaks warn about it first
+const GUST_DURATION: f32 = 0.5;
+const GUST_WARNING: f32 = 1.;
+// Streaks of air across the screen, running the way the gust is going to blow
+const STREAK_RATE: f32 = 24.;
+const STREAK_SPEED: f32 = 240.;
+const STREAK_LIFETIME: f32 = 0.4;
+const STREAK_SIZE: Vec2 = Vec2::new(1., 10.);
+const STREAK_COLOR: Color = Color::rgba(1., 1., 1., 0.5);
+// Over the bird and the pipes, under the score popups
+const STREAK_Z: f32 = 5.;
+// Half the width and height of the visible world
+const VIEW_EDGE: Vec2 = Vec2::new(72., 128.);
+
+/// Where the run's at with the wind
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Gust {
+    Calm,
+    /// One's about to blow, 1 for up and -1 for down
+    Coming {
+        direction: f32,
+    },
+    /// Blowing, `t` of the way through
+    Blowing {
+        direction: f32,
+        t: f32,
+    },
+}
+
+impl Gust {
+    /// Worked out from just the tick and the seed so the gusts play out the
+    /// same in a replay or after a snapshot without being saved, and without
+    /// taking anything from the numbers the pipes are picked with
+    fn at(tick: u64, seed: u64, difficulty: &Difficulty) -> Self {
+        if difficulty.gust_strength <= 0. || difficulty.gust_interval <= 0. {
+            return Gust::Calm;
+        }
+
+        // Never so often that one would blow before the last one's warning
+        let interval = difficulty.gust_interval.max(GUST_WARNING + GUST_DURATION);
+        let seconds = (tick as f64 / SIM_HZ) as f32;
+        let n = (seconds / interval).floor();
+        let into = seconds - n * interval;
+
+        let mixed = (seed ^ n as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+        let direction = if mixed >> 63 == 0 { 1. } else { -1. };
+
+        // Each one blows at the end of its interval, so a run gets a calm
+        // start before the first
+        let starts = interval - GUST_DURATION;
+        if into >= starts {
+            Gust::Blowing {
+                direction,
+                t: (into - starts) / GUST_DURATION,
+            }
+        } else if into >= starts - GUST_WARNING {
+            Gust::Coming { direction }
+        } else {
+            Gust::Calm
+        }
+    }
+
+    fn direction(self) -> Option<f32> {
+        match self {
+            Gust::Calm => None,
+            Gust::Coming { direction } | Gust::Blowing { direction, .. } => Some(direction),
+        }
+    }
+}
+
+#[derive(Component)]
+struct Streak {
+    direction: f32,
+    timer: Timer,
+}
+
+pub struct GustsPlugin;
+
+impl Plugin for GustsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            blow.in_set(SimSet::Physics)
+                .before(apply_gravity)
+                .run_if(in_state(AppState::Playing)),
+        )
+        .add_systems(
+            Update,
+            (
+                spawn_streaks.run_if(in_state(AppState::Playing)),
+                move_streaks,
+            )
+                .chain(),
+        );
+    }
+}
+
+// Eases in and back out over the gust so the bird's never jerked around
+fn blow(
+    mut query: Query<(&mut Velocity, &BirdState), With<Player>>,
+    tick: Res<SimTick>,
+    rng: Res<GameRng>,
+    difficulty: Res<Difficulty>,
+    time: Res<Time>,
+) {
+    let Gust::Blowing { direction, t } = Gust::at(tick.0, rng.seed, &difficulty) else {
+        return;
+    };
+
+    let push = direction * difficulty.gust_strength * (PI * t).sin() * time.delta_seconds();
+    for (mut velocity, state) in &mut query {
+        if *state == BirdState::Flying {
+            velocity.0 += push;
+        }
+    }
+}
+
+// Only for show, so they're placed with any old random numbers
+fn spawn_streaks(
+    mut commands: Commands,
+    mut owed: Local<f32>,
+    tick: Res<SimTick>,
+    rng: Res<GameRng>,
+    difficulty: Res<Difficulty>,
+    root: Query<Entity, With<Root>>,
+    time: Res<Time>,
+) {
+    let (Some(direction), Ok(root)) = (
+        Gust::at(tick.0, rng.seed, &difficulty).direction(),
+        root.get_single(),
+    ) else {
+        *owed = 0.;
+        return;
+    };
+
+    *owed += time.delta_seconds() * STREAK_RATE;
+    let count = owed.floor();
+    *owed -= count;
+
+    commands.entity(root).with_children(|parent| {
+        for _ in 0..count as usize {
+            let position = (Vec2::new(rand::random(), rand::random()) * 2. - 1.) * VIEW_EDGE;
+            parent.spawn((
+                Streak {
+                    direction,
+                    timer: Timer::from_seconds(STREAK_LIFETIME, TimerMode::Once),
+                },
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: STREAK_COLOR,
+                        custom_size: Some(STREAK_SIZE),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(position.extend(STREAK_Z)),
+                    ..default()
+                },
+            ));
+        }
+    });
+}
+
+fn move_streaks(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Streak, &mut Transform, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (entity, mut streak, mut transform, mut sprite) in &mut query {
+        if streak.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        transform.translation.y += streak.direction * STREAK_SPEED * time.delta_seconds();
+        sprite
+            .color
+            .set_a(STREAK_COLOR.a() * (1. - streak.timer.fraction()));
+    }
+}
@@ -0,0 +1,140 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bonus::PlayPhase,
+    difficulty::Difficulty,
+    hitboxes::Hitboxes,
+    scroll::{is_scrolling, ScrollEase},
+    AppState, Collider, GameRng, SimSet, GROUND_TOP,
+};
+
+const HAZARD_CHANCE: f64 = 0.2;
+const SPIKES_SIZE: Vec2 = Vec2::new(12., 5.);
+const CRAB_SIZE: Vec2 = Vec2::new(10., 6.);
+const CRAB_SPEED: f32 = 20.;
+// How far a crab walks either way, short enough to stay clear of the pipes
+const PATROL_RANGE: f32 = 40.;
+
+/// Something on the ground that's deadly to touch
+#[derive(Component, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Hazard {
+    Spikes,
+    Crab,
+}
+
+/// Walks back and forth around where it was spawned
+#[derive(Component, Serialize, Deserialize, Clone, Copy)]
+pub struct Patrol {
+    pub offset: f32,
+    pub speed: f32,
+}
+
+pub struct HazardsPlugin;
+
+impl Plugin for HazardsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                scroll_hazards.run_if(is_scrolling),
+                patrol.run_if(in_state(AppState::Playing)),
+            )
+                .chain()
+                .in_set(SimSet::Physics),
+        )
+        .add_systems(OnEnter(PlayPhase::Bonus), clear_hazards);
+    }
+}
+
+/// Sometimes puts spikes or a crab on the ground at `x`
+pub fn spawn_hazard(
+    commands: &mut Commands,
+    root: Entity,
+    hitboxes: &Hitboxes,
+    x: f32,
+    rng: &mut GameRng,
+) {
+    if !rng.gen_bool(HAZARD_CHANCE) {
+        return;
+    }
+
+    if rng.gen_bool(0.5) {
+        place_hazard(commands, root, hitboxes, Hazard::Spikes, x, None);
+    } else {
+        let speed = if rng.gen_bool(0.5) {
+            CRAB_SPEED
+        } else {
+            -CRAB_SPEED
+        };
+        let patrol = Patrol { offset: 0., speed };
+        place_hazard(commands, root, hitboxes, Hazard::Crab, x, Some(patrol));
+    }
+}
+
+/// Puts `hazard` on the ground at `x`, partway through its patrol if it has one
+pub fn place_hazard(
+    commands: &mut Commands,
+    root: Entity,
+    hitboxes: &Hitboxes,
+    hazard: Hazard,
+    x: f32,
+    patrol: Option<Patrol>,
+) {
+    let (size, hitbox, color) = match hazard {
+        Hazard::Spikes => (SPIKES_SIZE, hitboxes.spikes, Color::GRAY),
+        Hazard::Crab => (CRAB_SIZE, hitboxes.crab, Color::ORANGE_RED),
+    };
+
+    let mut entity = commands.spawn((
+        hazard,
+        Collider(hitbox.aabb()),
+        SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(size),
+                ..default()
+            },
+            transform: Transform::from_translation(Vec3::new(x, GROUND_TOP + size.y / 2., 2.)),
+            ..default()
+        },
+    ));
+    if let Some(patrol) = patrol {
+        entity.insert(patrol);
+    }
+    entity.set_parent(root);
+}
+
+fn scroll_hazards(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform), With<Hazard>>,
+    difficulty: Res<Difficulty>,
+    ease: Res<ScrollEase>,
+    time: Res<Time>,
+) {
+    for (entity, mut transform) in &mut query {
+        transform.translation.x += time.delta_seconds() * ease.speed(&difficulty);
+        if transform.translation.x < -144. * 2. {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn patrol(mut query: Query<(&mut Transform, &mut Patrol)>, time: Res<Time>) {
+    for (mut transform, mut patrol) in &mut query {
+        let step = patrol.speed * time.delta_seconds();
+        patrol.offset += step;
+        transform.translation.x += step;
+        if patrol.offset.abs() > PATROL_RANGE {
+            patrol.speed = -patrol.speed;
+        }
+    }
+}
+
+// The bonus round is meant to be free of anything that can kill the player
+fn clear_hazards(mut commands: Commands, query: Query<Entity, With<Hazard>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{replay::Playback, save, AppState, RunModifiers, Score, SimSet};
+
+const HIGH_SCORE_FILE: &str = "high_score.ron";
+
+/// The best score there's been for every set of modifiers, which are only
+/// ever compared with each other
+#[derive(Resource, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct HighScore {
+    scores: HashMap<RunModifiers, u32>,
+    /// The same, only since the game was started
+    #[serde(skip)]
+    session: HashMap<RunModifiers, u32>,
+}
+
+impl HighScore {
+    pub fn get(&self, modifiers: &RunModifiers) -> Option<u32> {
+        self.scores.get(modifiers).copied()
+    }
+
+    fn session(&self, modifiers: &RunModifiers) -> u32 {
+        self.session.get(modifiers).copied().unwrap_or_default()
+    }
+
+    /// Keeps `score` if it's the best yet, and says whether it was
+    fn beat(&mut self, modifiers: &RunModifiers, score: u32) -> bool {
+        let session = self.session.entry(modifiers.clone()).or_default();
+        *session = (*session).max(score);
+
+        let best = self.scores.entry(modifiers.clone()).or_default();
+        let beaten = score > *best;
+        *best = (*best).max(score);
+        beaten
+    }
+}
+
+/// The run's score has just gone past the best there's been for its
+/// modifiers
+#[derive(Event)]
+pub struct OnNewBest {
+    pub score: u32,
+}
+
+#[derive(Component)]
+struct HighScoreText;
+
+pub struct HighScorePlugin;
+
+impl Plugin for HighScorePlugin {
+    fn build(&self, app: &mut App) {
+        let high_score = match save::load::<HighScore>(HIGH_SCORE_FILE) {
+            Ok(high_score) => high_score.unwrap_or_default(),
+            Err(error) => {
+                warn!("Couldn't load high scores, starting fresh: {error}");
+                HighScore::default()
+            }
+        };
+
+        app.insert_resource(high_score)
+            .add_event::<OnNewBest>()
+            .add_systems(
+                FixedUpdate,
+                announce_best
+                    .after(SimSet::Collision)
+                    .before(SimSet::Rules)
+                    .run_if(in_state(AppState::Playing).and_then(not(resource_exists::<Playback>))),
+            )
+            .add_systems(
+                OnEnter(AppState::GameOver),
+                record_high_score.run_if(not(resource_exists::<Playback>)),
+            )
+            .add_systems(OnExit(AppState::GameOver), hide_high_score);
+    }
+}
+
+// Goes by the score it saw last, so it only goes off on the step the best
+// is passed. The best itself isn't kept until the run's over
+fn announce_best(
+    mut writer: EventWriter<OnNewBest>,
+    mut last: Local<u32>,
+    high_score: Res<HighScore>,
+    modifiers: Res<RunModifiers>,
+    score: Res<Score>,
+) {
+    let previous = high_score.get(&modifiers).unwrap_or_default();
+    if score.0 > previous && *last <= previous {
+        writer.send(OnNewBest { score: score.0 });
+    }
+    *last = score.0;
+}
+
+// The best of all time is on the score panel, so it's only the best since
+// the game was started that's shown here
+pub fn record_high_score(
+    mut commands: Commands,
+    mut high_score: ResMut<HighScore>,
+    modifiers: Res<RunModifiers>,
+    score: Res<Score>,
+) {
+    if high_score.beat(&modifiers, score.0) {
+        if let Err(error) = save::store(HIGH_SCORE_FILE, high_score.as_ref()) {
+            warn!("Couldn't save high scores: {error}");
+        }
+    }
+    let text = format!("Session best {}", high_score.session(&modifiers));
+
+    commands
+        .spawn((
+            HighScoreText,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(8.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                text,
+                TextStyle {
+                    font_size: 12.,
+                    color: Color::YELLOW,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn hide_high_score(mut commands: Commands, query: Query<Entity, With<HighScoreText>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,100 @@
+use bevy::{math::bounding::Aabb2d, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    create_world, replay::Playback, ron_asset::RonLoader, BuildWorld, GROUND_HALF_HEIGHT,
+    GROUND_WIDTH, PIPE_WIDTH,
+};
+
+const HITBOXES_FILE: &str = "flappy.atlas.ron";
+
+/// What counts as hitting something, as a box around the middle of its sprite
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Hitbox {
+    /// How far the middle of the box is from the middle of the sprite
+    #[serde(default)]
+    pub offset: Vec2,
+    pub size: Vec2,
+}
+
+impl Hitbox {
+    const fn new(size: Vec2) -> Self {
+        Self {
+            offset: Vec2::ZERO,
+            size,
+        }
+    }
+
+    pub fn aabb(self) -> Aabb2d {
+        Aabb2d::new(self.offset, self.size / 2.)
+    }
+}
+
+/// The hitboxes of everything that can be run into, kept with the sprite
+/// sheet so they can be tuned along with the art
+#[derive(Asset, TypePath, Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+#[serde(default)]
+pub struct Hitboxes {
+    pub bird: Hitbox,
+    /// The same for the top and the bottom pipe
+    pub pipe: Hitbox,
+    pub ground: Hitbox,
+    pub coin: Hitbox,
+    pub spikes: Hitbox,
+    pub crab: Hitbox,
+    pub portal: Hitbox,
+}
+
+impl Default for Hitboxes {
+    fn default() -> Self {
+        Self {
+            bird: Hitbox::new(Vec2::new(12., 8.)),
+            pipe: Hitbox::new(Vec2::new(PIPE_WIDTH, 160.)),
+            ground: Hitbox::new(Vec2::new(GROUND_WIDTH, GROUND_HALF_HEIGHT * 2.)),
+            coin: Hitbox::new(Vec2::splat(6.)),
+            spikes: Hitbox::new(Vec2::new(12., 5.)),
+            crab: Hitbox::new(Vec2::new(10., 6.)),
+            portal: Hitbox::new(Vec2::new(8., 24.)),
+        }
+    }
+}
+
+#[derive(Resource)]
+pub struct HitboxesHandle(Handle<Hitboxes>);
+
+/// The hitboxes the current run is played with
+#[derive(Resource, Default)]
+pub struct ActiveHitboxes(pub Hitboxes);
+
+pub struct HitboxesPlugin;
+
+impl Plugin for HitboxesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Hitboxes>()
+            .register_asset_loader(RonLoader::<Hitboxes>::new(&["atlas.ron"]));
+
+        let handle = app.world.resource::<AssetServer>().load(HITBOXES_FILE);
+        app.insert_resource(HitboxesHandle(handle))
+            .init_resource::<ActiveHitboxes>()
+            .add_systems(BuildWorld, pick_hitboxes.before(create_world));
+    }
+}
+
+// Only ever changes between runs, since everything that's already out there
+// was spawned with the old ones
+fn pick_hitboxes(
+    mut active: ResMut<ActiveHitboxes>,
+    handle: Res<HitboxesHandle>,
+    hitboxes: Res<Assets<Hitboxes>>,
+    playback: Option<Res<Playback>>,
+) {
+    // Replays play out with the hitboxes they were recorded with
+    let picked = match (playback, hitboxes.get(&handle.0)) {
+        (Some(playback), _) => playback.replay.hitboxes,
+        (None, Some(hitboxes)) => *hitboxes,
+        (None, None) => Hitboxes::default(),
+    };
+    if active.0 != picked {
+        active.0 = picked;
+    }
+}
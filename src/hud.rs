@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+
+use crate::{AppState, Atlas, Root, Score, SpriteSheet};
+
+// Where the middle of the score sits, just under the top of the screen
+const SCORE_POSITION: Vec3 = Vec3::new(0., 100., 10.);
+// Pixels between two digits
+const DIGIT_SPACING: f32 = 1.;
+
+/// The score at the top of the screen, made out of the digits on the sprite
+/// sheet
+#[derive(Component)]
+struct ScoreHud;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Playing), spawn_score)
+            .add_systems(OnExit(AppState::Playing), despawn_score)
+            .add_systems(Update, draw_score.run_if(in_state(AppState::Playing)));
+    }
+}
+
+/// How wide the glyph for `digit` is on the sprite sheet
+fn digit_width(digit: u32) -> f32 {
+    if digit == 1 {
+        8.
+    } else {
+        12.
+    }
+}
+
+fn spawn_score(mut commands: Commands, root: Query<Entity, With<Root>>) {
+    let hud = commands
+        .spawn((
+            ScoreHud,
+            SpatialBundle::from_transform(Transform::from_translation(SCORE_POSITION)),
+        ))
+        .id();
+    commands.entity(root.single()).add_child(hud);
+}
+
+fn despawn_score(mut commands: Commands, query: Query<Entity, With<ScoreHud>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn draw_score(
+    mut commands: Commands,
+    score: Res<Score>,
+    sheet: Res<SpriteSheet>,
+    query: Query<Entity, With<ScoreHud>>,
+    added: Query<(), Added<ScoreHud>>,
+) {
+    if !score.is_changed() && added.is_empty() {
+        return;
+    }
+
+    let digits: Vec<u32> = score
+        .0
+        .to_string()
+        .chars()
+        .filter_map(|c| c.to_digit(10))
+        .collect();
+    let widths: Vec<f32> = digits.iter().map(|digit| digit_width(*digit)).collect();
+    let total = widths.iter().sum::<f32>() + DIGIT_SPACING * (digits.len() - 1) as f32;
+
+    for hud in &query {
+        commands
+            .entity(hud)
+            .despawn_descendants()
+            .with_children(|parent| {
+                // Centered on the hud, left to right
+                let mut x = -total / 2.;
+                for (digit, width) in digits.iter().zip(&widths) {
+                    parent.spawn(SpriteSheetBundle {
+                        texture: sheet.texture.clone(),
+                        atlas: TextureAtlas {
+                            layout: sheet.layout.clone(),
+                            index: Atlas::digit(*digit) as usize,
+                        },
+                        transform: Transform::from_xyz(x + width / 2., 0., 0.),
+                        ..default()
+                    });
+                    x += width + DIGIT_SPACING;
+                }
+            });
+    }
+}
@@ -0,0 +1,101 @@
+use bevy::prelude::*;
+
+use crate::{
+    challenge::seed_code,
+    modes::{ModeRegistry, CLASSIC},
+    replay::{start_recording, Playback, Recording},
+    AppState, RunModifiers,
+};
+
+// How long (in seconds) the run's details stay up, the last of it fading out
+const INTRO_DURATION: f32 = 2.5;
+const FADE_DURATION: f32 = 0.5;
+
+/// The seed, mode and modifiers of the run that just started, in the corner
+/// for a moment so they end up in any recording of it
+#[derive(Component)]
+struct RunIntro(Timer);
+
+pub struct IntroPlugin;
+
+impl Plugin for IntroPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::Playing),
+            spawn_intro.after(start_recording),
+        )
+        .add_systems(Update, fade_intro.run_if(in_state(AppState::Playing)))
+        .add_systems(OnExit(AppState::Playing), despawn_intro);
+    }
+}
+
+// Read off the run's own record, so a replay shows what it was recorded with
+// rather than whatever the player has picked since
+fn spawn_intro(
+    mut commands: Commands,
+    recording: Option<Res<Recording>>,
+    playback: Option<Res<Playback>>,
+    registry: Res<ModeRegistry>,
+) {
+    let Some(replay) = playback
+        .as_ref()
+        .map(|playback| &playback.replay)
+        .or(recording.as_ref().map(|recording| &recording.0))
+    else {
+        return;
+    };
+
+    let mode = registry.current(&replay.modifiers).info.name;
+    // The mode already has a line of its own
+    let modifiers = RunModifiers {
+        mode: CLASSIC.to_string(),
+        ..replay.modifiers.clone()
+    }
+    .label();
+    let mut lines = vec![mode.to_string(), format!("Seed {}", seed_code(replay.seed))];
+    if modifiers != CLASSIC {
+        lines.push(modifiers);
+    }
+
+    commands.spawn((
+        RunIntro(Timer::from_seconds(INTRO_DURATION, TimerMode::Once)),
+        TextBundle::from_section(
+            lines.join("\n"),
+            TextStyle {
+                font_size: 10.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(8.),
+            left: Val::Px(8.),
+            ..default()
+        }),
+    ));
+}
+
+fn fade_intro(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut RunIntro, &mut Text)>,
+    time: Res<Time>,
+) {
+    for (entity, mut intro, mut text) in &mut query {
+        intro.0.tick(time.delta());
+        let alpha = (intro.0.remaining_secs() / FADE_DURATION).min(1.);
+        for section in &mut text.sections {
+            section.style.color.set_a(alpha);
+        }
+
+        if intro.0.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn despawn_intro(mut commands: Commands, query: Query<Entity, With<RunIntro>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,73 @@
+use std::{collections::HashSet, time::Duration};
+
+use bevy::{prelude::*, time::common_conditions::on_timer};
+
+use crate::{Player, Root};
+
+// Well past anything a run or the longest level lays out, so only entities
+// that have gone wrong end up outside of it
+const WORLD_BOUNDS: Rect = Rect {
+    min: Vec2::new(-512., -512.),
+    max: Vec2::new(4096., 512.),
+};
+const AUDIT_EVERY: Duration = Duration::from_secs(1);
+
+/// What the kill plane has caught, for the debug overlay
+#[derive(Resource, Default)]
+pub struct KillPlaneAudit {
+    /// How many entities have been despawned for leaving the world
+    pub despawned: usize,
+    /// Entities outside the world that can't be despawned, like the player
+    pub flagged: HashSet<Entity>,
+    /// The most recent thing that was caught
+    pub last: Option<String>,
+}
+
+pub struct KillPlanePlugin;
+
+impl Plugin for KillPlanePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KillPlaneAudit>()
+            .add_systems(Update, audit_world.run_if(on_timer(AUDIT_EVERY)));
+    }
+}
+
+// Anything left behind by the systems that are meant to clean up after
+// themselves would otherwise pile up over a long session
+fn audit_world(
+    mut commands: Commands,
+    mut audit: ResMut<KillPlaneAudit>,
+    root: Query<&Children, With<Root>>,
+    entities: Query<(&Transform, Has<Player>)>,
+) {
+    // Flags are only kept for as long as the entity is still out there
+    audit.flagged.retain(|&entity| {
+        entities
+            .get(entity)
+            .is_ok_and(|(transform, _)| !WORLD_BOUNDS.contains(transform.translation.xy()))
+    });
+
+    for children in &root {
+        for (entity, (transform, is_player)) in children
+            .iter()
+            .filter_map(|&entity| Some((entity, entities.get(entity).ok()?)))
+        {
+            let position = transform.translation.xy();
+            if WORLD_BOUNDS.contains(position) {
+                continue;
+            }
+
+            if is_player {
+                if audit.flagged.insert(entity) {
+                    warn!("Player {entity:?} left the world at {position}");
+                    audit.last = Some(format!("flagged player at {position:.0}"));
+                }
+            } else {
+                warn!("Despawning {entity:?}, it left the world at {position}");
+                commands.entity(entity).despawn_recursive();
+                audit.despawned += 1;
+                audit.last = Some(format!("despawned {entity:?} at {position:.0}"));
+            }
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use bevy::{math::bounding::Aabb2d, prelude::*};
+
+use crate::{
+    difficulty::Difficulty,
+    effects::{ActiveEffects, DeathZoom},
+    scroll::ScrollEase,
+    AppState, OnCrashed,
+};
+
+// Half the size of the visible world when not zoomed in
+const VIEW_HALF_SIZE: Vec2 = Vec2::new(72., 128.);
+
+/// The moment of the crash, held while the camera zooms in on it
+#[derive(Resource)]
+struct KillCam {
+    timer: Timer,
+    contact: Vec2,
+    collider: Option<Aabb2d>,
+    camera_translation: Vec3,
+    camera_scale: f32,
+}
+
+pub struct KillCamPlugin;
+
+impl Plugin for KillCamPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::KillCam), start_kill_cam)
+            .add_systems(
+                Update,
+                (
+                    zoom_on_contact,
+                    follow_collider,
+                    outline_collider,
+                    end_kill_cam,
+                )
+                    .run_if(in_state(AppState::KillCam)),
+            )
+            .add_systems(OnExit(AppState::KillCam), reset_camera);
+    }
+}
+
+fn start_kill_cam(
+    mut commands: Commands,
+    mut reader: EventReader<OnCrashed>,
+    camera: Query<(&Transform, &OrthographicProjection), With<Camera>>,
+    effects: Res<ActiveEffects>,
+) {
+    let Some(crash) = reader.read().last() else {
+        return;
+    };
+    let (transform, projection) = camera.single();
+
+    commands.insert_resource(KillCam {
+        timer: Timer::from_seconds(effects.0.death_zoom.freeze, TimerMode::Once),
+        contact: crash.contact,
+        collider: crash.collider,
+        camera_translation: transform.translation,
+        camera_scale: projection.scale,
+    });
+}
+
+fn zoom_on_contact(
+    kill_cam: Option<Res<KillCam>>,
+    mut camera: Query<(&mut Transform, &mut OrthographicProjection), With<Camera>>,
+    effects: Res<ActiveEffects>,
+) {
+    let Some(kill_cam) = kill_cam else {
+        return;
+    };
+    let (mut transform, mut projection) = camera.single_mut();
+    let DeathZoom {
+        duration,
+        zoom,
+        easing,
+        ..
+    } = effects.0.death_zoom;
+
+    let t = easing.apply((kill_cam.timer.elapsed_secs() / duration).min(1.));
+
+    // Don't let the zoomed view reach past the edges of the world
+    let bounds = VIEW_HALF_SIZE - VIEW_HALF_SIZE / zoom;
+    let target = kill_cam.contact.clamp(-bounds, bounds);
+
+    let from = kill_cam.camera_translation;
+    transform.translation = from.lerp(target.extend(from.z), t);
+    projection.scale = kill_cam.camera_scale * (1. - t + t / zoom);
+}
+
+// The world takes a moment to come to a stop after the crash, so the outline
+// has to move along with whatever was hit
+fn follow_collider(
+    kill_cam: Option<ResMut<KillCam>>,
+    difficulty: Res<Difficulty>,
+    ease: Res<ScrollEase>,
+    time: Res<Time>,
+) {
+    let Some(Aabb2d { min, max }) =
+        kill_cam.and_then(|kill_cam| kill_cam.into_inner().collider.as_mut())
+    else {
+        return;
+    };
+
+    let offset = Vec2::X * ease.speed(&difficulty) * time.delta_seconds();
+    *min += offset;
+    *max += offset;
+}
+
+fn outline_collider(kill_cam: Option<Res<KillCam>>, mut gizmos: Gizmos) {
+    let Some(Aabb2d { min, max }) = kill_cam.and_then(|kill_cam| kill_cam.collider) else {
+        return;
+    };
+
+    gizmos.rect_2d((min + max) / 2., 0., max - min, Color::RED);
+}
+
+fn end_kill_cam(
+    kill_cam: Option<ResMut<KillCam>>,
+    mut state: ResMut<NextState<AppState>>,
+    time: Res<Time>,
+) {
+    let finished = match kill_cam {
+        Some(mut kill_cam) => kill_cam.timer.tick(time.delta()).finished(),
+        None => true,
+    };
+
+    if finished {
+        state.set(AppState::Dying);
+    }
+}
+
+fn reset_camera(
+    mut commands: Commands,
+    kill_cam: Option<Res<KillCam>>,
+    mut camera: Query<(&mut Transform, &mut OrthographicProjection), With<Camera>>,
+) {
+    let Some(kill_cam) = kill_cam else {
+        return;
+    };
+    let (mut transform, mut projection) = camera.single_mut();
+
+    transform.translation = kill_cam.camera_translation;
+    projection.scale = kill_cam.camera_scale;
+    commands.remove_resource::<KillCam>();
+}
@@ -0,0 +1,188 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{replay::Playback, retention::ReplayIndex, AppState, RunModifiers};
+
+/// The best scores of runs played with the same modifiers, which are only
+/// ever compared with each other
+struct Category {
+    modifiers: RunModifiers,
+    scores: Vec<u32>,
+}
+
+#[derive(Resource, Default)]
+struct Leaderboard {
+    categories: Vec<Category>,
+    selected: usize,
+}
+
+#[derive(Component)]
+struct LeaderboardScreen;
+
+pub struct LeaderboardPlugin;
+
+impl Plugin for LeaderboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Leaderboard>()
+            .add_systems(
+                Update,
+                open_leaderboard.run_if(
+                    in_state(AppState::MainMenu).and_then(not(resource_exists::<Playback>)),
+                ),
+            )
+            .add_systems(OnEnter(AppState::Leaderboard), load_leaderboard)
+            .add_systems(
+                Update,
+                (
+                    browse_leaderboard,
+                    draw_leaderboard.run_if(resource_changed::<Leaderboard>),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Leaderboard)),
+            )
+            .add_systems(OnExit(AppState::Leaderboard), close_leaderboard);
+    }
+}
+
+fn open_leaderboard(keys: Res<ButtonInput<KeyCode>>, mut state: ResMut<NextState<AppState>>) {
+    if keys.just_pressed(KeyCode::KeyH) {
+        state.set(AppState::Leaderboard);
+    }
+}
+
+// The index keeps the best runs of every set of modifiers, so it already has
+// everything that makes it onto the board
+fn load_leaderboard(
+    mut leaderboard: ResMut<Leaderboard>,
+    index: Res<ReplayIndex>,
+    modifiers: Res<RunModifiers>,
+) {
+    let mut categories = HashMap::<RunModifiers, Vec<u32>>::new();
+    for entry in &index.replays {
+        categories
+            .entry(entry.modifiers.clone())
+            .or_default()
+            .push(entry.score);
+    }
+
+    let mut categories = categories
+        .into_iter()
+        .map(|(modifiers, mut scores)| {
+            scores.sort_by(|a, b| b.cmp(a));
+            scores.truncate(index.retention.best_per_mode);
+            Category { modifiers, scores }
+        })
+        .collect::<Vec<_>>();
+    categories.sort_by_key(|category| category.modifiers.label());
+
+    // Start on the tab for the modifiers the player has on right now
+    leaderboard.selected = categories
+        .iter()
+        .position(|category| category.modifiers == *modifiers)
+        .unwrap_or(0);
+    leaderboard.categories = categories;
+}
+
+fn close_leaderboard(mut commands: Commands, query: Query<Entity, With<LeaderboardScreen>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn browse_leaderboard(
+    mut leaderboard: ResMut<Leaderboard>,
+    mut state: ResMut<NextState<AppState>>,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        state.set(AppState::MainMenu);
+        return;
+    }
+
+    if leaderboard.categories.is_empty() {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::ArrowLeft) {
+        leaderboard.selected = leaderboard.selected.saturating_sub(1);
+    }
+    if keys.just_pressed(KeyCode::ArrowRight) {
+        leaderboard.selected = (leaderboard.selected + 1).min(leaderboard.categories.len() - 1);
+    }
+}
+
+fn draw_leaderboard(
+    mut commands: Commands,
+    leaderboard: Res<Leaderboard>,
+    query: Query<Entity, With<LeaderboardScreen>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let text = |value: String, size: f32, color: Color| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size: size,
+                color,
+                ..default()
+            },
+        )
+    };
+
+    commands
+        .spawn((
+            LeaderboardScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(12.)),
+                    row_gap: Val::Px(4.),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.8).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("Leaderboard".to_string(), 24., Color::WHITE));
+
+            if leaderboard.categories.is_empty() {
+                parent.spawn(text("No runs yet".to_string(), 14., Color::GRAY));
+            }
+
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_wrap: FlexWrap::Wrap,
+                        column_gap: Val::Px(8.),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for (i, category) in leaderboard.categories.iter().enumerate() {
+                        let color = if i == leaderboard.selected {
+                            Color::YELLOW
+                        } else {
+                            Color::GRAY
+                        };
+                        parent.spawn(text(category.modifiers.label(), 12., color));
+                    }
+                });
+
+            if let Some(category) = leaderboard.categories.get(leaderboard.selected) {
+                for (i, score) in category.scores.iter().enumerate() {
+                    parent.spawn(text(format!("{:>3}. {score}", i + 1), 14., Color::WHITE));
+                }
+            }
+
+            parent.spawn(text(
+                "Left/Right change mode, Esc back".to_string(),
+                12.,
+                Color::GRAY,
+            ));
+        });
+}
@@ -0,0 +1,304 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bonus::OnCoinCollected,
+    campaign::CampaignLevels,
+    difficulty::Difficulty,
+    modes::ModeRegistry,
+    profile::Profile,
+    replay::Playback,
+    scroll::{is_scrolling, ScrollEase},
+    AppState, Player, Root, RunModifiers, Score, SimSet, SimTick, SIM_HZ,
+};
+
+const STAR_SIZE: f32 = 16.;
+
+/// What it takes to get each of a level's three stars
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct StarTargets {
+    /// Scoring at least this much
+    pub score: u32,
+    /// Finishing within this many seconds
+    pub time: f32,
+    /// Collecting at least this many coins
+    pub coins: u32,
+}
+
+/// A run with an end to it, where getting there is rated out of three stars
+#[derive(Clone)]
+pub struct LevelGoal {
+    /// What the level's bests are saved under
+    pub id: String,
+    /// How far the finish line is from where the player starts
+    pub length: f32,
+    pub stars: StarTargets,
+}
+
+/// How a finished level went
+#[derive(Clone, Copy)]
+pub struct Rating {
+    pub score: u32,
+    pub time: f32,
+    pub coins: u32,
+    pub stars: u8,
+}
+
+impl Rating {
+    fn new(targets: &StarTargets, score: u32, time: f32, coins: u32) -> Self {
+        let stars = [
+            score >= targets.score,
+            time <= targets.time,
+            coins >= targets.coins,
+        ]
+        .into_iter()
+        .filter(|&earned| earned)
+        .count() as u8;
+        Self {
+            score,
+            time,
+            coins,
+            stars,
+        }
+    }
+}
+
+/// The best a level has gone, each part on its own
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+pub struct LevelBest {
+    pub stars: u8,
+    pub score: u32,
+    pub time: Option<f32>,
+    pub coins: u32,
+}
+
+impl LevelBest {
+    /// Takes in `rating`, true if anything about it was better than before
+    fn improve(&mut self, rating: &Rating) -> bool {
+        let before = *self;
+        self.stars = self.stars.max(rating.stars);
+        self.score = self.score.max(rating.score);
+        self.time = Some(self.time.map_or(rating.time, |time| time.min(rating.time)));
+        self.coins = self.coins.max(rating.coins);
+        self.stars != before.stars
+            || self.score != before.score
+            || self.time != before.time
+            || self.coins != before.coins
+    }
+}
+
+/// Bests of every level that's been finished, by id
+pub type LevelBests = HashMap<String, LevelBest>;
+
+/// Present for as long as the run being played is a level
+#[derive(Resource)]
+pub struct LevelRun {
+    goal: LevelGoal,
+    coins: u32,
+}
+
+/// Crossing it finishes the level
+#[derive(Component)]
+struct FinishLine;
+
+#[derive(Component)]
+struct LevelCompletePanel;
+
+pub struct LevelsPlugin;
+
+impl Plugin for LevelsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(AppState::Playing), start_level)
+            .add_systems(
+                FixedUpdate,
+                (
+                    scroll_finish_line
+                        .in_set(SimSet::Physics)
+                        .run_if(is_scrolling),
+                    (
+                        count_coins.after(SimSet::Collision).before(SimSet::Rules),
+                        cross_finish_line.in_set(SimSet::Rules),
+                    )
+                        .run_if(in_state(AppState::Playing)),
+                )
+                    .run_if(resource_exists::<LevelRun>),
+            )
+            .add_systems(OnEnter(AppState::LevelComplete), finish_level)
+            .add_systems(OnExit(AppState::LevelComplete), hide_panel);
+    }
+}
+
+fn start_level(
+    mut commands: Commands,
+    registry: Res<ModeRegistry>,
+    campaign: CampaignLevels,
+    modifiers: Res<RunModifiers>,
+    root: Query<Entity, With<Root>>,
+) {
+    let level = modifiers.level.as_deref().and_then(|id| campaign.get(id));
+    let Some(goal) = level
+        .map(|level| level.goal())
+        .or_else(|| registry.current(&modifiers).rules.level())
+    else {
+        commands.remove_resource::<LevelRun>();
+        return;
+    };
+
+    commands.entity(root.single()).with_children(|parent| {
+        parent.spawn((
+            FinishLine,
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(1., 1., 1., 0.6),
+                    custom_size: Some(Vec2::new(4., 256.)),
+                    ..default()
+                },
+                transform: Transform::from_translation(Vec3::new(goal.length, 0., 3.)),
+                ..default()
+            },
+        ));
+    });
+    commands.insert_resource(LevelRun { goal, coins: 0 });
+}
+
+fn scroll_finish_line(
+    mut query: Query<&mut Transform, With<FinishLine>>,
+    difficulty: Res<Difficulty>,
+    ease: Res<ScrollEase>,
+    time: Res<Time>,
+) {
+    for mut transform in &mut query {
+        transform.translation.x += time.delta_seconds() * ease.speed(&difficulty);
+    }
+}
+
+fn count_coins(mut level: ResMut<LevelRun>, mut reader: EventReader<OnCoinCollected>) {
+    level.coins += reader.read().count() as u32;
+}
+
+fn cross_finish_line(
+    finish: Query<&Transform, With<FinishLine>>,
+    player: Query<&Transform, With<Player>>,
+    mut state: ResMut<NextState<AppState>>,
+) {
+    // Crashing on the line still counts as a crash
+    if state.0.is_some() {
+        return;
+    }
+
+    let (Ok(finish), Ok(player)) = (finish.get_single(), player.get_single()) else {
+        return;
+    };
+    if finish.translation.x <= player.translation.x {
+        state.set(AppState::LevelComplete);
+    }
+}
+
+fn finish_level(
+    mut commands: Commands,
+    level: Res<LevelRun>,
+    score: Res<Score>,
+    tick: Res<SimTick>,
+    mut profile: ResMut<Profile>,
+    playback: Option<Res<Playback>>,
+) {
+    let goal = &level.goal;
+    let targets = &goal.stars;
+    let rating = Rating::new(targets, score.0, tick.0 as f32 / SIM_HZ as f32, level.coins);
+
+    // Watching a replay doesn't count towards the player's bests
+    let improved = playback.is_none()
+        && profile
+            .level_bests
+            .entry(goal.id.clone())
+            .or_default()
+            .improve(&rating);
+
+    let text = |value: String, size: f32, color: Color| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size: size,
+                color,
+                ..default()
+            },
+        )
+    };
+
+    commands
+        .spawn((
+            LevelCompletePanel,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(24.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(4.),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("Level complete!".to_string(), 16., Color::WHITE));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        column_gap: Val::Px(4.),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for star in 0..3 {
+                        let color = if star < rating.stars {
+                            Color::GOLD
+                        } else {
+                            Color::DARK_GRAY
+                        };
+                        parent.spawn(NodeBundle {
+                            style: Style {
+                                width: Val::Px(STAR_SIZE),
+                                height: Val::Px(STAR_SIZE),
+                                ..default()
+                            },
+                            background_color: color.into(),
+                            ..default()
+                        });
+                    }
+                });
+
+            let lines = [
+                (
+                    format!("Score {} / {}", rating.score, targets.score),
+                    rating.score >= targets.score,
+                ),
+                (
+                    format!("Time {:.1}s / {:.0}s", rating.time, targets.time),
+                    rating.time <= targets.time,
+                ),
+                (
+                    format!("Coins {} / {}", rating.coins, targets.coins),
+                    rating.coins >= targets.coins,
+                ),
+            ];
+            for (line, earned) in lines {
+                let color = if earned { Color::YELLOW } else { Color::WHITE };
+                parent.spawn(text(line, 12., color));
+            }
+
+            if improved {
+                parent.spawn(text("New best!".to_string(), 12., Color::GREEN));
+            }
+        });
+}
+
+fn hide_panel(mut commands: Commands, query: Query<Entity, With<LevelCompletePanel>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,262 @@
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+
+use crate::{
+    replay::{replay_name, Playback, Replay},
+    retention::ReplayIndex,
+    save::{self, SaveError},
+    AppState, NextSeed, RunModifiers,
+};
+
+// How many replays fit on the screen at once
+const VISIBLE_ENTRIES: usize = 16;
+
+struct Entry {
+    name: String,
+    replay: Replay,
+}
+
+#[derive(Resource, Default)]
+struct Library {
+    entries: Vec<Entry>,
+    selected: usize,
+    status: String,
+}
+
+#[derive(Component)]
+struct LibraryScreen;
+
+pub struct LibraryPlugin;
+
+impl Plugin for LibraryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Library>()
+            .add_systems(
+                Update,
+                open_library.run_if(
+                    in_state(AppState::MainMenu).and_then(not(resource_exists::<Playback>)),
+                ),
+            )
+            .add_systems(OnEnter(AppState::Replays), load_library)
+            .add_systems(
+                Update,
+                (
+                    browse_library,
+                    draw_library.run_if(resource_changed::<Library>),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::Replays)),
+            )
+            .add_systems(Update, import_dropped_files)
+            .add_systems(OnExit(AppState::Replays), close_library);
+    }
+}
+
+fn read_entries(index: &ReplayIndex) -> Vec<Entry> {
+    // Newest first
+    index
+        .replays
+        .iter()
+        .rev()
+        .filter_map(|entry| match save::load::<Replay>(&entry.name) {
+            Ok(Some(replay)) => Some(Entry {
+                name: entry.name.clone(),
+                replay,
+            }),
+            Ok(None) => None,
+            Err(error) => {
+                warn!("Skipping replay {}: {error}", entry.name);
+                None
+            }
+        })
+        .collect()
+}
+
+fn export(entry: &Entry) -> Result<PathBuf, SaveError> {
+    let dir = dirs::download_dir()
+        .or_else(dirs::home_dir)
+        .ok_or(SaveError::NoSaveDir)?;
+    let stem = Path::new(&entry.name)
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy();
+    let path = dir.join(format!("flappy-replay-{stem}.ron"));
+    save::store_path(&path, &entry.replay)?;
+    Ok(path)
+}
+
+fn import(path: &Path, index: &mut ReplayIndex) -> Result<(), SaveError> {
+    // Parse it first so broken files never make it into the library
+    let replay = save::load_path::<Replay>(path)?;
+    let name = replay_name();
+    save::store(&name, &replay)?;
+    index.add(name, &replay);
+    Ok(())
+}
+
+fn open_library(keys: Res<ButtonInput<KeyCode>>, mut state: ResMut<NextState<AppState>>) {
+    if keys.just_pressed(KeyCode::KeyL) {
+        state.set(AppState::Replays);
+    }
+}
+
+fn load_library(mut library: ResMut<Library>, index: Res<ReplayIndex>) {
+    library.entries = read_entries(&index);
+    library.selected = 0;
+    library.status.clear();
+}
+
+fn close_library(mut commands: Commands, query: Query<Entity, With<LibraryScreen>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn browse_library(
+    mut commands: Commands,
+    mut library: ResMut<Library>,
+    mut modifiers: ResMut<RunModifiers>,
+    mut next_seed: ResMut<NextSeed>,
+    mut state: ResMut<NextState<AppState>>,
+    keys: Res<ButtonInput<KeyCode>>,
+) {
+    if keys.just_pressed(KeyCode::Escape) {
+        state.set(AppState::MainMenu);
+        return;
+    }
+
+    if library.entries.is_empty() {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        library.selected = library.selected.saturating_sub(1);
+    }
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        library.selected = (library.selected + 1).min(library.entries.len() - 1);
+    }
+
+    if keys.just_pressed(KeyCode::Enter) {
+        let replay = library.entries[library.selected].replay.clone();
+        commands.insert_resource(Playback::watch(replay, &mut modifiers, &mut next_seed));
+        state.set(AppState::MainMenu);
+    }
+
+    if keys.just_pressed(KeyCode::KeyE) {
+        library.status = match export(&library.entries[library.selected]) {
+            Ok(path) => format!("Exported to {}", path.display()),
+            Err(error) => format!("Export failed: {error}"),
+        };
+    }
+}
+
+fn import_dropped_files(
+    mut reader: EventReader<FileDragAndDrop>,
+    mut library: ResMut<Library>,
+    mut index: ResMut<ReplayIndex>,
+) {
+    for event in reader.read() {
+        let FileDragAndDrop::DroppedFile { path_buf, .. } = event else {
+            continue;
+        };
+
+        library.status = match import(path_buf, &mut index) {
+            Ok(()) => {
+                library.entries = read_entries(&index);
+                library.selected = 0;
+                "Imported replay".to_string()
+            }
+            Err(error) => format!("Import failed: {error}"),
+        };
+    }
+}
+
+fn draw_library(
+    mut commands: Commands,
+    library: Res<Library>,
+    index: Res<ReplayIndex>,
+    query: Query<Entity, With<LibraryScreen>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let text = |value: String, size: f32, color: Color| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size: size,
+                color,
+                ..default()
+            },
+        )
+    };
+
+    commands
+        .spawn((
+            LibraryScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(12.)),
+                    row_gap: Val::Px(4.),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.8).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("Replays".to_string(), 24., Color::WHITE));
+
+            if library.entries.is_empty() {
+                parent.spawn(text("No replays yet".to_string(), 14., Color::GRAY));
+            }
+
+            let first = library
+                .selected
+                .saturating_sub(VISIBLE_ENTRIES / 2)
+                .min(library.entries.len().saturating_sub(VISIBLE_ENTRIES));
+            for (i, entry) in library
+                .entries
+                .iter()
+                .enumerate()
+                .skip(first)
+                .take(VISIBLE_ENTRIES)
+            {
+                let replay = &entry.replay;
+                let mut label = format!("{:>3}. score {}", i + 1, replay.score);
+                if replay.modifiers != RunModifiers::default() {
+                    label.push_str(&format!(" {}", replay.modifiers.label()));
+                }
+                if replay.modifiers.adaptive {
+                    label.push_str(&format!(" (adaptive gap {:+})", replay.gap_adjustment));
+                }
+
+                let color = if i == library.selected {
+                    Color::YELLOW
+                } else {
+                    Color::WHITE
+                };
+                parent.spawn(text(label, 14., color));
+            }
+
+            parent.spawn(text(
+                "Enter watch, E export, Esc back\nDrop a replay here to import".to_string(),
+                12.,
+                Color::GRAY,
+            ));
+            parent.spawn(text(
+                format!(
+                    "Keeping the best {} of every mode and the last {} runs",
+                    index.retention.best_per_mode, index.retention.recent
+                ),
+                12.,
+                Color::GRAY,
+            ));
+            parent.spawn(text(library.status.clone(), 12., Color::GRAY));
+        });
+}
@@ -0,0 +1,223 @@
+use std::{
+    collections::HashMap,
+    fmt::{self, Write as _},
+    fs::File,
+    io::Write as _,
+    sync::{Mutex, OnceLock},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::{
+    ecs::schedule::ExecutorKind,
+    log::{
+        tracing_subscriber::{
+            layer::{Context, SubscriberExt},
+            Layer,
+        },
+        BoxedSubscriber, LogPlugin,
+    },
+    prelude::*,
+    utils::tracing::{
+        field::{Field, Visit},
+        span::{Attributes, EnteredSpan, Id},
+        Event, Subscriber,
+    },
+};
+
+use crate::{save, AppState, GameRng, RunModifiers, SimTick};
+
+const LOG_DIR: &str = "logs";
+// How many sessions worth of logs are kept around
+const KEPT_LOGS: usize = 5;
+
+// The log file's opened before there's anything to log to, so what went wrong
+// is held onto until there is
+static OPEN_ERROR: OnceLock<String> = OnceLock::new();
+
+/// Writes every log to a file in the save directory, along with the fields of
+/// the spans it's in so a report like "seed X, tick Y" can be played back
+struct FileSink {
+    file: Mutex<File>,
+    /// The fields of every open span and the ones it's inside of. Bevy's
+    /// subscriber is boxed up by the time it gets here, so they can't be
+    /// looked up from it
+    spans: Mutex<HashMap<Id, String>>,
+}
+
+impl FileSink {
+    fn context(&self, id: Option<&Id>) -> String {
+        let spans = self.spans.lock().unwrap_or_else(|error| error.into_inner());
+        id.and_then(|id| spans.get(id)).cloned().unwrap_or_default()
+    }
+}
+
+impl<S: Subscriber> Layer<S> for FileSink {
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let parent = if attrs.is_contextual() {
+            ctx.current_span().id().cloned()
+        } else {
+            attrs.parent().cloned()
+        };
+        let mut context = self.context(parent.as_ref());
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        context.push_str(&fields.rest);
+
+        self.spans
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .insert(id.clone(), context);
+    }
+
+    fn on_close(&self, id: Id, _ctx: Context<'_, S>) {
+        self.spans
+            .lock()
+            .unwrap_or_else(|error| error.into_inner())
+            .remove(&id);
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+
+        let parent = if event.is_contextual() {
+            ctx.current_span().id().cloned()
+        } else {
+            event.parent().cloned()
+        };
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_millis())
+            .unwrap_or_default();
+        let metadata = event.metadata();
+        let line = format!(
+            "{millis} {} {}: {}{}{}\n",
+            metadata.level(),
+            metadata.target(),
+            fields.message,
+            fields.rest,
+            self.context(parent.as_ref()),
+        );
+
+        if let Ok(mut file) = self.file.lock() {
+            // Nowhere left to report it if the log can't be written
+            let _ = file.write_all(line.as_bytes());
+        }
+    }
+}
+
+#[derive(Default)]
+struct Fields {
+    message: String,
+    rest: String,
+}
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            let _ = write!(self.rest, " {}={value:?}", field.name());
+        }
+    }
+}
+
+/// Bevy's logging, plus a log file in release builds
+pub fn log_plugin() -> LogPlugin {
+    LogPlugin {
+        update_subscriber: Some(add_file_sink),
+        ..default()
+    }
+}
+
+fn add_file_sink(subscriber: BoxedSubscriber) -> BoxedSubscriber {
+    if cfg!(debug_assertions) {
+        return subscriber;
+    }
+
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+    let file = match save::create(&format!("{LOG_DIR}/{millis}.log")) {
+        Ok(file) => file,
+        Err(error) => {
+            let _ = OPEN_ERROR.set(error.to_string());
+            return subscriber;
+        }
+    };
+
+    if let Ok(logs) = save::list(LOG_DIR) {
+        for old in logs.iter().rev().skip(KEPT_LOGS) {
+            let _ = std::fs::remove_file(old);
+        }
+    }
+
+    Box::new(subscriber.with(FileSink {
+        file: Mutex::new(file),
+        spans: Mutex::default(),
+    }))
+}
+
+/// The run being played, entered for as long as it lasts
+struct RunSpan {
+    _entered: EnteredSpan,
+}
+
+/// The simulation step being run, entered for the length of it
+struct StepSpan {
+    _entered: EnteredSpan,
+}
+
+pub struct LoggingPlugin;
+
+impl Plugin for LoggingPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, report_open_error)
+            .add_systems(OnEnter(AppState::Playing), start_run)
+            .add_systems(OnEnter(AppState::MainMenu), end_run)
+            .add_systems(FixedFirst, enter_step)
+            .add_systems(FixedLast, exit_step);
+    }
+
+    fn finish(&self, app: &mut App) {
+        // Entered spans only count on the thread they were entered on, so
+        // systems are kept on the main thread to log inside of them
+        for (_, schedule) in app.world.resource_mut::<Schedules>().iter_mut() {
+            schedule.set_executor_kind(ExecutorKind::SingleThreaded);
+        }
+    }
+}
+
+fn report_open_error() {
+    if let Some(error) = OPEN_ERROR.get() {
+        warn!("Couldn't open the log file: {error}");
+    }
+}
+
+fn start_run(world: &mut World, mut runs: Local<u64>) {
+    // Out of the last one first, so this one isn't inside of it
+    world.remove_non_send_resource::<RunSpan>();
+
+    *runs += 1;
+    let seed = world.resource::<GameRng>().seed;
+    let mode = world.resource::<RunModifiers>().mode.clone();
+    let span = info_span!("run", run = *runs, seed, mode = %mode).entered();
+    world.insert_non_send_resource(RunSpan { _entered: span });
+    info!("Run started");
+}
+
+fn end_run(world: &mut World) {
+    world.remove_non_send_resource::<RunSpan>();
+}
+
+fn enter_step(world: &mut World) {
+    let tick = world.resource::<SimTick>().0;
+    let state = world.resource::<State<AppState>>().get().clone();
+    let span = info_span!("step", tick, state = ?state).entered();
+    world.insert_non_send_resource(StepSpan { _entered: span });
+}
+
+fn exit_step(world: &mut World) {
+    world.remove_non_send_resource::<StepSpan>();
+}
@@ -0,0 +1,96 @@
+use std::time::Duration;
+
+use bevy::{
+    prelude::*,
+    winit::{UpdateMode, WinitSettings},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::AppState;
+
+// Sounds that can play at once on a channel other than the music
+const LOW_POWER_VOICES: usize = 4;
+// Frames a second the menus are drawn at
+const MENU_FPS: f64 = 30.;
+
+/// One switch for everything that can be given up to make a battery last,
+/// for handhelds and laptops
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(default)]
+pub struct LowPower {
+    pub enabled: bool,
+}
+
+impl LowPower {
+    /// How many of `count` particles are spawned, never none at all
+    pub fn particles(&self, count: usize) -> usize {
+        if self.enabled {
+            count.div_ceil(2)
+        } else {
+            count
+        }
+    }
+
+    /// How many sounds can play at once on a channel, if there's a limit
+    pub fn voices(&self) -> Option<usize> {
+        self.enabled.then_some(LOW_POWER_VOICES)
+    }
+}
+
+/// Run condition for extras that are left out to save power
+pub fn saving_power(power: Res<LowPower>) -> bool {
+    power.enabled
+}
+
+pub struct LowPowerPlugin;
+
+impl Plugin for LowPowerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                multisample.run_if(resource_changed::<LowPower>),
+                cap_frame_rate
+                    .run_if(resource_changed::<LowPower>.or_else(state_changed::<AppState>)),
+            ),
+        );
+    }
+}
+
+// The camera doesn't tonemap or dither, so multisampling is the only pass
+// over the whole screen there is to give up
+fn multisample(power: Res<LowPower>, mut msaa: ResMut<Msaa>) {
+    *msaa = if power.enabled {
+        Msaa::Off
+    } else {
+        Msaa::default()
+    };
+}
+
+// Runs are always drawn as often as they can be, so flaps land when they
+// look like they do. Without a window there's nothing to cap
+fn cap_frame_rate(
+    power: Res<LowPower>,
+    state: Res<State<AppState>>,
+    settings: Option<ResMut<WinitSettings>>,
+) {
+    let Some(mut settings) = settings else {
+        return;
+    };
+
+    let in_run = matches!(
+        state.get(),
+        AppState::GetReady
+            | AppState::Playing
+            | AppState::KillCam
+            | AppState::Dying
+            | AppState::Restarting
+    );
+    settings.focused_mode = if power.enabled && !in_run {
+        UpdateMode::ReactiveLowPower {
+            wait: Duration::from_secs_f64(1. / MENU_FPS),
+        }
+    } else {
+        UpdateMode::Continuous
+    };
+}
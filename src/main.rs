@@ -1,40 +1,337 @@
+// Systems take everything they need as parameters, and queries are spelled out as types
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+mod accessories;
+mod actions;
+mod afk;
+mod assist;
+mod audio_settings;
+mod awards;
+mod behaviors;
+mod bonus;
+mod bookmarks;
+mod bots;
+mod breather;
+mod calibration;
+mod camera_shake;
+mod campaign;
+mod career;
+mod ceiling;
+mod challenge;
+mod characters;
+mod config;
+mod console;
+mod controls;
+mod curve;
+mod custom_levels;
+mod daily;
+mod debug_overlay;
+mod decals;
+mod decorations;
+mod determinism;
+mod difficulty;
+mod display;
+mod effects;
+#[cfg(feature = "events")]
+mod events;
+mod feedback;
+mod flash;
+mod get_ready;
+mod ghost;
+mod gusts;
+mod hazards;
+mod high_score;
+mod hitboxes;
+mod hud;
+mod intro;
+mod kill_plane;
+mod killcam;
+mod leaderboard;
+mod levels;
+mod library;
+mod logging;
+mod low_power;
+mod main_menu;
+mod mixer;
+mod modes;
+mod music;
+mod particles;
+mod pause;
+mod physics;
+mod popups;
+mod portals;
+mod practice;
+mod profile;
+mod prompts;
+mod puffs;
+mod randomizer;
+mod ranked;
+mod replay;
+mod restart;
+mod retention;
+mod ron_asset;
+mod roulette;
+mod save;
+mod score_panel;
+mod scoring;
+mod screenshot;
+mod scroll;
+mod settings;
+mod showcase;
+mod simulate;
+mod snapshot;
+#[cfg(feature = "spectate")]
+mod spectate;
+mod squash;
+mod suspend;
+mod telegraph;
+#[cfg(test)]
+mod tests;
+mod time_trial;
+mod touch;
+mod tutorials;
+mod wear;
+mod weather;
+mod zen;
+
+use std::f32::consts::TAU;
+
+use accessories::{AccessoriesPlugin, Slot};
+use actions::{Action, Actions, ActionsPlugin};
+use afk::AfkPlugin;
+use assist::AssistPlugin;
+use audio_settings::AudioSettingsPlugin;
+use awards::AwardsPlugin;
+use behaviors::{pose_pipes, set_behaviors, Behavior, BehaviorsPlugin, Oscillate};
 use bevy::{
     app::{App, Startup, Update},
     asset::{AssetMode, AssetPlugin},
-    ecs::query,
+    ecs::{schedule::ScheduleLabel, system::EntityCommands},
     math::{
         bounding::{Aabb2d, BoundingVolume, IntersectsVolume},
         vec2,
     },
     prelude::*,
-    render::{camera::Viewport, primitives::Aabb},
+    render::camera::{ScalingMode, Viewport},
 };
-use rand::Rng;
+use bonus::{BonusPlugin, PlayPhase};
+use bookmarks::{BookmarksPlugin, Draft};
+use bots::BotsPlugin;
+use breather::{Breather, BreatherPlugin};
+use calibration::CalibrationPlugin;
+use camera_shake::CameraShakePlugin;
+use campaign::CampaignPlugin;
+use career::CareerPlugin;
+use ceiling::{CeilingBehavior, CeilingPlugin, OnBonked};
+use challenge::ChallengePlugin;
+use characters::CharactersPlugin;
+use config::{ActiveConfig, ConfigPlugin, GameConfig};
+use console::{Console, ConsolePlugin};
+use controls::ControlsPlugin;
+use curve::CurvePlugin;
+use custom_levels::CustomLevelsPlugin;
+use daily::DailyPlugin;
+use debug_overlay::DebugOverlayPlugin;
+use decals::{DecalsPlugin, Repaint};
+use decorations::DecorationsPlugin;
+use determinism::DeterminismPlugin;
+use difficulty::{Difficulty, DifficultyPlugin, DifficultyPreset};
+use display::{DisplayPause, DisplayPlugin};
+use effects::{Easing, EffectsPlugin};
+use feedback::FeedbackPlugin;
+use flash::FlashPlugin;
+use get_ready::{GetReadyPlugin, GetReadySign};
+use ghost::GhostPlugin;
+use gusts::GustsPlugin;
+use hazards::{spawn_hazard, Hazard, HazardsPlugin};
+use high_score::HighScorePlugin;
+use hitboxes::{ActiveHitboxes, Hitboxes, HitboxesPlugin};
+use hud::HudPlugin;
+use intro::IntroPlugin;
+use kill_plane::KillPlanePlugin;
+use killcam::KillCamPlugin;
+use leaderboard::LeaderboardPlugin;
+use levels::LevelsPlugin;
+use library::LibraryPlugin;
+use logging::LoggingPlugin;
+use low_power::LowPowerPlugin;
+use main_menu::MainMenuPlugin;
+use mixer::MixerPlugin;
+use modes::{ModeRegistry, ModesPlugin, CLASSIC};
+use music::MusicPlugin;
+use particles::ParticlesPlugin;
+use pause::{PausePlugin, PauseState};
+use physics::{ActivePhysics, PhysicsPlugin, PhysicsPreset, CLASSIC_PHYSICS};
+use popups::PopupsPlugin;
+use portals::PortalsPlugin;
+use practice::PracticePlugin;
+use profile::ProfilePlugin;
+use prompts::PromptsPlugin;
+use puffs::PuffsPlugin;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use randomizer::RandomizerPlugin;
+use ranked::RankedPlugin;
+use replay::{Playback, ReplayPlugin};
+use restart::RestartPlugin;
+use retention::RetentionPlugin;
+use roulette::{Modifier, Roulette, RoulettePlugin};
+use score_panel::ScorePanelPlugin;
+use scoring::{Combo, PipeScore, ScoringPlugin};
+use screenshot::ScreenshotPlugin;
+use scroll::{is_scrolling, ScrollEase, ScrollPlugin};
+use serde::{Deserialize, Serialize};
+use settings::SettingsPlugin;
+use showcase::ShowcasePlugin;
+use simulate::SimulatePlugin;
+use snapshot::SnapshotPlugin;
+use squash::SquashPlugin;
+use suspend::SuspendPlugin;
+use telegraph::TelegraphPlugin;
+use time_trial::TimeTrialPlugin;
+use touch::TouchPlugin;
+use tutorials::TutorialsPlugin;
+use wear::WearPlugin;
+use weather::{Weather, WeatherPlugin, WeatherSetting};
+use zen::ZenPlugin;
 
 #[derive(States, Debug, Clone, PartialEq, Eq, Hash)]
 enum AppState {
     MainMenu,
+    /// The bird hovering in place until the first flap starts the run
+    GetReady,
     Playing,
+    KillCam,
+    /// The bird falling to the ground after a crash, before the run's over
+    Dying,
     GameOver,
+    /// The end of a level was reached
+    LevelComplete,
+    /// Picking one of the campaign's levels
+    LevelSelect,
+    /// Fading out of a run to go straight into a new one
+    Restarting,
+    Replays,
+    Bookmarks,
+    Leaderboard,
+    /// Typing in a code to play someone else's run
+    Challenge,
+    Settings,
+    /// Changing what's bound to what
+    Controls,
+    /// Tapping along to a beat to measure how late the player's taps land
+    Calibration,
+    /// Everything worked towards over every run
+    Career,
+}
+
+/// Lays out a new world for the next run from `NextSeed`. Runs when the main
+/// menu is entered, or on its own to go straight into another run
+#[derive(ScheduleLabel, Debug, Clone, PartialEq, Eq, Hash)]
+struct BuildWorld;
+
+/// The order things happen in within a single simulation step
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+enum SimSet {
+    Input,
+    Physics,
+    Collision,
+    Rules,
+    Transition,
 }
 
+const SIM_HZ: f64 = 120.;
 const PIPE_SPACE: f32 = 42.;
 const PIPE_TO_PIPE_SPACE: f32 = 160.;
+const PIPE_COLUMNS: usize = 4;
+// Where the first column of a world starts, off to the right of the screen
+const FIRST_PIPE_X: f32 = 144.;
 const PIPE_WIDTH: f32 = 26.;
 const SCROLL_SPEED: f32 = -100.;
-const TERMINAL_VELOCITY: f32 = -400.;
-const JUMP_VELOCITY: f32 = 200.;
-const GRAVITY: f32 = -982.;
+// How much of the ground shows along the bottom of the screen, the rest of it
+// hangs off below
+const GROUND_SHOWN: f32 = 20.;
+// Where the top of the ground is, which the bird crashes into
+const GROUND_TOP: f32 = -128. + GROUND_SHOWN;
+// How wide one tile of the ground is, and how far down its middle is from the
+// top of it
+const GROUND_WIDTH: f32 = 168.;
+const GROUND_HALF_HEIGHT: f32 = 28.;
+// How far apart the tiles of the background are, a pixel less than they're
+// wide so there's no seam
+const BACKGROUND_WIDTH: f32 = 143.;
+// A dying bird is done once it's lying on the ground
+const FLOOR: f32 = GROUND_TOP + 4.;
 
 #[derive(Component)]
 struct Player;
 
+/// What a bird is up to, which decides what gets to act on it
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Default)]
+enum BirdState {
+    /// Hovering in place until a run starts
+    #[default]
+    Idle,
+    /// Flapping and falling through a run
+    Flying,
+    /// Tumbling out of the sky after a crash
+    Dying,
+    /// Fallen out of the world, nothing moves it anymore
+    Dead,
+}
+
+impl BirdState {
+    /// Whether gravity has a hold on the bird
+    fn falls(self) -> bool {
+        matches!(self, BirdState::Flying | BirdState::Dying)
+    }
+}
+
 #[derive(Component)]
 struct Animation {
     t: f32,
     repeat: bool,
     frame: usize,
     frames: Vec<Frame>,
+    /// How fast the animation plays, 1 being as fast as its frames say
+    speed: f32,
+}
+
+impl Animation {
+    /// Plays `delta` seconds of the animation, however many frames that is
+    fn advance(&mut self, delta: f32) {
+        let mut delta = delta * self.speed;
+        loop {
+            let frame = &self.frames[self.frame];
+
+            let remaining = (1. - self.t) * frame.duration;
+
+            if delta < remaining {
+                self.t += delta / frame.duration;
+                break;
+            }
+
+            delta -= remaining;
+
+            let finished = self.frame + 1 >= self.frames.len();
+
+            match (finished, self.repeat) {
+                (true, true) => {
+                    self.frame = 0;
+                    self.t = 0.;
+                }
+                (true, false) => {
+                    self.frame = self.frames.len() - 1;
+                    self.t = 1.;
+                    break;
+                }
+                _ => {
+                    self.frame += 1;
+                    self.t = 0.;
+                }
+            }
+        }
+    }
 }
 
 struct Frame {
@@ -42,23 +339,358 @@ struct Frame {
     duration: f32,
 }
 
-#[derive(Event, Default)]
-struct OnJumped;
+#[derive(Event)]
+struct OnJumped {
+    position: Vec2,
+}
+
+/// Where the player hit the floor after crashing
+#[derive(Event)]
+struct OnDied {
+    position: Vec2,
+}
+
+/// Where the player hit something, and the collider it hit if it wasn't the
+/// edge of the world
+#[derive(Event)]
+struct OnCrashed {
+    /// Where the bird was when it crashed
+    position: Vec2,
+    contact: Vec2,
+    collider: Option<Aabb2d>,
+}
+
+/// The player made it past an obstacle, which scores `points`
+#[derive(Event)]
+struct PipePassed {
+    obstacle: Entity,
+    position: Vec2,
+    points: u32,
+}
 
 #[derive(Component)]
 struct Velocity(f32);
 
-#[derive(Resource)]
-struct Gravity(f32);
+#[derive(Resource, Default)]
+struct Score(u32);
+
+/// A flap waiting for the next simulation step to happen, along with how far
+/// into that step it came in
+#[derive(Resource, Default)]
+struct QueuedFlap(Option<f32>);
+
+/// How much of the current step the player has already moved through, which
+/// is where in the step the flap happened
+#[derive(Resource, Default)]
+struct StepOffset(f32);
 
+/// Simulation steps since the run started
+#[derive(Resource, Default)]
+struct SimTick(u64);
+
+/// Everything random about a run comes from here so it can be played again
+#[derive(Resource, Deref, DerefMut)]
+struct GameRng {
+    seed: u64,
+    #[deref]
+    rng: ChaCha8Rng,
+}
+
+impl GameRng {
+    fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: ChaCha8Rng::seed_from_u64(seed),
+        }
+    }
+}
+
+/// Seed for the next world, a fresh one is rolled if there's none
+#[derive(Resource, Default)]
+struct NextSeed(Option<u64>);
+
+/// Seed given with `--seed`, which every world is laid out from unless
+/// something like a replay asks for one of its own. The same seed always gets
+/// the same pipes
+#[derive(Resource, Default)]
+struct PinnedSeed(Option<u64>);
+
+impl PinnedSeed {
+    fn from_args() -> Self {
+        let mut args = std::env::args().skip_while(|arg| arg != "--seed").skip(1);
+        let seed = args.next().and_then(|value| match value.parse() {
+            Ok(seed) => Some(seed),
+            Err(_) => {
+                warn!("{value} isn't a seed, rolling a fresh one for every world");
+                None
+            }
+        });
+        Self(seed)
+    }
+}
+
+/// Options that change how a run plays, kept alongside the run so it's clear
+/// what kind of run a result came from
+#[derive(Resource, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+struct RunModifiers {
+    /// Id of the mode the run is played in
+    #[serde(default = "classic_mode")]
+    mode: String,
+    /// Id of the campaign level being played, if it's one of those
+    #[serde(default)]
+    level: Option<String>,
+    adaptive: bool,
+    ceiling: CeilingBehavior,
+    /// Name of the physics preset the bird flies with
+    #[serde(default = "classic_physics")]
+    physics: String,
+    /// Whether the bird's path is drawn out ahead of it
+    #[serde(default)]
+    assist: bool,
+    /// Whether the first few columns after a run's picked back up have wide
+    /// gaps in the middle
+    #[serde(default)]
+    breather: bool,
+    /// How forgiving the gaps, the speed and the gravity are
+    #[serde(default)]
+    preset: DifficultyPreset,
+}
+
+// What the modifiers go by when something needs to tell them apart, like the
+// tutorials that explain them
+const ADAPTIVE: &str = "adaptive";
+const BONK_CEILING: &str = "bonk-ceiling";
+const ASSIST: &str = "assist";
+const BREATHER: &str = "breather";
+
+fn classic_mode() -> String {
+    CLASSIC.to_string()
+}
+
+fn classic_physics() -> String {
+    CLASSIC_PHYSICS.to_string()
+}
+
+impl Default for RunModifiers {
+    fn default() -> Self {
+        Self {
+            mode: classic_mode(),
+            level: None,
+            adaptive: false,
+            ceiling: CeilingBehavior::default(),
+            physics: classic_physics(),
+            assist: false,
+            breather: false,
+            preset: DifficultyPreset::Normal,
+        }
+    }
+}
+
+impl RunModifiers {
+    fn from_args() -> Self {
+        let mut modifiers = RunModifiers::default();
+        for arg in std::env::args() {
+            match arg.as_str() {
+                "--adaptive" => modifiers.adaptive = true,
+                "--bonk-ceiling" => modifiers.ceiling = CeilingBehavior::Bonk,
+                "--assist" => modifiers.assist = true,
+                "--breather" => modifiers.breather = true,
+                _ => {}
+            }
+        }
+        modifiers
+    }
+
+    /// Ids of the mode and of every modifier that's on
+    fn tutorial_ids(&self) -> Vec<&str> {
+        let mut ids = vec![self.mode.as_str()];
+        if self.adaptive {
+            ids.push(ADAPTIVE);
+        }
+        if self.ceiling == CeilingBehavior::Bonk {
+            ids.push(BONK_CEILING);
+        }
+        if self.assist {
+            ids.push(ASSIST);
+        }
+        if self.breather {
+            ids.push(BREATHER);
+        }
+        ids
+    }
+
+    /// What the modifiers are called when they're shown to the player
+    fn label(&self) -> String {
+        let mut parts = Vec::new();
+        if self.mode != CLASSIC {
+            parts.push(self.mode.as_str());
+        }
+        if let Some(level) = &self.level {
+            parts.push(level.as_str());
+        }
+        if self.adaptive {
+            parts.push("adaptive");
+        }
+        if self.ceiling == CeilingBehavior::Bonk {
+            parts.push("bonk ceiling");
+        }
+        let physics = self.physics.to_lowercase();
+        if self.physics != CLASSIC_PHYSICS {
+            parts.push(physics.as_str());
+        }
+        if self.assist {
+            parts.push("assist");
+        }
+        if self.breather {
+            parts.push("breather");
+        }
+        if self.preset != DifficultyPreset::Normal {
+            parts.push(self.preset.name());
+        }
+
+        if parts.is_empty() {
+            "classic".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+}
+
+/// A tile of any of the layers behind the pipes
 #[derive(Component)]
 struct Background;
 
+/// One tile of the strip of ground along the bottom, scrolling along with the
+/// pipes
+#[derive(Component)]
+struct Ground;
+
+// How long into a run the sky starts to turn, and how long it takes to get
+// all the way round to the other end of the day
+const DUSK_START: f32 = 45.;
+const DUSK_LENGTH: f32 = 90.;
+// Mixed into the run's seed for the time of day and the weather, so they come
+// out the same for a seed but leave the run's random numbers alone
+const SCENERY_SEED: u64 = 0x5CE7E;
+
+/// Whether a run starts out in the day or at night, rolled for every world
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default)]
+enum TimeOfDay {
+    #[default]
+    Day,
+    Night,
+}
+
+impl TimeOfDay {
+    fn roll(rng: &mut impl Rng) -> Self {
+        if rng.gen_bool(0.5) {
+            TimeOfDay::Day
+        } else {
+            TimeOfDay::Night
+        }
+    }
+
+    /// What the sky and the ground are tinted with. There's only the one
+    /// background in the sheet, so the night is the day made darker and bluer
+    fn tint(self) -> Color {
+        match self {
+            TimeOfDay::Day => Color::WHITE,
+            TimeOfDay::Night => Color::rgb(0.35, 0.4, 0.7),
+        }
+    }
+
+    fn other(self) -> Self {
+        match self {
+            TimeOfDay::Day => TimeOfDay::Night,
+            TimeOfDay::Night => TimeOfDay::Day,
+        }
+    }
+
+    /// The tint once the run's been going for `world_time`, fading from this
+    /// time of day into the other one the longer it lasts
+    fn sky(self, world_time: &WorldTime) -> Color {
+        let t = ((world_time.0 - DUSK_START) / DUSK_LENGTH).clamp(0., 1.);
+        let from = Vec4::from_array(self.tint().as_rgba_f32());
+        let to = Vec4::from_array(self.other().tint().as_rgba_f32());
+        Color::rgba_from_array(from.lerp(to, Easing::Smoothstep.apply(t)))
+    }
+}
+
+/// How long the world's run has been going, which the sky slowly turns with
+#[derive(Resource, Default)]
+struct WorldTime(f32);
+
+/// How fast a tile scrolls by, as a part of how fast the pipes go. Whatever's
+/// further away goes slower
+#[derive(Component, Clone, Copy)]
+struct ScrollSpeed(f32);
+
+/// How far apart the tiles of a layer are, each going back around behind the
+/// other once it's off the screen
+#[derive(Component)]
+struct Tiled(f32);
+
+/// A slice of the background that scrolls on its own
+struct Layer {
+    atlas: Atlas,
+    /// Where the middle of it goes
+    height: f32,
+    speed: ScrollSpeed,
+}
+
+// From the back to the front. The sky has everything else drawn over it, so
+// it stays put and fills in whatever the others don't cover
+const LAYERS: [Layer; 3] = [
+    Layer {
+        atlas: Atlas::Background,
+        height: 0.,
+        speed: ScrollSpeed(0.),
+    },
+    Layer {
+        atlas: Atlas::Clouds,
+        height: -33.5,
+        speed: ScrollSpeed(0.25),
+    },
+    Layer {
+        atlas: Atlas::Skyline,
+        height: -55.5,
+        speed: ScrollSpeed(0.5),
+    },
+];
+
 #[derive(Component)]
 struct Obstacle;
 
+#[derive(Component, Clone, Copy, PartialEq, Serialize, Deserialize, Debug)]
+enum Pattern {
+    Regular,
+    /// The gap bobs up and down around `center` as it scrolls by
+    MovingGap {
+        center: f32,
+    },
+}
+
+/// The player has made it past this obstacle going forward since it was last
+/// recycled. It's only taken off when the obstacle comes back around, so going
+/// back past a pipe and through it again doesn't score it twice
+#[derive(Component)]
+struct Passed;
+
+/// Which side of the player an obstacle was on after the last step. The
+/// player's gone past it when that flips, the way it flipped being the way
+/// they went. Taken off with `Passed` so it starts over from wherever the
+/// obstacle ends up
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug)]
+enum Side {
+    Ahead,
+    Behind,
+}
+
 #[derive(Component)]
-struct Pipe;
+enum Pipe {
+    Top,
+    Bottom,
+}
 
 #[derive(Component)]
 struct Collider(Aabb2d);
@@ -66,6 +698,15 @@ struct Collider(Aabb2d);
 #[derive(Component)]
 struct Root;
 
+/// The sprite sheet everything in the world is drawn from
+#[derive(Resource)]
+struct SpriteSheet {
+    texture: Handle<Image>,
+    layout: Handle<TextureAtlasLayout>,
+    hitboxes: Hitboxes,
+}
+
+#[derive(Clone, Copy)]
 enum Atlas {
     Background = 0,
     Bird1 = 1,
@@ -73,20 +714,149 @@ enum Atlas {
     Bird3 = 3,
     PipeTop = 4,
     PipeBottom = 5,
+    Digit0 = 6,
+    Digit1 = 7,
+    Digit2 = 8,
+    Digit3 = 9,
+    Digit4 = 10,
+    Digit5 = 11,
+    Digit6 = 12,
+    Digit7 = 13,
+    Digit8 = 14,
+    Digit9 = 15,
+    GetReady = 16,
+    ScorePanel = 17,
+    MedalBronze = 18,
+    MedalSilver = 19,
+    MedalGold = 20,
+    MedalPlatinum = 21,
+    NewBest = 22,
+    SmallDigit0 = 23,
+    SmallDigit1 = 24,
+    SmallDigit2 = 25,
+    SmallDigit3 = 26,
+    SmallDigit4 = 27,
+    SmallDigit5 = 28,
+    SmallDigit6 = 29,
+    SmallDigit7 = 30,
+    SmallDigit8 = 31,
+    SmallDigit9 = 32,
+    Title = 33,
+    Ground = 34,
+    Clouds = 35,
+    Skyline = 36,
 }
 
-fn random_pipe_height() -> f32 {
-    let mut rng = rand::thread_rng();
+impl Atlas {
+    fn from_index(index: usize) -> Option<Self> {
+        [
+            Atlas::Background,
+            Atlas::Bird1,
+            Atlas::Bird2,
+            Atlas::Bird3,
+            Atlas::PipeTop,
+            Atlas::PipeBottom,
+            Atlas::Digit0,
+            Atlas::Digit1,
+            Atlas::Digit2,
+            Atlas::Digit3,
+            Atlas::Digit4,
+            Atlas::Digit5,
+            Atlas::Digit6,
+            Atlas::Digit7,
+            Atlas::Digit8,
+            Atlas::Digit9,
+            Atlas::GetReady,
+            Atlas::ScorePanel,
+            Atlas::MedalBronze,
+            Atlas::MedalSilver,
+            Atlas::MedalGold,
+            Atlas::MedalPlatinum,
+            Atlas::NewBest,
+            Atlas::SmallDigit0,
+            Atlas::SmallDigit1,
+            Atlas::SmallDigit2,
+            Atlas::SmallDigit3,
+            Atlas::SmallDigit4,
+            Atlas::SmallDigit5,
+            Atlas::SmallDigit6,
+            Atlas::SmallDigit7,
+            Atlas::SmallDigit8,
+            Atlas::SmallDigit9,
+            Atlas::Title,
+            Atlas::Ground,
+            Atlas::Clouds,
+            Atlas::Skyline,
+        ]
+        .into_iter()
+        .find(|atlas| *atlas as usize == index)
+    }
+
+    /// The big number glyph for `digit`, which has to be below 10
+    fn digit(digit: u32) -> Self {
+        Self::from_index(Atlas::Digit0 as usize + digit as usize).unwrap_or(Atlas::Digit0)
+    }
+
+    /// The small number glyph for `digit`, which has to be below 10
+    fn small_digit(digit: u32) -> Self {
+        Self::from_index(Atlas::SmallDigit0 as usize + digit as usize).unwrap_or(Atlas::SmallDigit0)
+    }
+
+    /// Where whatever is worn in `slot` goes on this frame, from the middle of
+    /// the sprite. Only the bird has anywhere to wear things
+    fn anchor(self, slot: Slot) -> Option<Vec2> {
+        match (self, slot) {
+            // The top of the head, which stays put while the wing flaps
+            (Atlas::Bird1 | Atlas::Bird2 | Atlas::Bird3, Slot::Hat) => Some(Vec2::new(1.5, 6.)),
+            // Between the head and the wing, tugged down as the wing comes down
+            (Atlas::Bird1 | Atlas::Bird2, Slot::Scarf) => Some(Vec2::new(-0.5, -0.5)),
+            (Atlas::Bird3, Slot::Scarf) => Some(Vec2::new(-0.5, -1.5)),
+            _ => None,
+        }
+    }
+}
+
+impl Pattern {
+    fn behaviors(self, config: &GameConfig) -> Vec<Behavior> {
+        match self {
+            Pattern::Regular => Vec::new(),
+            Pattern::MovingGap { center } => vec![Behavior::Oscillate(Oscillate {
+                center,
+                amplitude: config.moving_gap_amplitude,
+                wavelength: config.moving_gap_wavelength,
+            })],
+        }
+    }
+}
+
+fn random_pipe_height(rng: &mut GameRng) -> f32 {
     rng.gen_range(48..=154) as f32
 }
 
+fn random_pattern(difficulty: &Difficulty, config: &GameConfig, rng: &mut GameRng) -> Pattern {
+    let weights = difficulty.patterns;
+    let total = weights.regular + weights.moving_gap;
+    if difficulty.is_high() && total > 0. && rng.gen_range(0. ..total) < weights.moving_gap {
+        // Keep the moving gap within the same bounds as a regular one
+        let amplitude = config.moving_gap_amplitude;
+        let center = rng.gen_range(48. + amplitude..=154. - amplitude);
+        Pattern::MovingGap { center }
+    } else {
+        Pattern::Regular
+    }
+}
+
 fn startup(mut commands: Commands) {
-    commands.insert_resource(Gravity(GRAVITY));
     commands.spawn(Camera2dBundle {
+        // The same stretch of the world is in view however big the viewport
+        // ends up being on the screen
         projection: OrthographicProjection {
             far: 1000.,
             near: -1000.,
-            scale: 0.5,
+            scaling_mode: ScalingMode::Fixed {
+                width: 144.,
+                height: 256.,
+            },
             ..default()
         },
         camera: Camera {
@@ -105,12 +875,23 @@ fn create_world(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlasLayout>>,
+    difficulty: Res<Difficulty>,
+    mut score: ResMut<Score>,
+    mut rng: ResMut<GameRng>,
+    mut next_seed: ResMut<NextSeed>,
+    pinned_seed: Res<PinnedSeed>,
+    weather: Res<WeatherSetting>,
+    hitboxes: Res<ActiveHitboxes>,
     query: Query<Entity, With<Root>>,
 ) {
     for entity in &query {
         commands.entity(entity).despawn_recursive();
     }
 
+    score.0 = 0;
+    let seed = next_seed.0.take().or(pinned_seed.0);
+    *rng = GameRng::new(seed.unwrap_or_else(rand::random));
+
     let flappy_sheet = asset_server.load::<Image>("flappy.png");
 
     fn rect(x: f32, y: f32, w: f32, h: f32) -> Rect {
@@ -130,8 +911,60 @@ fn create_world(
     texture_atlas.add_texture(rect(152., 3., PIPE_WIDTH, 160.));
     // The bottom pipe
     texture_atlas.add_texture(rect(180., 3., PIPE_WIDTH, 160.));
+    // The big score digits, zero through nine. One is narrower than the rest
+    texture_atlas.add_texture(rect(254., 98., 12., 18.));
+    texture_atlas.add_texture(rect(238., 80., 8., 18.));
+    for (x, y) in [
+        (325., 148.),
+        (339., 148.),
+        (353., 148.),
+        (367., 148.),
+        (325., 172.),
+        (339., 172.),
+        (353., 172.),
+        (367., 172.),
+    ] {
+        texture_atlas.add_texture(rect(x, y, 12., 18.));
+    }
+    // The "Get Ready!" sign
+    texture_atlas.add_texture(rect(254., 71., 92., 25.));
+    // The game over panel, and the medals that go on it from worst to best
+    texture_atlas.add_texture(rect(260., 195., 113., 57.));
+    for (x, y) in [(214., 102.), (214., 78.), (384., 154.), (384., 130.)] {
+        texture_atlas.add_texture(rect(x, y, 22., 22.));
+    }
+    // The "NEW" tag for a best score
+    texture_atlas.add_texture(rect(214., 126., 16., 7.));
+    // The small digits, zero through nine. One is narrower than the rest
+    for (x, y, w) in [
+        (279., 171., 6.),
+        (282., 180., 3.),
+        (289., 171., 6.),
+        (289., 180., 6.),
+        (298., 171., 6.),
+        (298., 180., 6.),
+        (306., 171., 6.),
+        (306., 180., 6.),
+        (315., 171., 6.),
+        (315., 180., 6.),
+    ] {
+        texture_atlas.add_texture(rect(x, y, w, 7.));
+    }
+    // The "FlappyBird" logo
+    texture_atlas.add_texture(rect(152., 200., 89., 24.));
+    // The ground
+    texture_atlas.add_texture(rect(215., 10., GROUND_WIDTH, GROUND_HALF_HEIGHT * 2.));
+    // The clouds and the skyline along with the bushes in front of it, cut
+    // out of the background so they can scroll on their own
+    texture_atlas.add_texture(rect(3., 152., 144., 19.));
+    texture_atlas.add_texture(rect(3., 171., 144., 25.));
 
     let handle_texture_atlas = texture_atlases.add(texture_atlas);
+    let sheet = SpriteSheet {
+        texture: flappy_sheet.clone(),
+        layout: handle_texture_atlas.clone(),
+        hitboxes: hitboxes.0,
+    };
 
     let bird_frames = vec![
         Frame {
@@ -153,13 +986,15 @@ fn create_world(
         .with_children(|parent| {
             parent.spawn((
                 Player,
-                Collider(Aabb2d::new(Vec2::new(0., 0.), Vec2::new(6., 4.))),
+                BirdState::Idle,
+                Collider(sheet.hitboxes.bird.aabb()),
                 Velocity(0.),
                 Animation {
                     frame: 2,
                     repeat: false,
                     t: 0.,
                     frames: bird_frames,
+                    speed: 1.,
                 },
                 SpriteSheetBundle {
                     texture: flappy_sheet.clone(),
@@ -172,132 +1007,323 @@ fn create_world(
                 },
             ));
 
-            parent
-                .spawn((
-                    Background,
+            parent.spawn((
+                GetReadySign,
+                SpriteSheetBundle {
+                    texture: flappy_sheet.clone(),
+                    atlas: TextureAtlas {
+                        layout: handle_texture_atlas.clone(),
+                        index: Atlas::GetReady as usize,
+                    },
+                    transform: Transform::from_translation(Vec3::new(0., 48., 5.)),
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+            ));
+
+            for (depth, layer) in LAYERS.iter().enumerate() {
+                for i in 0..2 {
+                    parent.spawn((
+                        Background,
+                        layer.speed,
+                        Tiled(BACKGROUND_WIDTH),
+                        SpriteSheetBundle {
+                            texture: flappy_sheet.clone(),
+                            atlas: TextureAtlas {
+                                layout: handle_texture_atlas.clone(),
+                                index: layer.atlas as usize,
+                            },
+                            transform: Transform::from_translation(Vec3::new(
+                                i as f32 * BACKGROUND_WIDTH,
+                                layer.height,
+                                -1. + depth as f32 * 0.1,
+                            )),
+                            ..default()
+                        },
+                    ));
+                }
+            }
+
+            // Side by side so there's always one under the whole screen, over
+            // the bottom of the pipes
+            for i in 0..2 {
+                parent.spawn((
+                    Ground,
+                    ScrollSpeed(1.),
+                    Tiled(GROUND_WIDTH),
+                    Collider(sheet.hitboxes.ground.aabb()),
                     SpriteSheetBundle {
                         texture: flappy_sheet.clone(),
                         atlas: TextureAtlas {
                             layout: handle_texture_atlas.clone(),
-                            index: Atlas::Background as usize,
+                            index: Atlas::Ground as usize,
                         },
-                        transform: Transform::from_translation(Vec3::new(0., 0., -1.)),
+                        transform: Transform::from_translation(Vec3::new(
+                            i as f32 * GROUND_WIDTH,
+                            GROUND_TOP - GROUND_HALF_HEIGHT,
+                            1.5,
+                        )),
                         ..default()
                     },
-                ))
-                .with_children(|parent| {
-                    parent.spawn((SpriteSheetBundle {
-                        texture: flappy_sheet.clone(),
-                        atlas: TextureAtlas {
-                            layout: handle_texture_atlas.clone(),
-                            index: Atlas::Background as usize,
-                        },
-                        transform: Transform::from_translation(Vec3::new(143., 0., 0.)),
-                        ..default()
-                    },));
-                });
-
-            for i in 0..4 {
-                let offset = random_pipe_height();
-                parent
-                    .spawn((
-                        Obstacle,
-                        SpatialBundle {
-                            transform: Transform::from_translation(Vec3::new(
-                                i as f32 * PIPE_TO_PIPE_SPACE + 144.,
-                                offset,
-                                1.,
-                            )),
-                            ..default()
-                        },
-                    ))
-                    .with_children(|parent| {
-                        parent.spawn((
-                            Pipe,
-                            Collider(Aabb2d::new(
-                                Vec2::new(0., 0.),
-                                Vec2::new(PIPE_WIDTH / 2., 80.),
-                            )),
-                            SpriteSheetBundle {
-                                texture: flappy_sheet.clone(),
-                                atlas: TextureAtlas {
-                                    layout: handle_texture_atlas.clone(),
-                                    index: Atlas::PipeTop as usize,
-                                },
-                                ..default()
-                            },
-                        ));
-                        parent.spawn((
-                            Pipe,
-                            Collider(Aabb2d::new(
-                                Vec2::new(0., 0.),
-                                Vec2::new(PIPE_WIDTH / 2., 80.),
-                            )),
-                            SpriteSheetBundle {
-                                texture: flappy_sheet.clone(),
-                                atlas: TextureAtlas {
-                                    layout: handle_texture_atlas.clone(),
-                                    index: Atlas::PipeBottom as usize,
-                                },
-                                transform: Transform::from_translation(Vec3::new(
-                                    0.,
-                                    -160. - PIPE_SPACE,
-                                    0.,
-                                )),
-                                ..default()
-                            },
-                        ));
-                    });
+                ));
+            }
+
+            for i in 0..difficulty.pipe_columns {
+                let offset = random_pipe_height(&mut rng);
+                let x = i as f32 * difficulty.pipe_to_pipe_space + FIRST_PIPE_X;
+                spawn_obstacle(
+                    parent,
+                    &sheet,
+                    Vec3::new(x, offset, 1.),
+                    difficulty.pipe_space,
+                );
             }
         });
+    commands.insert_resource(sheet);
+    let mut scenery = ChaCha8Rng::seed_from_u64(rng.seed ^ SCENERY_SEED);
+    commands.insert_resource(TimeOfDay::roll(&mut scenery));
+    commands.insert_resource(WorldTime::default());
+    commands.insert_resource(Weather::roll(*weather, &mut scenery));
 }
 
-fn input(
-    mut query: Query<&mut Velocity, With<Player>>,
-    buttons: Res<ButtonInput<MouseButton>>,
+/// Spawns a regular obstacle with its top pipe at `translation` and a
+/// `pipe_space` tall gap under it
+fn spawn_obstacle<'a>(
+    parent: &'a mut ChildBuilder,
+    sheet: &SpriteSheet,
+    translation: Vec3,
+    pipe_space: f32,
+) -> EntityCommands<'a> {
+    let pipe = |pipe: Pipe, index: Atlas, y: f32| {
+        (
+            pipe,
+            Collider(sheet.hitboxes.pipe.aabb()),
+            SpriteSheetBundle {
+                texture: sheet.texture.clone(),
+                atlas: TextureAtlas {
+                    layout: sheet.layout.clone(),
+                    index: index as usize,
+                },
+                transform: Transform::from_translation(Vec3::new(0., y, 0.)),
+                ..default()
+            },
+        )
+    };
+
+    let mut obstacle = parent.spawn((
+        Obstacle,
+        Pattern::Regular,
+        Repaint,
+        SpatialBundle {
+            transform: Transform::from_translation(translation),
+            ..default()
+        },
+    ));
+    obstacle.with_children(|parent| {
+        parent.spawn(pipe(Pipe::Top, Atlas::PipeTop, 0.));
+        parent.spawn(pipe(Pipe::Bottom, Atlas::PipeBottom, -160. - pipe_space));
+    });
+    obstacle
+}
+
+// The simulation is behind the frame by however much time is left over for
+// the next step, so that's where the click lands within it. Otherwise a flap
+// would always happen at the start of a step and faster screens would get to
+// pick their moment more finely
+fn input(mut queued: ResMut<QueuedFlap>, mut actions: ResMut<Actions>, fixed: Res<Time<Fixed>>) {
+    if actions.take(Action::Flap) {
+        queued.0 = Some(fixed.overstep_fraction().min(1.));
+    }
+}
+
+fn flap(
+    mut query: Query<(&mut Transform, &mut Velocity, &BirdState), With<Player>>,
+    mut queued: ResMut<QueuedFlap>,
+    mut offset: ResMut<StepOffset>,
     mut writer: EventWriter<OnJumped>,
+    physics: Res<ActivePhysics>,
+    time: Res<Time>,
 ) {
-    let mut velocity = query.single_mut();
-    if buttons.just_pressed(MouseButton::Left) {
-        velocity.0 = JUMP_VELOCITY;
-        writer.send(OnJumped);
+    let Some(fraction) = queued.0.take() else {
+        return;
+    };
+
+    for (mut transform, mut velocity, state) in &mut query {
+        if *state != BirdState::Flying {
+            continue;
+        }
+
+        // Fall for the part of the step before the flap, gravity takes care
+        // of the rest of it
+        fall(
+            &mut transform,
+            &mut velocity,
+            &physics.0,
+            time.delta_seconds() * fraction,
+        );
+        offset.0 = fraction;
+        velocity.0 = physics.0.jump;
+        writer.send(OnJumped {
+            position: transform.translation.xy(),
+        });
     }
 }
 
+// Moves by the average of the speed before and after, which is exact for
+// steady gravity, so the bird follows the same arc however long the steps are.
+// Hitting terminal speed partway through splits the step in two
+fn fall(transform: &mut Transform, velocity: &mut Velocity, physics: &PhysicsPreset, delta: f32) {
+    let before = velocity.0;
+    velocity.0 += physics.gravity * delta;
+    if velocity.0 >= physics.terminal {
+        transform.translation.y += (before + velocity.0) / 2. * delta;
+        return;
+    }
+
+    velocity.0 = physics.terminal;
+    let speeding_up = if physics.gravity < 0. {
+        ((physics.terminal - before) / physics.gravity).clamp(0., delta)
+    } else {
+        0.
+    };
+    transform.translation.y +=
+        (before + physics.terminal) / 2. * speeding_up + physics.terminal * (delta - speeding_up);
+}
+
+fn advance_tick(mut tick: ResMut<SimTick>) {
+    tick.0 += 1;
+}
+
+fn reset_tick(mut tick: ResMut<SimTick>) {
+    tick.0 = 0;
+}
+
 fn apply_gravity(
-    mut query: Query<(&mut Transform, &mut Velocity), With<Player>>,
-    gravity: Res<Gravity>,
+    mut query: Query<(&mut Transform, &mut Velocity, &BirdState), With<Player>>,
+    mut offset: ResMut<StepOffset>,
+    physics: Res<ActivePhysics>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_seconds() * (1. - std::mem::take(&mut offset.0));
+    for (mut transform, mut velocity, state) in &mut query {
+        if state.falls() {
+            fall(&mut transform, &mut velocity, &physics.0, delta);
+        }
+    }
+}
+
+/// Whether the world is moving along, rather than held still for a menu, the
+/// kill cam or a restart
+fn world_running(state: Res<State<AppState>>) -> bool {
+    matches!(
+        state.get(),
+        AppState::Playing | AppState::Dying | AppState::GameOver
+    )
+}
+
+// Everything about the run starts from the same place no matter where the
+// bird was hovering
+fn take_off(mut query: Query<(&mut Transform, &mut Velocity, &mut BirdState), With<Player>>) {
+    for (mut transform, mut velocity, mut state) in &mut query {
+        transform.translation.y = 0.;
+        transform.rotation = Quat::IDENTITY;
+        velocity.0 = 0.;
+        *state = BirdState::Flying;
+    }
+}
+
+fn hover(
+    mut query: Query<(&mut Transform, &BirdState), With<Player>>,
+    config: Res<ActiveConfig>,
     time: Res<Time>,
 ) {
-    for (mut transform, mut velocity) in &mut query {
-        velocity.0 += gravity.0 * time.delta_seconds();
-        velocity.0 = velocity.0.max(TERMINAL_VELOCITY);
+    let GameConfig {
+        hover_height,
+        hover_rate,
+        ..
+    } = config.0;
+    let y = hover_height * (time.elapsed_seconds() * hover_rate * TAU).sin();
+    for (mut transform, state) in &mut query {
+        if *state == BirdState::Idle {
+            transform.translation.y = y;
+        }
+    }
+}
+
+fn land(
+    mut query: Query<(&Transform, &mut Velocity, &mut BirdState), With<Player>>,
+    mut writer: EventWriter<OnDied>,
+) {
+    for (transform, mut velocity, mut state) in &mut query {
+        if hit_floor(transform, &mut velocity, &mut state) {
+            writer.send(OnDied {
+                position: transform.translation.xy(),
+            });
+        }
+    }
+}
 
-        transform.translation.y += velocity.0 * time.delta_seconds();
+/// Stops a bird that's crashed once it's fallen to the floor, the player or a
+/// bot. Whether it's just landed
+fn hit_floor(transform: &Transform, velocity: &mut Velocity, state: &mut BirdState) -> bool {
+    let landed = *state == BirdState::Dying && transform.translation.y < FLOOR;
+    if landed {
+        velocity.0 = 0.;
+        *state = BirdState::Dead;
     }
+    landed
 }
 
-fn apply_rotation(mut query: Query<(&mut Transform, &Velocity), With<Player>>) {
-    let (mut transform, velocity) = query.single_mut();
+/// Starts a bird falling after it's crashed, the player or a bot. It's
+/// stopped dead by the hit rather than thrown up by it
+fn start_dying(velocity: &mut Velocity, state: &mut BirdState) {
+    velocity.0 = 0.;
+    *state = BirdState::Dying;
+}
 
-    // Make the player point towards the direction it's moving (up/down)
-    let range = JUMP_VELOCITY - TERMINAL_VELOCITY;
-    let normalized_velocity = (velocity.0 - TERMINAL_VELOCITY) / range;
-    let rotation = (-90. + (normalized_velocity) * 180.0).clamp(-30., 90.);
+// The run's only over once every bird that crashed has hit the ground
+fn finish_dying(query: Query<&BirdState, With<Player>>, mut state: ResMut<NextState<AppState>>) {
+    if !query.iter().any(|bird| *bird == BirdState::Dying) {
+        state.set(AppState::GameOver);
+    }
+}
+
+fn apply_rotation(
+    mut query: Query<(&mut Transform, &Velocity, &BirdState), With<Player>>,
+    physics: Res<ActivePhysics>,
+) {
+    for (mut transform, velocity, state) in &mut query {
+        // A dying bird keeps pointing the way it falls, straight down in the end
+        if state.falls() {
+            tilt(&mut transform, velocity, &physics.0);
+        }
+    }
+}
+
+/// Turns a bird a bit further towards the direction it's moving (up/down)
+fn tilt(transform: &mut Transform, velocity: &Velocity, physics: &PhysicsPreset) {
+    let range = physics.jump - physics.terminal;
+    let normalized_velocity = (velocity.0 - physics.terminal) / range;
+    let rotation = physics.rotation.angle(normalized_velocity);
 
     transform.rotation = transform.rotation.lerp(
         Quat::from_euler(EulerRot::YXZ, 0., 0., rotation.to_radians()),
-        0.5,
+        physics.rotation.follow,
     );
 }
 
 fn trigger_jump_animation(
-    mut query: Query<&mut Animation, With<Player>>,
+    mut query: Query<(&mut Animation, &BirdState), With<Player>>,
     mut reader: EventReader<OnJumped>,
 ) {
-    let mut animation = query.single_mut();
-    for _ in reader.read() {
-        animation.frame = 0
+    if reader.read().count() == 0 {
+        return;
+    }
+
+    for (mut animation, state) in &mut query {
+        if *state == BirdState::Flying {
+            animation.frame = 0;
+        }
     }
 }
 
@@ -305,148 +1331,530 @@ fn update_animation(
     mut query: Query<(&mut TextureAtlas, &mut Animation), With<Player>>,
     time: Res<Time>,
 ) {
-    let mut delta = time.delta_seconds();
-
     for (mut texture_atlas, mut animation) in &mut query {
-        loop {
-            let frame = &animation.frames[animation.frame];
+        animation.advance(time.delta_seconds());
+        texture_atlas.index = animation.frames[animation.frame].index;
+    }
+}
 
-            let remaining = (1. - animation.t) * frame.duration;
+// Eh, this should've been a material on a sprite
+// but it's not implemented yet
+fn scroll_layers(
+    mut query: Query<(&mut Transform, &ScrollSpeed, &Tiled)>,
+    difficulty: Res<Difficulty>,
+    ease: Res<ScrollEase>,
+    time: Res<Time>,
+) {
+    let speed = time.delta_seconds() * ease.speed(&difficulty);
+    for (mut transform, ScrollSpeed(part), Tiled(width)) in &mut query {
+        transform.translation.x += speed * part;
+        if transform.translation.x < -width {
+            transform.translation.x += width * 2.;
+        }
+    }
+}
 
-            if delta < remaining {
-                animation.t += delta / frame.duration;
-                break;
-            }
+fn pass_world_time(mut world_time: ResMut<WorldTime>, time: Res<Time>) {
+    world_time.0 += time.delta_seconds();
+}
 
-            delta -= remaining;
+fn paint_sky(
+    time_of_day: Res<TimeOfDay>,
+    world_time: Res<WorldTime>,
+    mut query: Query<(&mut Sprite, Ref<Transform>), Or<(With<Background>, With<Ground>)>>,
+) {
+    let repaint = time_of_day.is_changed() || world_time.is_changed();
+    for (mut sprite, transform) in &mut query {
+        if repaint || transform.is_added() {
+            sprite.color = time_of_day.sky(&world_time);
+        }
+    }
+}
 
-            let finished = animation.frame + 1 >= animation.frames.len();
+fn scroll_pipes(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform, &mut Pattern, &Children), With<Obstacle>>,
+    mut pipes: Query<(&Pipe, &mut Transform), Without<Obstacle>>,
+    root: Query<Entity, With<Root>>,
+    difficulty: Res<Difficulty>,
+    ease: Res<ScrollEase>,
+    mut rng: ResMut<GameRng>,
+    mut breather: ResMut<Breather>,
+    config: Res<ActiveConfig>,
+    sheet: Res<SpriteSheet>,
+    time: Res<Time>,
+) {
+    let spacing = difficulty.pipe_to_pipe_space;
+    // A column comes back around once it's as far behind where the world
+    // started as all of the columns are long
+    let recycle_x = FIRST_PIPE_X - query.iter().len() as f32 * spacing;
+    for (_, mut transform, ..) in &mut query {
+        transform.translation.x += time.delta_seconds() * ease.speed(&difficulty);
+    }
 
-            match (finished, animation.repeat) {
-                (true, true) => {
-                    animation.frame = 0;
-                    animation.t = 0.;
-                }
-                (true, false) => {
-                    animation.frame = animation.frames.len() - 1;
-                    animation.t = 1.;
-                    break;
-                }
-                _ => {
-                    animation.frame += 1;
-                    animation.t = 0.;
-                }
+    let mut last_x = query
+        .iter()
+        .map(|(_, transform, ..)| transform.translation.x)
+        .fold(f32::MIN, f32::max);
+    for (entity, mut transform, mut pattern, children) in &mut query {
+        if transform.translation.x < recycle_x {
+            let offset = random_pipe_height(&mut rng);
+            // Lined up behind the last column so the spacing can change mid-run
+            last_x += spacing;
+            transform.translation.x = last_x;
+            transform.translation.y = offset;
+            *pattern = random_pattern(&difficulty, &config.0, &mut rng);
+            let mut pipe_space = difficulty.pipe_space;
+            // Rolled all the same so the columns after it come out as they
+            // would've otherwise
+            if let Some((height, space)) = breather.gap(pipe_space) {
+                transform.translation.y = height;
+                *pattern = Pattern::Regular;
+                pipe_space = space;
             }
-        }
+            pose_pipes(children, &mut pipes, pipe_space, 0.);
+            let mut entity = commands.entity(entity);
+            set_behaviors(&mut entity, &pattern.behaviors(&config.0));
+            entity.remove::<(Passed, Side, PipeScore)>().insert(Repaint);
 
-        texture_atlas.index = animation.frames[animation.frame].index;
+            // Halfway to the next pipe so it's clear of both
+            let x = transform.translation.x + spacing / 2.;
+            spawn_hazard(&mut commands, root.single(), &sheet.hitboxes, x, &mut rng);
+        }
     }
 }
 
-// Eh, this should've been a material on a sprite
-// but it's not implemented yet
-fn scroll_backgrounds(mut query: Query<&mut Transform, With<Background>>, time: Res<Time>) {
-    for mut transform in &mut query {
-        transform.translation.x += time.delta_seconds() * SCROLL_SPEED;
-        if transform.translation.x < -143. {
-            transform.translation.x += 143.;
+fn pass_pipes(
+    mut commands: Commands,
+    mut writer: EventWriter<PipePassed>,
+    mut combo: ResMut<Combo>,
+    roulette: Res<Roulette>,
+    registry: Res<ModeRegistry>,
+    modifiers: Res<RunModifiers>,
+    player: Query<&Transform, With<Player>>,
+    obstacles: Query<
+        (
+            Entity,
+            &Transform,
+            Option<&Side>,
+            Has<Passed>,
+            Option<&PipeScore>,
+        ),
+        With<Obstacle>,
+    >,
+) {
+    let points = registry.current(&modifiers).rules.points_per_pipe();
+    let points = match roulette.active() {
+        Some(Modifier::DoubleScore) => points * 2,
+        _ => points,
+    };
+
+    let player = player.single();
+    for (entity, transform, side, passed, pipe) in &obstacles {
+        // Checked from both sides since the player can be sent back past a
+        // pipe as well as forward, and in one step as far as they like
+        let now = if transform.translation.x < player.translation.x {
+            Side::Behind
+        } else {
+            Side::Ahead
+        };
+        if side == Some(&now) {
+            continue;
+        }
+        commands.entity(entity).insert(now);
+
+        // Going back past it never scores, and neither does going forward
+        // past it again
+        if side == Some(&Side::Ahead) && !passed {
+            commands.entity(entity).insert(Passed);
+            writer.send(PipePassed {
+                obstacle: entity,
+                position: player.translation.xy(),
+                points: combo.score(points, pipe),
+            });
         }
     }
 }
 
-fn scroll_pipes(mut query: Query<&mut Transform, With<Obstacle>>, time: Res<Time>) {
-    let scroll_back = PIPE_TO_PIPE_SPACE * 4.;
-    for mut transform in &mut query {
-        transform.translation.x += time.delta_seconds() * SCROLL_SPEED;
-        if transform.translation.x < -144. * 2. {
-            let offset = random_pipe_height();
-            transform.translation.x += scroll_back;
-            transform.translation.y = offset;
-        }
+fn score_pipes(mut score: ResMut<Score>, mut reader: EventReader<PipePassed>) {
+    for passed in reader.read() {
+        score.0 += passed.points;
     }
 }
 
 fn crash_and_die(
-    mut query: Query<(&Transform, &Collider, &mut Velocity), With<Player>>,
-    pipes: Query<(&GlobalTransform, &Collider), With<Pipe>>,
+    mut query: Query<(&mut Transform, &Collider, &mut Velocity, &mut BirdState), With<Player>>,
+    pipes: Query<(&Parent, &Transform, &Collider), (With<Pipe>, Without<Player>)>,
+    obstacles: Query<(&Transform, &Visibility), (With<Obstacle>, Without<Player>)>,
+    hazards: Query<(&Transform, &Collider), (With<Hazard>, Without<Player>)>,
+    grounds: Query<(&Transform, &Collider), (With<Ground>, Without<Player>)>,
+    modifiers: Res<RunModifiers>,
+    config: Res<ActiveConfig>,
+    registry: Res<ModeRegistry>,
     mut state: ResMut<NextState<AppState>>,
+    mut writer: EventWriter<OnCrashed>,
+    mut bonked: EventWriter<OnBonked>,
 ) {
-    let (transform, Collider(player_collider), mut velocity) = query.single_mut();
+    let crashes_end_run = registry.current(&modifiers).rules.crashes_end_run();
+    let mut crashed = false;
+    let mut flying = 0;
 
-    let player = offset_aabb(player_collider, &transform.translation);
+    for (mut transform, Collider(player_collider), mut velocity, mut bird) in &mut query {
+        if *bird != BirdState::Flying {
+            continue;
+        }
 
-    if transform.translation.y < -128. || transform.translation.y > 128. {
-        state.set(AppState::GameOver);
-        velocity.0 = JUMP_VELOCITY * 2.;
-        return;
-    }
+        if transform.translation.y > 128. && modifiers.ceiling == CeilingBehavior::Bonk {
+            transform.translation.y = 128.;
+            velocity.0 = -config.0.bonk_knockdown;
+            bonked.send(OnBonked {
+                position: transform.translation.xy(),
+            });
+        }
+
+        let player = offset_aabb(player_collider, &transform.translation);
+
+        let hit_pipe = || {
+            pipes
+                .iter()
+                .find_map(|(parent, t, Collider(pipe_collider))| {
+                    let (obstacle, visibility) = obstacles.get(parent.get()).ok()?;
+
+                    // Pipes that are hidden away aren't part of the run right now
+                    if visibility == Visibility::Hidden {
+                        return None;
+                    }
+
+                    // Going by the local transforms since the global ones are only
+                    // up to date once per frame, not once per step
+                    let pipe = pipe_aabb(pipe_collider, obstacle, t);
+                    touch_pipe(&player, pipe, t.rotation)
+                })
+        };
+
+        let hit_hazard = || {
+            hazards.iter().find_map(|(t, Collider(hazard_collider))| {
+                let hazard = offset_aabb(hazard_collider, &t.translation);
+                hazard.intersects(&player).then(|| OnCrashed {
+                    position: player.center(),
+                    contact: hazard.closest_point(player.center()),
+                    collider: Some(hazard),
+                })
+            })
+        };
 
-    for (t, Collider(pipe_collider)) in &pipes {
-        let pipe = offset_aabb(pipe_collider, &t.translation());
-        if pipe.intersects(&player) {
-            state.set(AppState::GameOver);
-            velocity.0 = JUMP_VELOCITY * 2.;
-            return;
+        // The ground ends the run whatever the mode, like going off the top
+        let hit_ground = || {
+            touch_ground(&player, &grounds).map(|ground| OnCrashed {
+                position: player.center(),
+                contact: ground.closest_point(player.center()),
+                collider: Some(ground),
+            })
+        };
+
+        let crash = if transform.translation.y > 128. {
+            Some(OnCrashed {
+                position: player.center(),
+                contact: player.center(),
+                collider: None,
+            })
+        } else if crashes_end_run {
+            hit_ground().or_else(hit_pipe).or_else(hit_hazard)
+        } else {
+            hit_ground()
+        };
+
+        match crash {
+            Some(crash) => {
+                start_dying(&mut velocity, &mut bird);
+                writer.send(crash);
+                crashed = true;
+            }
+            None => flying += 1,
         }
     }
+
+    // The run goes on for as long as there's a bird still in the air
+    if crashed && flying == 0 {
+        state.set(AppState::KillCam);
+    }
+}
+
+/// Where `bird` hits `pipe` when the pipe's turned by `rotation`, if it does
+fn touch_pipe(bird: &Aabb2d, pipe: Aabb2d, rotation: Quat) -> Option<OnCrashed> {
+    // A tilted pipe is checked upright, with the bird turned the other way
+    // around it
+    let turned = rotation.inverse() * (bird.center() - pipe.center()).extend(0.);
+    let turned = Aabb2d::new(pipe.center() + turned.xy(), bird.half_size());
+    pipe.intersects(&turned).then(|| {
+        let contact = pipe.closest_point(turned.center()) - pipe.center();
+        OnCrashed {
+            position: bird.center(),
+            contact: pipe.center() + (rotation * contact.extend(0.)).xy(),
+            collider: Some(pipe),
+        }
+    })
+}
+
+/// The tile of ground `bird` is touching, if it is
+fn touch_ground<'a>(
+    bird: &Aabb2d,
+    grounds: impl IntoIterator<Item = (&'a Transform, &'a Collider)>,
+) -> Option<Aabb2d> {
+    grounds
+        .into_iter()
+        .map(|(t, Collider(ground))| offset_aabb(ground, &t.translation))
+        .find(|ground| ground.intersects(bird))
 }
 
 fn offset_aabb(aabb: &Aabb2d, translation: &Vec3) -> Aabb2d {
-    let offset = translation.xy();
-    Aabb2d::new(offset, aabb.half_size())
+    Aabb2d::new(aabb.center() + translation.xy(), aabb.half_size())
 }
 
-fn start_game(
-    mut state: ResMut<NextState<AppState>>,
-    mut query: Query<&mut Velocity, With<Player>>,
-    buttons: Res<ButtonInput<MouseButton>>,
-    mut writer: EventWriter<OnJumped>,
-) {
-    let mut velocity = query.single_mut();
-    if buttons.just_pressed(MouseButton::Left) {
-        state.set(AppState::Playing);
-        velocity.0 = JUMP_VELOCITY;
-        writer.send(OnJumped);
-    }
+/// Where a pipe's hitbox is, going by the local transforms of its obstacle and
+/// itself. Its offset turns along with the pipe so it stays put on the sprite
+fn pipe_aabb(aabb: &Aabb2d, obstacle: &Transform, pipe: &Transform) -> Aabb2d {
+    let offset = pipe.rotation * aabb.center().extend(0.);
+    let turned = Aabb2d::new(offset.xy(), aabb.half_size());
+    offset_aabb(&turned, &(obstacle.translation + pipe.translation))
+}
+
+fn build_world(world: &mut World) {
+    world.run_schedule(BuildWorld);
 }
 
-fn restart_game(mut state: ResMut<NextState<AppState>>, buttons: Res<ButtonInput<MouseButton>>) {
-    if buttons.just_pressed(MouseButton::Left) {
+fn restart_game(mut state: ResMut<NextState<AppState>>, mut actions: ResMut<Actions>) {
+    if actions.take(Action::Flap) {
         state.set(AppState::MainMenu);
     }
 }
 
 fn main() {
     App::new()
-        .add_plugins(
+        .add_plugins(determinism::shadow(simulate::headless(
             DefaultPlugins
                 .set(AssetPlugin {
                     mode: AssetMode::Processed,
                     ..default()
                 })
-                .set(ImagePlugin::default_nearest()),
-        )
+                .set(ImagePlugin::default_nearest())
+                .set(logging::log_plugin())
+                .build(),
+        )))
+        // Before everything that reads the settings it loads
+        .add_plugins(SettingsPlugin)
+        .add_plugins((
+            ProfilePlugin,
+            CurvePlugin,
+            DifficultyPlugin,
+            TelegraphPlugin,
+            KillCamPlugin,
+            BonusPlugin,
+            CeilingPlugin,
+            ReplayPlugin,
+            RetentionPlugin,
+            LibraryPlugin,
+            BookmarksPlugin,
+            RoulettePlugin,
+            HazardsPlugin,
+            DecorationsPlugin,
+            FeedbackPlugin,
+        ))
+        .add_plugins((
+            SnapshotPlugin,
+            ConsolePlugin,
+            SuspendPlugin,
+            LeaderboardPlugin,
+            CharactersPlugin,
+            AwardsPlugin,
+            ScrollPlugin,
+            EffectsPlugin,
+            DeterminismPlugin,
+            DebugOverlayPlugin,
+            LoggingPlugin,
+            ModesPlugin,
+            DailyPlugin,
+            ZenPlugin,
+            TimeTrialPlugin,
+        ))
+        .add_plugins((
+            BehaviorsPlugin,
+            LevelsPlugin,
+            CampaignPlugin,
+            CustomLevelsPlugin,
+            PortalsPlugin,
+            KillPlanePlugin,
+            AccessoriesPlugin,
+            RestartPlugin,
+            SimulatePlugin,
+            GhostPlugin,
+            PracticePlugin,
+            HighScorePlugin,
+            PhysicsPlugin,
+            RankedPlugin,
+            PausePlugin,
+        ))
+        .add_plugins((
+            HudPlugin,
+            MixerPlugin,
+            MusicPlugin,
+            ChallengePlugin,
+            AudioSettingsPlugin,
+            PromptsPlugin,
+            ScoringPlugin,
+            DisplayPlugin,
+            TouchPlugin,
+            RandomizerPlugin,
+            ActionsPlugin,
+            AssistPlugin,
+            ControlsPlugin,
+            #[cfg(feature = "events")]
+            events::EventsPlugin,
+            #[cfg(feature = "spectate")]
+            spectate::SpectatePlugin,
+        ))
+        .add_plugins((
+            DecalsPlugin,
+            GetReadyPlugin,
+            IntroPlugin,
+            ScorePanelPlugin,
+            LowPowerPlugin,
+            BotsPlugin,
+            WearPlugin,
+            MainMenuPlugin,
+            ScreenshotPlugin,
+            ShowcasePlugin,
+            FlashPlugin,
+            CameraShakePlugin,
+            CalibrationPlugin,
+            TutorialsPlugin,
+            SquashPlugin,
+        ))
+        .add_plugins((
+            ParticlesPlugin,
+            AfkPlugin,
+            PuffsPlugin,
+            PopupsPlugin,
+            BreatherPlugin,
+            CareerPlugin,
+            WeatherPlugin,
+            GustsPlugin,
+            ConfigPlugin,
+            HitboxesPlugin,
+        ))
         .insert_state(AppState::MainMenu)
+        .insert_resource(RunModifiers::from_args())
+        .insert_resource(Time::<Fixed>::from_hz(SIM_HZ))
+        .insert_resource(GameRng::new(0))
+        .init_resource::<NextSeed>()
+        .insert_resource(PinnedSeed::from_args())
+        .init_resource::<Score>()
+        .init_resource::<QueuedFlap>()
+        .init_resource::<StepOffset>()
+        .init_resource::<SimTick>()
         .add_event::<OnJumped>()
+        .add_event::<OnCrashed>()
+        .add_event::<OnDied>()
+        .add_event::<PipePassed>()
+        .configure_sets(
+            FixedUpdate,
+            (
+                SimSet::Input,
+                SimSet::Physics,
+                SimSet::Collision,
+                SimSet::Rules,
+                SimSet::Transition,
+            )
+                .chain(),
+        )
         .add_systems(Startup, startup)
-        .add_systems(OnEnter(AppState::MainMenu), create_world)
-        .add_systems(Update, start_game.run_if(in_state(AppState::MainMenu)))
-        .add_systems(Update, restart_game.run_if(in_state(AppState::GameOver)))
+        .add_systems(BuildWorld, create_world)
+        .add_systems(OnEnter(AppState::MainMenu), build_world)
+        .add_systems(OnEnter(AppState::Playing), (reset_tick, take_off))
+        .add_systems(
+            Update,
+            // Typing a bookmark name takes the keys for itself
+            restart_game.run_if(
+                in_state(AppState::GameOver)
+                    .or_else(in_state(AppState::LevelComplete))
+                    .and_then(not(resource_exists::<Playback>))
+                    .and_then(not(resource_exists::<Draft>)),
+            ),
+        )
+        .add_systems(
+            Update,
+            (update_animation, trigger_jump_animation, apply_rotation).run_if(world_running),
+        )
+        .add_systems(Update, hover)
         .add_systems(
             Update,
-            (apply_gravity, update_animation).run_if(not(in_state(AppState::MainMenu))),
+            // Spaces typed into the console aren't flaps, and neither is
+            // anything pressed while the run is held still for the display
+            // or by the player
+            input.run_if(
+                in_state(AppState::Playing)
+                    .and_then(in_state(PauseState::Running))
+                    .and_then(not(resource_exists::<Playback>))
+                    .and_then(not(resource_exists::<Console>))
+                    .and_then(not(resource_exists::<DisplayPause>)),
+            ),
         )
+        .add_systems(Update, scroll_layers.run_if(is_scrolling))
         .add_systems(
             Update,
             (
-                input,
-                trigger_jump_animation,
-                scroll_backgrounds,
-                scroll_pipes,
-                crash_and_die,
-                apply_rotation,
+                pass_world_time.run_if(in_state(AppState::Playing)),
+                paint_sky,
+            )
+                .chain()
+                .run_if(resource_exists::<TimeOfDay>.and_then(resource_exists::<WorldTime>)),
+        )
+        .add_systems(
+            FixedUpdate,
+            // Each of these only acts on the birds in the state it cares about
+            (
+                flap.in_set(SimSet::Input),
+                (apply_gravity, land).chain().in_set(SimSet::Physics),
+                crash_and_die.in_set(SimSet::Collision),
+            )
+                .run_if(world_running),
+        )
+        .add_systems(
+            FixedUpdate,
+            finish_dying
+                .in_set(SimSet::Rules)
+                .run_if(in_state(AppState::Dying)),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                (pass_pipes, score_pipes)
+                    .chain()
+                    .in_set(SimSet::Collision)
+                    .after(crash_and_die)
+                    .run_if(in_state(PlayPhase::Normal)),
+                advance_tick.in_set(SimSet::Transition),
             )
                 .run_if(in_state(AppState::Playing)),
         )
+        .add_systems(
+            FixedUpdate,
+            // Keeps going after a crash until the world has come to a stop
+            scroll_pipes
+                .in_set(SimSet::Physics)
+                .run_if(in_state(PlayPhase::Normal).and_then(is_scrolling)),
+        )
+        .add_systems(
+            FixedUpdate,
+            // Changes of state have to happen in between steps so a run plays out
+            // the same no matter how many steps a frame happens to fit
+            (
+                apply_state_transition::<AppState>,
+                apply_state_transition::<PlayPhase>,
+            )
+                .chain()
+                .in_set(SimSet::Transition)
+                .after(advance_tick),
+        )
         .run();
 }
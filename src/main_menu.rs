@@ -0,0 +1,239 @@
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    actions::{Action, Actions},
+    build_world,
+    mixer::{Channel, PlaySound, CONFIRM_SOUND},
+    replay::Playback,
+    AppState, Atlas, SpriteSheet,
+};
+
+// Where the logo sits, above the bird and over the rest of the world
+const LOGO_POSITION: Vec3 = Vec3::new(0., 72., 20.);
+
+/// A line on the main menu
+#[derive(Clone, Copy, PartialEq)]
+enum Entry {
+    Play,
+    Settings,
+    Quit,
+}
+
+const ENTRIES: [Entry; 3] = [Entry::Play, Entry::Settings, Entry::Quit];
+
+#[derive(Resource, Default)]
+struct MainMenu {
+    selected: usize,
+}
+
+#[derive(Component)]
+struct Logo;
+
+#[derive(Component)]
+struct MenuEntries;
+
+/// A toggle's label in the column down the left of the main menu, in the
+/// order they're stacked in
+#[derive(Component, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MenuRow {
+    Bird,
+    Physics,
+    Assist,
+    Breather,
+    Opponent,
+    Preset,
+    Adaptive,
+}
+
+/// Holds the toggles' labels so each one gets a line of its own
+#[derive(Component)]
+struct MenuRows;
+
+pub struct MainMenuPlugin;
+
+impl Plugin for MainMenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MainMenu>()
+            .add_systems(Startup, spawn_rows)
+            .add_systems(Update, stack_rows)
+            .add_systems(
+                OnEnter(AppState::MainMenu),
+                // After the world's laid out, so there's a sheet to draw the
+                // logo from
+                (reset_selection, spawn_logo.after(build_world))
+                    .run_if(not(resource_exists::<Playback>)),
+            )
+            .add_systems(
+                Update,
+                (choose, draw_entries.run_if(resource_changed::<MainMenu>))
+                    .chain()
+                    .run_if(
+                        in_state(AppState::MainMenu).and_then(not(resource_exists::<Playback>)),
+                    ),
+            )
+            .add_systems(
+                OnExit(AppState::MainMenu),
+                (despawn::<Logo>, despawn::<MenuEntries>),
+            );
+    }
+}
+
+fn reset_selection(mut menu: ResMut<MainMenu>) {
+    menu.selected = 0;
+}
+
+fn spawn_rows(mut commands: Commands) {
+    commands.spawn((
+        MenuRows,
+        NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(8.),
+                left: Val::Px(8.),
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            ..default()
+        },
+    ));
+}
+
+// Each toggle's plugin spawns its own label in whatever order they run in, so
+// they're put back in order whenever a new one shows up
+fn stack_rows(
+    mut commands: Commands,
+    column: Query<Entity, With<MenuRows>>,
+    rows: Query<(Entity, &MenuRow)>,
+    added: Query<(), Added<MenuRow>>,
+) {
+    if added.is_empty() {
+        return;
+    }
+    let Ok(column) = column.get_single() else {
+        return;
+    };
+
+    let mut rows = rows.iter().collect::<Vec<_>>();
+    rows.sort_by_key(|(_, row)| **row);
+    let rows = rows
+        .into_iter()
+        .map(|(entity, _)| entity)
+        .collect::<Vec<_>>();
+    commands.entity(column).push_children(&rows);
+}
+
+fn spawn_logo(mut commands: Commands, sheet: Res<SpriteSheet>) {
+    commands.spawn((
+        Logo,
+        SpriteSheetBundle {
+            texture: sheet.texture.clone(),
+            atlas: TextureAtlas {
+                layout: sheet.layout.clone(),
+                index: Atlas::Title as usize,
+            },
+            transform: Transform::from_translation(LOGO_POSITION),
+            ..default()
+        },
+    ));
+}
+
+// Anything that flaps picks what's selected, which is Play unless the player
+// has moved off it, so a click or a tap still starts a run. Up flaps too, but
+// on the menu it only ever moves the selection
+fn choose(
+    mut menu: ResMut<MainMenu>,
+    mut actions: ResMut<Actions>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepad: Res<ButtonInput<GamepadButton>>,
+    mut state: ResMut<NextState<AppState>>,
+    mut exit: EventWriter<AppExit>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    let pad = |button: GamepadButtonType| {
+        gamepad
+            .get_just_pressed()
+            .any(|pressed| pressed.button_type == button)
+    };
+    let up = keys.just_pressed(KeyCode::ArrowUp) || pad(GamepadButtonType::DPadUp);
+    let down = keys.just_pressed(KeyCode::ArrowDown) || pad(GamepadButtonType::DPadDown);
+    let flap = actions.take(Action::Flap);
+
+    if up {
+        menu.selected = menu.selected.saturating_sub(1);
+    }
+    if down {
+        menu.selected = (menu.selected + 1).min(ENTRIES.len() - 1);
+    }
+    if !(keys.just_pressed(KeyCode::Enter) || (flap && !up)) {
+        return;
+    }
+    sounds.send(PlaySound::new(CONFIRM_SOUND, Channel::Ui));
+    match ENTRIES[menu.selected] {
+        Entry::Play => state.set(AppState::GetReady),
+        Entry::Settings => state.set(AppState::Settings),
+        Entry::Quit => {
+            exit.send(AppExit);
+        }
+    }
+}
+
+fn draw_entries(
+    mut commands: Commands,
+    menu: Res<MainMenu>,
+    query: Query<Entity, With<MenuEntries>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let text = |value: &str, size: f32, color: Color| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size: size,
+                color,
+                ..default()
+            },
+        )
+    };
+
+    commands
+        .spawn((
+            MenuEntries,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(96.),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(8.),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            for (i, entry) in ENTRIES.iter().enumerate() {
+                let label = match entry {
+                    Entry::Play => "Play",
+                    Entry::Settings => "Settings",
+                    Entry::Quit => "Quit",
+                };
+                let color = if i == menu.selected {
+                    Color::YELLOW
+                } else {
+                    Color::WHITE
+                };
+                parent.spawn(text(label, 16., color));
+            }
+
+            parent.spawn(text("Up/Down pick, Enter choose", 12., Color::GRAY));
+        });
+}
+
+fn despawn<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
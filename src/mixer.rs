@@ -0,0 +1,230 @@
+use bevy::{audio::Volume, prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    audio_settings::AudioSettings, effects::Easing, low_power::LowPower, music::MusicSettings,
+    ron_asset::RonLoader,
+};
+
+const MIX_FILE: &str = "game.audio.ron";
+/// Played on the Ui channel whenever something's picked on a menu
+pub const CONFIRM_SOUND: &str = "sounds/confirm.wav";
+
+/// What a sound is for, which decides what it's mixed with
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Channel {
+    Music,
+    Effects,
+    /// Menus and confirmations, anything that isn't in the world
+    Ui,
+}
+
+/// How the music dips under another sound and comes back up
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct Duck {
+    /// How loud the music is at the bottom of the dip, from 0 to 1
+    pub depth: f32,
+    /// Seconds it takes to get down there
+    pub attack: f32,
+    /// Seconds it stays down
+    pub hold: f32,
+    /// Seconds it takes to come back up
+    pub release: f32,
+    pub easing: Easing,
+}
+
+impl Default for Duck {
+    fn default() -> Self {
+        Self {
+            depth: 0.4,
+            attack: 0.05,
+            hold: 0.3,
+            release: 0.5,
+            easing: Easing::Smoothstep,
+        }
+    }
+}
+
+impl Duck {
+    /// How loud the music is `elapsed` seconds into the dip, nothing once
+    /// it's all the way back up
+    fn gain(&self, elapsed: f32) -> Option<f32> {
+        let down = |t: f32| 1. - (1. - self.depth) * self.easing.apply(t.clamp(0., 1.));
+        let release = self.attack + self.hold;
+        if elapsed < self.attack {
+            Some(down(elapsed / self.attack))
+        } else if elapsed < release {
+            Some(self.depth)
+        } else if elapsed < release + self.release {
+            Some(down(1. - (elapsed - release) / self.release))
+        } else {
+            None
+        }
+    }
+}
+
+/// How the channels are mixed together
+#[derive(Asset, TypePath, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct AudioMix {
+    /// The dip any sound on a channel puts the music in
+    pub ducking: HashMap<Channel, Duck>,
+    pub music: MusicSettings,
+}
+
+#[derive(Resource)]
+struct MixHandle(Handle<AudioMix>);
+
+/// The mix in use, the defaults until the file has loaded
+#[derive(Resource, Default)]
+pub struct ActiveMix(pub AudioMix);
+
+/// Plays a sound from the assets folder on a channel
+#[derive(Event)]
+pub struct PlaySound {
+    pub path: String,
+    pub channel: Channel,
+    pub volume: f32,
+    pub speed: f32,
+}
+
+impl PlaySound {
+    pub fn new(path: impl Into<String>, channel: Channel) -> Self {
+        Self {
+            path: path.into(),
+            channel,
+            volume: 1.,
+            speed: 1.,
+        }
+    }
+}
+
+/// A sound that's playing, at `volume` before it's mixed
+#[derive(Component)]
+pub struct Sound {
+    pub channel: Channel,
+    pub volume: f32,
+}
+
+/// The dips the music is in right now, with how far into each one it is.
+/// Where they overlap the deepest one wins
+#[derive(Resource, Default)]
+pub struct Ducking(Vec<(Duck, f32)>);
+
+impl Ducking {
+    pub fn duck(&mut self, duck: Duck) {
+        self.0.push((duck, 0.));
+    }
+
+    fn gain(&self) -> f32 {
+        self.0
+            .iter()
+            .filter_map(|(duck, elapsed)| duck.gain(*elapsed))
+            .fold(1., f32::min)
+    }
+
+    /// How loud a sound on `channel` is played compared to its own volume
+    pub fn mix(&self, channel: Channel) -> f32 {
+        match channel {
+            Channel::Music => self.gain(),
+            Channel::Effects | Channel::Ui => 1.,
+        }
+    }
+}
+
+pub struct MixerPlugin;
+
+impl Plugin for MixerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<AudioMix>()
+            .register_asset_loader(RonLoader::<AudioMix>::new(&["audio.ron"]));
+
+        let handle = app.world.resource::<AssetServer>().load(MIX_FILE);
+        app.insert_resource(MixHandle(handle))
+            .init_resource::<ActiveMix>()
+            .init_resource::<Ducking>()
+            .add_event::<PlaySound>()
+            .add_systems(Update, (reload_mix, play_sounds, duck_music, mix).chain());
+    }
+}
+
+fn reload_mix(
+    mut active: ResMut<ActiveMix>,
+    mut reader: EventReader<AssetEvent<AudioMix>>,
+    handle: Res<MixHandle>,
+    mixes: Res<Assets<AudioMix>>,
+) {
+    for event in reader.read() {
+        if !event.is_loaded_with_dependencies(&handle.0) && !event.is_modified(&handle.0) {
+            continue;
+        }
+
+        if let Some(mix) = mixes.get(&handle.0) {
+            info!("Audio mix loaded");
+            active.0 = mix.clone();
+        }
+    }
+}
+
+pub fn play_sounds(
+    mut commands: Commands,
+    mut reader: EventReader<PlaySound>,
+    mut ducking: ResMut<Ducking>,
+    mix: Res<ActiveMix>,
+    settings: Res<AudioSettings>,
+    power: Res<LowPower>,
+    asset_server: Res<AssetServer>,
+    playing: Query<&Sound>,
+) {
+    let mut voices = HashMap::<Channel, usize>::new();
+    for sound in &playing {
+        *voices.entry(sound.channel).or_default() += 1;
+    }
+
+    for sound in reader.read() {
+        // The music is never cut, only sounds on top of it
+        let playing = voices.entry(sound.channel).or_default();
+        if sound.channel != Channel::Music && power.voices().is_some_and(|limit| *playing >= limit)
+        {
+            continue;
+        }
+        *playing += 1;
+
+        if let Some(duck) = mix.0.ducking.get(&sound.channel) {
+            ducking.duck(*duck);
+        }
+
+        commands.spawn((
+            Sound {
+                channel: sound.channel,
+                volume: sound.volume,
+            },
+            AudioBundle {
+                source: asset_server.load(sound.path.clone()),
+                settings: PlaybackSettings::DESPAWN
+                    .with_volume(Volume::new(
+                        sound.volume * ducking.mix(sound.channel) * settings.gain(sound.channel),
+                    ))
+                    .with_speed(sound.speed),
+            },
+        ));
+    }
+}
+
+pub fn duck_music(mut ducking: ResMut<Ducking>, time: Res<Time>) {
+    for (_, elapsed) in &mut ducking.0 {
+        *elapsed += time.delta_seconds();
+    }
+    ducking
+        .0
+        .retain(|(duck, elapsed)| duck.gain(*elapsed).is_some());
+}
+
+// Everything that plays goes through here, so the player's settings apply
+// to it as soon as they're changed
+fn mix(ducking: Res<Ducking>, settings: Res<AudioSettings>, query: Query<(&Sound, &AudioSink)>) {
+    for (sound, sink) in &query {
+        sink.set_volume(sound.volume * ducking.mix(sound.channel) * settings.gain(sound.channel));
+    }
+}
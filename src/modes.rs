@@ -0,0 +1,431 @@
+use bevy::prelude::*;
+
+use crate::{
+    build_world, campaign::in_campaign, create_world, difficulty::Difficulty, levels::LevelGoal,
+    profile::Profile, replay::Playback, retention::ReplayIndex, AppState, Atlas, BuildWorld,
+    NextSeed, RunModifiers, Score, SimTick, SpriteSheet,
+};
+
+/// The mode runs are played in unless another one is picked
+pub const CLASSIC: &str = "classic";
+
+const CARD_WIDTH: f32 = 112.;
+const CARD_GAP: f32 = 16.;
+// How much of the way to the picked card the carousel slides every second
+const SLIDE_RATE: f32 = 12.;
+
+/// How a mode is shown as a card on the main menu
+#[derive(Clone)]
+pub struct ModeInfo {
+    /// What runs of the mode are saved as, so it can't change once released
+    pub id: &'static str,
+    pub name: &'static str,
+    pub blurb: &'static str,
+    pub preview: Atlas,
+    pub color: Color,
+    /// Every run of the mode is played on this seed instead of a random one
+    pub seed: Option<fn() -> u64>,
+}
+
+/// A way to play. Everything a mode does differently from a classic run goes
+/// through here, so the rest of the game doesn't have to know about it
+pub trait GameMode: Send + Sync + 'static {
+    fn info(&self) -> ModeInfo;
+
+    /// Adds whatever the mode needs on top of a classic run. Its systems
+    /// should only run while `mode_is` the mode
+    fn setup(&self, _app: &mut App) {}
+
+    /// How many points getting through a pipe is worth
+    fn points_per_pipe(&self) -> u32 {
+        1
+    }
+
+    /// Whether running into pipes and hazards ends the run
+    fn crashes_end_run(&self) -> bool {
+        true
+    }
+
+    /// Where the run ends if it isn't endless
+    fn level(&self) -> Option<LevelGoal> {
+        None
+    }
+
+    /// What the game over screen says about a run that lasted `ticks` steps
+    fn results(&self, score: u32, _ticks: u64) -> String {
+        format!("Score {score}")
+    }
+}
+
+pub struct Mode {
+    pub info: ModeInfo,
+    pub rules: Box<dyn GameMode>,
+}
+
+/// Every mode, in the order they're shown in
+#[derive(Resource, Default)]
+pub struct ModeRegistry {
+    pub modes: Vec<Mode>,
+}
+
+impl ModeRegistry {
+    pub fn get(&self, id: &str) -> Option<&Mode> {
+        self.modes.iter().find(|mode| mode.info.id == id)
+    }
+
+    /// The mode runs are played in right now, classic if it's gone missing
+    pub fn current(&self, modifiers: &RunModifiers) -> &Mode {
+        self.get(&modifiers.mode)
+            .or_else(|| self.get(CLASSIC))
+            .expect("the classic mode is always registered")
+    }
+}
+
+/// Lets a mode's plugin put it on the main menu
+pub trait AddGameMode {
+    fn add_game_mode(&mut self, mode: impl GameMode) -> &mut Self;
+}
+
+impl AddGameMode for App {
+    fn add_game_mode(&mut self, mode: impl GameMode) -> &mut Self {
+        mode.setup(self);
+        let mode = Mode {
+            info: mode.info(),
+            rules: Box::new(mode),
+        };
+        self.init_resource::<ModeRegistry>()
+            .world
+            .resource_mut::<ModeRegistry>()
+            .modes
+            .push(mode);
+        self
+    }
+}
+
+/// Run condition for systems that belong to a single mode
+pub fn mode_is(id: &'static str) -> impl Fn(Res<RunModifiers>) -> bool + Clone {
+    move |modifiers| modifiers.mode == id
+}
+
+struct Classic;
+
+impl GameMode for Classic {
+    fn info(&self) -> ModeInfo {
+        ModeInfo {
+            id: CLASSIC,
+            name: "Classic",
+            blurb: "Fly as far as you can",
+            preview: Atlas::Bird1,
+            color: Color::rgb(0.2, 0.5, 0.3),
+            seed: None,
+        }
+    }
+}
+
+/// How far the carousel has slid, in cards
+#[derive(Resource, Default)]
+struct Carousel {
+    offset: f32,
+}
+
+#[derive(Component)]
+struct CarouselRoot;
+
+#[derive(Component)]
+struct CarouselTrack;
+
+#[derive(Component)]
+struct ResultsText;
+
+pub struct ModesPlugin;
+
+impl Plugin for ModesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_game_mode(Classic)
+            .init_resource::<Carousel>()
+            .add_systems(Startup, restore_mode)
+            .add_systems(BuildWorld, seed_world.before(create_world))
+            .add_systems(
+                OnEnter(AppState::MainMenu),
+                spawn_carousel
+                    .after(build_world)
+                    .run_if(not(resource_exists::<Playback>).and_then(not(in_campaign))),
+            )
+            .add_systems(OnExit(AppState::MainMenu), despawn_carousel)
+            .add_systems(OnEnter(AppState::GameOver), show_results)
+            .add_systems(OnExit(AppState::GameOver), hide_results)
+            .add_systems(
+                Update,
+                (pick_mode, slide_carousel).chain().run_if(
+                    in_state(AppState::MainMenu)
+                        .and_then(not(resource_exists::<Playback>))
+                        .and_then(not(in_campaign)),
+                ),
+            );
+    }
+}
+
+// Picks up where the player left off last time, as long as the mode's still around
+fn restore_mode(
+    mut modifiers: ResMut<RunModifiers>,
+    profile: Res<Profile>,
+    registry: Res<ModeRegistry>,
+) {
+    if registry.get(&profile.mode).is_some() {
+        modifiers.mode.clone_from(&profile.mode);
+    }
+}
+
+fn seed_world(
+    mut next_seed: ResMut<NextSeed>,
+    modifiers: Res<RunModifiers>,
+    registry: Res<ModeRegistry>,
+) {
+    // Replays come with a seed of their own
+    if next_seed.0.is_some() {
+        return;
+    }
+
+    if let Some(seed) = registry
+        .get(&modifiers.mode)
+        .and_then(|mode| mode.info.seed)
+    {
+        next_seed.0 = Some(seed());
+    }
+}
+
+fn pick_mode(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    registry: Res<ModeRegistry>,
+    mut modifiers: ResMut<RunModifiers>,
+    mut profile: ResMut<Profile>,
+    mut next_seed: ResMut<NextSeed>,
+) {
+    let step = match (
+        keys.just_pressed(KeyCode::ArrowLeft),
+        keys.just_pressed(KeyCode::ArrowRight),
+    ) {
+        (true, false) => -1,
+        (false, true) => 1,
+        _ => return,
+    };
+
+    let current = registry
+        .modes
+        .iter()
+        .position(|mode| mode.info.id == modifiers.mode)
+        .unwrap_or(0);
+    let Some(next) = current
+        .checked_add_signed(step)
+        .and_then(|i| registry.modes.get(i))
+    else {
+        return;
+    };
+
+    // A mode with a seed of its own has a world of its own, and so does a
+    // level picked in the mode being left. Either has to be laid out again
+    // when switching to or from it
+    let level = modifiers.level.take();
+    if level.is_some() || registry.modes[current].info.seed.is_some() || next.info.seed.is_some() {
+        next_seed.0 = next.info.seed.map(|seed| seed());
+        commands.add(build_world);
+    }
+
+    modifiers.mode = next.info.id.to_string();
+    profile.mode = next.info.id.to_string();
+}
+
+fn spawn_carousel(
+    mut commands: Commands,
+    mut carousel: ResMut<Carousel>,
+    registry: Res<ModeRegistry>,
+    modifiers: Res<RunModifiers>,
+    index: Res<ReplayIndex>,
+    sheet: Res<SpriteSheet>,
+) {
+    carousel.offset = registry
+        .modes
+        .iter()
+        .position(|mode| mode.info.id == modifiers.mode)
+        .unwrap_or(0) as f32;
+
+    let text = |value: String, size: f32, color: Color| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size: size,
+                color,
+                ..default()
+            },
+        )
+    };
+
+    commands
+        .spawn((
+            CarouselRoot,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Px(96.),
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(32.),
+                    justify_content: JustifyContent::Center,
+                    overflow: Overflow::clip(),
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    CarouselTrack,
+                    NodeBundle {
+                        style: Style {
+                            position_type: PositionType::Absolute,
+                            height: Val::Px(80.),
+                            column_gap: Val::Px(CARD_GAP),
+                            ..default()
+                        },
+                        ..default()
+                    },
+                ))
+                .with_children(|parent| {
+                    for Mode { info: mode, .. } in &registry.modes {
+                        let best = index
+                            .replays
+                            .iter()
+                            .filter(|entry| entry.modifiers.mode == mode.id)
+                            .map(|entry| entry.score)
+                            .max();
+
+                        parent
+                            .spawn(NodeBundle {
+                                style: Style {
+                                    width: Val::Px(CARD_WIDTH),
+                                    flex_shrink: 0.,
+                                    flex_direction: FlexDirection::Column,
+                                    align_items: AlignItems::Center,
+                                    padding: UiRect::all(Val::Px(4.)),
+                                    row_gap: Val::Px(2.),
+                                    ..default()
+                                },
+                                background_color: mode.color.with_a(0.8).into(),
+                                ..default()
+                            })
+                            .with_children(|parent| {
+                                parent.spawn(AtlasImageBundle {
+                                    style: Style {
+                                        height: Val::Px(24.),
+                                        ..default()
+                                    },
+                                    image: UiImage::new(sheet.texture.clone()),
+                                    texture_atlas: TextureAtlas {
+                                        layout: sheet.layout.clone(),
+                                        index: mode.preview as usize,
+                                    },
+                                    ..default()
+                                });
+                                parent.spawn(text(mode.name.to_string(), 14., Color::WHITE));
+                                parent.spawn(text(mode.blurb.to_string(), 10., Color::WHITE));
+                                let badge = match best {
+                                    Some(score) => format!("Best {score}"),
+                                    None => "No runs yet".to_string(),
+                                };
+                                parent.spawn(text(badge, 10., Color::YELLOW));
+                            });
+                    }
+                });
+
+            parent.spawn(
+                text(
+                    "< Left/Right mode >  M campaign".to_string(),
+                    10.,
+                    Color::WHITE,
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(0.),
+                    ..default()
+                }),
+            );
+        });
+}
+
+fn despawn_carousel(mut commands: Commands, query: Query<Entity, With<CarouselRoot>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn slide_carousel(
+    mut carousel: ResMut<Carousel>,
+    registry: Res<ModeRegistry>,
+    modifiers: Res<RunModifiers>,
+    root: Query<&Node, With<CarouselRoot>>,
+    mut track: Query<&mut Style, With<CarouselTrack>>,
+    time: Res<Time>,
+) {
+    let (Ok(root), Ok(mut track)) = (root.get_single(), track.get_single_mut()) else {
+        return;
+    };
+
+    let target = registry
+        .modes
+        .iter()
+        .position(|mode| mode.info.id == modifiers.mode)
+        .unwrap_or(0) as f32;
+    let t = 1. - (-SLIDE_RATE * time.delta_seconds()).exp();
+    carousel.offset += (target - carousel.offset) * t;
+
+    // The picked card sits in the middle with its neighbours peeking in
+    let center = (root.size().x - CARD_WIDTH) / 2.;
+    track.left = Val::Px(center - carousel.offset * (CARD_WIDTH + CARD_GAP));
+}
+
+fn show_results(
+    mut commands: Commands,
+    registry: Res<ModeRegistry>,
+    modifiers: Res<RunModifiers>,
+    difficulty: Res<Difficulty>,
+    score: Res<Score>,
+    tick: Res<SimTick>,
+) {
+    let mut results = registry.current(&modifiers).rules.results(score.0, tick.0);
+    // Played on a gap of its own, so it's never mistaken for a regular run
+    if modifiers.adaptive {
+        results.push_str(&format!("\nAdaptive, gap {:+}", difficulty.gap_adjustment));
+    }
+
+    commands
+        .spawn((
+            ResultsText,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(24.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                results,
+                TextStyle {
+                    font_size: 16.,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn hide_results(mut commands: Commands, query: Query<Entity, With<ResultsText>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,195 @@
+use bevy::{audio::Volume, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    mixer::{ActiveMix, Channel, Sound},
+    AppState,
+};
+
+/// Which track plays where, and how one goes into the next
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct MusicSettings {
+    /// Paths in the assets folder, it's quiet wherever one is left out
+    pub menu: Option<String>,
+    pub playing: Option<String>,
+    pub volume: f32,
+    /// Seconds it takes one track to fade into the next
+    pub crossfade: f32,
+    /// How loud the run's track keeps playing once it's over, from 0 to 1
+    pub game_over: f32,
+}
+
+impl Default for MusicSettings {
+    fn default() -> Self {
+        Self {
+            menu: None,
+            playing: None,
+            volume: 0.6,
+            crossfade: 1.,
+            game_over: 0.3,
+        }
+    }
+}
+
+impl MusicSettings {
+    /// The track that goes with `state` and how loud it plays
+    fn track(&self, state: &AppState) -> Option<(&str, f32)> {
+        let (track, level) = match state {
+            AppState::MainMenu
+            | AppState::LevelSelect
+            | AppState::Replays
+            | AppState::Bookmarks
+            | AppState::Leaderboard
+            | AppState::Career
+            | AppState::Challenge
+            | AppState::Settings
+            | AppState::Controls => (&self.menu, 1.),
+            AppState::GetReady
+            | AppState::Playing
+            | AppState::KillCam
+            | AppState::Dying
+            | AppState::Restarting => (&self.playing, 1.),
+            AppState::GameOver | AppState::LevelComplete => (&self.playing, self.game_over),
+            // Held silent rather than stopped, so it picks up where it left
+            // off once the clicks are done
+            AppState::Calibration => (&self.menu, 0.),
+        };
+        track.as_deref().map(|track| (track, level * self.volume))
+    }
+}
+
+/// A track that's fading from one volume to another
+struct Track {
+    path: String,
+    entity: Entity,
+    from: f32,
+    to: f32,
+    elapsed: f32,
+}
+
+impl Track {
+    fn volume(&self, crossfade: f32) -> f32 {
+        if crossfade <= 0. {
+            return self.to;
+        }
+        let t = (self.elapsed / crossfade).min(1.);
+        self.from + (self.to - self.from) * t
+    }
+
+    fn fade_to(&mut self, volume: f32, crossfade: f32) {
+        self.from = self.volume(crossfade);
+        self.to = volume;
+        self.elapsed = 0.;
+    }
+}
+
+/// The music that's playing, along with the tracks that are fading out under it
+#[derive(Resource, Default)]
+pub struct MusicController {
+    current: Option<Track>,
+    fading: Vec<Track>,
+}
+
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MusicController>().add_systems(
+            Update,
+            (
+                pick_music.run_if(state_changed::<AppState>.or_else(resource_changed::<ActiveMix>)),
+                fade_music,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn pick_music(
+    mut commands: Commands,
+    mut controller: ResMut<MusicController>,
+    state: Res<State<AppState>>,
+    mix: Res<ActiveMix>,
+    asset_server: Res<AssetServer>,
+) {
+    let settings = &mix.0.music;
+    let target = settings.track(state.get());
+    let controller = controller.as_mut();
+
+    // The same track carries on across states, only getting louder or quieter
+    if let (Some(current), Some((path, volume))) = (&mut controller.current, target) {
+        if current.path == path {
+            if current.to != volume {
+                current.fade_to(volume, settings.crossfade);
+            }
+            return;
+        }
+    }
+
+    if let Some(mut current) = controller.current.take() {
+        current.fade_to(0., settings.crossfade);
+        controller.fading.push(current);
+    }
+    let Some((path, volume)) = target else {
+        return;
+    };
+
+    // Coming back to a track before it's faded out picks it up where it is
+    if let Some(i) = controller
+        .fading
+        .iter()
+        .position(|track| track.path == path)
+    {
+        let mut track = controller.fading.remove(i);
+        track.fade_to(volume, settings.crossfade);
+        controller.current = Some(track);
+        return;
+    }
+
+    // Starts out silent and fades in while the last one fades out
+    let entity = commands
+        .spawn((
+            Sound {
+                channel: Channel::Music,
+                volume: 0.,
+            },
+            AudioBundle {
+                source: asset_server.load(path.to_string()),
+                settings: PlaybackSettings::LOOP.with_volume(Volume::new(0.)),
+            },
+        ))
+        .id();
+    controller.current = Some(Track {
+        path: path.to_string(),
+        entity,
+        from: 0.,
+        to: volume,
+        elapsed: 0.,
+    });
+}
+
+fn fade_music(
+    mut commands: Commands,
+    mut controller: ResMut<MusicController>,
+    mut query: Query<&mut Sound>,
+    mix: Res<ActiveMix>,
+    time: Res<Time>,
+) {
+    let crossfade = mix.0.music.crossfade;
+    let controller = controller.as_mut();
+    for track in controller.current.iter_mut().chain(&mut controller.fading) {
+        track.elapsed += time.delta_seconds();
+        if let Ok(mut sound) = query.get_mut(track.entity) {
+            sound.volume = track.volume(crossfade);
+        }
+    }
+
+    controller.fading.retain(|track| {
+        let faded = track.elapsed >= crossfade;
+        if faded {
+            commands.entity(track.entity).despawn_recursive();
+        }
+        !faded
+    });
+}
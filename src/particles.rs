@@ -0,0 +1,83 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// A burst of little squares flying off from a point and fading out
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Particles {
+    pub count: usize,
+    pub color: Color,
+    pub size: Vec2,
+    pub speed: f32,
+    pub lifetime: f32,
+    /// The range of directions they fly off in, in degrees
+    pub angles: (f32, f32),
+    /// Takes the color of the bird's feathers instead of `color`
+    #[serde(default)]
+    pub feathers: bool,
+    /// How much bigger they've gotten by the time they're gone, 0 keeps them
+    /// the size they start at
+    #[serde(default)]
+    pub grow: f32,
+}
+
+#[derive(Component)]
+pub struct Particle {
+    pub velocity: Vec2,
+    pub lifetime: Timer,
+    pub grow: f32,
+}
+
+impl Particles {
+    /// Sends `count` of them off from `position`, which goes by whatever
+    /// they're spawned under
+    pub fn burst(&self, parent: &mut ChildBuilder, position: Vec3, color: Color, count: usize) {
+        let mut rng = rand::thread_rng();
+        let (from, to) = self.angles;
+        for _ in 0..count {
+            let angle = rng.gen_range(from.min(to)..=from.max(to)).to_radians();
+            parent.spawn((
+                Particle {
+                    velocity: Vec2::from_angle(angle) * self.speed,
+                    lifetime: Timer::from_seconds(self.lifetime, TimerMode::Once),
+                    grow: self.grow,
+                },
+                SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(self.size),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(position),
+                    ..default()
+                },
+            ));
+        }
+    }
+}
+
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_particles);
+    }
+}
+
+fn update_particles(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Particle, &mut Transform, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (entity, mut particle, mut transform, mut sprite) in &mut query {
+        if particle.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        transform.translation += (particle.velocity * time.delta_seconds()).extend(0.);
+        let fraction = particle.lifetime.fraction();
+        transform.scale = Vec3::splat(1. + particle.grow * fraction);
+        sprite.color.set_a(1. - fraction);
+    }
+}
@@ -0,0 +1,195 @@
+use bevy::{prelude::*, window::WindowFocused};
+
+use crate::{
+    actions::{Action, Actions},
+    console::Console,
+    display::DisplayPause,
+    mixer::{Channel, PlaySound, CONFIRM_SOUND},
+    AppState,
+};
+
+/// Whether the run is going or the player's holding it still, inside of
+/// `AppState::Playing`
+#[derive(States, Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
+/// A line on the pause overlay
+#[derive(Clone, Copy, PartialEq)]
+enum Choice {
+    Resume,
+    Quit,
+}
+
+const CHOICES: [Choice; 2] = [Choice::Resume, Choice::Quit];
+
+#[derive(Resource, Default)]
+struct PauseMenu {
+    selected: usize,
+}
+
+#[derive(Component)]
+struct PauseOverlay;
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<PauseState>()
+            .init_resource::<PauseMenu>()
+            .add_systems(
+                Update,
+                // The console has the keys to itself while it's open
+                pause_run.run_if(
+                    in_state(AppState::Playing)
+                        .and_then(in_state(PauseState::Running))
+                        .and_then(not(resource_exists::<Console>)),
+                ),
+            )
+            .add_systems(OnEnter(PauseState::Paused), (hold_still, reset_selection))
+            .add_systems(
+                Update,
+                (choose, draw_overlay.run_if(resource_changed::<PauseMenu>))
+                    .chain()
+                    .run_if(in_state(PauseState::Paused)),
+            )
+            .add_systems(OnExit(PauseState::Paused), (let_go, despawn_overlay))
+            .add_systems(OnExit(AppState::Playing), end_pause);
+    }
+}
+
+// Clicking away from the window pauses too, so the bird isn't left flying
+// into a pipe while the player's somewhere else
+fn pause_run(
+    mut actions: ResMut<Actions>,
+    mut focus: EventReader<WindowFocused>,
+    mut state: ResMut<NextState<PauseState>>,
+) {
+    let unfocused = focus.read().any(|event| !event.focused);
+    if actions.take(Action::Pause) || unfocused {
+        state.set(PauseState::Paused);
+    }
+}
+
+fn hold_still(mut time: ResMut<Time<Virtual>>) {
+    time.pause();
+}
+
+// Whatever else is holding the run still lets go of it in its own time
+fn let_go(
+    mut time: ResMut<Time<Virtual>>,
+    display: Option<Res<DisplayPause>>,
+    console: Option<Res<Console>>,
+) {
+    if display.is_none() && console.is_none() {
+        time.unpause();
+    }
+}
+
+fn reset_selection(mut menu: ResMut<PauseMenu>) {
+    menu.selected = 0;
+}
+
+fn choose(
+    mut menu: ResMut<PauseMenu>,
+    mut actions: ResMut<Actions>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut pause: ResMut<NextState<PauseState>>,
+    mut state: ResMut<NextState<AppState>>,
+    mut sounds: EventWriter<PlaySound>,
+) {
+    if actions.take(Action::Pause) {
+        pause.set(PauseState::Running);
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        menu.selected = menu.selected.saturating_sub(1);
+    }
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        menu.selected = (menu.selected + 1).min(CHOICES.len() - 1);
+    }
+    if !keys.just_pressed(KeyCode::Enter) {
+        return;
+    }
+    sounds.send(PlaySound::new(CONFIRM_SOUND, Channel::Ui));
+    match CHOICES[menu.selected] {
+        Choice::Resume => pause.set(PauseState::Running),
+        // Leaving the run lets go of the pause along with it
+        Choice::Quit => state.set(AppState::MainMenu),
+    }
+}
+
+// However the run was left, the next one starts out going
+fn end_pause(state: Res<State<PauseState>>, mut next: ResMut<NextState<PauseState>>) {
+    if *state.get() == PauseState::Paused {
+        next.set(PauseState::Running);
+    }
+}
+
+fn despawn_overlay(mut commands: Commands, query: Query<Entity, With<PauseOverlay>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn draw_overlay(
+    mut commands: Commands,
+    menu: Res<PauseMenu>,
+    query: Query<Entity, With<PauseOverlay>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let text = |value: &str, size: f32, color: Color| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size: size,
+                color,
+                ..default()
+            },
+        )
+    };
+
+    commands
+        .spawn((
+            PauseOverlay,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    position_type: PositionType::Absolute,
+                    flex_direction: FlexDirection::Column,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    row_gap: Val::Px(8.),
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(text("Paused", 32., Color::WHITE));
+
+            for (i, choice) in CHOICES.iter().enumerate() {
+                let label = match choice {
+                    Choice::Resume => "Resume",
+                    Choice::Quit => "Quit to menu",
+                };
+                let color = if i == menu.selected {
+                    Color::YELLOW
+                } else {
+                    Color::WHITE
+                };
+                parent.spawn(text(label, 16., color));
+            }
+
+            parent.spawn(text("Up/Down pick, Enter choose", 12., Color::GRAY));
+        });
+}
@@ -0,0 +1,289 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    characters::ActiveCharacter,
+    create_world,
+    effects::Easing,
+    flap,
+    main_menu::MenuRow,
+    replay::{Playback, Recording},
+    ron_asset::RonLoader,
+    AppState, BuildWorld, RunModifiers, SimSet, SimTick,
+};
+
+const PHYSICS_FILE: &str = "game.physics.ron";
+
+/// The preset runs are played with unless another one is picked
+pub const CLASSIC_PHYSICS: &str = "Classic";
+
+/// How the bird tilts with its speed, from nose down when it's falling as fast
+/// as it can to nose up right after a flap
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct Rotation {
+    /// How the angle goes from one end to the other
+    pub easing: Easing,
+    /// The angles (in degrees) it's kept between
+    pub lowest: f32,
+    pub highest: f32,
+    /// How much of the way to its angle the bird turns every frame
+    pub follow: f32,
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Self {
+            easing: Easing::Linear,
+            lowest: -30.,
+            highest: 90.,
+            follow: 0.5,
+        }
+    }
+}
+
+impl Rotation {
+    /// Where the bird should be pointing, `t` being how far its speed is
+    /// from falling as fast as it can to having just flapped
+    pub fn angle(&self, t: f32) -> f32 {
+        let t = self.easing.apply(t.clamp(0., 1.));
+        (-90. + t * 180.).clamp(self.lowest, self.highest)
+    }
+}
+
+/// How the bird moves. Every run is played with one, and a replay keeps the
+/// one it was recorded with
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct PhysicsPreset {
+    pub name: String,
+    pub gravity: f32,
+    /// How fast the bird goes up right after a flap
+    pub jump: f32,
+    /// The fastest the bird can fall
+    pub terminal: f32,
+    pub rotation: Rotation,
+}
+
+impl Default for PhysicsPreset {
+    fn default() -> Self {
+        Self {
+            name: CLASSIC_PHYSICS.to_string(),
+            gravity: -982.,
+            jump: 200.,
+            terminal: -400.,
+            rotation: Rotation::default(),
+        }
+    }
+}
+
+#[derive(Asset, TypePath, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct PhysicsPresets {
+    pub presets: Vec<PhysicsPreset>,
+}
+
+#[derive(Resource)]
+pub struct PhysicsHandle(pub Handle<PhysicsPresets>);
+
+/// The preset the current run plays by
+#[derive(Resource, Default)]
+pub struct ActivePhysics(pub PhysicsPreset);
+
+/// Physics waiting for the next simulation step to take over, like the config
+#[derive(Resource, Default)]
+pub struct PendingPhysics(pub Option<PhysicsPreset>);
+
+#[derive(Component)]
+struct PhysicsLabel;
+
+pub struct PhysicsPlugin;
+
+impl Plugin for PhysicsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<PhysicsPresets>()
+            .register_asset_loader(RonLoader::<PhysicsPresets>::new(&["physics.ron"]));
+
+        let handle = app.world.resource::<AssetServer>().load(PHYSICS_FILE);
+        app.insert_resource(PhysicsHandle(handle))
+            .init_resource::<ActivePhysics>()
+            .init_resource::<PendingPhysics>()
+            .add_systems(BuildWorld, pick_physics.before(create_world))
+            .add_systems(
+                Update,
+                reload_physics
+                    .run_if(in_state(AppState::Playing).and_then(not(resource_exists::<Playback>))),
+            )
+            .add_systems(
+                FixedUpdate,
+                apply_physics.in_set(SimSet::Input).before(flap),
+            )
+            .add_systems(OnEnter(AppState::MainMenu), spawn_label)
+            .add_systems(OnExit(AppState::MainMenu), despawn_label)
+            .add_systems(
+                Update,
+                (
+                    (
+                        follow_character,
+                        cycle_physics.run_if(not(resource_exists::<Playback>)),
+                    ),
+                    pick_physics.run_if(
+                        resource_changed::<RunModifiers>
+                            .or_else(on_event::<AssetEvent<PhysicsPresets>>()),
+                    ),
+                    draw_label,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::MainMenu)),
+            );
+    }
+}
+
+fn pick_physics(
+    mut active: ResMut<ActivePhysics>,
+    handle: Res<PhysicsHandle>,
+    presets: Res<Assets<PhysicsPresets>>,
+    modifiers: Res<RunModifiers>,
+    playback: Option<Res<Playback>>,
+) {
+    // Replays play out with the physics they were recorded with
+    if let Some(playback) = playback {
+        active.0 = playback.replay.physics.clone();
+        return;
+    }
+
+    let preset = picked(&presets, &handle, &modifiers);
+    if active.0 != preset {
+        active.0 = preset;
+    }
+}
+
+/// The preset the modifiers ask for, as it is in the file right now
+fn picked(
+    presets: &Assets<PhysicsPresets>,
+    handle: &PhysicsHandle,
+    modifiers: &RunModifiers,
+) -> PhysicsPreset {
+    let preset = presets.get(&handle.0).and_then(|presets| {
+        presets
+            .presets
+            .iter()
+            .find(|preset| preset.name == modifiers.physics)
+    });
+    let mut preset = match preset {
+        Some(preset) => preset.clone(),
+        None => PhysicsPreset::default(),
+    };
+    // Whatever the bird flies with, it's lighter or heavier on the difficulty
+    // preset, which the replay keeps along with the rest
+    preset.gravity *= modifiers.preset.gravity();
+    preset
+}
+
+// Edits to the presets apply in the middle of a run too, on the menu they're
+// picked up by `pick_physics`
+fn reload_physics(
+    mut pending: ResMut<PendingPhysics>,
+    mut reader: EventReader<AssetEvent<PhysicsPresets>>,
+    handle: Res<PhysicsHandle>,
+    presets: Res<Assets<PhysicsPresets>>,
+    modifiers: Res<RunModifiers>,
+) {
+    for event in reader.read() {
+        if event.is_loaded_with_dependencies(&handle.0) || event.is_modified(&handle.0) {
+            pending.0 = Some(picked(&presets, &handle, &modifiers));
+        }
+    }
+}
+
+// Kept in the recording along with the step it applied on, so a replay flies
+// with the physics the run had at every point
+fn apply_physics(
+    mut pending: ResMut<PendingPhysics>,
+    mut active: ResMut<ActivePhysics>,
+    recording: Option<ResMut<Recording>>,
+    tick: Res<SimTick>,
+) {
+    let Some(preset) = pending.0.take() else {
+        return;
+    };
+    if active.0 == preset {
+        return;
+    }
+
+    info!("Physics changed to {} mid-run", preset.name);
+    if let Some(mut recording) = recording {
+        recording.0.physics_changes.push((tick.0, preset.clone()));
+    }
+    active.0 = preset;
+}
+
+// A bird that comes with physics of its own flies with them once it's picked
+fn follow_character(
+    active: Res<ActiveCharacter>,
+    mut modifiers: ResMut<RunModifiers>,
+    mut last: Local<Option<String>>,
+    playback: Option<Res<Playback>>,
+) {
+    if playback.is_some() || last.as_ref() == Some(&active.0.name) {
+        return;
+    }
+    *last = Some(active.0.name.clone());
+
+    if let Some(physics) = &active.0.physics {
+        if modifiers.physics != *physics {
+            modifiers.physics.clone_from(physics);
+        }
+    }
+}
+
+fn cycle_physics(
+    keys: Res<ButtonInput<KeyCode>>,
+    handle: Res<PhysicsHandle>,
+    presets: Res<Assets<PhysicsPresets>>,
+    mut modifiers: ResMut<RunModifiers>,
+) {
+    if !keys.just_pressed(KeyCode::KeyP) {
+        return;
+    }
+    let Some(presets) = presets.get(&handle.0) else {
+        return;
+    };
+    if presets.presets.is_empty() {
+        return;
+    }
+
+    let current = presets
+        .presets
+        .iter()
+        .position(|preset| preset.name == modifiers.physics);
+    let next = current.map_or(0, |i| (i + 1) % presets.presets.len());
+    modifiers.physics.clone_from(&presets.presets[next].name);
+}
+
+fn spawn_label(mut commands: Commands) {
+    commands.spawn((
+        PhysicsLabel,
+        MenuRow::Physics,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 14.,
+                color: Color::WHITE,
+                ..default()
+            },
+        ),
+    ));
+}
+
+fn despawn_label(mut commands: Commands, query: Query<Entity, With<PhysicsLabel>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn draw_label(active: Res<ActivePhysics>, mut query: Query<&mut Text, With<PhysicsLabel>>) {
+    for mut text in &mut query {
+        text.sections[0].value = format!("P physics: {}", active.0.name);
+    }
+}
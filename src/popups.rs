@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+
+use crate::{effects::Easing, PipePassed, Root};
+
+// How far a popup floats up before it's gone, and how long that takes
+const POPUP_RISE: f32 = 16.;
+const POPUP_DURATION: f32 = 0.6;
+// Over the pipes and the bird
+const POPUP_Z: f32 = 6.;
+
+/// Text floating up from somewhere in the world and fading out as it goes
+#[derive(Component)]
+pub struct Popup {
+    /// Where it started out
+    from: Vec2,
+    timer: Timer,
+    easing: Easing,
+}
+
+impl Popup {
+    /// Spawns `value` floating up from `position`, which goes by whatever
+    /// it's spawned under
+    pub fn spawn(parent: &mut ChildBuilder, value: String, position: Vec2, color: Color) {
+        parent.spawn((
+            Popup {
+                from: position,
+                timer: Timer::from_seconds(POPUP_DURATION, TimerMode::Once),
+                easing: Easing::Out,
+            },
+            Text2dBundle {
+                text: Text::from_section(
+                    value,
+                    TextStyle {
+                        font_size: 8.,
+                        color,
+                        ..default()
+                    },
+                ),
+                transform: Transform::from_translation(position.extend(POPUP_Z)),
+                ..default()
+            },
+        ));
+    }
+}
+
+pub struct PopupsPlugin;
+
+impl Plugin for PopupsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, (pop_points, float_popups).chain());
+    }
+}
+
+fn pop_points(
+    mut commands: Commands,
+    mut reader: EventReader<PipePassed>,
+    root: Query<Entity, With<Root>>,
+) {
+    let Ok(root) = root.get_single() else {
+        reader.clear();
+        return;
+    };
+
+    // Modes that don't score don't have anything to show
+    for passed in reader.read().filter(|passed| passed.points > 0) {
+        commands.entity(root).with_children(|parent| {
+            Popup::spawn(
+                parent,
+                format!("+{}", passed.points),
+                passed.position,
+                Color::WHITE,
+            );
+        });
+    }
+}
+
+fn float_popups(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Popup, &mut Transform, &mut Text)>,
+    time: Res<Time>,
+) {
+    for (entity, mut popup, mut transform, mut text) in &mut query {
+        if popup.timer.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        let fraction = popup.timer.fraction();
+        let rise = popup.easing.apply(fraction) * POPUP_RISE;
+        transform.translation = (popup.from + Vec2::Y * rise).extend(POPUP_Z);
+        for section in &mut text.sections {
+            section.style.color.set_a(1. - fraction);
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use bevy::{math::bounding::IntersectsVolume, prelude::*};
+
+use crate::{
+    crash_and_die,
+    difficulty::Difficulty,
+    hitboxes::Hitboxes,
+    offset_aabb,
+    scroll::{is_scrolling, ScrollEase},
+    AppState, Collider, Player, SimSet,
+};
+
+const PORTAL_SIZE: Vec2 = Vec2::new(8., 24.);
+const ENTRANCE_COLOR: Color = Color::rgb(0.6, 0.2, 0.9);
+const EXIT_COLOR: Color = Color::rgb(0.9, 0.6, 1.);
+
+/// Flying into it puts the player at the height of its exit
+#[derive(Component)]
+struct Portal {
+    to: f32,
+}
+
+/// Where a portal lets out, only there to be seen
+#[derive(Component)]
+struct PortalExit;
+
+pub struct PortalsPlugin;
+
+impl Plugin for PortalsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                scroll_portals.in_set(SimSet::Physics).run_if(is_scrolling),
+                // Coming out of a portal into a pipe is on whoever placed it
+                enter_portals
+                    .in_set(SimSet::Collision)
+                    .before(crash_and_die)
+                    .run_if(in_state(AppState::Playing)),
+            ),
+        );
+    }
+}
+
+/// Puts a portal at `x` that takes the player from a height of `from` to `to`
+pub fn spawn_portal(parent: &mut ChildBuilder, hitboxes: &Hitboxes, x: f32, from: f32, to: f32) {
+    let sprite = |color, y| SpriteBundle {
+        sprite: Sprite {
+            color,
+            custom_size: Some(PORTAL_SIZE),
+            ..default()
+        },
+        transform: Transform::from_xyz(x, y, 2.),
+        ..default()
+    };
+
+    parent.spawn((
+        Portal { to },
+        Collider(hitboxes.portal.aabb()),
+        sprite(ENTRANCE_COLOR, from),
+    ));
+    parent.spawn((PortalExit, sprite(EXIT_COLOR, to)));
+}
+
+fn scroll_portals(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform), Or<(With<Portal>, With<PortalExit>)>>,
+    difficulty: Res<Difficulty>,
+    ease: Res<ScrollEase>,
+    time: Res<Time>,
+) {
+    for (entity, mut transform) in &mut query {
+        transform.translation.x += time.delta_seconds() * ease.speed(&difficulty);
+        if transform.translation.x < -144. * 2. {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn enter_portals(
+    mut commands: Commands,
+    mut player: Query<(&mut Transform, &Collider), With<Player>>,
+    portals: Query<(Entity, &Transform, &Collider, &Portal), Without<Player>>,
+) {
+    let (mut transform, Collider(player_collider)) = player.single_mut();
+    let bounds = offset_aabb(player_collider, &transform.translation);
+
+    for (entity, portal_transform, Collider(portal_collider), portal) in &portals {
+        if offset_aabb(portal_collider, &portal_transform.translation).intersects(&bounds) {
+            transform.translation.y = portal.to;
+            // A portal closes behind the player so it only takes them once
+            commands.entity(entity).despawn_recursive();
+            break;
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use bevy::{prelude::*, sprite::Anchor};
+
+use crate::{
+    bookmarks::Draft,
+    difficulty::Difficulty,
+    modes::{mode_is, AddGameMode, GameMode, ModeInfo},
+    profile::Profile,
+    tutorials::{AddTutorial, Tutorial, BETWEEN_PIPES},
+    world_running, AppState, Atlas, Obstacle, OnJumped, Player, Root, SimSet, SimTick, Velocity,
+    PIPE_WIDTH,
+};
+
+const PRACTICE: &str = "practice";
+// Where the readouts sit, up and to the right of the bird
+const HUD_OFFSET: Vec3 = Vec3::new(10., 8., 5.);
+
+struct Practice;
+
+impl GameMode for Practice {
+    fn info(&self) -> ModeInfo {
+        ModeInfo {
+            id: PRACTICE,
+            name: "Practice",
+            blurb: "F shows frame data",
+            preview: Atlas::Bird1,
+            color: Color::rgb(0.45, 0.45, 0.45),
+            seed: None,
+        }
+    }
+
+    fn setup(&self, app: &mut App) {
+        app.init_resource::<FrameData>()
+            .add_systems(
+                OnEnter(AppState::Playing),
+                (reset_frame_data, spawn_hud).run_if(mode_is(PRACTICE)),
+            )
+            .add_systems(
+                FixedUpdate,
+                read_frame_data
+                    .in_set(SimSet::Rules)
+                    .run_if(in_state(AppState::Playing).and_then(mode_is(PRACTICE))),
+            )
+            .add_systems(
+                Update,
+                (
+                    // Typing a bookmark name takes the keys for itself
+                    toggle_hud.run_if(not(resource_exists::<Draft>)),
+                    draw_hud.run_if(world_running),
+                )
+                    .chain()
+                    .run_if(mode_is(PRACTICE)),
+            );
+    }
+}
+
+pub struct PracticePlugin;
+
+impl Plugin for PracticePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_game_mode(Practice).add_tutorial(Tutorial {
+            id: PRACTICE,
+            title: "Practice",
+            text: "Runs here don't count. Press F to see the bird's speed and how far off the gap it is",
+            art: Atlas::Bird1,
+            example: BETWEEN_PIPES,
+        });
+    }
+}
+
+/// The numbers behind the last step, for learning when to flap
+#[derive(Resource, Default)]
+struct FrameData {
+    velocity: f32,
+    /// How far above the middle of the next gap the bird is, below it when
+    /// it's negative
+    above_gap: Option<f32>,
+    /// The step the bird last flapped on
+    last_flap: u64,
+    tick: u64,
+}
+
+#[derive(Component)]
+struct FrameDataHud;
+
+fn reset_frame_data(mut data: ResMut<FrameData>) {
+    *data = FrameData::default();
+}
+
+fn read_frame_data(
+    mut data: ResMut<FrameData>,
+    mut reader: EventReader<OnJumped>,
+    player: Query<(&Transform, &Velocity), With<Player>>,
+    obstacles: Query<&Transform, With<Obstacle>>,
+    difficulty: Res<Difficulty>,
+    tick: Res<SimTick>,
+) {
+    if reader.read().count() > 0 {
+        data.last_flap = tick.0;
+    }
+    data.tick = tick.0;
+
+    let Some((transform, velocity)) = player.iter().next() else {
+        return;
+    };
+    let position = transform.translation;
+    // The closest obstacle that isn't behind the bird yet
+    let next = obstacles
+        .iter()
+        .map(|obstacle| obstacle.translation)
+        .filter(|obstacle| obstacle.x + PIPE_WIDTH / 2. > position.x)
+        .min_by(|a, b| a.x.total_cmp(&b.x));
+
+    data.velocity = velocity.0;
+    data.above_gap = next.map(|obstacle| {
+        let center = obstacle.y - 80. - difficulty.pipe_space / 2.;
+        position.y - center
+    });
+}
+
+fn spawn_hud(mut commands: Commands, profile: Res<Profile>, root: Query<Entity, With<Root>>) {
+    let hud = commands
+        .spawn((
+            FrameDataHud,
+            Text2dBundle {
+                text: Text::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 8.,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ),
+                text_anchor: Anchor::BottomLeft,
+                visibility: if profile.frame_data {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                },
+                ..default()
+            },
+        ))
+        .id();
+    commands.entity(root.single()).add_child(hud);
+}
+
+fn toggle_hud(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut profile: ResMut<Profile>,
+    mut query: Query<&mut Visibility, With<FrameDataHud>>,
+) {
+    if !keys.just_pressed(KeyCode::KeyF) {
+        return;
+    }
+
+    profile.frame_data = !profile.frame_data;
+    for mut visibility in &mut query {
+        *visibility = if profile.frame_data {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn draw_hud(
+    data: Res<FrameData>,
+    player: Query<&Transform, With<Player>>,
+    mut query: Query<(&mut Text, &mut Transform), (With<FrameDataHud>, Without<Player>)>,
+) {
+    let Some(player) = player.iter().next() else {
+        return;
+    };
+
+    let gap = match data.above_gap {
+        Some(above) => format!("{above:+.0}"),
+        None => "-".to_string(),
+    };
+    for (mut text, mut transform) in &mut query {
+        transform.translation = player.translation + HUD_OFFSET;
+        text.sections[0].value = format!(
+            "vel {:+.0}\ngap {gap}\nflap {}",
+            data.velocity,
+            data.tick.saturating_sub(data.last_flap)
+        );
+    }
+}
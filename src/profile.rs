@@ -0,0 +1,87 @@
+use std::collections::BTreeSet;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bookmarks::Bookmark,
+    bots::BotLevel,
+    career::Career,
+    decals::DecalChoice,
+    difficulty::PerformanceModel,
+    ghost::GhostSettings,
+    levels::LevelBests,
+    ranked::RankedRecord,
+    save::{self, FlushSaves},
+    score_panel::Medal,
+};
+
+const PROFILE_FILE: &str = "profile.ron";
+
+/// Everything about the player that should survive a restart
+#[derive(Resource, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Profile {
+    pub performance: PerformanceModel,
+    pub bookmarks: Vec<Bookmark>,
+    /// Name of the bird the player flies as
+    pub character: String,
+    /// Id of the mode that was picked last
+    pub mode: String,
+    pub level_bests: LevelBests,
+    /// Titles of every award that's been earned at least once
+    pub achievements: BTreeSet<String>,
+    /// Ids of what the bird wears, if anything
+    pub hat: Option<String>,
+    pub scarf: Option<String>,
+    /// Whether every run rolls a bird and accessories of its own
+    pub random_look: bool,
+    /// What gets painted on the pipes
+    pub decals: DecalChoice,
+    pub ghost: GhostSettings,
+    /// Whether practice runs show the frame data next to the bird
+    pub frame_data: bool,
+    pub ranked: RankedRecord,
+    /// The bot that flies alongside the player, if any
+    pub opponent: Option<BotLevel>,
+    /// Every medal that's been earned at least once
+    pub medals: BTreeSet<Medal>,
+    /// How much later than the beat the player taps along to it, in seconds,
+    /// as measured on the timing screen
+    pub input_offset: f32,
+    /// Ids of every mode and modifier whose tutorial has been flapped past
+    pub tutorials: BTreeSet<String>,
+    pub career: Career,
+}
+
+pub struct ProfilePlugin;
+
+impl Plugin for ProfilePlugin {
+    fn build(&self, app: &mut App) {
+        let profile = match save::load::<Profile>(PROFILE_FILE) {
+            Ok(profile) => profile.unwrap_or_default(),
+            Err(error) => {
+                warn!("Couldn't load profile, starting fresh: {error}");
+                Profile::default()
+            }
+        };
+
+        app.insert_resource(profile)
+            .add_event::<FlushSaves>()
+            .add_systems(
+                Last,
+                save_profile.run_if(resource_changed::<Profile>.or_else(on_event::<FlushSaves>())),
+            );
+    }
+}
+
+fn save_profile(profile: Res<Profile>) {
+    // Nothing new to write when it was just loaded
+    if profile.is_added() {
+        return;
+    }
+
+    if let Err(error) = save::store(PROFILE_FILE, profile.as_ref()) {
+        warn!("Couldn't save profile: {error}");
+    }
+}
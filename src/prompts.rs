@@ -0,0 +1,274 @@
+use bevy::{
+    input::{gamepad::GamepadButton, InputSystem},
+    prelude::*,
+};
+
+use crate::{
+    actions::{Action, ActionMap, Button},
+    replay::Playback,
+    touch::{track_touches, LastTouch},
+    AppState, Score,
+};
+
+const GLYPH_FILE: &str = "glyphs.png";
+// Every glyph is a square this many pixels across
+const GLYPH_SIZE: f32 = 16.;
+// One for each device, side by side
+const GLYPH_COUNT: usize = 4;
+
+/// What the player is playing with
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum Device {
+    #[default]
+    Mouse,
+    Gamepad,
+    Touch,
+    Keyboard,
+}
+
+impl Device {
+    /// What the player does to flap, which every prompt starts with
+    fn verb(self, map: &ActionMap) -> String {
+        if self == Device::Touch && map.touch(Action::Flap) {
+            return "Tap".to_string();
+        }
+        match map.first(Action::Flap, self) {
+            Some(button @ Button::Mouse(_)) => button.name(),
+            Some(button) => format!("Press {}", button.name()),
+            None => "Tap".to_string(),
+        }
+    }
+
+    /// Where its glyph is in the glyph atlas
+    fn glyph(self) -> usize {
+        self as usize
+    }
+}
+
+/// The device the player used last, which the prompts are shown for
+#[derive(Resource, Default, PartialEq)]
+pub struct LastDevice(pub Device);
+
+/// The gamepad a button was last pressed on, out of however many are
+/// plugged in. It's the one that rumbles
+#[derive(Resource, Default, PartialEq)]
+pub struct LastGamepad(pub Option<Gamepad>);
+
+#[derive(Resource)]
+struct Glyphs {
+    image: Handle<Image>,
+    layout: Handle<TextureAtlasLayout>,
+}
+
+/// A line telling the player what to press for `action`, with the glyph for
+/// their device in front of it. Redrawn whenever they pick up another device
+/// or change what flaps
+#[derive(Component)]
+pub struct Prompt {
+    action: &'static str,
+    font_size: f32,
+}
+
+impl Prompt {
+    pub fn new(action: &'static str) -> Self {
+        Self {
+            action,
+            font_size: 12.,
+        }
+    }
+
+    /// The prompt along with the row its glyph and text are laid out in
+    pub fn bundle(self) -> (Self, NodeBundle) {
+        (
+            self,
+            NodeBundle {
+                style: Style {
+                    align_items: AlignItems::Center,
+                    column_gap: Val::Px(4.),
+                    ..default()
+                },
+                ..default()
+            },
+        )
+    }
+}
+
+#[derive(Component)]
+struct MenuPrompt;
+
+/// Shown at the start of a run until the first pipe is behind the bird
+#[derive(Component)]
+struct TutorialPrompt;
+
+pub struct PromptsPlugin;
+
+impl Plugin for PromptsPlugin {
+    fn build(&self, app: &mut App) {
+        let image = app.world.resource::<AssetServer>().load(GLYPH_FILE);
+        let layout = app.world.resource_mut::<Assets<TextureAtlasLayout>>().add(
+            TextureAtlasLayout::from_grid(Vec2::splat(GLYPH_SIZE), GLYPH_COUNT, 1, None, None),
+        );
+
+        app.insert_resource(Glyphs { image, layout })
+            .init_resource::<LastDevice>()
+            .init_resource::<LastGamepad>()
+            .add_systems(
+                PreUpdate,
+                detect_device.after(InputSystem).after(track_touches),
+            )
+            .add_systems(
+                OnEnter(AppState::MainMenu),
+                spawn_menu_prompt.run_if(not(resource_exists::<Playback>)),
+            )
+            .add_systems(OnExit(AppState::MainMenu), despawn::<MenuPrompt>)
+            // Shown while the bird waits for its first flap as well. Going on
+            // into the run swaps it for a new one within the same frame
+            .add_systems(
+                OnEnter(AppState::GetReady),
+                spawn_tutorial.run_if(not(resource_exists::<Playback>)),
+            )
+            .add_systems(OnExit(AppState::GetReady), despawn::<TutorialPrompt>)
+            .add_systems(
+                OnEnter(AppState::Playing),
+                spawn_tutorial.run_if(not(resource_exists::<Playback>)),
+            )
+            .add_systems(OnExit(AppState::Playing), despawn::<TutorialPrompt>)
+            .add_systems(
+                Update,
+                (
+                    end_tutorial.run_if(in_state(AppState::Playing)),
+                    draw_prompts,
+                ),
+            );
+    }
+}
+
+fn detect_device(
+    mut last: ResMut<LastDevice>,
+    mut last_gamepad: ResMut<LastGamepad>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepad: Res<ButtonInput<GamepadButton>>,
+    touches: Res<Touches>,
+    last_touch: Res<LastTouch>,
+    time: Res<Time<Real>>,
+) {
+    if let Some(button) = gamepad.get_just_pressed().next() {
+        last_gamepad.set_if_neq(LastGamepad(Some(button.gamepad)));
+    }
+
+    let device = if touches.any_just_pressed() {
+        Device::Touch
+    } else if gamepad.get_just_pressed().next().is_some() {
+        Device::Gamepad
+    } else if mouse.get_just_pressed().next().is_some() && !last_touch.emulated_click(&time) {
+        Device::Mouse
+    } else if keys.get_just_pressed().next().is_some() {
+        Device::Keyboard
+    } else {
+        return;
+    };
+    // Only a new device redraws the prompts
+    last.set_if_neq(LastDevice(device));
+}
+
+fn spawn_prompt<M: Component>(commands: &mut Commands, marker: M, prompt: Prompt, top: Val) {
+    commands
+        .spawn((
+            marker,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    position_type: PositionType::Absolute,
+                    top,
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(prompt.bundle());
+        });
+}
+
+fn spawn_menu_prompt(mut commands: Commands) {
+    spawn_prompt(
+        &mut commands,
+        MenuPrompt,
+        Prompt {
+            font_size: 16.,
+            ..Prompt::new("to start")
+        },
+        Val::Percent(60.),
+    );
+}
+
+fn spawn_tutorial(mut commands: Commands) {
+    spawn_prompt(
+        &mut commands,
+        TutorialPrompt,
+        Prompt::new("to flap"),
+        Val::Percent(60.),
+    );
+}
+
+fn end_tutorial(
+    mut commands: Commands,
+    score: Res<Score>,
+    query: Query<Entity, With<TutorialPrompt>>,
+) {
+    if score.0 == 0 {
+        return;
+    }
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn despawn<M: Component>(mut commands: Commands, query: Query<Entity, With<M>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn draw_prompts(
+    mut commands: Commands,
+    device: Res<LastDevice>,
+    map: Res<ActionMap>,
+    glyphs: Res<Glyphs>,
+    query: Query<(Entity, Ref<Prompt>)>,
+) {
+    for (entity, prompt) in &query {
+        if !device.is_changed() && !map.is_changed() && !prompt.is_added() {
+            continue;
+        }
+
+        commands
+            .entity(entity)
+            .despawn_descendants()
+            .with_children(|parent| {
+                parent.spawn(AtlasImageBundle {
+                    style: Style {
+                        width: Val::Px(GLYPH_SIZE),
+                        height: Val::Px(GLYPH_SIZE),
+                        ..default()
+                    },
+                    image: UiImage::new(glyphs.image.clone()),
+                    texture_atlas: TextureAtlas {
+                        layout: glyphs.layout.clone(),
+                        index: device.0.glyph(),
+                    },
+                    ..default()
+                });
+                parent.spawn(TextBundle::from_section(
+                    format!("{} {}", device.0.verb(&map), prompt.action),
+                    TextStyle {
+                        font_size: prompt.font_size,
+                        color: Color::WHITE,
+                        ..default()
+                    },
+                ));
+            });
+    }
+}
@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+
+use crate::{low_power::LowPower, particles::Particles, OnJumped, Root};
+
+// A couple of little clouds of air left behind by each flap, drifting off
+// back and down and puffing out as they fade
+const PUFF: Particles = Particles {
+    count: 2,
+    color: Color::rgba(1., 1., 1., 0.6),
+    size: Vec2::new(3., 3.),
+    speed: 12.,
+    lifetime: 0.35,
+    angles: (190., 230.),
+    feathers: false,
+    grow: 1.5,
+};
+// Where they start from, off the bird's tail
+const PUFF_OFFSET: Vec2 = Vec2::new(-6., -2.);
+// Just under the bird
+const PUFF_Z: f32 = 3.5;
+
+pub struct PuffsPlugin;
+
+impl Plugin for PuffsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, puff_on_flap);
+    }
+}
+
+fn puff_on_flap(
+    mut commands: Commands,
+    mut reader: EventReader<OnJumped>,
+    power: Res<LowPower>,
+    root: Query<Entity, With<Root>>,
+) {
+    let Ok(root) = root.get_single() else {
+        reader.clear();
+        return;
+    };
+
+    for event in reader.read() {
+        commands.entity(root).with_children(|parent| {
+            PUFF.burst(
+                parent,
+                (event.position + PUFF_OFFSET).extend(PUFF_Z),
+                PUFF.color,
+                power.particles(PUFF.count),
+            );
+        });
+    }
+}
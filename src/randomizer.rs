@@ -0,0 +1,286 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    accessories::{self, Slot},
+    characters::{ActiveCharacter, Characters},
+    profile::Profile,
+    replay::Playback,
+    AppState,
+};
+
+// Seconds each reel spins for before it stops, from left to right
+const REEL_STOPS: [f32; 3] = [0.8, 1.2, 1.6];
+// How many options a reel goes past on its way to the one it stops on
+const REEL_SPIN: f32 = 24.;
+
+/// One of the things being rolled, with every option it could land on
+struct Reel {
+    /// What the profile saves each option as, `None` for wearing nothing
+    ids: Vec<Option<String>>,
+    names: Vec<String>,
+    rolled: usize,
+}
+
+impl Reel {
+    fn roll(options: Vec<(Option<String>, String)>, rng: &mut impl Rng) -> Self {
+        let rolled = rng.gen_range(0..options.len());
+        let (ids, names) = options.into_iter().unzip();
+        Self { ids, names, rolled }
+    }
+
+    /// The option showing `elapsed` seconds in, slowing down until it
+    /// comes to a stop on the rolled one at `stop`
+    fn showing(&self, elapsed: f32, stop: f32) -> usize {
+        let left = (1. - elapsed / stop).max(0.);
+        let steps = (left * left * REEL_SPIN).ceil() as usize;
+        (self.rolled + steps) % self.names.len()
+    }
+
+    fn id(&self) -> Option<String> {
+        self.ids[self.rolled].clone()
+    }
+}
+
+/// A look being rolled on the menu, slot machine style. Stays up once it's
+/// landed until the run starts
+#[derive(Resource)]
+struct Reveal {
+    elapsed: f32,
+    /// The bird, the hat and the scarf, in that order
+    reels: [Reel; 3],
+    landed: bool,
+}
+
+impl Reveal {
+    // Birds with physics of their own are left out, so a run plays the same
+    // whatever it ends up looking like. It's all rolled off to the side of the
+    // game's own random numbers for the same reason
+    fn roll(characters: &Characters, profile: &Profile) -> Option<Self> {
+        let birds = characters
+            .all()
+            .iter()
+            .filter(|character| character.physics.is_none())
+            .map(|character| (Some(character.name.clone()), character.name.clone()))
+            .collect::<Vec<_>>();
+        if birds.is_empty() {
+            return None;
+        }
+
+        let wearable = |slot| {
+            accessories::available(slot, profile)
+                .into_iter()
+                .map(|(id, name)| (Some(id.to_string()), name.to_string()))
+                .chain([(None, "none".to_string())])
+                .collect::<Vec<_>>()
+        };
+
+        let mut rng = rand::thread_rng();
+        Some(Self {
+            elapsed: 0.,
+            reels: [
+                Reel::roll(birds, &mut rng),
+                Reel::roll(wearable(Slot::Hat), &mut rng),
+                Reel::roll(wearable(Slot::Scarf), &mut rng),
+            ],
+            landed: false,
+        })
+    }
+
+    /// Puts the rolled look on the bird, which is what the profile says it wears
+    fn wear(&self, profile: &mut Profile) {
+        let [bird, hat, scarf] = &self.reels;
+        if let Some(name) = bird.id() {
+            profile.character = name;
+        }
+        profile.hat = hat.id();
+        profile.scarf = scarf.id();
+    }
+}
+
+#[derive(Component)]
+struct LookPanel;
+
+pub struct RandomizerPlugin;
+
+impl Plugin for RandomizerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::MainMenu),
+            (roll_look, spawn_panel).run_if(not(resource_exists::<Playback>)),
+        )
+        .add_systems(
+            Update,
+            (
+                toggle_random_look,
+                spin_reels.run_if(resource_exists::<Reveal>),
+                draw_panel,
+            )
+                .chain()
+                .run_if(in_state(AppState::MainMenu).and_then(not(resource_exists::<Playback>))),
+        )
+        .add_systems(OnExit(AppState::MainMenu), (land_now, despawn_panel))
+        // A quick restart goes straight into the next run, so there's no time
+        // to show it off
+        .add_systems(
+            OnEnter(AppState::Restarting),
+            roll_instantly.run_if(not(resource_exists::<Playback>)),
+        );
+    }
+}
+
+fn roll_look(mut commands: Commands, characters: Characters, profile: Res<Profile>) {
+    if !profile.random_look {
+        return;
+    }
+    if let Some(reveal) = Reveal::roll(&characters, &profile) {
+        commands.insert_resource(reveal);
+    }
+}
+
+fn roll_instantly(characters: Characters, mut profile: ResMut<Profile>) {
+    if !profile.random_look {
+        return;
+    }
+    if let Some(reveal) = Reveal::roll(&characters, &profile) {
+        reveal.wear(&mut profile);
+    }
+}
+
+fn toggle_random_look(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    characters: Characters,
+    mut profile: ResMut<Profile>,
+) {
+    if !keys.just_pressed(KeyCode::KeyD) {
+        return;
+    }
+
+    profile.random_look = !profile.random_look;
+    if !profile.random_look {
+        commands.remove_resource::<Reveal>();
+    } else if let Some(reveal) = Reveal::roll(&characters, &profile) {
+        commands.insert_resource(reveal);
+    }
+}
+
+fn spin_reels(
+    mut reveal: ResMut<Reveal>,
+    mut active: ResMut<ActiveCharacter>,
+    mut profile: ResMut<Profile>,
+    characters: Characters,
+    time: Res<Time>,
+) {
+    if reveal.landed {
+        return;
+    }
+    reveal.elapsed += time.delta_seconds();
+    let elapsed = reveal.elapsed;
+
+    // The bird changes color along with the reel
+    let bird = &reveal.reels[0];
+    let showing = &bird.names[bird.showing(elapsed, REEL_STOPS[0])];
+    if active.0.name != *showing {
+        if let Some(character) = characters.all().iter().find(|c| c.name == *showing) {
+            active.0 = character.clone();
+        }
+    }
+
+    if elapsed >= REEL_STOPS[REEL_STOPS.len() - 1] {
+        reveal.wear(&mut profile);
+        reveal.landed = true;
+    }
+}
+
+// Starting a run in the middle of a roll skips to the end of it
+fn land_now(mut commands: Commands, reveal: Option<Res<Reveal>>, mut profile: ResMut<Profile>) {
+    let Some(reveal) = reveal else {
+        return;
+    };
+    if !reveal.landed {
+        reveal.wear(&mut profile);
+    }
+    commands.remove_resource::<Reveal>();
+}
+
+fn spawn_panel(mut commands: Commands) {
+    let section = |size: f32| {
+        TextSection::new(
+            "",
+            TextStyle {
+                font_size: size,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+    };
+
+    commands
+        .spawn((
+            LookPanel,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    position_type: PositionType::Absolute,
+                    top: Val::Percent(68.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_sections([section(12.), section(12.), section(12.), section(10.)])
+                    .with_text_justify(JustifyText::Center),
+            );
+        });
+}
+
+fn despawn_panel(mut commands: Commands, query: Query<Entity, With<LookPanel>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn draw_panel(
+    reveal: Option<Res<Reveal>>,
+    profile: Res<Profile>,
+    panel: Query<&Children, With<LookPanel>>,
+    added: Query<(), Added<LookPanel>>,
+    mut text: Query<&mut Text>,
+) {
+    // Every frame while the reels are spinning, otherwise only when there's
+    // something new to show. Turning it off changes the profile too
+    let spinning = reveal.as_ref().is_some_and(|reveal| reveal.is_changed());
+    if !spinning && !profile.is_changed() && added.is_empty() {
+        return;
+    }
+
+    for children in &panel {
+        let mut texts = text.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            let [bird, hat, scarf, toggle] = &mut text.sections[..] else {
+                continue;
+            };
+
+            for (section, (i, stop)) in [bird, hat, scarf]
+                .into_iter()
+                .zip(REEL_STOPS.into_iter().enumerate())
+            {
+                let Some(reveal) = &reveal else {
+                    section.value.clear();
+                    continue;
+                };
+                let reel = &reveal.reels[i];
+                let stopped = reveal.elapsed >= stop;
+                section.value = format!("[ {} ] ", reel.names[reel.showing(reveal.elapsed, stop)]);
+                section.style.color = if stopped { Color::YELLOW } else { Color::WHITE };
+            }
+
+            let state = if profile.random_look { "on" } else { "off" };
+            toggle.value = format!("\nD random look: {state}");
+        }
+    }
+}
@@ -0,0 +1,265 @@
+use std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    modes::{mode_is, AddGameMode, GameMode, ModeInfo},
+    profile::Profile,
+    replay::Playback,
+    tutorials::{AddTutorial, Tutorial, BETWEEN_PIPES},
+    AppState, Atlas, RunModifiers, Score,
+};
+
+const RANKED: &str = "ranked";
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+// Runs a season starts with before there's a rating to show
+const PLACEMENT_RUNS: u32 = 5;
+// How many of the latest runs the rating is worked out from
+const ROLLING_RUNS: usize = 10;
+// The best runs out of those that count towards it
+const BEST_OF: usize = 3;
+// The rating each tier starts at, from the bottom up
+const TIERS: [(u32, &str, Color); 5] = [
+    (0, "Bronze", Color::rgb(0.8, 0.5, 0.2)),
+    (500, "Silver", Color::SILVER),
+    (1500, "Gold", Color::GOLD),
+    (3000, "Platinum", Color::rgb(0.6, 0.9, 0.9)),
+    (5000, "Diamond", Color::rgb(0.7, 0.8, 1.)),
+];
+
+/// A calendar month, UTC. Ratings start over with every new one
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Season {
+    year: i64,
+    month: u32,
+}
+
+impl Season {
+    fn now() -> Self {
+        let days = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64 / SECONDS_PER_DAY)
+            .unwrap_or_default();
+
+        // Days since 1970 to a date, counting years from March so leap days
+        // fall at the end of them
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_from_march = (5 * day_of_year + 2) / 153;
+        let month = if month_from_march < 10 {
+            month_from_march + 3
+        } else {
+            month_from_march - 9
+        };
+
+        Self {
+            year: year_of_era + era * 400 + i64::from(month <= 2),
+            month: month as u32,
+        }
+    }
+}
+
+impl fmt::Display for Season {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{:02}", self.year, self.month)
+    }
+}
+
+/// How a season that's over went
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SeasonResult {
+    pub season: Season,
+    /// Left out if the placement runs were never finished
+    pub rating: Option<u32>,
+    pub runs: u32,
+}
+
+/// How the player's doing in the current season, and how they did in the
+/// ones before it
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(default)]
+pub struct RankedRecord {
+    season: Option<Season>,
+    runs: u32,
+    /// Scores of the latest runs, oldest first
+    recent: Vec<u32>,
+    history: Vec<SeasonResult>,
+}
+
+impl RankedRecord {
+    /// The best few of the latest runs averaged, once the placement runs are done
+    fn rating(&self) -> Option<u32> {
+        if self.runs < PLACEMENT_RUNS || self.recent.is_empty() {
+            return None;
+        }
+        let mut best = self.recent.clone();
+        best.sort_by(|a, b| b.cmp(a));
+        best.truncate(BEST_OF);
+        Some(best.iter().sum::<u32>() * 100 / best.len() as u32)
+    }
+
+    fn record(&mut self, score: u32) {
+        self.runs += 1;
+        self.recent.push(score);
+        if self.recent.len() > ROLLING_RUNS {
+            self.recent.remove(0);
+        }
+    }
+
+    /// Puts the season that's going away in the history and starts a new one
+    fn start_season(&mut self, season: Season) {
+        if let Some(old) = self.season.filter(|_| self.runs > 0) {
+            self.history.push(SeasonResult {
+                season: old,
+                rating: self.rating(),
+                runs: self.runs,
+            });
+        }
+        self.season = Some(season);
+        self.runs = 0;
+        self.recent.clear();
+    }
+}
+
+fn tier(rating: u32) -> (&'static str, Color) {
+    TIERS
+        .iter()
+        .rev()
+        .find(|(from, ..)| rating >= *from)
+        .map_or((TIERS[0].1, TIERS[0].2), |(_, name, color)| (*name, *color))
+}
+
+struct Ranked;
+
+impl GameMode for Ranked {
+    fn info(&self) -> ModeInfo {
+        ModeInfo {
+            id: RANKED,
+            name: "Ranked",
+            blurb: "Climb this month's ladder",
+            preview: Atlas::Digit1,
+            color: Color::rgb(0.55, 0.2, 0.5),
+            seed: None,
+        }
+    }
+
+    fn setup(&self, app: &mut App) {
+        // Leaving a run any way at all counts, so a bad one can't be thrown
+        // away with a quick restart
+        app.add_systems(
+            OnExit(AppState::Playing),
+            record_run.run_if(mode_is(RANKED).and_then(not(resource_exists::<Playback>))),
+        );
+    }
+}
+
+#[derive(Component)]
+struct RatingBadge;
+
+pub struct RankedPlugin;
+
+impl Plugin for RankedPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_game_mode(Ranked)
+            .add_tutorial(Tutorial {
+                id: RANKED,
+                title: "Ranked",
+                text: "Every run moves you up or down this month's ladder, which starts over next month",
+                art: Atlas::Digit1,
+                example: BETWEEN_PIPES,
+            })
+            .add_systems(
+                OnEnter(AppState::MainMenu),
+                (roll_season, spawn_badge)
+                    .chain()
+                    .run_if(not(resource_exists::<Playback>)),
+            )
+            .add_systems(OnExit(AppState::MainMenu), despawn_badge);
+    }
+}
+
+// Checked every time the menu comes up, so a season can end while the game's open
+fn roll_season(mut profile: ResMut<Profile>) {
+    let season = Season::now();
+    if profile.ranked.season != Some(season) {
+        profile.ranked.start_season(season);
+    }
+}
+
+// Only runs with nothing else changed are ranked, so every rating is earned
+// the same way
+fn record_run(mut profile: ResMut<Profile>, modifiers: Res<RunModifiers>, score: Res<Score>) {
+    let ranked = RunModifiers {
+        mode: RANKED.to_string(),
+        ..default()
+    };
+    if *modifiers != ranked {
+        info!("Ranked run not counted, it was played with modifiers");
+        return;
+    }
+
+    let ranked = &mut profile.ranked;
+    ranked.record(score.0);
+    match ranked.rating() {
+        Some(rating) => info!("Ranked rating {rating}"),
+        None => info!("Placement run {}/{PLACEMENT_RUNS}", ranked.runs),
+    }
+}
+
+fn spawn_badge(mut commands: Commands, profile: Res<Profile>) {
+    let ranked = &profile.ranked;
+    let season = ranked
+        .season
+        .map(|season| season.to_string())
+        .unwrap_or_default();
+    let (standing, color) = match ranked.rating() {
+        Some(rating) => {
+            let (name, color) = tier(rating);
+            (format!("{name} {rating}"), color)
+        }
+        None => (
+            format!("Placement {}/{PLACEMENT_RUNS}", ranked.runs),
+            Color::WHITE,
+        ),
+    };
+    let last = match ranked.history.last() {
+        Some(&SeasonResult {
+            rating: Some(rating),
+            ..
+        }) => format!("\nLast season {} {rating}", tier(rating).0),
+        _ => String::new(),
+    };
+
+    commands.spawn((
+        RatingBadge,
+        TextBundle::from_section(
+            format!("Ranked {season}\n{standing}{last}"),
+            TextStyle {
+                font_size: 10.,
+                color,
+                ..default()
+            },
+        )
+        .with_text_justify(JustifyText::Right)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(64.),
+            right: Val::Px(8.),
+            ..default()
+        }),
+    ));
+}
+
+fn despawn_badge(mut commands: Commands, query: Query<Entity, With<RatingBadge>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
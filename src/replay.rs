@@ -0,0 +1,245 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actions::{Action, Actions},
+    bots::OpponentRecord,
+    config::{ActiveConfig, GameConfig, PendingConfig},
+    curve::{ActiveCurve, DifficultyCurve},
+    difficulty::Difficulty,
+    hitboxes::{ActiveHitboxes, Hitboxes},
+    physics::{ActivePhysics, PendingPhysics, PhysicsPreset},
+    retention::ReplayIndex,
+    save,
+    snapshot::OnSnapshotRestored,
+    AppState, GameRng, NextSeed, OnJumped, QueuedFlap, RunModifiers, Score, SimSet, SimTick,
+    StepOffset,
+};
+
+pub const REPLAY_DIR: &str = "replays";
+
+/// Everything needed to play a run back the way it happened
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Replay {
+    pub seed: u64,
+    pub modifiers: RunModifiers,
+    pub gap_adjustment: f32,
+    pub curve: DifficultyCurve,
+    /// Missing from replays that were recorded before there were presets,
+    /// which all flew with the classic one
+    #[serde(default)]
+    pub physics: PhysicsPreset,
+    /// Missing from replays that were recorded before there was a config
+    /// file, which all played with the defaults
+    #[serde(default)]
+    pub config: GameConfig,
+    /// Missing from replays that were recorded before the hitboxes were in a
+    /// file, which all had the defaults
+    #[serde(default)]
+    pub hitboxes: Hitboxes,
+    /// Edits to the physics and the config that applied partway through the
+    /// run, with the step each one applied on
+    #[serde(default)]
+    pub physics_changes: Vec<(u64, PhysicsPreset)>,
+    #[serde(default)]
+    pub config_changes: Vec<(u64, GameConfig)>,
+    /// The simulation steps the player flapped on
+    pub flaps: Vec<u64>,
+    /// How far into its step each flap happened, missing from replays that
+    /// were recorded before flaps were timed within a step
+    #[serde(default)]
+    pub offsets: Vec<f32>,
+    pub score: u32,
+    /// The bot that raced the player, missing from replays that were
+    /// recorded before there were bots
+    #[serde(default)]
+    pub opponent: Option<OpponentRecord>,
+}
+
+/// The run that's being played right now
+#[derive(Resource)]
+pub struct Recording(pub Replay);
+
+/// Present while watching a replay instead of playing
+#[derive(Resource)]
+pub struct Playback {
+    pub replay: Replay,
+    next_flap: usize,
+    /// The player's own modifiers, put back once the replay is over
+    modifiers: RunModifiers,
+}
+
+impl Playback {
+    /// Sets up the next world to be the one `replay` was recorded in
+    pub fn watch(replay: Replay, modifiers: &mut RunModifiers, next_seed: &mut NextSeed) -> Self {
+        next_seed.0 = Some(replay.seed);
+        let modifiers = std::mem::replace(modifiers, replay.modifiers.clone());
+        Self {
+            replay,
+            next_flap: 0,
+            modifiers,
+        }
+    }
+}
+
+/// A fresh name for a replay in the save directory
+pub fn replay_name() -> String {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+    format!("{REPLAY_DIR}/{millis}.ron")
+}
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::Playing),
+            start_recording.run_if(not(resource_exists::<Playback>)),
+        )
+        .add_systems(
+            FixedUpdate,
+            (
+                (feed_playback, feed_changes)
+                    .before(SimSet::Input)
+                    .run_if(in_state(AppState::Playing).and_then(resource_exists::<Playback>)),
+                record_flaps.after(SimSet::Input).before(SimSet::Physics),
+            ),
+        )
+        .add_systems(
+            OnEnter(AppState::GameOver),
+            save_recording.run_if(resource_exists::<Recording>),
+        )
+        .add_systems(
+            OnEnter(AppState::LevelComplete),
+            save_recording.run_if(resource_exists::<Recording>),
+        )
+        .add_systems(Update, discard_recording)
+        .add_systems(
+            Update,
+            (
+                start_playback.run_if(in_state(AppState::MainMenu)),
+                stop_playback.run_if(
+                    in_state(AppState::GameOver).or_else(in_state(AppState::LevelComplete)),
+                ),
+            )
+                .run_if(resource_exists::<Playback>),
+        );
+    }
+}
+
+/// Starts recording the run that's just begun
+pub fn start_recording(
+    mut commands: Commands,
+    rng: Res<GameRng>,
+    modifiers: Res<RunModifiers>,
+    difficulty: Res<Difficulty>,
+    curve: Res<ActiveCurve>,
+    physics: Res<ActivePhysics>,
+    config: Res<ActiveConfig>,
+    hitboxes: Res<ActiveHitboxes>,
+) {
+    commands.insert_resource(Recording(Replay {
+        seed: rng.seed,
+        modifiers: modifiers.clone(),
+        gap_adjustment: difficulty.gap_adjustment,
+        curve: curve.0.clone(),
+        physics: physics.0.clone(),
+        config: config.0,
+        hitboxes: hitboxes.0,
+        physics_changes: Vec::new(),
+        config_changes: Vec::new(),
+        flaps: Vec::new(),
+        offsets: Vec::new(),
+        score: 0,
+        opponent: None,
+    }));
+}
+
+fn record_flaps(
+    recording: Option<ResMut<Recording>>,
+    mut reader: EventReader<OnJumped>,
+    tick: Res<SimTick>,
+    offset: Res<StepOffset>,
+) {
+    // Always read so no stale flaps are left over for the next recording
+    let flapped = reader.read().count() > 0;
+    if let (true, Some(mut recording)) = (flapped, recording) {
+        recording.0.flaps.push(tick.0);
+        recording.0.offsets.push(offset.0);
+    }
+}
+
+fn save_recording(
+    mut commands: Commands,
+    recording: Res<Recording>,
+    score: Res<Score>,
+    mut index: ResMut<ReplayIndex>,
+) {
+    let mut replay = recording.0.clone();
+    replay.score = score.0;
+    commands.remove_resource::<Recording>();
+
+    let name = replay_name();
+    match save::store(&name, &replay) {
+        Ok(()) => index.add(name, &replay),
+        Err(error) => warn!("Couldn't save replay: {error}"),
+    }
+}
+
+// A run that's been rewound can't be played back from its seed and flaps alone
+fn discard_recording(mut commands: Commands, mut reader: EventReader<OnSnapshotRestored>) {
+    if reader.read().count() > 0 {
+        commands.remove_resource::<Recording>();
+    }
+}
+
+fn feed_playback(
+    mut playback: ResMut<Playback>,
+    mut queued: ResMut<QueuedFlap>,
+    tick: Res<SimTick>,
+) {
+    let next = playback.next_flap;
+    if playback.replay.flaps.get(next) == Some(&tick.0) {
+        queued.0 = Some(playback.replay.offsets.get(next).copied().unwrap_or(0.));
+        playback.next_flap += 1;
+    }
+}
+
+// Lined up to apply on the same steps they did when the run was recorded
+fn feed_changes(
+    playback: Res<Playback>,
+    mut physics: ResMut<PendingPhysics>,
+    mut config: ResMut<PendingConfig>,
+    tick: Res<SimTick>,
+) {
+    let replay = &playback.replay;
+    if let Some((_, preset)) = replay.physics_changes.iter().find(|(at, _)| *at == tick.0) {
+        physics.0 = Some(preset.clone());
+    }
+    if let Some((_, changed)) = replay.config_changes.iter().find(|(at, _)| *at == tick.0) {
+        config.0 = Some(*changed);
+    }
+}
+
+fn start_playback(mut state: ResMut<NextState<AppState>>) {
+    state.set(AppState::Playing);
+}
+
+fn stop_playback(
+    mut commands: Commands,
+    playback: Res<Playback>,
+    mut modifiers: ResMut<RunModifiers>,
+    mut state: ResMut<NextState<AppState>>,
+    mut actions: ResMut<Actions>,
+) {
+    if actions.take(Action::Flap) {
+        *modifiers = playback.modifiers.clone();
+        commands.remove_resource::<Playback>();
+        state.set(AppState::Replays);
+    }
+}
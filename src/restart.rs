@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+
+use crate::{
+    actions::{Action, ActionMap, Actions},
+    bookmarks::Draft,
+    build_world,
+    prompts::{LastDevice, Prompt},
+    replay::Playback,
+    AppState, GameRng, NextSeed, QueuedFlap,
+};
+
+// How long (in seconds) the screen takes to go dark, and to come back again
+const FADE_DURATION: f32 = 0.15;
+
+#[derive(Component)]
+struct RestartHint;
+
+/// A black screen over everything while a quick restart happens
+#[derive(Component)]
+struct Fade(Timer);
+
+pub struct RestartPlugin;
+
+impl Plugin for RestartPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::GameOver),
+            spawn_hint.run_if(not(resource_exists::<Playback>)),
+        )
+        .add_systems(OnExit(AppState::GameOver), despawn_hint)
+        .add_systems(
+            Update,
+            // Typing a bookmark name takes the keys for itself
+            retry_seed.run_if(
+                in_state(AppState::GameOver)
+                    .and_then(not(resource_exists::<Playback>))
+                    .and_then(not(resource_exists::<Draft>)),
+            ),
+        )
+        .add_systems(
+            Update,
+            quick_restart.run_if(
+                in_state(AppState::Playing)
+                    .or_else(in_state(AppState::GameOver))
+                    .and_then(not(resource_exists::<Playback>))
+                    .and_then(not(resource_exists::<Draft>)),
+            ),
+        )
+        .add_systems(OnEnter(AppState::Restarting), spawn_fade)
+        .add_systems(
+            Update,
+            (
+                fade_out.run_if(in_state(AppState::Restarting)),
+                fade_in.run_if(not(in_state(AppState::Restarting))),
+            ),
+        );
+    }
+}
+
+/// Lays out the world again and starts playing in it right away
+fn start_next_run(
+    commands: &mut Commands,
+    state: &mut NextState<AppState>,
+    queued: &mut QueuedFlap,
+) {
+    commands.add(build_world);
+    state.set(AppState::Playing);
+    // Same as starting from the menu, the run starts with a flap
+    queued.0 = Some(0.);
+}
+
+// Goes again on the same pipes without going through the main menu
+fn retry_seed(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    rng: Res<GameRng>,
+    mut next_seed: ResMut<NextSeed>,
+    mut state: ResMut<NextState<AppState>>,
+    mut queued: ResMut<QueuedFlap>,
+) {
+    if keys.just_pressed(KeyCode::KeyT) {
+        next_seed.0 = Some(rng.seed);
+        start_next_run(&mut commands, &mut state, &mut queued);
+    }
+}
+
+// Throws the run away for a new one, whether it's over yet or not
+fn quick_restart(mut actions: ResMut<Actions>, mut state: ResMut<NextState<AppState>>) {
+    if actions.take(Action::Restart) {
+        state.set(AppState::Restarting);
+    }
+}
+
+fn spawn_fade(mut commands: Commands, mut query: Query<&mut Fade>) {
+    // Restarting again while still fading in goes dark from where it was
+    if let Ok(mut fade) = query.get_single_mut() {
+        let remaining = fade.0.remaining();
+        fade.0.reset();
+        fade.0.set_elapsed(remaining);
+        return;
+    }
+
+    commands.spawn((
+        Fade(Timer::from_seconds(FADE_DURATION, TimerMode::Once)),
+        NodeBundle {
+            style: Style {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            background_color: Color::NONE.into(),
+            z_index: ZIndex::Global(i32::MAX),
+            ..default()
+        },
+    ));
+}
+
+fn fade_out(
+    mut commands: Commands,
+    mut query: Query<(&mut Fade, &mut BackgroundColor)>,
+    mut state: ResMut<NextState<AppState>>,
+    mut queued: ResMut<QueuedFlap>,
+    time: Res<Time>,
+) {
+    for (mut fade, mut color) in &mut query {
+        fade.0.tick(time.delta());
+        color.0 = Color::BLACK.with_a(fade.0.fraction());
+
+        if fade.0.just_finished() {
+            // The same timer is used again to fade back in
+            fade.0.reset();
+            start_next_run(&mut commands, &mut state, &mut queued);
+        }
+    }
+}
+
+fn fade_in(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Fade, &mut BackgroundColor)>,
+    time: Res<Time>,
+) {
+    for (entity, mut fade, mut color) in &mut query {
+        fade.0.tick(time.delta());
+        color.0 = Color::BLACK.with_a(fade.0.fraction_remaining());
+
+        if fade.0.finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn spawn_hint(mut commands: Commands, map: Res<ActionMap>, device: Res<LastDevice>) {
+    let restart = map
+        .first(Action::Restart, device.0)
+        .map_or(String::new(), |button| {
+            format!("   {} new run", button.name())
+        });
+
+    commands
+        .spawn((
+            RestartHint,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(48.),
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(Prompt::new("for menu").bundle());
+            parent.spawn(TextBundle::from_section(
+                format!("{restart}   T retry seed"),
+                TextStyle {
+                    font_size: 12.,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn despawn_hint(mut commands: Commands, query: Query<Entity, With<RestartHint>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
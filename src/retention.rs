@@ -0,0 +1,153 @@
+use std::collections::{HashMap, HashSet};
+
+use bevy::{prelude::*, tasks::IoTaskPool};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    replay::{Replay, REPLAY_DIR},
+    save, RunModifiers,
+};
+
+const INDEX_FILE: &str = "replays.ron";
+
+/// How many replays are kept around, edited by hand in the index file
+#[derive(Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct Retention {
+    /// The best scoring runs kept for every combination of modifiers
+    pub best_per_mode: usize,
+    /// The latest runs kept no matter how they went
+    pub recent: usize,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            best_per_mode: 5,
+            recent: 10,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct IndexEntry {
+    pub name: String,
+    pub score: u32,
+    pub modifiers: RunModifiers,
+}
+
+/// Every replay in the save directory, oldest first
+#[derive(Resource, Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct ReplayIndex {
+    pub retention: Retention,
+    pub replays: Vec<IndexEntry>,
+    /// Replays that were dropped from the index but are still on disk
+    #[serde(skip)]
+    pruned: Vec<String>,
+}
+
+impl ReplayIndex {
+    /// Builds the index from whatever is in the replay folder, for saves from
+    /// before there was one
+    fn rebuild() -> Self {
+        let mut index = ReplayIndex::default();
+        let paths = match save::list(REPLAY_DIR) {
+            Ok(paths) => paths,
+            Err(error) => {
+                warn!("Couldn't list replays: {error}");
+                return index;
+            }
+        };
+
+        for path in paths {
+            let Some(file) = path.file_name() else {
+                continue;
+            };
+            match save::load_path::<Replay>(&path) {
+                Ok(replay) => {
+                    index.add(format!("{REPLAY_DIR}/{}", file.to_string_lossy()), &replay)
+                }
+                Err(error) => warn!("Skipping replay {}: {error}", path.display()),
+            }
+        }
+        index
+    }
+
+    pub fn add(&mut self, name: String, replay: &Replay) {
+        self.replays.push(IndexEntry {
+            name,
+            score: replay.score,
+            modifiers: replay.modifiers.clone(),
+        });
+        self.prune();
+    }
+
+    /// Drops everything the retention settings don't cover, the files are
+    /// deleted later on
+    fn prune(&mut self) {
+        let recent = self.replays.len().saturating_sub(self.retention.recent);
+        let mut keep = (recent..self.replays.len()).collect::<HashSet<_>>();
+
+        let mut modes = HashMap::<_, Vec<_>>::new();
+        for (i, entry) in self.replays.iter().enumerate() {
+            modes.entry(&entry.modifiers).or_default().push(i);
+        }
+        for mut runs in modes.into_values() {
+            // Newer runs win ties
+            runs.sort_by_key(|&i| std::cmp::Reverse((self.replays[i].score, i)));
+            keep.extend(runs.into_iter().take(self.retention.best_per_mode));
+        }
+
+        let mut i = 0;
+        let pruned = &mut self.pruned;
+        self.replays.retain(|entry| {
+            let kept = keep.contains(&i);
+            i += 1;
+            if !kept {
+                pruned.push(entry.name.clone());
+            }
+            kept
+        });
+    }
+}
+
+pub struct RetentionPlugin;
+
+impl Plugin for RetentionPlugin {
+    fn build(&self, app: &mut App) {
+        let mut index = match save::load::<ReplayIndex>(INDEX_FILE) {
+            Ok(Some(index)) => index,
+            Ok(None) => ReplayIndex::rebuild(),
+            Err(error) => {
+                warn!("Couldn't load replay index, rebuilding it: {error}");
+                ReplayIndex::rebuild()
+            }
+        };
+        // The retention settings might have been changed since the last run
+        index.prune();
+
+        app.insert_resource(index)
+            .add_systems(Last, save_index.run_if(resource_changed::<ReplayIndex>));
+    }
+}
+
+fn save_index(mut index: ResMut<ReplayIndex>) {
+    // Clearing out the deleted replays shouldn't count as another change
+    let pruned = std::mem::take(&mut index.bypass_change_detection().pruned);
+    if !pruned.is_empty() {
+        IoTaskPool::get()
+            .spawn(async move {
+                for name in pruned {
+                    if let Err(error) = save::remove(&name) {
+                        warn!("Couldn't delete replay {name}: {error}");
+                    }
+                }
+            })
+            .detach();
+    }
+
+    if let Err(error) = save::store(INDEX_FILE, index.as_ref()) {
+        warn!("Couldn't save replay index: {error}");
+    }
+}
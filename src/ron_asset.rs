@@ -0,0 +1,66 @@
+use std::{fmt, io, marker::PhantomData};
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::de::DeserializeOwned;
+
+#[derive(Debug)]
+pub enum RonAssetError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+}
+
+impl fmt::Display for RonAssetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RonAssetError::Io(error) => write!(f, "{error}"),
+            RonAssetError::Parse(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for RonAssetError {}
+
+/// Loads any asset that's written by hand as a RON file
+pub struct RonLoader<A> {
+    extensions: &'static [&'static str],
+    asset: PhantomData<fn() -> A>,
+}
+
+impl<A> RonLoader<A> {
+    pub fn new(extensions: &'static [&'static str]) -> Self {
+        Self {
+            extensions,
+            asset: PhantomData,
+        }
+    }
+}
+
+impl<A: Asset + DeserializeOwned> AssetLoader for RonLoader<A> {
+    type Asset = A;
+    type Settings = ();
+    type Error = RonAssetError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<A, RonAssetError>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader
+                .read_to_end(&mut bytes)
+                .await
+                .map_err(RonAssetError::Io)?;
+            ron::de::from_bytes(&bytes).map_err(RonAssetError::Parse)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+}
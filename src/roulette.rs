@@ -0,0 +1,301 @@
+use std::f32::consts::TAU;
+
+use bevy::{
+    math::bounding::{Aabb2d, BoundingVolume},
+    prelude::*,
+};
+use rand::Rng;
+
+use crate::{
+    snapshot::OnSnapshotRestored, AppState, Collider, GameRng, Passed, Player, Root, Score, SimSet,
+    SimTick, Velocity, SIM_HZ,
+};
+
+const ROULETTE_EVERY: u32 = 40;
+const SPIN_DURATION: f32 = 1.5;
+// How long each name stays up while the roulette is spinning
+const SPIN_FLICKER: f32 = 0.1;
+const MODIFIER_PIPES: u32 = 10;
+const WIND_STRENGTH: f32 = 300.;
+// Seconds between one gust and the next one blowing the same way
+const WIND_PERIOD: f32 = 2.;
+const TINY_SCALE: f32 = 0.5;
+
+/// A short lived twist on the rules that the roulette lands on
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Modifier {
+    /// Gusts push the bird up and down
+    Wind,
+    /// The bird and its hitbox are half the size
+    TinyBird,
+    /// Every pipe is worth two points
+    DoubleScore,
+    /// The lights go out
+    Night,
+}
+
+impl Modifier {
+    const ALL: [Modifier; 4] = [
+        Modifier::Wind,
+        Modifier::TinyBird,
+        Modifier::DoubleScore,
+        Modifier::Night,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Modifier::Wind => "Wind",
+            Modifier::TinyBird => "Tiny bird",
+            Modifier::DoubleScore => "Double score",
+            Modifier::Night => "Night",
+        }
+    }
+}
+
+struct Spin {
+    result: Modifier,
+    timer: Timer,
+}
+
+#[derive(Resource)]
+pub struct Roulette {
+    next_at: u32,
+    spin: Option<Spin>,
+    active: Option<Modifier>,
+    pipes_left: u32,
+}
+
+impl Default for Roulette {
+    fn default() -> Self {
+        Self {
+            next_at: ROULETTE_EVERY,
+            spin: None,
+            active: None,
+            pipes_left: 0,
+        }
+    }
+}
+
+impl Roulette {
+    pub fn active(&self) -> Option<Modifier> {
+        self.active
+    }
+}
+
+#[derive(Event)]
+enum ModifierEvent {
+    Started(Modifier),
+    Ended(Modifier),
+}
+
+#[derive(Component)]
+struct RouletteLabel;
+
+#[derive(Component)]
+struct NightOverlay;
+
+pub struct RoulettePlugin;
+
+impl Plugin for RoulettePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Roulette>()
+            .add_event::<ModifierEvent>()
+            .add_systems(OnEnter(AppState::Playing), (reset_roulette, spawn_label))
+            .add_systems(OnExit(AppState::Playing), despawn_label)
+            .add_systems(
+                FixedUpdate,
+                (
+                    blow_wind.in_set(SimSet::Physics),
+                    (
+                        catch_up_roulette,
+                        start_spin,
+                        finish_spin,
+                        count_pipes,
+                        apply_modifiers,
+                    )
+                        .chain()
+                        .in_set(SimSet::Rules),
+                )
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(Update, draw_label.run_if(in_state(AppState::Playing)));
+    }
+}
+
+fn reset_roulette(mut roulette: ResMut<Roulette>) {
+    *roulette = Roulette::default();
+}
+
+// After a snapshot the next spin is wherever the score says it should be
+fn catch_up_roulette(
+    mut roulette: ResMut<Roulette>,
+    score: Res<Score>,
+    mut reader: EventReader<OnSnapshotRestored>,
+) {
+    if reader.read().count() > 0 {
+        roulette.next_at = (score.0 / ROULETTE_EVERY + 1) * ROULETTE_EVERY;
+    }
+}
+
+fn start_spin(mut roulette: ResMut<Roulette>, score: Res<Score>, mut rng: ResMut<GameRng>) {
+    if score.0 < roulette.next_at || roulette.spin.is_some() {
+        return;
+    }
+
+    roulette.next_at += ROULETTE_EVERY;
+    // Picked up front so the spin itself is only for show
+    let result = Modifier::ALL[rng.gen_range(0..Modifier::ALL.len())];
+    roulette.spin = Some(Spin {
+        result,
+        timer: Timer::from_seconds(SPIN_DURATION, TimerMode::Once),
+    });
+}
+
+fn finish_spin(
+    mut roulette: ResMut<Roulette>,
+    mut writer: EventWriter<ModifierEvent>,
+    time: Res<Time>,
+) {
+    let Some(spin) = &mut roulette.spin else {
+        return;
+    };
+    if !spin.timer.tick(time.delta()).finished() {
+        return;
+    }
+
+    let result = spin.result;
+    roulette.spin = None;
+    if let Some(previous) = roulette.active.replace(result) {
+        writer.send(ModifierEvent::Ended(previous));
+    }
+    roulette.pipes_left = MODIFIER_PIPES;
+    writer.send(ModifierEvent::Started(result));
+}
+
+fn count_pipes(
+    mut roulette: ResMut<Roulette>,
+    passed: Query<(), Added<Passed>>,
+    mut writer: EventWriter<ModifierEvent>,
+) {
+    let Some(active) = roulette.active else {
+        return;
+    };
+
+    roulette.pipes_left = roulette
+        .pipes_left
+        .saturating_sub(passed.iter().count() as u32);
+    if roulette.pipes_left == 0 {
+        roulette.active = None;
+        writer.send(ModifierEvent::Ended(active));
+    }
+}
+
+fn apply_modifiers(
+    mut commands: Commands,
+    mut reader: EventReader<ModifierEvent>,
+    mut player: Query<(&mut Transform, &mut Collider), With<Player>>,
+    night: Query<Entity, With<NightOverlay>>,
+    root: Query<Entity, With<Root>>,
+) {
+    let (mut transform, mut collider) = player.single_mut();
+    for event in reader.read() {
+        match *event {
+            ModifierEvent::Started(Modifier::TinyBird) => {
+                transform.scale = Vec3::splat(TINY_SCALE);
+                collider.0 = Aabb2d::new(collider.0.center(), collider.0.half_size() * TINY_SCALE);
+            }
+            ModifierEvent::Ended(Modifier::TinyBird) => {
+                transform.scale = Vec3::ONE;
+                collider.0 = Aabb2d::new(collider.0.center(), collider.0.half_size() / TINY_SCALE);
+            }
+            ModifierEvent::Started(Modifier::Night) => {
+                commands.entity(root.single()).with_children(|parent| {
+                    parent.spawn((
+                        NightOverlay,
+                        SpriteBundle {
+                            sprite: Sprite {
+                                color: Color::rgba(0., 0., 0.1, 0.6),
+                                custom_size: Some(Vec2::new(144., 256.)),
+                                ..default()
+                            },
+                            transform: Transform::from_translation(Vec3::new(0., 0., 8.)),
+                            ..default()
+                        },
+                    ));
+                });
+            }
+            ModifierEvent::Ended(Modifier::Night) => {
+                for entity in &night {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+            // The rest are checked for wherever they apply
+            ModifierEvent::Started(_) | ModifierEvent::Ended(_) => {}
+        }
+    }
+}
+
+fn blow_wind(
+    roulette: Res<Roulette>,
+    mut query: Query<&mut Velocity, With<Player>>,
+    tick: Res<SimTick>,
+    time: Res<Time>,
+) {
+    if roulette.active != Some(Modifier::Wind) {
+        return;
+    }
+
+    // Driven by the simulation step so replays blow the same way
+    let phase = tick.0 as f32 / SIM_HZ as f32 / WIND_PERIOD * TAU;
+    for mut velocity in &mut query {
+        velocity.0 += phase.sin() * WIND_STRENGTH * time.delta_seconds();
+    }
+}
+
+fn spawn_label(mut commands: Commands) {
+    commands.spawn((
+        RouletteLabel,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 16.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(8.),
+            right: Val::Px(8.),
+            ..default()
+        }),
+    ));
+}
+
+fn despawn_label(mut commands: Commands, query: Query<Entity, With<RouletteLabel>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn draw_label(roulette: Res<Roulette>, mut query: Query<&mut Text, With<RouletteLabel>>) {
+    let label = match (&roulette.spin, roulette.active) {
+        (Some(spin), _) => {
+            // Flick through the names and settle on the result for the last bit
+            let remaining = spin.timer.remaining_secs();
+            let shown = if remaining < SPIN_FLICKER * 3. {
+                spin.result
+            } else {
+                let flick = (spin.timer.elapsed_secs() / SPIN_FLICKER) as usize;
+                Modifier::ALL[flick % Modifier::ALL.len()]
+            };
+            format!("? {} ?", shown.name())
+        }
+        (None, Some(active)) => format!("{} x{}", active.name(), roulette.pipes_left),
+        (None, None) => String::new(),
+    };
+
+    for mut text in &mut query {
+        text.sections[0].value.clone_from(&label);
+    }
+}
@@ -0,0 +1,156 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::ecs::event::Event;
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::determinism;
+
+/// Asks for everything that's saved when it changes to be written out now,
+/// changed or not
+#[derive(Event)]
+pub struct FlushSaves;
+
+#[derive(Debug)]
+pub enum SaveError {
+    NoSaveDir,
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Serialize(ron::Error),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::NoSaveDir => write!(f, "no save directory on this platform"),
+            SaveError::Io(error) => write!(f, "{error}"),
+            SaveError::Parse(error) => write!(f, "{error}"),
+            SaveError::Serialize(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(error: io::Error) -> Self {
+        SaveError::Io(error)
+    }
+}
+
+// Batch simulations play thousands of games that aren't the player's, and the
+// determinism check's shadow plays every run over again, none of which should
+// end up in their saves
+fn dry_run() -> bool {
+    std::env::args().any(|arg| arg == "--simulate") || determinism::is_shadow()
+}
+
+fn save_path(name: &str) -> Result<PathBuf, SaveError> {
+    let dir = dirs::data_dir().ok_or(SaveError::NoSaveDir)?;
+    Ok(dir.join("flappy-potato").join(name))
+}
+
+// Settings go where the platform keeps them, apart from the player's progress
+fn config_path(name: &str) -> Result<PathBuf, SaveError> {
+    let dir = dirs::config_dir().ok_or(SaveError::NoSaveDir)?;
+    Ok(dir.join("flappy-potato").join(name))
+}
+
+fn load_if_written<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, SaveError> {
+    match load_path(path) {
+        Ok(value) => Ok(Some(value)),
+        Err(SaveError::Io(error)) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error),
+    }
+}
+
+/// Reads `name` from the save directory, `Ok(None)` if it was never written
+pub fn load<T: DeserializeOwned>(name: &str) -> Result<Option<T>, SaveError> {
+    load_if_written(&save_path(name)?)
+}
+
+pub fn store<T: Serialize>(name: &str, value: &T) -> Result<(), SaveError> {
+    if dry_run() {
+        return Ok(());
+    }
+    store_path(&save_path(name)?, value)
+}
+
+/// Like `load` but from the config directory
+pub fn load_config<T: DeserializeOwned>(name: &str) -> Result<Option<T>, SaveError> {
+    load_if_written(&config_path(name)?)
+}
+
+/// Like `store` but to the config directory
+pub fn store_config<T: Serialize>(name: &str, value: &T) -> Result<(), SaveError> {
+    if dry_run() {
+        return Ok(());
+    }
+    store_path(&config_path(name)?, value)
+}
+
+/// Deletes `name` from the save directory, fine if it's already gone
+pub fn remove(name: &str) -> Result<(), SaveError> {
+    if dry_run() {
+        return Ok(());
+    }
+    match fs::remove_file(save_path(name)?) {
+        Err(error) if error.kind() != io::ErrorKind::NotFound => Err(error.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Opens `name` in the save directory for writing, replacing what was there
+pub fn create(name: &str) -> Result<fs::File, SaveError> {
+    let path = save_path(name)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    Ok(fs::File::create(path)?)
+}
+
+/// Where `name` goes in the save directory, for whatever writes it on its own
+pub fn prepare(name: &str) -> Result<PathBuf, SaveError> {
+    let path = save_path(name)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    Ok(path)
+}
+
+/// Every file in the `dir` folder of the save directory, sorted by name
+pub fn list(dir: &str) -> Result<Vec<PathBuf>, SaveError> {
+    let entries = match fs::read_dir(save_path(dir)?) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error.into()),
+    };
+
+    let mut paths = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+/// Like `load` but for files outside of the save directory
+pub fn load_path<T: DeserializeOwned>(path: &Path) -> Result<T, SaveError> {
+    let contents = fs::read_to_string(path)?;
+    ron::from_str(&contents).map_err(SaveError::Parse)
+}
+
+/// Like `store` but for files outside of the save directory
+pub fn store_path<T: Serialize>(path: &Path, value: &T) -> Result<(), SaveError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    let contents = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())
+        .map_err(SaveError::Serialize)?;
+    fs::write(path, contents)?;
+    Ok(())
+}
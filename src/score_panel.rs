@@ -0,0 +1,172 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    high_score::{record_high_score, HighScore},
+    profile::Profile,
+    replay::Playback,
+    AppState, Atlas, RunModifiers, Score, SpriteSheet,
+};
+
+// Where the panel comes to a stop, in the middle of the screen over everything
+const PANEL_POSITION: Vec3 = Vec3::new(0., 0., 20.);
+// How far below that it slides in from, far enough to start off screen
+const SLIDE_DISTANCE: f32 = 160.;
+// How long (in seconds) it takes to slide in
+const SLIDE_DURATION: f32 = 0.4;
+// The right edges of the score and the best, and how high up they are, from
+// the middle of the panel
+const SCORE_ANCHOR: Vec2 = Vec2::new(46.5, 7.5);
+const BEST_ANCHOR: Vec2 = Vec2::new(46.5, -13.);
+const NEW_POSITION: Vec2 = Vec2::new(18., -4.5);
+const MEDAL_POSITION: Vec2 = Vec2::new(-33., -3.5);
+// Pixels between two digits
+const DIGIT_SPACING: f32 = 1.;
+// The score each medal is earned at, from the best down
+const MEDALS: [(u32, Medal); 4] = [
+    (40, Medal::Platinum),
+    (30, Medal::Gold),
+    (20, Medal::Silver),
+    (10, Medal::Bronze),
+];
+
+/// What a run that scored well enough gets on the results panel
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Medal {
+    Bronze,
+    Silver,
+    Gold,
+    Platinum,
+}
+
+impl Medal {
+    /// The medal a run that scored `score` earns, if any
+    fn earned(score: u32) -> Option<Medal> {
+        MEDALS
+            .iter()
+            .find(|(from, _)| score >= *from)
+            .map(|(_, medal)| *medal)
+    }
+
+    fn atlas(self) -> Atlas {
+        match self {
+            Medal::Bronze => Atlas::MedalBronze,
+            Medal::Silver => Atlas::MedalSilver,
+            Medal::Gold => Atlas::MedalGold,
+            Medal::Platinum => Atlas::MedalPlatinum,
+        }
+    }
+}
+
+/// The panel from the original game that slides in when a run is over
+#[derive(Component)]
+struct ScorePanel(Timer);
+
+pub struct ScorePanelPlugin;
+
+impl Plugin for ScorePanelPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(AppState::GameOver),
+            (
+                // Before the best is updated, so it can tell whether it's just been beaten
+                spawn_panel.before(record_high_score),
+                record_medal,
+            ),
+        )
+        .add_systems(Update, slide_panel.run_if(in_state(AppState::GameOver)))
+        .add_systems(OnExit(AppState::GameOver), despawn_panel);
+    }
+}
+
+/// How wide the small glyph for `digit` is on the sprite sheet
+fn digit_width(digit: u32) -> f32 {
+    if digit == 1 {
+        3.
+    } else {
+        6.
+    }
+}
+
+fn spawn_panel(
+    mut commands: Commands,
+    sheet: Res<SpriteSheet>,
+    high_score: Res<HighScore>,
+    modifiers: Res<RunModifiers>,
+    score: Res<Score>,
+    playback: Option<Res<Playback>>,
+) {
+    let previous = high_score.get(&modifiers).unwrap_or_default();
+    // Replays don't count towards the best, so they don't beat it either
+    let new_best = playback.is_none() && score.0 > previous;
+    let best = if new_best { score.0 } else { previous };
+
+    let sprite = |atlas: Atlas, translation: Vec3| SpriteSheetBundle {
+        texture: sheet.texture.clone(),
+        atlas: TextureAtlas {
+            layout: sheet.layout.clone(),
+            index: atlas as usize,
+        },
+        transform: Transform::from_translation(translation),
+        ..default()
+    };
+
+    commands
+        .spawn((
+            ScorePanel(Timer::from_seconds(SLIDE_DURATION, TimerMode::Once)),
+            sprite(Atlas::ScorePanel, PANEL_POSITION - Vec3::Y * SLIDE_DISTANCE),
+        ))
+        .with_children(|parent| {
+            for (value, anchor) in [(score.0, SCORE_ANCHOR), (best, BEST_ANCHOR)] {
+                let digits: Vec<u32> = value
+                    .to_string()
+                    .chars()
+                    .filter_map(|c| c.to_digit(10))
+                    .collect();
+
+                // Right to left from the anchor, so the numbers line up
+                // with the labels above them
+                let mut x = anchor.x;
+                for digit in digits.into_iter().rev() {
+                    let width = digit_width(digit);
+                    parent.spawn(sprite(
+                        Atlas::small_digit(digit),
+                        Vec3::new(x - width / 2., anchor.y, 0.1),
+                    ));
+                    x -= width + DIGIT_SPACING;
+                }
+            }
+
+            if new_best {
+                parent.spawn(sprite(Atlas::NewBest, NEW_POSITION.extend(0.1)));
+            }
+            if let Some(medal) = Medal::earned(score.0) {
+                parent.spawn(sprite(medal.atlas(), MEDAL_POSITION.extend(0.1)));
+            }
+        });
+}
+
+// Replays have already been counted when they were played
+fn record_medal(mut profile: ResMut<Profile>, score: Res<Score>, playback: Option<Res<Playback>>) {
+    let Some(medal) = Medal::earned(score.0) else {
+        return;
+    };
+    if playback.is_none() && !profile.medals.contains(&medal) {
+        profile.medals.insert(medal);
+    }
+}
+
+fn slide_panel(mut query: Query<(&mut ScorePanel, &mut Transform)>, time: Res<Time>) {
+    for (mut panel, mut transform) in &mut query {
+        panel.0.tick(time.delta());
+        // Eases out so it settles into place rather than stopping dead
+        let t = 1. - (1. - panel.0.fraction()).powi(3);
+        transform.translation = PANEL_POSITION - Vec3::Y * SLIDE_DISTANCE * (1. - t);
+    }
+}
+
+fn despawn_panel(mut commands: Commands, query: Query<Entity, With<ScorePanel>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
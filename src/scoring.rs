@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::BuildWorld;
+
+/// Something about a pipe that changes what getting through it does
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PipeTag {
+    /// Adds one to the combo before the pipe is scored
+    Bonus,
+    /// Halves the combo before the pipe is scored, rounding down
+    Cursed,
+}
+
+/// What a pipe laid out by a level is worth. Any other pipe is worth the
+/// usual points, plus the combo
+#[derive(Component, Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct PipeScore {
+    /// How many times the usual points it's worth
+    pub weight: u32,
+    pub tags: Vec<PipeTag>,
+}
+
+/// Points every pipe scores on top of its own, built up by bonus pipes and
+/// knocked back down by cursed ones
+#[derive(Resource, Default)]
+pub struct Combo(pub u32);
+
+impl Combo {
+    /// What getting through a pipe that's usually worth `points` scores,
+    /// after its tags have had their way with the combo
+    pub fn score(&mut self, points: u32, pipe: Option<&PipeScore>) -> u32 {
+        let Some(pipe) = pipe else {
+            return points + self.0;
+        };
+
+        for tag in &pipe.tags {
+            match tag {
+                PipeTag::Bonus => self.0 += 1,
+                PipeTag::Cursed => self.0 /= 2,
+            }
+        }
+        points * pipe.weight + self.0
+    }
+}
+
+pub struct ScoringPlugin;
+
+impl Plugin for ScoringPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Combo>()
+            .add_systems(BuildWorld, reset_combo);
+    }
+}
+
+// Starts over along with the score whenever there's a new world
+fn reset_combo(mut combo: ResMut<Combo>) {
+    combo.0 = 0;
+}
@@ -0,0 +1,71 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::{prelude::*, render::view::screenshot::ScreenshotManager, window::PrimaryWindow};
+use serde::{Deserialize, Serialize};
+
+use crate::{high_score::OnNewBest, save};
+
+const SCREENSHOT_DIR: &str = "screenshots";
+// Seconds that have to go by before another best is worth a screenshot, so a
+// string of short runs beating a low best doesn't fill the folder
+const SCREENSHOT_COOLDOWN: f32 = 30.;
+
+/// Whether a screenshot's saved the moment a new best is set
+#[derive(Resource, Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct ScreenshotSettings {
+    pub on_new_best: bool,
+}
+
+impl Default for ScreenshotSettings {
+    fn default() -> Self {
+        Self { on_new_best: true }
+    }
+}
+
+pub struct ScreenshotPlugin;
+
+impl Plugin for ScreenshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, capture_best);
+    }
+}
+
+// Without a window there's nothing to take a picture of
+fn capture_best(
+    mut reader: EventReader<OnNewBest>,
+    mut manager: ResMut<ScreenshotManager>,
+    mut last: Local<Option<f32>>,
+    settings: Res<ScreenshotSettings>,
+    window: Query<Entity, With<PrimaryWindow>>,
+    time: Res<Time<Real>>,
+) {
+    let Some(best) = reader.read().last() else {
+        return;
+    };
+    let now = time.elapsed_seconds();
+    let cooling_down = last.is_some_and(|last| now - last < SCREENSHOT_COOLDOWN);
+    let Ok(window) = window.get_single() else {
+        return;
+    };
+    if !settings.on_new_best || cooling_down {
+        return;
+    }
+
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis())
+        .unwrap_or_default();
+    let name = format!("{SCREENSHOT_DIR}/best-{}-{millis}.png", best.score);
+    let saved = save::prepare(&name)
+        .map_err(|error| error.to_string())
+        .and_then(|path| {
+            manager
+                .save_screenshot_to_disk(window, path)
+                .map_err(|error| error.to_string())
+        });
+    match saved {
+        Ok(()) => *last = Some(now),
+        Err(error) => warn!("Couldn't take a screenshot of the new best: {error}"),
+    }
+}
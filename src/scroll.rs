@@ -0,0 +1,51 @@
+use bevy::prelude::*;
+
+use crate::{difficulty::Difficulty, AppState, SimSet, SimTick, SIM_HZ};
+
+// How long (in seconds) the world takes to get up to speed or come to a stop
+const EASE_DURATION: f32 = 0.3;
+
+/// How far along the world is in getting up to speed, so it never starts or
+/// stops scrolling all at once
+#[derive(Resource, Default)]
+pub struct ScrollEase(f32);
+
+impl ScrollEase {
+    /// The speed everything that moves along with the world scrolls at
+    pub fn speed(&self, difficulty: &Difficulty) -> f32 {
+        let t = self.0;
+        difficulty.scroll_speed * t * t * (3. - 2. * t)
+    }
+}
+
+pub fn is_scrolling(ease: Res<ScrollEase>) -> bool {
+    ease.0 > 0.
+}
+
+pub struct ScrollPlugin;
+
+impl Plugin for ScrollPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScrollEase>().add_systems(
+            FixedUpdate,
+            (
+                speed_up.run_if(in_state(AppState::Playing)),
+                slow_down.run_if(not(in_state(AppState::Playing))),
+            )
+                .after(SimSet::Input)
+                .before(SimSet::Physics),
+        );
+    }
+}
+
+// Follows the tick rather than counting up on its own, so a run plays out the
+// same when it's replayed or picked up again from a snapshot
+fn speed_up(mut ease: ResMut<ScrollEase>, tick: Res<SimTick>) {
+    ease.0 = (tick.0 as f32 / (EASE_DURATION * SIM_HZ as f32)).min(1.);
+}
+
+fn slow_down(mut ease: ResMut<ScrollEase>, time: Res<Time>) {
+    if ease.0 > 0. {
+        ease.0 = (ease.0 - time.delta_seconds() / EASE_DURATION).max(0.);
+    }
+}
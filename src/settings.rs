@@ -0,0 +1,154 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actions::ActionMap,
+    audio_settings::AudioSettings,
+    difficulty::DifficultyPreset,
+    low_power::LowPower,
+    save::{self, FlushSaves},
+    screenshot::ScreenshotSettings,
+    weather::WeatherSetting,
+};
+
+const SETTINGS_FILE: &str = "settings.ron";
+// Where each part of the settings was saved on its own before they were
+// kept together
+const OLD_AUDIO_FILE: &str = "audio.ron";
+const OLD_CONTROLS_FILE: &str = "controls.ron";
+const OLD_POWER_FILE: &str = "power.ron";
+const OLD_SCREENSHOT_FILE: &str = "screenshots.ron";
+
+/// Everything the player has set up about how the game plays and looks, kept
+/// together in one file in the config directory. Each part is a resource of
+/// its own while the game's running, this is what they were last saved as
+#[derive(Resource, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct Settings {
+    pub audio: AudioSettings,
+    pub controls: ActionMap,
+    pub video: VideoSettings,
+    pub accessibility: Accessibility,
+    /// The preset runs are played on unless a replay or a challenge says
+    /// otherwise
+    pub difficulty: DifficultyPreset,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default)]
+#[serde(default)]
+pub struct VideoSettings {
+    pub low_power: LowPower,
+    pub screenshots: ScreenshotSettings,
+    pub weather: WeatherSetting,
+}
+
+/// For anyone who'd rather the game didn't move them around
+#[derive(Resource, Serialize, Deserialize, Clone, Copy)]
+#[serde(default)]
+pub struct Accessibility {
+    pub screen_shake: bool,
+    pub rumble: bool,
+    /// Keeps the bird from squashing and stretching
+    pub reduced_motion: bool,
+}
+
+impl Default for Accessibility {
+    fn default() -> Self {
+        Self {
+            screen_shake: true,
+            rumble: true,
+            reduced_motion: false,
+        }
+    }
+}
+
+impl Settings {
+    /// Whatever was saved before the settings had a file of their own, with
+    /// anything that can't be read left as it is out of the box
+    fn from_old_files() -> Self {
+        fn load<T: serde::de::DeserializeOwned + Default>(name: &str) -> T {
+            save::load(name).ok().flatten().unwrap_or_default()
+        }
+
+        Self {
+            audio: load(OLD_AUDIO_FILE),
+            controls: load(OLD_CONTROLS_FILE),
+            video: VideoSettings {
+                low_power: load(OLD_POWER_FILE),
+                screenshots: load(OLD_SCREENSHOT_FILE),
+                weather: WeatherSetting::default(),
+            },
+            accessibility: Accessibility::default(),
+            difficulty: DifficultyPreset::default(),
+        }
+    }
+}
+
+/// Has to go in before any of the plugins that read the settings
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = match save::load_config::<Settings>(SETTINGS_FILE) {
+            Ok(settings) => settings.unwrap_or_else(Settings::from_old_files),
+            Err(error) => {
+                warn!("Couldn't load settings, starting fresh: {error}");
+                Settings::default()
+            }
+        };
+
+        app.insert_resource(settings.audio)
+            .insert_resource(settings.controls.clone().fill_in())
+            .insert_resource(settings.video.low_power)
+            .insert_resource(settings.video.screenshots)
+            .insert_resource(settings.video.weather)
+            .insert_resource(settings.accessibility)
+            .insert_resource(settings.difficulty)
+            .insert_resource(settings)
+            .add_event::<FlushSaves>()
+            .add_systems(
+                Last,
+                save_settings.run_if(
+                    resource_changed::<AudioSettings>
+                        .or_else(resource_changed::<ActionMap>)
+                        .or_else(resource_changed::<LowPower>)
+                        .or_else(resource_changed::<ScreenshotSettings>)
+                        .or_else(resource_changed::<WeatherSetting>)
+                        .or_else(resource_changed::<Accessibility>)
+                        .or_else(resource_changed::<DifficultyPreset>)
+                        .or_else(on_event::<FlushSaves>()),
+                ),
+            );
+    }
+}
+
+fn save_settings(
+    mut settings: ResMut<Settings>,
+    audio: Res<AudioSettings>,
+    controls: Res<ActionMap>,
+    low_power: Res<LowPower>,
+    screenshots: Res<ScreenshotSettings>,
+    weather: Res<WeatherSetting>,
+    accessibility: Res<Accessibility>,
+    difficulty: Res<DifficultyPreset>,
+) {
+    // Nothing new to write when they were just loaded
+    if settings.is_added() {
+        return;
+    }
+
+    *settings = Settings {
+        audio: *audio,
+        controls: controls.clone(),
+        video: VideoSettings {
+            low_power: *low_power,
+            screenshots: *screenshots,
+            weather: *weather,
+        },
+        accessibility: *accessibility,
+        difficulty: *difficulty,
+    };
+    if let Err(error) = save::store_config(SETTINGS_FILE, settings.as_ref()) {
+        warn!("Couldn't save settings: {error}");
+    }
+}
@@ -0,0 +1,225 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::{
+    accessories::{self, Slot},
+    build_world,
+    characters::{Character, Characters},
+    profile::Profile,
+    replay::Playback,
+    AppState, Atlas, SpriteSheet,
+};
+
+// Off to the side of the player's own bird, under the logo
+const SHOWCASE_POSITION: Vec3 = Vec3::new(44., 24., 4.);
+// Seconds each look is shown off for before the next one
+const SHOWCASE_INTERVAL: f32 = 3.;
+// Bobs like the player's bird does, only out of step with it
+const HOVER_HEIGHT: f32 = 4.;
+const HOVER_RATE: f32 = 0.8;
+const HOVER_PHASE: f32 = 0.5;
+// How fast the trail drifts off behind it, since the world isn't scrolling
+const TRAIL_DRIFT: f32 = -40.;
+
+/// One of the looks the menu shows off
+struct Loadout {
+    character: Character,
+    hat: Option<&'static str>,
+    scarf: Option<&'static str>,
+}
+
+/// Every bird, and everything unlocked to wear, each shown at least once.
+/// Birds are paired with accessories as they come, so they don't all show
+/// up with the same hat
+fn loadouts(characters: &[Character], profile: &Profile) -> Vec<Loadout> {
+    let hats = accessories::available(Slot::Hat, profile);
+    let scarves = accessories::available(Slot::Scarf, profile);
+    let count = characters.len().max(hats.len()).max(scarves.len()).max(1);
+
+    let nth = |options: &[(&'static str, &'static str)], i: usize| {
+        (!options.is_empty()).then(|| options[i % options.len()].0)
+    };
+    (0..count)
+        .map(|i| Loadout {
+            character: if characters.is_empty() {
+                Character::default()
+            } else {
+                characters[i % characters.len()].clone()
+            },
+            hat: nth(&hats, i),
+            scarf: nth(&scarves, i),
+        })
+        .collect()
+}
+
+/// Which look is up next, and when
+#[derive(Resource)]
+struct Showcase {
+    next: usize,
+    timer: Timer,
+}
+
+impl Default for Showcase {
+    fn default() -> Self {
+        Self {
+            next: 0,
+            timer: Timer::from_seconds(SHOWCASE_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+/// The bird on the menu that shows off what the player has unlocked
+#[derive(Component)]
+struct ShowcaseBird {
+    trail: Option<(Timer, Color, f32, f32)>,
+}
+
+#[derive(Component)]
+struct ShowcaseDot(Timer);
+
+pub struct ShowcasePlugin;
+
+impl Plugin for ShowcasePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Showcase>()
+            .add_systems(
+                OnEnter(AppState::MainMenu),
+                // After the world's laid out, so there's a sheet to draw from
+                start_showcase
+                    .after(build_world)
+                    .run_if(not(resource_exists::<Playback>)),
+            )
+            .add_systems(
+                Update,
+                (next_look, hover, lay_trail).chain().run_if(
+                    in_state(AppState::MainMenu).and_then(not(resource_exists::<Playback>)),
+                ),
+            )
+            .add_systems(Update, fade_trail)
+            .add_systems(OnExit(AppState::MainMenu), despawn::<ShowcaseBird>);
+    }
+}
+
+fn start_showcase(mut showcase: ResMut<Showcase>) {
+    *showcase = Showcase::default();
+    // Shows the first look straight away
+    let duration = showcase.timer.duration();
+    showcase.timer.set_elapsed(duration);
+}
+
+// Made anew for every look, wearing only what the profile has unlocked
+fn next_look(
+    mut commands: Commands,
+    mut showcase: ResMut<Showcase>,
+    characters: Characters,
+    profile: Res<Profile>,
+    sheet: Res<SpriteSheet>,
+    birds: Query<Entity, With<ShowcaseBird>>,
+    time: Res<Time>,
+) {
+    let first = showcase.timer.elapsed() >= showcase.timer.duration();
+    if !first && !showcase.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    if first {
+        showcase.timer.reset();
+    }
+
+    for entity in &birds {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    let loadouts = loadouts(characters.all(), &profile);
+    let look = &loadouts[showcase.next % loadouts.len()];
+    showcase.next = (showcase.next + 1) % loadouts.len();
+
+    let trail = look.character.trail.as_ref().map(|trail| {
+        (
+            Timer::from_seconds(trail.interval, TimerMode::Repeating),
+            trail.color,
+            trail.size,
+            trail.lifetime,
+        )
+    });
+    commands
+        .spawn((
+            ShowcaseBird { trail },
+            SpriteSheetBundle {
+                texture: sheet.texture.clone(),
+                atlas: TextureAtlas {
+                    layout: sheet.layout.clone(),
+                    index: Atlas::Bird1 as usize,
+                },
+                sprite: Sprite {
+                    color: look.character.tint,
+                    ..default()
+                },
+                transform: Transform::from_translation(SHOWCASE_POSITION),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            accessories::wear(parent, look.hat, &profile);
+            accessories::wear(parent, look.scarf, &profile);
+        });
+}
+
+fn hover(mut query: Query<&mut Transform, With<ShowcaseBird>>, time: Res<Time>) {
+    let t = time.elapsed_seconds() * HOVER_RATE + HOVER_PHASE;
+    for mut transform in &mut query {
+        transform.translation.y = SHOWCASE_POSITION.y + HOVER_HEIGHT * (t * TAU).sin();
+    }
+}
+
+fn lay_trail(
+    mut commands: Commands,
+    mut query: Query<(&mut ShowcaseBird, &Transform)>,
+    time: Res<Time>,
+) {
+    for (mut bird, transform) in &mut query {
+        let Some((timer, color, size, lifetime)) = &mut bird.trail else {
+            continue;
+        };
+        if !timer.tick(time.delta()).just_finished() {
+            continue;
+        }
+
+        commands.spawn((
+            ShowcaseDot(Timer::from_seconds(*lifetime, TimerMode::Once)),
+            SpriteBundle {
+                sprite: Sprite {
+                    color: *color,
+                    custom_size: Some(Vec2::splat(*size)),
+                    ..default()
+                },
+                // Just behind the bird
+                transform: Transform::from_translation(transform.translation - Vec3::Z * 0.5),
+                ..default()
+            },
+        ));
+    }
+}
+
+// Left to fade out even after the menu's gone
+fn fade_trail(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut ShowcaseDot, &mut Transform, &mut Sprite)>,
+    time: Res<Time>,
+) {
+    for (entity, mut dot, mut transform, mut sprite) in &mut query {
+        if dot.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        transform.translation.x += TRAIL_DRIFT * time.delta_seconds();
+        sprite.color.set_a(1. - dot.0.fraction());
+    }
+}
+
+fn despawn<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,280 @@
+use std::{collections::BTreeMap, fs, ops::Range, path::PathBuf, process, time::Duration};
+
+use bevy::{
+    app::{AppExit, PluginGroupBuilder, ScheduleRunnerPlugin},
+    prelude::*,
+    time::TimeUpdateStrategy,
+    window::ExitCondition,
+    winit::WinitPlugin,
+};
+
+use crate::{
+    create_world,
+    curve::ActiveCurve,
+    difficulty::{apply_difficulty, Difficulty},
+    AppState, BirdState, BuildWorld, NextSeed, Obstacle, Passed, Player, QueuedFlap, RunModifiers,
+    SimSet, Velocity, PIPE_WIDTH,
+};
+
+// Every gap the adaptive mode can end up nudging the curve to, from its
+// tightest to its widest
+const SWEEP: [f32; 5] = [-16., -8., 0., 8., 16.];
+// A game the bot gets this far in is called off, it could go on forever
+const MAX_PIPES: u32 = 500;
+const CSV_FILE: &str = "simulation.csv";
+// How much game time each update moves along, a lot of steps at once since
+// nobody is watching
+const FRAME: Duration = Duration::from_millis(250);
+
+/// What plays the simulated games
+enum Bot {
+    /// Flaps whenever it's fallen below the middle of the next gap
+    Heuristic,
+}
+
+/// A batch of bot games across the difficulty settings, and how they went
+#[derive(Resource)]
+struct Simulation {
+    /// How many games are played for each setting
+    games: usize,
+    bot: Bot,
+    /// The seeds the games go through, over and over if there are more games
+    seeds: Range<u64>,
+    csv: PathBuf,
+    /// Which of the settings is being played, and how many games into it
+    setting: usize,
+    game: usize,
+    /// Pipes passed in the game that's being played
+    pipes: u32,
+    /// How many games ended at each pipe, for each of the settings
+    deaths: Vec<BTreeMap<u32, usize>>,
+    /// How many games got all the way to `MAX_PIPES`, for each of the settings
+    survived: Vec<usize>,
+}
+
+impl Simulation {
+    fn from_args() -> Result<Option<Self>, String> {
+        let mut games = None;
+        let mut bot = Bot::Heuristic;
+        let mut seeds = None;
+        let mut csv = PathBuf::from(CSV_FILE);
+
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{arg} needs a value"));
+            match arg.as_str() {
+                "--simulate" => {
+                    let value = value()?;
+                    let count = value
+                        .parse::<usize>()
+                        .map_err(|_| format!("{value} isn't a number of games"))?;
+                    games = Some(count.max(1));
+                }
+                "--bot" => {
+                    bot = match value()?.as_str() {
+                        "heuristic" => Bot::Heuristic,
+                        other => return Err(format!("there's no bot called {other}")),
+                    }
+                }
+                "--seed-range" => {
+                    let value = value()?;
+                    let range = value
+                        .split_once("..")
+                        .and_then(|(start, end)| Some(start.parse().ok()?..end.parse().ok()?))
+                        .filter(|range: &Range<u64>| !range.is_empty())
+                        .ok_or(format!("{value} isn't a range of seeds like 0..1000"))?;
+                    seeds = Some(range);
+                }
+                "--csv" => csv = PathBuf::from(value()?),
+                _ => {}
+            }
+        }
+
+        let Some(games) = games else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            games,
+            bot,
+            seeds: seeds.unwrap_or(0..games as u64),
+            csv,
+            setting: 0,
+            game: 0,
+            pipes: 0,
+            deaths: vec![BTreeMap::new(); SWEEP.len()],
+            survived: vec![0; SWEEP.len()],
+        }))
+    }
+
+    fn seed(&self) -> u64 {
+        let length = self.seeds.end - self.seeds.start;
+        self.seeds.start + self.game as u64 % length
+    }
+
+    /// Death rate at every pipe, for every setting
+    fn to_csv(&self) -> String {
+        let mut csv = "gap_adjustment,pipe,reached,deaths,death_rate\n".to_string();
+        for (i, gap) in SWEEP.into_iter().enumerate() {
+            let deaths = &self.deaths[i];
+            let last = deaths.keys().last().copied().unwrap_or(0);
+            let mut reached = self.games;
+            for pipe in 0..=last {
+                let died = deaths.get(&pipe).copied().unwrap_or(0);
+                let rate = died as f32 / reached.max(1) as f32;
+                csv += &format!("{gap},{pipe},{reached},{died},{rate:.4}\n");
+                reached -= died;
+            }
+        }
+        csv
+    }
+}
+
+/// Makes the default plugins run without a window when simulating, as fast as
+/// the computer allows
+pub fn headless(plugins: PluginGroupBuilder) -> PluginGroupBuilder {
+    if !std::env::args().any(|arg| arg == "--simulate") {
+        return plugins;
+    }
+
+    plugins
+        .set(WindowPlugin {
+            primary_window: None,
+            exit_condition: ExitCondition::DontExit,
+            close_when_requested: false,
+        })
+        .disable::<WinitPlugin>()
+        .add(ScheduleRunnerPlugin::run_loop(Duration::ZERO))
+}
+
+pub struct SimulatePlugin;
+
+impl Plugin for SimulatePlugin {
+    fn build(&self, app: &mut App) {
+        let simulation = match Simulation::from_args() {
+            Ok(Some(simulation)) => simulation,
+            Ok(None) => return,
+            Err(error) => {
+                eprintln!("Can't simulate: {error}");
+                process::exit(2);
+            }
+        };
+
+        app.insert_resource(simulation)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(FRAME))
+            .add_systems(
+                BuildWorld,
+                use_setting.after(apply_difficulty).before(create_world),
+            )
+            .add_systems(OnEnter(AppState::Playing), reset_pipes)
+            .add_systems(
+                FixedUpdate,
+                (
+                    fly_bot.before(SimSet::Input),
+                    count_pipes.in_set(SimSet::Rules),
+                )
+                    .run_if(in_state(AppState::Playing)),
+            )
+            .add_systems(Update, next_game);
+    }
+}
+
+fn use_setting(
+    simulation: Res<Simulation>,
+    mut difficulty: ResMut<Difficulty>,
+    curve: Res<ActiveCurve>,
+) {
+    difficulty.gap_adjustment = SWEEP[simulation.setting];
+    difficulty.follow(&curve.0, 0);
+}
+
+fn reset_pipes(mut simulation: ResMut<Simulation>) {
+    simulation.pipes = 0;
+}
+
+fn fly_bot(
+    simulation: Res<Simulation>,
+    mut queued: ResMut<QueuedFlap>,
+    player: Query<(&Transform, &Velocity, &BirdState), With<Player>>,
+    obstacles: Query<&Transform, With<Obstacle>>,
+    difficulty: Res<Difficulty>,
+) {
+    for (transform, velocity, state) in &player {
+        if *state != BirdState::Flying {
+            continue;
+        }
+
+        let position = transform.translation;
+        // The closest obstacle that isn't behind the bird yet
+        let next = obstacles
+            .iter()
+            .map(|obstacle| obstacle.translation)
+            .filter(|obstacle| obstacle.x + PIPE_WIDTH / 2. > position.x - 6.)
+            .min_by(|a, b| a.x.total_cmp(&b.x));
+        let target = next
+            .map_or(0., |obstacle| obstacle.y - 80. - difficulty.pipe_space / 2.)
+            .max(-100.);
+        let flap = match simulation.bot {
+            Bot::Heuristic => position.y < target - 6. && velocity.0 < 0.,
+        };
+        if flap {
+            queued.0 = Some(0.);
+        }
+    }
+}
+
+fn count_pipes(mut simulation: ResMut<Simulation>, passed: Query<(), Added<Passed>>) {
+    simulation.pipes += passed.iter().count() as u32;
+}
+
+fn next_game(
+    mut simulation: ResMut<Simulation>,
+    mut modifiers: ResMut<RunModifiers>,
+    mut next_seed: ResMut<NextSeed>,
+    state: Res<State<AppState>>,
+    mut next_state: ResMut<NextState<AppState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    // Done, just waiting to exit
+    if simulation.setting == SWEEP.len() {
+        return;
+    }
+
+    let setting = simulation.setting;
+    match state.get() {
+        // The very first game, nothing to record yet
+        AppState::MainMenu => {}
+        AppState::GameOver => {
+            let pipes = simulation.pipes;
+            *simulation.deaths[setting].entry(pipes).or_default() += 1;
+            simulation.game += 1;
+        }
+        AppState::Playing if simulation.pipes >= MAX_PIPES => {
+            simulation.survived[setting] += 1;
+            simulation.game += 1;
+        }
+        _ => return,
+    }
+
+    if simulation.game == simulation.games {
+        info!(
+            "Simulated {} games at a gap of {:+}, {} made it to {MAX_PIPES} pipes",
+            simulation.games, SWEEP[setting], simulation.survived[setting]
+        );
+        simulation.setting += 1;
+        simulation.game = 0;
+    }
+
+    if simulation.setting == SWEEP.len() {
+        match fs::write(&simulation.csv, simulation.to_csv()) {
+            Ok(()) => info!("Simulation written to {}", simulation.csv.display()),
+            Err(error) => error!("Couldn't write the simulation: {error}"),
+        }
+        exit.send(AppExit);
+        return;
+    }
+
+    // Always a classic run, whatever the player last picked
+    *modifiers = RunModifiers::default();
+    next_seed.0 = Some(simulation.seed());
+    next_state.set(AppState::Restarting);
+}
@@ -0,0 +1,275 @@
+use bevy::{
+    ecs::system::{EntityCommands, SystemParam},
+    prelude::*,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    behaviors::{pipe_space, pose_pipes, set_behaviors, Behavior, Behaviors},
+    config::{ActiveConfig, GameConfig},
+    curve::ActiveCurve,
+    difficulty::Difficulty,
+    hazards::{place_hazard, Hazard, Patrol},
+    scoring::{Combo, PipeScore},
+    spawn_obstacle, AppState, GameRng, Obstacle, Passed, Pattern, Pipe, Player, Root, Score, Side,
+    SimSet, SimTick, SpriteSheet, Velocity,
+};
+
+pub const SNAPSHOT_DIR: &str = "snapshots";
+
+/// Everything the simulation needs to pick a run back up from a single step
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Snapshot {
+    pub tick: u64,
+    pub score: u32,
+    /// Missing from snapshots taken before there were combos, which is the
+    /// same as not having one
+    #[serde(default)]
+    pub combo: u32,
+    pub rng: RngState,
+    pub player: PlayerState,
+    pub obstacles: Vec<ObstacleState>,
+    pub hazards: Vec<HazardState>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct RngState {
+    pub seed: u64,
+    /// How far into the seed's stream the run has got
+    pub word_pos: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct PlayerState {
+    pub y: f32,
+    pub velocity: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct ObstacleState {
+    pub x: f32,
+    pub y: f32,
+    pub pattern: Pattern,
+    pub passed: bool,
+    pub pipe_space: f32,
+    /// Missing from snapshots taken before obstacles had behaviors of their
+    /// own, which only ever had the ones their pattern comes with
+    #[serde(default)]
+    pub behaviors: Option<Vec<Behavior>>,
+    /// Only set on the pipes of a level
+    #[serde(default)]
+    pub score: Option<PipeScore>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct HazardState {
+    pub hazard: Hazard,
+    pub x: f32,
+    pub patrol: Option<Patrol>,
+}
+
+/// A snapshot waiting for the next simulation step to be put in place
+#[derive(Resource, Default)]
+pub struct QueuedRestore(pub Option<Snapshot>);
+
+/// The run was rewound to a snapshot, so it's no longer one straight playthrough
+#[derive(Event)]
+pub struct OnSnapshotRestored;
+
+/// The parts of the world a snapshot is taken from
+#[derive(SystemParam)]
+pub struct SimState<'w, 's> {
+    tick: Res<'w, SimTick>,
+    score: Res<'w, Score>,
+    combo: Res<'w, Combo>,
+    rng: Res<'w, GameRng>,
+    player: Query<'w, 's, (&'static Transform, &'static Velocity), With<Player>>,
+    obstacles: Query<
+        'w,
+        's,
+        (
+            &'static Transform,
+            &'static Pattern,
+            Has<Passed>,
+            &'static Children,
+            Behaviors,
+            Option<&'static PipeScore>,
+        ),
+        With<Obstacle>,
+    >,
+    pipes: Query<'w, 's, &'static Transform, With<Pipe>>,
+    hazards: Query<'w, 's, (&'static Transform, &'static Hazard, Option<&'static Patrol>)>,
+}
+
+impl SimState<'_, '_> {
+    /// The current state of the run, `None` if there's no world to take it from
+    pub fn capture(&self) -> Option<Snapshot> {
+        let (transform, velocity) = self.player.get_single().ok()?;
+
+        let obstacles = self
+            .obstacles
+            .iter()
+            .map(
+                |(transform, pattern, passed, children, behaviors, score)| ObstacleState {
+                    x: transform.translation.x,
+                    y: transform.translation.y,
+                    pattern: *pattern,
+                    passed,
+                    pipe_space: pipe_space(self.pipes.iter_many(children)),
+                    behaviors: Some(behaviors.to_vec()),
+                    score: score.cloned(),
+                },
+            )
+            .collect();
+
+        let hazards = self
+            .hazards
+            .iter()
+            .map(|(transform, hazard, patrol)| HazardState {
+                hazard: *hazard,
+                x: transform.translation.x,
+                patrol: patrol.copied(),
+            })
+            .collect();
+
+        Some(Snapshot {
+            tick: self.tick.0,
+            score: self.score.0,
+            combo: self.combo.0,
+            rng: RngState {
+                seed: self.rng.seed,
+                word_pos: self.rng.rng.get_word_pos() as u64,
+            },
+            player: PlayerState {
+                y: transform.translation.y,
+                velocity: velocity.0,
+            },
+            obstacles,
+            hazards,
+        })
+    }
+}
+
+pub struct SnapshotPlugin;
+
+impl Plugin for SnapshotPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<QueuedRestore>()
+            .add_event::<OnSnapshotRestored>()
+            .add_systems(
+                FixedUpdate,
+                restore_snapshot
+                    .before(SimSet::Input)
+                    .run_if(in_state(AppState::Playing)),
+            );
+    }
+}
+
+pub fn restore_snapshot(
+    mut commands: Commands,
+    mut queued: ResMut<QueuedRestore>,
+    mut tick: ResMut<SimTick>,
+    mut score: ResMut<Score>,
+    mut combo: ResMut<Combo>,
+    mut rng: ResMut<GameRng>,
+    mut difficulty: ResMut<Difficulty>,
+    curve: Res<ActiveCurve>,
+    config: Res<ActiveConfig>,
+    mut player: Query<
+        (&mut Transform, &mut Velocity),
+        (With<Player>, Without<Obstacle>, Without<Pipe>),
+    >,
+    mut obstacles: Query<
+        (Entity, &mut Transform, &mut Pattern, &Children),
+        (With<Obstacle>, Without<Player>),
+    >,
+    mut pipes: Query<(&Pipe, &mut Transform), Without<Obstacle>>,
+    hazards: Query<Entity, With<Hazard>>,
+    root: Query<Entity, With<Root>>,
+    sheet: Res<SpriteSheet>,
+    mut writer: EventWriter<OnSnapshotRestored>,
+) {
+    let Some(snapshot) = queued.0.take() else {
+        return;
+    };
+
+    tick.0 = snapshot.tick;
+    score.0 = snapshot.score;
+    combo.0 = snapshot.combo;
+    // Otherwise the first step would still go at the speed of the old score
+    difficulty.follow(&curve.0, snapshot.score);
+    let mut restored = ChaCha8Rng::seed_from_u64(snapshot.rng.seed);
+    restored.set_word_pos(snapshot.rng.word_pos as u128);
+    *rng = GameRng {
+        seed: snapshot.rng.seed,
+        rng: restored,
+    };
+
+    let (mut transform, mut velocity) = player.single_mut();
+    transform.translation.y = snapshot.player.y;
+    velocity.0 = snapshot.player.velocity;
+
+    // A snapshot can come from a world laid out with more or fewer columns, so
+    // the ones it doesn't have go and the ones it's missing are made. Which one
+    // ends up where doesn't matter since they're all set from scratch
+    let mut states = snapshot.obstacles.iter();
+    for (entity, mut transform, mut pattern, children) in &mut obstacles {
+        let Some(state) = states.next() else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+        transform.translation.x = state.x;
+        transform.translation.y = state.y;
+        *pattern = state.pattern;
+        // Whatever tilt the pipes had is put back by their behaviors next step
+        pose_pipes(children, &mut pipes, state.pipe_space, 0.);
+        restore_obstacle(&mut commands.entity(entity), state, &config.0);
+    }
+    let root = root.single();
+    commands.entity(root).with_children(|parent| {
+        for state in states {
+            let translation = Vec3::new(state.x, state.y, 1.);
+            let mut entity = spawn_obstacle(parent, &sheet, translation, state.pipe_space);
+            entity.insert(state.pattern);
+            restore_obstacle(&mut entity, state, &config.0);
+        }
+    });
+
+    for entity in &hazards {
+        commands.entity(entity).despawn_recursive();
+    }
+    for state in &snapshot.hazards {
+        place_hazard(
+            &mut commands,
+            root,
+            &sheet.hitboxes,
+            state.hazard,
+            state.x,
+            state.patrol,
+        );
+    }
+
+    writer.send(OnSnapshotRestored);
+}
+
+// The parts of an obstacle that are components of their own
+fn restore_obstacle(entity: &mut EntityCommands, state: &ObstacleState, config: &GameConfig) {
+    let behaviors = state
+        .behaviors
+        .clone()
+        .unwrap_or_else(|| state.pattern.behaviors(config));
+    set_behaviors(entity, &behaviors);
+    // Worked out again from where the obstacle's been put
+    entity.remove::<Side>();
+    if state.passed {
+        entity.insert(Passed);
+    } else {
+        entity.remove::<Passed>();
+    }
+    match &state.score {
+        Some(score) => entity.insert(score.clone()),
+        None => entity.remove::<PipeScore>(),
+    };
+}
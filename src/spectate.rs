@@ -0,0 +1,243 @@
+use std::{
+    io::ErrorKind,
+    net::{TcpListener, TcpStream},
+    time::Duration,
+};
+
+use bevy::{prelude::*, utils::HashMap};
+use serde::Serialize;
+use tungstenite::{Error, Message, WebSocket};
+
+use crate::{
+    bonus::PlayPhase, difficulty::Difficulty, scroll::ScrollEase, AppState, Obstacle, Player,
+    Score, SimSet, SimTick,
+};
+
+// A spectator that takes longer than this to say hello isn't let in, the game
+// waits on it in the meantime
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_millis(500);
+// Anything that's moved less than this is where the spectators think it is
+const MOVED: f32 = 0.01;
+
+/// What spectators are sent every step, as JSON
+#[derive(Serialize)]
+struct Frame {
+    tick: u64,
+    /// Only sent when the game has gone into another state
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state: Option<String>,
+    player: [f32; 2],
+    score: u32,
+    /// How far every obstacle scrolled since the last frame
+    shift: f32,
+    /// Obstacles that aren't just where scrolling put them, new ones included
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    obstacles: Vec<ObstacleUpdate>,
+    /// Ids of obstacles that are gone
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    removed: Vec<u32>,
+}
+
+#[derive(Serialize)]
+struct ObstacleUpdate {
+    id: u32,
+    /// Where the top pipe is, with a `gap` tall space under it
+    x: f32,
+    y: f32,
+    gap: f32,
+}
+
+#[derive(Resource)]
+struct Spectating {
+    listener: TcpListener,
+    spectators: Vec<WebSocket<TcpStream>>,
+    /// Where the spectators were last told every obstacle is
+    sent: HashMap<Entity, Vec2>,
+    /// The last frame that went out, nothing is sent while nothing changes
+    last: Option<(String, [f32; 2], u32)>,
+}
+
+pub struct SpectatePlugin;
+
+impl Plugin for SpectatePlugin {
+    fn build(&self, app: &mut App) {
+        let mut args = std::env::args().skip_while(|arg| arg != "--spectate");
+        let Some(address) = args.nth(1) else {
+            return;
+        };
+
+        let listener = match TcpListener::bind(&address) {
+            Ok(listener) => listener,
+            Err(error) => {
+                warn!("Couldn't listen for spectators on {address}: {error}");
+                return;
+            }
+        };
+        if let Err(error) = listener.set_nonblocking(true) {
+            warn!("Couldn't listen for spectators on {address}: {error}");
+            return;
+        }
+        info!("Spectators can watch on ws://{address}");
+
+        app.insert_resource(Spectating {
+            listener,
+            spectators: Vec::new(),
+            sent: HashMap::new(),
+            last: None,
+        })
+        .add_systems(Update, (let_in_spectators, listen_to_spectators))
+        .add_systems(
+            FixedUpdate,
+            stream_frame.after(SimSet::Rules).before(SimSet::Transition),
+        );
+    }
+}
+
+fn let_in_spectators(mut spectating: ResMut<Spectating>) {
+    while let Ok((stream, address)) = spectating.listener.accept() {
+        match handshake(stream) {
+            Ok(socket) => {
+                info!("Spectator joined from {address}");
+                spectating.spectators.push(socket);
+                // Everyone gets the whole layout again so the new spectator
+                // has it too
+                spectating.sent.clear();
+                spectating.last = None;
+            }
+            Err(error) => warn!("Spectator from {address} couldn't join: {error}"),
+        }
+    }
+}
+
+fn handshake(stream: TcpStream) -> Result<WebSocket<TcpStream>, String> {
+    stream
+        .set_nonblocking(false)
+        .and_then(|()| stream.set_read_timeout(Some(HANDSHAKE_TIMEOUT)))
+        .map_err(|error| error.to_string())?;
+    let socket = tungstenite::accept(stream).map_err(|error| error.to_string())?;
+    socket
+        .get_ref()
+        .set_nonblocking(true)
+        .map_err(|error| error.to_string())?;
+    Ok(socket)
+}
+
+/// Whether the spectator is still there after `result`
+fn connected<T>(result: Result<T, Error>) -> bool {
+    match result {
+        Ok(_) => true,
+        // Still on its way, it'll go out with whatever's sent next
+        Err(Error::Io(error)) if error.kind() == ErrorKind::WouldBlock => true,
+        Err(_) => false,
+    }
+}
+
+// Spectators only watch, but reading is how it's noticed that they've left
+// and how their pings get answered
+fn listen_to_spectators(mut spectating: ResMut<Spectating>) {
+    spectating.spectators.retain_mut(|socket| loop {
+        match socket.read() {
+            Ok(_) => continue,
+            Err(Error::Io(error)) if error.kind() == ErrorKind::WouldBlock => break true,
+            Err(_) => break false,
+        }
+    });
+}
+
+fn stream_frame(
+    mut spectating: ResMut<Spectating>,
+    state: Res<State<AppState>>,
+    phase: Res<State<PlayPhase>>,
+    player: Query<&Transform, With<Player>>,
+    obstacles: Query<(Entity, &Transform), With<Obstacle>>,
+    score: Res<Score>,
+    tick: Res<SimTick>,
+    difficulty: Res<Difficulty>,
+    ease: Res<ScrollEase>,
+    time: Res<Time>,
+) {
+    if spectating.spectators.is_empty() {
+        return;
+    }
+
+    // Same as the pipes, which only scroll along in between bonus rounds
+    let shift = if *phase.get() == PlayPhase::Normal {
+        ease.speed(&difficulty) * time.delta_seconds()
+    } else {
+        0.
+    };
+
+    let spectating = spectating.as_mut();
+    let mut updates = Vec::new();
+    for (entity, transform) in &obstacles {
+        let position = transform.translation.xy();
+        let expected = spectating
+            .sent
+            .get(&entity)
+            .map(|sent| *sent + Vec2::new(shift, 0.));
+        let sent = match expected {
+            Some(expected) if expected.distance(position) <= MOVED => expected,
+            _ => {
+                updates.push(ObstacleUpdate {
+                    id: entity.index(),
+                    x: position.x,
+                    y: position.y,
+                    gap: difficulty.pipe_space,
+                });
+                position
+            }
+        };
+        spectating.sent.insert(entity, sent);
+    }
+    let mut removed = Vec::new();
+    spectating.sent.retain(|entity, _| {
+        let kept = obstacles.contains(*entity);
+        if !kept {
+            removed.push(entity.index());
+        }
+        kept
+    });
+
+    let position = player
+        .iter()
+        .next()
+        .map_or([0., 0.], |transform| transform.translation.xy().into());
+    let current = (format!("{:?}", state.get()), position, score.0);
+    let changed_state = spectating
+        .last
+        .as_ref()
+        .is_none_or(|(last, ..)| *last != current.0);
+    let unchanged = spectating.last.as_ref() == Some(&current)
+        && shift == 0.
+        && updates.is_empty()
+        && removed.is_empty();
+    if unchanged {
+        return;
+    }
+
+    let frame = Frame {
+        tick: tick.0,
+        state: changed_state.then(|| current.0.clone()),
+        player: position,
+        score: score.0,
+        shift,
+        obstacles: updates,
+        removed,
+    };
+    spectating.last = Some(current);
+
+    let json = match serde_json::to_string(&frame) {
+        Ok(json) => json,
+        Err(error) => {
+            warn!("Couldn't write a frame for spectators: {error}");
+            return;
+        }
+    };
+    spectating.spectators.retain_mut(|socket| {
+        let sent = connected(socket.send(Message::Text(json.clone())));
+        if !sent {
+            info!("Spectator left");
+        }
+        sent
+    });
+}
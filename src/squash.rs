@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+
+use crate::{
+    characters::ActiveCharacter, effects::Easing, settings::Accessibility, OnCrashed, OnJumped,
+    Player,
+};
+
+// How far a flap stretches the bird, taller and thinner, and how long it
+// takes to go back to its own shape
+const FLAP_STRETCH: Vec2 = Vec2::new(-0.15, 0.2);
+const FLAP_DURATION: f32 = 0.15;
+// How flat a crash squashes the bird against what it hit, before it falls
+const HIT_SQUASH: f32 = 0.35;
+const HIT_DURATION: f32 = 0.25;
+
+/// A bird springing back into shape from being squashed or stretched
+#[derive(Component)]
+struct Squash {
+    /// The scale it started out at
+    from: Vec2,
+    timer: Timer,
+    easing: Easing,
+}
+
+impl Squash {
+    fn new(from: Vec2, duration: f32) -> Self {
+        Self {
+            from,
+            timer: Timer::from_seconds(duration, TimerMode::Once),
+            easing: Easing::Out,
+        }
+    }
+}
+
+pub struct SquashPlugin;
+
+impl Plugin for SquashPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            ((stretch_on_flap, squash_on_crash), spring_back)
+                .chain()
+                .run_if(motion_allowed),
+        );
+    }
+}
+
+fn motion_allowed(accessibility: Res<Accessibility>) -> bool {
+    !accessibility.reduced_motion
+}
+
+fn stretch_on_flap(
+    mut commands: Commands,
+    mut reader: EventReader<OnJumped>,
+    character: Res<ActiveCharacter>,
+    query: Query<Entity, With<Player>>,
+) {
+    if reader.read().count() == 0 || character.0.squash <= 0. {
+        return;
+    }
+
+    let from = Vec2::ONE + FLAP_STRETCH * character.0.squash;
+    for entity in &query {
+        commands
+            .entity(entity)
+            .insert(Squash::new(from, FLAP_DURATION));
+    }
+}
+
+// Flattened along the way it was going when it hit, worked out in the bird's
+// own turned frame since that's the one the scale applies in. Hitting the
+// edges of the world is always top or bottom
+fn squash_on_crash(
+    mut commands: Commands,
+    mut reader: EventReader<OnCrashed>,
+    character: Res<ActiveCharacter>,
+    query: Query<(Entity, &Transform), With<Player>>,
+) {
+    let Some(crash) = reader.read().last() else {
+        return;
+    };
+    if character.0.squash <= 0. {
+        return;
+    }
+
+    let amount = HIT_SQUASH * character.0.squash;
+    for (entity, transform) in &query {
+        let toward = (crash.contact - transform.translation.xy()).extend(0.);
+        let toward = (transform.rotation.inverse() * toward).xy();
+        let from = if toward.x.abs() > toward.y.abs() {
+            Vec2::new(1. - amount, 1. + amount)
+        } else {
+            Vec2::new(1. + amount, 1. - amount)
+        };
+        commands
+            .entity(entity)
+            .insert(Squash::new(from, HIT_DURATION));
+    }
+}
+
+fn spring_back(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Squash, &mut Transform)>,
+    time: Res<Time>,
+) {
+    for (entity, mut squash, mut transform) in &mut query {
+        let t = squash
+            .easing
+            .apply(squash.timer.tick(time.delta()).fraction());
+        transform.scale = squash.from.lerp(Vec2::ONE, t).extend(1.);
+        if squash.timer.finished() {
+            commands.entity(entity).remove::<Squash>();
+        }
+    }
+}
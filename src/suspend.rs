@@ -0,0 +1,193 @@
+use bevy::{app::AppExit, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    build_world,
+    difficulty::Difficulty,
+    replay::Playback,
+    save,
+    snapshot::{QueuedRestore, SimState, Snapshot},
+    AppState, BuildWorld, RunModifiers,
+};
+
+const SUSPEND_FILE: &str = "suspended.ron";
+
+/// A run that was still going when the game was closed
+#[derive(Resource, Serialize, Deserialize)]
+struct SuspendedRun {
+    modifiers: RunModifiers,
+    gap_adjustment: f32,
+    snapshot: Snapshot,
+}
+
+/// The player's own modifiers, put back once the resumed run is over
+#[derive(Resource)]
+struct Resumed {
+    modifiers: RunModifiers,
+}
+
+#[derive(Component)]
+struct ResumePrompt;
+
+pub struct SuspendPlugin;
+
+impl Plugin for SuspendPlugin {
+    fn build(&self, app: &mut App) {
+        match save::load::<SuspendedRun>(SUSPEND_FILE) {
+            Ok(Some(run)) => {
+                app.insert_resource(run);
+            }
+            Ok(None) => {}
+            Err(error) => warn!("Couldn't load the suspended run: {error}"),
+        }
+
+        app.add_systems(
+            Last,
+            suspend_run
+                .run_if(in_state(AppState::Playing).and_then(not(resource_exists::<Playback>))),
+        )
+        .add_systems(
+            OnEnter(AppState::MainMenu),
+            draw_prompt.run_if(resource_exists::<SuspendedRun>),
+        )
+        .add_systems(
+            Update,
+            answer_prompt.run_if(
+                in_state(AppState::MainMenu)
+                    .and_then(resource_exists::<SuspendedRun>)
+                    .and_then(not(resource_exists::<Playback>)),
+            ),
+        )
+        .add_systems(
+            OnExit(AppState::MainMenu),
+            discard_suspended_run.run_if(resource_exists::<SuspendedRun>),
+        )
+        .add_systems(
+            OnEnter(AppState::GameOver),
+            restore_modifiers.run_if(resource_exists::<Resumed>),
+        )
+        .add_systems(
+            OnEnter(AppState::LevelComplete),
+            restore_modifiers.run_if(resource_exists::<Resumed>),
+        )
+        .add_systems(
+            OnEnter(AppState::Restarting),
+            restore_modifiers.run_if(resource_exists::<Resumed>),
+        )
+        // Quitting the run from the pause overlay, in time for the menu's
+        // world to be laid out with the player's own modifiers
+        .add_systems(
+            OnEnter(AppState::MainMenu),
+            restore_modifiers
+                .before(build_world)
+                .run_if(resource_exists::<Resumed>),
+        );
+    }
+}
+
+fn suspend_run(
+    mut reader: EventReader<AppExit>,
+    sim: SimState,
+    modifiers: Res<RunModifiers>,
+    difficulty: Res<Difficulty>,
+) {
+    if reader.read().count() == 0 {
+        return;
+    }
+
+    let Some(snapshot) = sim.capture() else {
+        return;
+    };
+    let run = SuspendedRun {
+        modifiers: modifiers.clone(),
+        gap_adjustment: difficulty.gap_adjustment,
+        snapshot,
+    };
+    if let Err(error) = save::store(SUSPEND_FILE, &run) {
+        warn!("Couldn't suspend the run: {error}");
+    }
+}
+
+fn draw_prompt(mut commands: Commands) {
+    commands
+        .spawn((
+            ResumePrompt,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    position_type: PositionType::Absolute,
+                    bottom: Val::Px(12.),
+                    justify_content: JustifyContent::Center,
+                    ..default()
+                },
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Resume run? Y resume, N discard",
+                TextStyle {
+                    font_size: 14.,
+                    color: Color::WHITE,
+                    ..default()
+                },
+            ));
+        });
+}
+
+fn answer_prompt(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    query: Query<Entity, With<ResumePrompt>>,
+) {
+    if keys.just_pressed(KeyCode::KeyY) {
+        commands.add(resume_run);
+    } else if keys.just_pressed(KeyCode::KeyN) {
+        forget_suspended_run(&mut commands, &query);
+    }
+}
+
+// The menu's world was laid out with the player's modifiers, so it's laid out
+// again with the run's before the snapshot goes on top of it. That way it has
+// the physics, the preset and the columns the run had
+fn resume_run(world: &mut World) {
+    let run = world.resource::<SuspendedRun>();
+    let (run_modifiers, gap_adjustment, snapshot) = (
+        run.modifiers.clone(),
+        run.gap_adjustment,
+        run.snapshot.clone(),
+    );
+    let modifiers = std::mem::replace(&mut *world.resource_mut::<RunModifiers>(), run_modifiers);
+    world.run_schedule(BuildWorld);
+
+    world.resource_mut::<Difficulty>().gap_adjustment = gap_adjustment;
+    world.resource_mut::<QueuedRestore>().0 = Some(snapshot);
+    world.insert_resource(Resumed { modifiers });
+    world
+        .resource_mut::<NextState<AppState>>()
+        .set(AppState::Playing);
+}
+
+// Starting any other run gives up on the suspended one
+fn discard_suspended_run(mut commands: Commands, query: Query<Entity, With<ResumePrompt>>) {
+    forget_suspended_run(&mut commands, &query);
+}
+
+fn forget_suspended_run(commands: &mut Commands, query: &Query<Entity, With<ResumePrompt>>) {
+    for entity in query {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.remove_resource::<SuspendedRun>();
+    if let Err(error) = save::remove(SUSPEND_FILE) {
+        warn!("Couldn't discard the suspended run: {error}");
+    }
+}
+
+fn restore_modifiers(
+    mut commands: Commands,
+    resumed: Res<Resumed>,
+    mut modifiers: ResMut<RunModifiers>,
+) {
+    *modifiers = resumed.modifiers.clone();
+    commands.remove_resource::<Resumed>();
+}
@@ -0,0 +1,118 @@
+use bevy::prelude::*;
+
+use crate::{difficulty::Difficulty, AppState, Obstacle, Pattern, Root, PIPE_WIDTH};
+
+// How many seconds of warning an unusual pattern gets before it's visible
+const LOOK_AHEAD: f32 = 1.;
+const FLASH_INTERVAL: f32 = 0.1;
+// Half the width of the visible world
+const VIEW_EDGE: f32 = 72.;
+
+/// Flashes at the edge of the screen until `obstacle` scrolls into view
+#[derive(Component)]
+struct Telegraph {
+    obstacle: Entity,
+    flash: Timer,
+}
+
+pub struct TelegraphPlugin;
+
+impl Plugin for TelegraphPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (spawn_telegraphs, update_telegraphs).run_if(in_state(AppState::Playing)),
+        );
+    }
+}
+
+fn time_until_visible(x: f32, difficulty: &Difficulty) -> f32 {
+    (x - PIPE_WIDTH / 2. - VIEW_EDGE) / -difficulty.scroll_speed
+}
+
+fn gap_center(obstacle: &Transform, difficulty: &Difficulty) -> f32 {
+    // The top pipe is 160 tall and centered on the obstacle
+    obstacle.translation.y - 80. - difficulty.pipe_space / 2.
+}
+
+fn spawn_telegraphs(
+    mut commands: Commands,
+    difficulty: Res<Difficulty>,
+    obstacles: Query<(Entity, &Transform, &Pattern), With<Obstacle>>,
+    telegraphs: Query<&Telegraph>,
+    root: Query<Entity, With<Root>>,
+) {
+    if !difficulty.is_high() {
+        return;
+    }
+
+    for (entity, transform, pattern) in &obstacles {
+        if *pattern == Pattern::Regular {
+            continue;
+        }
+
+        let remaining = time_until_visible(transform.translation.x, &difficulty);
+        if !(0. ..=LOOK_AHEAD).contains(&remaining) {
+            continue;
+        }
+
+        if telegraphs
+            .iter()
+            .any(|telegraph| telegraph.obstacle == entity)
+        {
+            continue;
+        }
+
+        commands.entity(root.single()).with_children(|parent| {
+            parent.spawn((
+                Telegraph {
+                    obstacle: entity,
+                    flash: Timer::from_seconds(FLASH_INTERVAL, TimerMode::Repeating),
+                },
+                SpriteBundle {
+                    sprite: Sprite {
+                        color: Color::WHITE,
+                        custom_size: Some(Vec2::new(4., 12.)),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(Vec3::new(
+                        VIEW_EDGE - 2.,
+                        gap_center(transform, &difficulty),
+                        5.,
+                    )),
+                    ..default()
+                },
+            ));
+        });
+    }
+}
+
+fn update_telegraphs(
+    mut commands: Commands,
+    mut telegraphs: Query<(Entity, &mut Telegraph, &mut Transform, &mut Visibility)>,
+    obstacles: Query<&Transform, (With<Obstacle>, Without<Telegraph>)>,
+    difficulty: Res<Difficulty>,
+    time: Res<Time>,
+) {
+    for (entity, mut telegraph, mut transform, mut visibility) in &mut telegraphs {
+        let Ok(obstacle) = obstacles.get(telegraph.obstacle) else {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        };
+
+        if time_until_visible(obstacle.translation.x, &difficulty) <= 0. {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+
+        // Follow the gap in case it's moving
+        transform.translation.y = gap_center(obstacle, &difficulty);
+
+        if telegraph.flash.tick(time.delta()).just_finished() {
+            *visibility = match *visibility {
+                Visibility::Hidden => Visibility::Inherited,
+                _ => Visibility::Hidden,
+            };
+        }
+    }
+}
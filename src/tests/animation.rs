@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+
+use crate::{update_animation, Animation, Frame, Player};
+
+// How far apart the runs are allowed to drift, in seconds into the animation
+const TOLERANCE: f32 = 1e-4;
+// Long enough for the looping animation to come around a bunch of times
+const PLAY_FOR: [f32; 2] = [1., 2.5];
+const SPEEDS: [f32; 3] = [0.5, 1., 1.75];
+
+fn animation(repeat: bool, speed: f32) -> Animation {
+    let durations = [0.01, 0.02, 0.005, 0.1];
+    Animation {
+        t: 0.,
+        repeat,
+        frame: 0,
+        frames: durations
+            .into_iter()
+            .enumerate()
+            .map(|(index, duration)| Frame { index, duration })
+            .collect(),
+        speed,
+    }
+}
+
+fn length(animation: &Animation) -> f32 {
+    animation.frames.iter().map(|frame| frame.duration).sum()
+}
+
+/// How far into the animation it is, in seconds
+fn elapsed(animation: &Animation) -> f32 {
+    let done = animation.frames[..animation.frame]
+        .iter()
+        .map(|frame| frame.duration)
+        .sum::<f32>();
+    done + animation.t * animation.frames[animation.frame].duration
+}
+
+// Runs the bird's animation system for `seconds` with a fixed step between
+// frames, and hands back where the animation ended up
+fn play(repeat: bool, speed: f32, fps: f32, seconds: f32) -> Animation {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            1. / fps,
+        )))
+        .add_systems(Update, update_animation);
+
+    let bird = app
+        .world
+        .spawn((Player, TextureAtlas::default(), animation(repeat, speed)))
+        .id();
+
+    // The first update only starts the clock
+    let steps = (seconds * fps).round() as usize;
+    for _ in 0..=steps {
+        app.update();
+    }
+
+    app.world.entity_mut(bird).take::<Animation>().unwrap()
+}
+
+// Every frame rate has to end up at the same point of the animation after
+// the same amount of time
+fn check(fps: f32) {
+    for repeat in [true, false] {
+        for speed in SPEEDS {
+            for seconds in PLAY_FOR {
+                let length = length(&animation(repeat, speed));
+                let played = seconds * speed;
+                let expected = if repeat {
+                    played % length
+                } else {
+                    played.min(length)
+                };
+
+                let actual = elapsed(&play(repeat, speed, fps, seconds));
+                let drift = (actual - expected).abs();
+                // Landing right at the end of a loop is the same as being
+                // back at the start of it
+                let drift = if repeat {
+                    drift.min(length - drift)
+                } else {
+                    drift
+                };
+                assert!(
+                    drift <= TOLERANCE,
+                    "{actual}s into the animation instead of {expected}s after {seconds}s at \
+                     {speed}x (repeat {repeat})",
+                );
+            }
+        }
+    }
+}
+
+// Low enough that several of the short frames go by in a single step
+#[test]
+fn animation_keeps_time_at_30_fps() {
+    check(30.);
+}
+
+#[test]
+fn animation_keeps_time_at_60_fps() {
+    check(60.);
+}
+
+#[test]
+fn animation_keeps_time_at_240_fps() {
+    check(240.);
+}
@@ -0,0 +1,83 @@
+use crate::{
+    curve::DifficultyCurve,
+    difficulty::{Difficulty, DifficultyPreset},
+};
+
+fn curve() -> DifficultyCurve {
+    ron::from_str(include_str!("../../assets/difficulty.curve.ron")).unwrap()
+}
+
+fn at(score: u32, preset: DifficultyPreset) -> Difficulty {
+    let mut difficulty = Difficulty {
+        preset,
+        ..Default::default()
+    };
+    difficulty.follow(&curve(), score);
+    difficulty
+}
+
+#[test]
+fn the_curve_starts_where_its_first_point_is() {
+    let curve = curve();
+    let first = curve.points[0];
+    let difficulty = at(0, DifficultyPreset::Normal);
+    assert_eq!(difficulty.scroll_speed, first.scroll_speed);
+    assert_eq!(difficulty.pipe_space, first.pipe_space);
+    assert_eq!(difficulty.pipe_to_pipe_space, first.pipe_to_pipe_space);
+    assert_eq!(difficulty.pipe_columns, curve.pipe_columns);
+}
+
+#[test]
+fn the_curve_is_eased_between_its_points() {
+    let curve = curve();
+    let (from, to) = (curve.points[0], curve.points[1]);
+    let difficulty = at((from.score + to.score) / 2, DifficultyPreset::Normal);
+    assert_eq!(
+        difficulty.scroll_speed,
+        (from.scroll_speed + to.scroll_speed) / 2.
+    );
+    assert_eq!(
+        difficulty.pipe_space,
+        (from.pipe_space + to.pipe_space) / 2.
+    );
+}
+
+#[test]
+fn the_curve_stays_at_its_last_point_past_the_end() {
+    let last = *curve().points.last().unwrap();
+    let difficulty = at(last.score * 10, DifficultyPreset::Normal);
+    assert_eq!(difficulty.scroll_speed, last.scroll_speed);
+    assert_eq!(difficulty.pipe_space, last.pipe_space);
+    assert_eq!(difficulty.pipe_to_pipe_space, last.pipe_to_pipe_space);
+}
+
+#[test]
+fn scoring_more_never_makes_it_easier() {
+    let mut before = at(0, DifficultyPreset::Normal);
+    for score in 1..=200 {
+        let now = at(score, DifficultyPreset::Normal);
+        assert!(now.scroll_speed <= before.scroll_speed, "faster at {score}");
+        assert!(now.pipe_space <= before.pipe_space, "narrower at {score}");
+        assert!(
+            now.pipe_to_pipe_space <= before.pipe_to_pipe_space,
+            "closer together at {score}"
+        );
+        before = now;
+    }
+    assert!(before.scroll_speed < at(0, DifficultyPreset::Normal).scroll_speed);
+}
+
+#[test]
+fn presets_shift_the_whole_curve() {
+    for score in [0, 25, 100] {
+        let easy = at(score, DifficultyPreset::Easy);
+        let normal = at(score, DifficultyPreset::Normal);
+        let hard = at(score, DifficultyPreset::Hard);
+        assert!(easy.pipe_space > normal.pipe_space && normal.pipe_space > hard.pipe_space);
+        assert!(easy.scroll_speed > normal.scroll_speed && normal.scroll_speed > hard.scroll_speed);
+        assert!(
+            easy.pipe_to_pipe_space > normal.pipe_to_pipe_space
+                && normal.pipe_to_pipe_space > hard.pipe_to_pipe_space
+        );
+    }
+}
@@ -0,0 +1,109 @@
+use std::{path::Path, time::Duration};
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+
+use crate::{
+    audio_settings::AudioSettings,
+    feedback::{Cue, FeedbackMap},
+    low_power::LowPower,
+    mixer::{
+        duck_music, play_sounds, ActiveMix, AudioMix, Channel, Ducking, PlaySound, CONFIRM_SOUND,
+    },
+};
+
+const FRAME: f32 = 1. / 60.;
+// Longer than any of the dips in the assets
+const LISTEN_FOR: f32 = 3.;
+
+fn mix() -> AudioMix {
+    ron::from_str(include_str!("../../assets/game.audio.ron")).unwrap()
+}
+
+fn feedback() -> FeedbackMap {
+    ron::from_str(include_str!("../../assets/game.feedback.ron")).unwrap()
+}
+
+fn mixer() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<AudioSource>()
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            FRAME,
+        )))
+        .insert_resource(ActiveMix(mix()))
+        .init_resource::<Ducking>()
+        .init_resource::<AudioSettings>()
+        .init_resource::<LowPower>()
+        .add_event::<PlaySound>()
+        .add_systems(Update, (play_sounds, duck_music).chain());
+    // The first update only starts the clock
+    app.update();
+    app
+}
+
+/// How loud the music is on every frame from now on
+fn listen(app: &mut App) -> Vec<f32> {
+    let frames = (LISTEN_FOR / FRAME) as usize;
+    (0..frames)
+        .map(|_| {
+            app.update();
+            app.world.resource::<Ducking>().mix(Channel::Music)
+        })
+        .collect()
+}
+
+// Goes all the way down to the bottom of the dip and comes back up after
+fn assert_dips(volume: &[f32], depth: f32, what: &str) {
+    let lowest = volume.iter().copied().fold(1., f32::min);
+    assert!(
+        (lowest - depth).abs() < 1e-4,
+        "the music only went down to {lowest} under {what}, not {depth}",
+    );
+    assert_eq!(
+        volume.last(),
+        Some(&1.),
+        "the music didn't come back up after {what}"
+    );
+}
+
+#[test]
+fn music_dips_under_menu_confirmations() {
+    let mut app = mixer();
+    assert_eq!(app.world.resource::<Ducking>().mix(Channel::Music), 1.);
+
+    app.world
+        .send_event(PlaySound::new(CONFIRM_SOUND, Channel::Ui));
+    let volume = listen(&mut app);
+
+    let depth = mix().ducking[&Channel::Ui].depth;
+    assert_dips(&volume, depth, "a confirmation");
+    assert!(Path::new("assets").join(CONFIRM_SOUND).exists());
+}
+
+#[test]
+fn music_dips_under_crashing_and_dying() {
+    let map = feedback();
+    for cue in [Cue::Crash, Cue::Die] {
+        let feedback = &map.cues[&cue];
+        let sound = feedback.sound.as_ref().expect("every crash makes a sound");
+        assert!(
+            Path::new("assets").join(sound).exists(),
+            "{sound} is missing"
+        );
+        let duck = feedback.duck.expect("every crash dips the music");
+
+        let mut app = mixer();
+        app.world.resource_mut::<Ducking>().duck(duck);
+        let volume = listen(&mut app);
+        assert_dips(&volume, duck.depth, &format!("{cue:?}"));
+    }
+}
+
+#[test]
+fn effects_leave_the_music_alone() {
+    let mut app = mixer();
+    app.world
+        .send_event(PlaySound::new("sounds/flap.wav", Channel::Effects));
+    let volume = listen(&mut app);
+    assert!(volume.iter().all(|volume| *volume == 1.));
+}
@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+
+use crate::{
+    actions::{Action, Actions},
+    apply_gravity, flap, input,
+    physics::ActivePhysics,
+    BirdState, OnJumped, Player, QueuedFlap, StepOffset, Velocity,
+};
+
+// Frames go by at the same rate for both simulations, so the flaps are
+// pressed at the same instants
+const FRAME_RATE: f64 = 240.;
+const FRAMES: usize = 480;
+// None of them on a step of the slower simulation, so they all land partway
+// into one
+const FLAPS: [usize; 4] = [13, 110, 195, 262];
+// Every fourth frame is at the end of a step for both simulations
+const SAMPLE_EVERY: usize = 4;
+// The bird's moved exactly along its arc however long the steps are, so all
+// that's left is rounding
+const POSITION_TOLERANCE: f32 = 0.01;
+const VELOCITY_TOLERANCE: f32 = 0.01;
+const LANDING_TOLERANCE: f32 = 0.0001;
+// Where the bird counts as landed, well below where it starts
+const LANDING_Y: f32 = -100.;
+
+/// How high the bird is and how fast it's going, sampled every
+/// `SAMPLE_EVERY` frames
+fn trace(sim_hz: f64) -> Vec<(f32, f32)> {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(
+            1. / FRAME_RATE,
+        )))
+        .insert_resource(Time::<Fixed>::from_hz(sim_hz))
+        .init_resource::<Actions>()
+        .init_resource::<ActivePhysics>()
+        .init_resource::<QueuedFlap>()
+        .init_resource::<StepOffset>()
+        .add_event::<OnJumped>()
+        .add_systems(Update, input)
+        .add_systems(FixedUpdate, (flap, apply_gravity).chain());
+
+    let bird = app
+        .world
+        .spawn((
+            Player,
+            Transform::default(),
+            Velocity(0.),
+            BirdState::Flying,
+        ))
+        .id();
+
+    // The first update only starts the clock, so frame `n` is at `n / 240`
+    let mut trace = vec![];
+    for frame in 0..=FRAMES {
+        if FLAPS.contains(&frame) {
+            app.world.resource_mut::<Actions>().press(Action::Flap);
+        }
+        app.update();
+
+        if frame % SAMPLE_EVERY == 0 {
+            let bird = app.world.entity(bird);
+            trace.push((
+                bird.get::<Transform>().unwrap().translation.y,
+                bird.get::<Velocity>().unwrap().0,
+            ));
+        }
+    }
+    trace
+}
+
+fn apex(trace: &[(f32, f32)]) -> f32 {
+    trace.iter().map(|(y, _)| *y).fold(f32::MIN, f32::max)
+}
+
+/// When the bird first drops below `LANDING_Y`, in seconds
+fn landing(trace: &[(f32, f32)]) -> f32 {
+    let sample = SAMPLE_EVERY as f32 / FRAME_RATE as f32;
+    let i = trace
+        .iter()
+        .position(|(y, _)| *y < LANDING_Y)
+        .expect("the bird never landed");
+    // Somewhere between the sample before and this one
+    let (above, below) = (trace[i - 1].0, trace[i].0);
+    (i as f32 - (LANDING_Y - below) / (above - below)) * sample
+}
+
+#[test]
+fn flaps_play_out_the_same_at_60_and_240_hz() {
+    let slow = trace(60.);
+    let fast = trace(240.);
+    assert_eq!(slow.len(), fast.len());
+
+    for (i, ((slow_y, slow_v), (fast_y, fast_v))) in slow.iter().zip(&fast).enumerate() {
+        assert!(
+            (slow_y - fast_y).abs() <= POSITION_TOLERANCE,
+            "at sample {i} the bird's at {slow_y} at 60 Hz but {fast_y} at 240 Hz",
+        );
+        assert!(
+            (slow_v - fast_v).abs() <= VELOCITY_TOLERANCE,
+            "at sample {i} the bird's going {slow_v} at 60 Hz but {fast_v} at 240 Hz",
+        );
+    }
+
+    let (slow_apex, fast_apex) = (apex(&slow), apex(&fast));
+    assert!(
+        (slow_apex - fast_apex).abs() <= POSITION_TOLERANCE,
+        "the bird topped out at {slow_apex} at 60 Hz but {fast_apex} at 240 Hz",
+    );
+
+    let (slow_landing, fast_landing) = (landing(&slow), landing(&fast));
+    assert!(
+        (slow_landing - fast_landing).abs() <= LANDING_TOLERANCE,
+        "the bird landed after {slow_landing}s at 60 Hz but {fast_landing}s at 240 Hz",
+    );
+}
@@ -0,0 +1,37 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{math::bounding::BoundingVolume, prelude::*};
+
+use crate::{
+    hitboxes::{Hitbox, Hitboxes},
+    offset_aabb, pipe_aabb,
+};
+
+#[test]
+fn the_atlas_file_has_the_same_hitboxes_as_the_defaults() {
+    let shipped: Hitboxes = ron::from_str(include_str!("../../assets/flappy.atlas.ron")).unwrap();
+    assert_eq!(shipped, Hitboxes::default());
+}
+
+#[test]
+fn hitboxes_are_placed_by_their_offset() {
+    let hitbox = Hitbox {
+        offset: Vec2::new(2., -1.),
+        size: Vec2::new(12., 8.),
+    };
+    let placed = offset_aabb(&hitbox.aabb(), &Vec3::new(10., 20., 0.));
+    assert_eq!(placed.center(), Vec2::new(12., 19.));
+    assert_eq!(placed.half_size(), Vec2::new(6., 4.));
+}
+
+#[test]
+fn a_pipe_hitbox_turns_its_offset_along_with_the_pipe() {
+    let hitbox = Hitbox {
+        offset: Vec2::new(0., 10.),
+        size: Vec2::new(26., 160.),
+    };
+    let obstacle = Transform::from_xyz(100., 0., 0.);
+    let pipe = Transform::from_xyz(0., 50., 0.).with_rotation(Quat::from_rotation_z(FRAC_PI_2));
+    let placed = pipe_aabb(&hitbox.aabb(), &obstacle, &pipe);
+    assert!(placed.center().abs_diff_eq(Vec2::new(90., 50.), 1e-4));
+}
@@ -0,0 +1,9 @@
+mod animation;
+mod difficulty;
+mod ducking;
+mod flap_rate;
+mod hitboxes;
+mod passing;
+mod seeds;
+mod shake;
+mod snapshot;
@@ -0,0 +1,119 @@
+use bevy::prelude::*;
+
+use crate::{
+    modes::{AddGameMode, GameMode, ModeInfo, CLASSIC},
+    pass_pipes,
+    roulette::Roulette,
+    score_pipes,
+    scoring::Combo,
+    Atlas, Obstacle, PipePassed, Player, RunModifiers, Score,
+};
+
+/// Stands in for the classic mode, which every run falls back on
+struct Classic;
+
+impl GameMode for Classic {
+    fn info(&self) -> ModeInfo {
+        ModeInfo {
+            id: CLASSIC,
+            name: "Classic",
+            blurb: "",
+            preview: Atlas::Bird1,
+            color: Color::WHITE,
+            seed: None,
+        }
+    }
+}
+
+/// Just the scoring of a run, with the player put wherever a portal or a
+/// dash might send them between steps
+struct Run {
+    app: App,
+    player: Entity,
+}
+
+impl Run {
+    fn new(obstacles: &[f32]) -> Self {
+        let mut app = App::new();
+        app.add_game_mode(Classic)
+            .init_resource::<Combo>()
+            .init_resource::<Roulette>()
+            .init_resource::<Score>()
+            .insert_resource(RunModifiers::default())
+            .add_event::<PipePassed>()
+            .add_systems(Update, (pass_pipes, score_pipes).chain());
+
+        let player = app.world.spawn((Player, Transform::default())).id();
+        for x in obstacles {
+            app.world.spawn((Obstacle, Transform::from_xyz(*x, 0., 0.)));
+        }
+
+        let mut run = Self { app, player };
+        run.step();
+        run
+    }
+
+    fn step(&mut self) {
+        self.app.update();
+    }
+
+    fn move_to(&mut self, x: f32) {
+        self.app
+            .world
+            .get_mut::<Transform>(self.player)
+            .unwrap()
+            .translation
+            .x = x;
+        self.step();
+    }
+
+    fn score(&self) -> u32 {
+        self.app.world.resource::<Score>().0
+    }
+}
+
+#[test]
+fn going_past_a_pipe_scores_it() {
+    let mut run = Run::new(&[40.]);
+    assert_eq!(run.score(), 0);
+
+    run.move_to(60.);
+    assert_eq!(run.score(), 1);
+}
+
+#[test]
+fn going_back_and_forth_past_a_pipe_scores_it_once() {
+    let mut run = Run::new(&[40.]);
+    for _ in 0..5 {
+        run.move_to(60.);
+        run.move_to(20.);
+    }
+    assert_eq!(run.score(), 1);
+}
+
+#[test]
+fn going_back_past_a_pipe_never_scores_it() {
+    let mut run = Run::new(&[40.]);
+    run.move_to(60.);
+    run.move_to(-100.);
+    assert_eq!(run.score(), 1);
+
+    // Including one the player's never been past going forward
+    let mut run = Run::new(&[-40.]);
+    run.move_to(-60.);
+    assert_eq!(run.score(), 0);
+
+    run.move_to(-20.);
+    assert_eq!(run.score(), 1);
+}
+
+#[test]
+fn jumping_past_several_pipes_in_one_step_scores_all_of_them() {
+    let mut run = Run::new(&[40., 120., 200.]);
+    run.move_to(250.);
+    assert_eq!(run.score(), 3);
+
+    run.move_to(0.);
+    run.move_to(250.);
+    assert_eq!(run.score(), 3);
+}
@@ -0,0 +1,112 @@
+use bevy::{ecs::system::RunSystemOnce, prelude::*};
+
+use crate::{
+    breather::Breather,
+    config::ActiveConfig,
+    create_world,
+    difficulty::Difficulty,
+    hazards::{Hazard, Patrol},
+    hitboxes::ActiveHitboxes,
+    scroll::ScrollEase,
+    scroll_pipes,
+    weather::{Weather, WeatherSetting},
+    GameRng, NextSeed, Obstacle, Pattern, PinnedSeed, Score, TimeOfDay,
+};
+
+// Enough for every column to come back around a bunch of times, with a few
+// hazards showing up in between
+const STEPS: usize = 80;
+
+/// Everything about a world that's picked with random numbers
+#[derive(PartialEq, Debug)]
+struct Layout {
+    /// Where each column is and what pattern it has, after every step
+    columns: Vec<Vec<(f32, f32, Pattern)>>,
+    /// Where the hazards ended up along the ground and which way the crabs
+    /// walk
+    hazards: Vec<(Hazard, f32, Option<f32>)>,
+    time_of_day: TimeOfDay,
+    weather: Weather,
+}
+
+// Lays out a world on `seed` and moves the columns along by hand, so they're
+// recycled and get hazards put in between them like they would in a run
+fn lay_out(seed: u64) -> Layout {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Image>()
+        .init_asset::<TextureAtlasLayout>()
+        .init_resource::<Difficulty>()
+        .init_resource::<Score>()
+        .init_resource::<NextSeed>()
+        .init_resource::<ScrollEase>()
+        .init_resource::<Breather>()
+        .init_resource::<ActiveConfig>()
+        .init_resource::<ActiveHitboxes>()
+        .insert_resource(GameRng::new(0))
+        .insert_resource(PinnedSeed(Some(seed)))
+        .insert_resource(WeatherSetting::Random);
+    app.update();
+
+    app.world.run_system_once(create_world);
+
+    let spacing = app.world.resource::<Difficulty>().pipe_to_pipe_space;
+    let mut columns = vec![];
+    for _ in 0..STEPS {
+        let mut obstacles = app.world.query_filtered::<&mut Transform, With<Obstacle>>();
+        for mut transform in obstacles.iter_mut(&mut app.world) {
+            transform.translation.x -= spacing / 2.;
+        }
+        app.world.run_system_once(scroll_pipes);
+
+        let mut obstacles = app
+            .world
+            .query_filtered::<(&Transform, &Pattern), With<Obstacle>>();
+        let mut step = obstacles
+            .iter(&app.world)
+            .map(|(transform, pattern)| {
+                (transform.translation.x, transform.translation.y, *pattern)
+            })
+            .collect::<Vec<_>>();
+        step.sort_by(|a, b| a.0.total_cmp(&b.0));
+        columns.push(step);
+    }
+
+    let mut hazards = app
+        .world
+        .query::<(&Hazard, &Transform, Option<&Patrol>)>()
+        .iter(&app.world)
+        .map(|(hazard, transform, patrol)| {
+            (
+                *hazard,
+                transform.translation.x,
+                patrol.map(|patrol| patrol.speed),
+            )
+        })
+        .collect::<Vec<_>>();
+    hazards.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+    Layout {
+        columns,
+        hazards,
+        time_of_day: *app.world.resource::<TimeOfDay>(),
+        weather: *app.world.resource::<Weather>(),
+    }
+}
+
+#[test]
+fn the_same_seed_lays_out_the_same_world() {
+    for seed in [0, 7, 12345, u64::MAX] {
+        let first = lay_out(seed);
+        assert!(
+            !first.hazards.is_empty(),
+            "seed {seed} didn't get any hazards to compare"
+        );
+        assert_eq!(first, lay_out(seed), "seed {seed} laid out two worlds");
+    }
+}
+
+#[test]
+fn different_seeds_lay_out_different_worlds() {
+    assert_ne!(lay_out(1).columns, lay_out(2).columns);
+}
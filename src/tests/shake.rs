@@ -0,0 +1,67 @@
+use std::time::Duration;
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+
+use crate::{
+    camera_shake::{shake_world, Shake},
+    effects::ShakeSettings,
+    Root,
+};
+
+const WORLD_HALF_SIZE: Vec2 = Vec2::new(72., 128.);
+const FRAMES: usize = 120;
+
+// Shakes the world as hard as it goes with the camera at `center`, zoomed by
+// `scale`, and checks the camera never sees past the world on any frame
+fn check(center: Vec2, scale: f32) {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+            1. / 60.,
+        )))
+        .init_resource::<Shake>()
+        .add_systems(Update, shake_world);
+
+    let root = app.world.spawn((Root, Transform::default())).id();
+    app.world.spawn((
+        Camera::default(),
+        Transform::from_translation(center.extend(100.)),
+        OrthographicProjection { scale, ..default() },
+    ));
+    app.world.resource_mut::<Shake>().add(
+        ShakeSettings {
+            amplitude: 12.,
+            frequency: 30.,
+            decay: 0.5,
+        },
+        1.,
+    );
+
+    let view = Rect::from_center_half_size(center, WORLD_HALF_SIZE * scale);
+    let mut moved = false;
+    for frame in 0..FRAMES {
+        app.update();
+
+        let transform = app.world.get::<Transform>(root).unwrap();
+        let world = Rect::from_corners(
+            transform.transform_point(-WORLD_HALF_SIZE.extend(0.)).xy(),
+            transform.transform_point(WORLD_HALF_SIZE.extend(0.)).xy(),
+        );
+        assert!(
+            world.min.cmple(view.min + 1e-3).all() && world.max.cmpge(view.max - 1e-3).all(),
+            "on frame {frame} the camera at {center} sees {view:?} past the world at {world:?}",
+        );
+        moved |= transform.translation.xy() != center * (1. - transform.scale.x);
+    }
+    assert!(moved, "the world never shook");
+}
+
+#[test]
+fn shaking_never_shows_past_the_world() {
+    check(Vec2::ZERO, 1.);
+}
+
+#[test]
+fn shaking_zoomed_in_never_shows_past_the_world() {
+    check(Vec2::new(30., -60.), 0.5);
+}
@@ -0,0 +1,93 @@
+use bevy::{ecs::system::RunSystemOnce, prelude::*};
+
+use crate::{
+    breather::Breather,
+    config::ActiveConfig,
+    create_world,
+    curve::ActiveCurve,
+    difficulty::Difficulty,
+    hitboxes::ActiveHitboxes,
+    scoring::Combo,
+    scroll::ScrollEase,
+    snapshot::{restore_snapshot, OnSnapshotRestored, QueuedRestore, SimState, Snapshot},
+    weather::WeatherSetting,
+    GameRng, NextSeed, PinnedSeed, Score, SimTick,
+};
+
+fn world() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Image>()
+        .init_asset::<TextureAtlasLayout>()
+        .init_resource::<Difficulty>()
+        .init_resource::<Score>()
+        .init_resource::<Combo>()
+        .init_resource::<SimTick>()
+        .init_resource::<NextSeed>()
+        .init_resource::<ScrollEase>()
+        .init_resource::<Breather>()
+        .init_resource::<ActiveConfig>()
+        .init_resource::<ActiveHitboxes>()
+        .init_resource::<ActiveCurve>()
+        .init_resource::<QueuedRestore>()
+        .add_event::<OnSnapshotRestored>()
+        .insert_resource(GameRng::new(0))
+        .insert_resource(PinnedSeed(Some(3)))
+        .insert_resource(WeatherSetting::Random);
+    app.update();
+    app.world.run_system_once(create_world);
+    app
+}
+
+fn capture(app: &mut App) -> Snapshot {
+    app.world
+        .run_system_once(|sim: SimState| sim.capture())
+        .expect("there's a world to capture")
+}
+
+// Where each column is, how wide its gap is and whether it's been passed
+fn columns(snapshot: &Snapshot) -> Vec<(f32, f32, f32, bool)> {
+    let mut columns = snapshot
+        .obstacles
+        .iter()
+        .map(|state| (state.x, state.y, state.pipe_space, state.passed))
+        .collect::<Vec<_>>();
+    columns.sort_by(|a, b| a.0.total_cmp(&b.0));
+    columns
+}
+
+fn restore(app: &mut App, snapshot: &Snapshot) -> Snapshot {
+    app.world.resource_mut::<QueuedRestore>().0 = Some(snapshot.clone());
+    app.world.run_system_once(restore_snapshot);
+    capture(app)
+}
+
+#[test]
+fn restoring_more_columns_than_the_world_has_makes_the_rest() {
+    let mut app = world();
+    let mut snapshot = capture(&mut app);
+    let last = snapshot.obstacles.last().unwrap().clone();
+    for i in 1..=3 {
+        let mut extra = last.clone();
+        extra.x += i as f32 * 100.;
+        extra.y -= i as f32 * 10.;
+        extra.pipe_space += i as f32;
+        extra.passed = i % 2 == 0;
+        snapshot.obstacles.push(extra);
+    }
+
+    let restored = restore(&mut app, &snapshot);
+    assert_eq!(columns(&restored), columns(&snapshot));
+}
+
+#[test]
+fn restoring_fewer_columns_than_the_world_has_gets_rid_of_the_rest() {
+    let mut app = world();
+    let mut snapshot = capture(&mut app);
+    assert!(snapshot.obstacles.len() > 1);
+    snapshot.obstacles.truncate(1);
+    snapshot.obstacles[0].x += 50.;
+
+    let restored = restore(&mut app, &snapshot);
+    assert_eq!(columns(&restored), columns(&snapshot));
+}
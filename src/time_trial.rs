@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+
+use crate::{
+    modes::{mode_is, AddGameMode, GameMode, ModeInfo},
+    tutorials::{AddTutorial, Tutorial, BETWEEN_PIPES},
+    AppState, Atlas, SimSet, SimTick, SIM_HZ,
+};
+
+const TIME_TRIAL: &str = "time-trial";
+// Steps a run lasts if the bird makes it that far, a minute's worth
+const TIME_LIMIT: u64 = 60 * SIM_HZ as u64;
+
+#[derive(Component)]
+struct Countdown;
+
+struct TimeTrial;
+
+impl GameMode for TimeTrial {
+    fn info(&self) -> ModeInfo {
+        ModeInfo {
+            id: TIME_TRIAL,
+            name: "Time Trial",
+            blurb: "Most pipes in a minute",
+            preview: Atlas::Bird3,
+            color: Color::rgb(0.55, 0.2, 0.3),
+            seed: None,
+        }
+    }
+
+    fn setup(&self, app: &mut App) {
+        let playing = || in_state(AppState::Playing).and_then(mode_is(TIME_TRIAL));
+        app.add_systems(
+            OnEnter(AppState::Playing),
+            spawn_countdown.run_if(mode_is(TIME_TRIAL)),
+        )
+        .add_systems(OnExit(AppState::Playing), despawn_countdown)
+        .add_systems(
+            FixedUpdate,
+            stop_clock.in_set(SimSet::Rules).run_if(playing()),
+        )
+        .add_systems(Update, update_countdown.run_if(playing()));
+    }
+
+    fn results(&self, score: u32, ticks: u64) -> String {
+        let left = remaining(ticks);
+        if left > 0. {
+            format!("{score} pipes, crashed with {left:.1}s left")
+        } else {
+            format!("Time's up! {score} pipes")
+        }
+    }
+}
+
+pub struct TimeTrialPlugin;
+
+impl Plugin for TimeTrialPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_game_mode(TimeTrial).add_tutorial(Tutorial {
+            id: TIME_TRIAL,
+            title: "Time Trial",
+            text: "You've got a minute to get through as many pipes as you can",
+            art: Atlas::Bird3,
+            example: BETWEEN_PIPES,
+        });
+    }
+}
+
+/// Seconds left on the clock after `ticks` steps
+fn remaining(ticks: u64) -> f32 {
+    TIME_LIMIT.saturating_sub(ticks) as f32 / SIM_HZ as f32
+}
+
+fn stop_clock(tick: Res<SimTick>, mut state: ResMut<NextState<AppState>>) {
+    if tick.0 >= TIME_LIMIT {
+        state.set(AppState::GameOver);
+    }
+}
+
+fn spawn_countdown(mut commands: Commands) {
+    commands.spawn((
+        Countdown,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 16.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            top: Val::Px(4.),
+            left: Val::Px(4.),
+            ..default()
+        }),
+    ));
+}
+
+fn despawn_countdown(mut commands: Commands, query: Query<Entity, With<Countdown>>) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn update_countdown(mut query: Query<&mut Text, With<Countdown>>, tick: Res<SimTick>) {
+    if let Ok(mut text) = query.get_single_mut() {
+        text.sections[0].value = format!("{:.1}", remaining(tick.0));
+    }
+}
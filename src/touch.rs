@@ -0,0 +1,37 @@
+use bevy::{input::InputSystem, prelude::*};
+
+// Browsers follow a tap up with a click of their own a moment later, which
+// would flap the bird a second time. Seconds a click is ignored for after one
+const EMULATED_CLICK_WINDOW: f32 = 0.5;
+
+/// When the screen was last touched, in real seconds since the game started
+#[derive(Resource, Default)]
+pub struct LastTouch(Option<f32>);
+
+impl LastTouch {
+    /// Whether a click right now is just the browser's copy of a tap
+    pub fn emulated_click(&self, time: &Time<Real>) -> bool {
+        self.0
+            .is_some_and(|touched| time.elapsed_seconds() - touched < EMULATED_CLICK_WINDOW)
+    }
+}
+
+pub struct TouchPlugin;
+
+impl Plugin for TouchPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LastTouch>()
+            .add_systems(PreUpdate, track_touches.after(InputSystem));
+    }
+}
+
+// Counted up to the finger coming off the screen, which is when the click
+// comes along
+pub fn track_touches(mut last: ResMut<LastTouch>, touches: Res<Touches>, time: Res<Time<Real>>) {
+    let touching = touches.any_just_pressed()
+        || touches.any_just_released()
+        || touches.iter().next().is_some();
+    if touching {
+        last.0 = Some(time.elapsed_seconds());
+    }
+}
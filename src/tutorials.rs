@@ -0,0 +1,254 @@
+use bevy::prelude::*;
+
+use crate::{
+    actions::{Action, Actions},
+    get_ready::take_off,
+    profile::Profile,
+    replay::Playback,
+    AppState, Atlas, RunModifiers, SpriteSheet,
+};
+
+// How big the still on a card is
+const EXAMPLE_SIZE: Vec2 = Vec2::new(96., 64.);
+// Behind the still, the color of the sky
+const EXAMPLE_BACKGROUND: Color = Color::rgb(0.3, 0.75, 0.8);
+
+/// A bird heading for the gap between two pipes, for a card's still
+pub const BETWEEN_PIPES: &[(Atlas, Vec2)] = &[
+    (Atlas::PipeBottom, Vec2::new(48., -136.)),
+    (Atlas::PipeTop, Vec2::new(48., 48.)),
+    (Atlas::Bird1, Vec2::new(16., 30.)),
+];
+
+/// What a mode or a modifier is about, shown on a card the first time a run
+/// is played with it
+pub struct Tutorial {
+    /// Id of the mode or the modifier it's about
+    pub id: &'static str,
+    pub title: &'static str,
+    pub text: &'static str,
+    pub art: Atlas,
+    /// A still of it being played, each sprite placed from the bottom left
+    /// of the still
+    pub example: &'static [(Atlas, Vec2)],
+}
+
+/// Every tutorial there is, found by the id of what it's about
+#[derive(Resource, Default)]
+pub struct TutorialRegistry {
+    tutorials: Vec<Tutorial>,
+}
+
+impl TutorialRegistry {
+    pub fn get(&self, id: &str) -> Option<&Tutorial> {
+        self.tutorials.iter().find(|tutorial| tutorial.id == id)
+    }
+}
+
+/// Lets the plugin behind a mode or a modifier explain it
+pub trait AddTutorial {
+    fn add_tutorial(&mut self, tutorial: Tutorial) -> &mut Self;
+}
+
+impl AddTutorial for App {
+    fn add_tutorial(&mut self, tutorial: Tutorial) -> &mut Self {
+        self.init_resource::<TutorialRegistry>()
+            .world
+            .resource_mut::<TutorialRegistry>()
+            .tutorials
+            .push(tutorial);
+        self
+    }
+}
+
+/// Ids of the cards still to be shown before the run can start, the one up
+/// now first
+#[derive(Resource)]
+struct Cards(Vec<&'static str>);
+
+#[derive(Component)]
+struct TutorialCard;
+
+pub struct TutorialsPlugin;
+
+impl Plugin for TutorialsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TutorialRegistry>()
+            .add_systems(
+                OnEnter(AppState::GetReady),
+                queue_cards.run_if(not(resource_exists::<Playback>)),
+            )
+            .add_systems(
+                Update,
+                (
+                    next_card,
+                    draw_card.run_if(resource_exists_and_changed::<Cards>),
+                )
+                    .chain()
+                    // Takes the flap before it can start the run
+                    .before(take_off)
+                    .run_if(in_state(AppState::GetReady).and_then(resource_exists::<Cards>)),
+            )
+            .add_systems(OnExit(AppState::GetReady), close_cards);
+    }
+}
+
+// Anything the player hasn't had explained yet, the mode before its modifiers
+fn queue_cards(
+    mut commands: Commands,
+    registry: Res<TutorialRegistry>,
+    modifiers: Res<RunModifiers>,
+    profile: Res<Profile>,
+) {
+    let unseen = modifiers
+        .tutorial_ids()
+        .into_iter()
+        .filter_map(|id| registry.get(id))
+        .filter(|tutorial| !profile.tutorials.contains(tutorial.id))
+        .map(|tutorial| tutorial.id)
+        .collect::<Vec<_>>();
+
+    if !unseen.is_empty() {
+        commands.insert_resource(Cards(unseen));
+    }
+}
+
+// Only counts as seen once it's been flapped past, backing out to the menu
+// leaves it for next time
+fn next_card(
+    mut commands: Commands,
+    mut cards: ResMut<Cards>,
+    mut profile: ResMut<Profile>,
+    mut actions: ResMut<Actions>,
+    keys: Res<ButtonInput<KeyCode>>,
+    query: Query<Entity, With<TutorialCard>>,
+) {
+    if !(actions.take(Action::Flap) || keys.just_pressed(KeyCode::Enter)) {
+        return;
+    }
+
+    let seen = cards.0.remove(0);
+    profile.tutorials.insert(seen.to_string());
+    if cards.0.is_empty() {
+        commands.remove_resource::<Cards>();
+        for entity in &query {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+fn draw_card(
+    mut commands: Commands,
+    cards: Res<Cards>,
+    registry: Res<TutorialRegistry>,
+    sheet: Res<SpriteSheet>,
+    query: Query<Entity, With<TutorialCard>>,
+) {
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+    let Some(tutorial) = cards.0.first().and_then(|id| registry.get(id)) else {
+        return;
+    };
+
+    let text = |value: &str, size: f32, color: Color| {
+        TextBundle::from_section(
+            value,
+            TextStyle {
+                font_size: size,
+                color,
+                ..default()
+            },
+        )
+    };
+    let sprite = |atlas: Atlas, style: Style| AtlasImageBundle {
+        style,
+        image: UiImage::new(sheet.texture.clone()),
+        texture_atlas: TextureAtlas {
+            layout: sheet.layout.clone(),
+            index: atlas as usize,
+        },
+        ..default()
+    };
+
+    commands
+        .spawn((
+            TutorialCard,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.),
+                    height: Val::Percent(100.),
+                    position_type: PositionType::Absolute,
+                    justify_content: JustifyContent::Center,
+                    align_items: AlignItems::Center,
+                    ..default()
+                },
+                background_color: Color::rgba(0., 0., 0., 0.6).into(),
+                ..default()
+            },
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Px(200.),
+                        flex_direction: FlexDirection::Column,
+                        align_items: AlignItems::Center,
+                        padding: UiRect::all(Val::Px(8.)),
+                        row_gap: Val::Px(6.),
+                        ..default()
+                    },
+                    background_color: Color::rgba(0.1, 0.1, 0.1, 0.9).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(sprite(
+                        tutorial.art,
+                        Style {
+                            height: Val::Px(24.),
+                            ..default()
+                        },
+                    ));
+                    parent.spawn(text(tutorial.title, 16., Color::WHITE));
+                    parent.spawn(text(tutorial.text, 12., Color::WHITE));
+
+                    parent
+                        .spawn(NodeBundle {
+                            style: Style {
+                                width: Val::Px(EXAMPLE_SIZE.x),
+                                height: Val::Px(EXAMPLE_SIZE.y),
+                                overflow: Overflow::clip(),
+                                ..default()
+                            },
+                            background_color: EXAMPLE_BACKGROUND.into(),
+                            ..default()
+                        })
+                        .with_children(|parent| {
+                            for &(atlas, position) in tutorial.example {
+                                parent.spawn(sprite(
+                                    atlas,
+                                    Style {
+                                        position_type: PositionType::Absolute,
+                                        left: Val::Px(position.x),
+                                        bottom: Val::Px(position.y),
+                                        ..default()
+                                    },
+                                ));
+                            }
+                        });
+
+                    let hint = match cards.0.len() {
+                        1 => "Flap to start".to_string(),
+                        left => format!("Flap for the next one, {} to go", left - 1),
+                    };
+                    parent.spawn(text(&hint, 10., Color::GRAY));
+                });
+        });
+}
+
+fn close_cards(mut commands: Commands, query: Query<Entity, With<TutorialCard>>) {
+    commands.remove_resource::<Cards>();
+    for entity in &query {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,125 @@
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    accessories::{part, Part},
+    create_world,
+    decals::{paint_pipes, Repaint},
+    BuildWorld, GameRng, Obstacle, Pipe,
+};
+
+// Mixed into the run's seed so the pipes look the same every time the run's
+// played, without taking anything from the run's own random numbers
+const WEAR_SEED: u64 = 0x3EA5;
+// Most a pipe's color drifts from the sprite's, darker or more washed out
+const TINT_JITTER: f32 = 0.08;
+// How likely a pipe is to be drawn the other way around, and to have moss
+// growing by its lip
+const FLIP_CHANCE: f64 = 0.5;
+const MOSS_CHANCE: f64 = 0.2;
+// Where the moss goes, measured from the middle of the pipe towards the gap,
+// just behind the lip
+const MOSS_Y: f32 = 67.;
+
+const MOSS: Color = Color::rgb(0.35, 0.55, 0.2);
+const DARK_MOSS: Color = Color::rgb(0.25, 0.4, 0.15);
+
+const MOSS_PARTS: &[Part] = &[
+    part(0., 0., 10., 2., MOSS),
+    part(-3., -1.5, 3., 2., MOSS),
+    part(2., -2., 2., 3., DARK_MOSS),
+    part(4., 0.5, 3., 1., DARK_MOSS),
+];
+
+/// Rolls how worn the pipes of a column look each time it's laid out
+#[derive(Resource)]
+struct Weathering(ChaCha8Rng);
+
+impl Default for Weathering {
+    fn default() -> Self {
+        Self(ChaCha8Rng::seed_from_u64(WEAR_SEED))
+    }
+}
+
+#[derive(Component)]
+struct Moss;
+
+pub struct WearPlugin;
+
+impl Plugin for WearPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Weathering>()
+            .add_systems(BuildWorld, reset_weathering.after(create_world))
+            // Before the decals are painted, which is what lets go of the
+            // columns that were just laid out
+            .add_systems(Update, wear_pipes.before(paint_pipes));
+    }
+}
+
+fn reset_weathering(mut weathering: ResMut<Weathering>, rng: Res<GameRng>) {
+    weathering.0 = ChaCha8Rng::seed_from_u64(rng.seed ^ WEAR_SEED);
+}
+
+// Columns are worn from the closest out, so the same run wears the same
+// columns the same way however many came around in one frame
+fn wear_pipes(
+    mut commands: Commands,
+    mut weathering: ResMut<Weathering>,
+    obstacles: Query<(&Transform, &Children), (With<Obstacle>, With<Repaint>)>,
+    mut pipes: Query<(Entity, &Pipe, &mut Sprite, Option<&Children>)>,
+    moss: Query<(), With<Moss>>,
+) {
+    let mut columns = obstacles.iter().collect::<Vec<_>>();
+    columns.sort_by(|(a, _), (b, _)| a.translation.x.total_cmp(&b.translation.x));
+
+    let rng = &mut weathering.0;
+    for (_, children) in columns {
+        let mut pipes = pipes.iter_many_mut(children);
+        while let Some((entity, side, mut sprite, grown)) = pipes.fetch_next() {
+            for &child in grown.iter().copied().flatten() {
+                if moss.contains(child) {
+                    commands.entity(child).despawn_recursive();
+                }
+            }
+
+            let shade = 1. - rng.gen_range(0. ..=TINT_JITTER);
+            let fade = rng.gen_range(0. ..=TINT_JITTER);
+            sprite.color = Color::rgb(shade, shade - fade / 2., shade - fade);
+            sprite.flip_x = rng.gen_bool(FLIP_CHANCE);
+
+            if !rng.gen_bool(MOSS_CHANCE) {
+                continue;
+            }
+            let toward_gap = match side {
+                Pipe::Top => -1.,
+                Pipe::Bottom => 1.,
+            };
+            let x = rng.gen_range(-4. ..=4.);
+            commands.entity(entity).with_children(|parent| {
+                parent
+                    .spawn((
+                        Moss,
+                        SpatialBundle::from_transform(Transform::from_xyz(
+                            x,
+                            MOSS_Y * toward_gap,
+                            0.05,
+                        )),
+                    ))
+                    .with_children(|parent| {
+                        for part in MOSS_PARTS {
+                            parent.spawn(SpriteBundle {
+                                sprite: Sprite {
+                                    color: part.color,
+                                    custom_size: Some(part.size),
+                                    ..default()
+                                },
+                                transform: Transform::from_translation(part.offset.extend(0.)),
+                                ..default()
+                            });
+                        }
+                    });
+            });
+        }
+    }
+}
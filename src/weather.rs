@@ -0,0 +1,189 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{difficulty::Difficulty, low_power::LowPower, scroll::ScrollEase, Root, GROUND_TOP};
+
+// Where drops start out, just over the top of the screen. They're spread out
+// past the right edge too since the world and the wind carry them left as
+// they fall
+const WEATHER_TOP: f32 = 132.;
+const WEATHER_LEFT: f32 = -72.;
+const WEATHER_RIGHT: f32 = 144.;
+// Over the bird and the pipes, under the score popups
+const WEATHER_Z: f32 = 5.;
+// How far snow sways from side to side, and how many times a second
+const SNOW_SWAY: f32 = 6.;
+const SNOW_SWAY_RATE: f32 = 0.5;
+
+/// What weather the player wants, saved with the rest of the video settings
+#[derive(Resource, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum WeatherSetting {
+    #[default]
+    Off,
+    Rain,
+    Snow,
+    /// Rolled again for every world
+    Random,
+}
+
+impl WeatherSetting {
+    const ALL: [WeatherSetting; 4] = [
+        WeatherSetting::Off,
+        WeatherSetting::Rain,
+        WeatherSetting::Snow,
+        WeatherSetting::Random,
+    ];
+
+    /// The one `step` places along, going back around at either end
+    pub fn cycle(self, step: i32) -> Self {
+        let i = Self::ALL
+            .iter()
+            .position(|setting| *setting == self)
+            .unwrap_or(0) as i32;
+        Self::ALL[(i + step).rem_euclid(Self::ALL.len() as i32) as usize]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            WeatherSetting::Off => "off",
+            WeatherSetting::Rain => "rain",
+            WeatherSetting::Snow => "snow",
+            WeatherSetting::Random => "random",
+        }
+    }
+}
+
+/// The weather in the world the run is played in
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum Weather {
+    #[default]
+    Clear,
+    Rain,
+    Snow,
+}
+
+impl Weather {
+    pub fn roll(setting: WeatherSetting, rng: &mut impl Rng) -> Self {
+        match setting {
+            WeatherSetting::Off => Weather::Clear,
+            WeatherSetting::Rain => Weather::Rain,
+            WeatherSetting::Snow => Weather::Snow,
+            WeatherSetting::Random => match rng.gen_range(0..3) {
+                0 => Weather::Clear,
+                1 => Weather::Rain,
+                _ => Weather::Snow,
+            },
+        }
+    }
+
+    /// How hard the wind blows to the left, in pixels a second. Only the
+    /// drops go with it for now, nothing about the bird does
+    pub fn wind(self) -> f32 {
+        match self {
+            Weather::Clear => 0.,
+            Weather::Rain => 24.,
+            Weather::Snow => 10.,
+        }
+    }
+
+    /// How many drops come down every second, how fast they fall, and what
+    /// they look like
+    fn drops(self) -> Option<(f32, f32, Vec2, Color)> {
+        match self {
+            Weather::Clear => None,
+            Weather::Rain => Some((60., 220., Vec2::new(1., 5.), Color::rgba(0.7, 0.8, 1., 0.6))),
+            Weather::Snow => Some((20., 24., Vec2::new(2., 2.), Color::rgba(1., 1., 1., 0.9))),
+        }
+    }
+}
+
+/// A raindrop or snowflake, falling until it reaches the ground
+#[derive(Component)]
+struct Precipitation {
+    fall: f32,
+    /// Where along its sway it started, so the flakes don't all move together
+    phase: f32,
+}
+
+pub struct WeatherPlugin;
+
+impl Plugin for WeatherPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (spawn_precipitation, fall)
+                .chain()
+                .run_if(resource_exists::<Weather>),
+        );
+    }
+}
+
+fn spawn_precipitation(
+    mut commands: Commands,
+    mut owed: Local<f32>,
+    weather: Res<Weather>,
+    power: Res<LowPower>,
+    root: Query<Entity, With<Root>>,
+    time: Res<Time>,
+) {
+    let (Some((rate, fall, size, color)), Ok(root)) = (weather.drops(), root.get_single()) else {
+        *owed = 0.;
+        return;
+    };
+
+    // Carried over between frames so the rate holds up at any frame rate
+    *owed += time.delta_seconds() * power.particles(rate as usize) as f32;
+    let count = owed.floor();
+    *owed -= count;
+
+    commands.entity(root).with_children(|parent| {
+        for _ in 0..count as usize {
+            let x = WEATHER_LEFT + rand::random::<f32>() * (WEATHER_RIGHT - WEATHER_LEFT);
+            parent.spawn((
+                Precipitation {
+                    fall,
+                    phase: rand::random::<f32>() * TAU,
+                },
+                SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    transform: Transform::from_xyz(x, WEATHER_TOP, WEATHER_Z),
+                    ..default()
+                },
+            ));
+        }
+    });
+}
+
+// Scrolls along with the world on top of the wind, so it looks like it's
+// coming down over the ground going by rather than following the bird
+fn fall(
+    mut commands: Commands,
+    mut query: Query<(Entity, &Precipitation, &mut Transform)>,
+    weather: Res<Weather>,
+    difficulty: Res<Difficulty>,
+    ease: Res<ScrollEase>,
+    time: Res<Time>,
+) {
+    let delta = time.delta_seconds();
+    let sway = match *weather {
+        Weather::Snow => SNOW_SWAY,
+        _ => 0.,
+    };
+    for (entity, drop, mut transform) in &mut query {
+        let t = time.elapsed_seconds() * SNOW_SWAY_RATE * TAU + drop.phase;
+        transform.translation.x += delta
+            * (ease.speed(&difficulty) - weather.wind() + sway * SNOW_SWAY_RATE * TAU * t.cos());
+        transform.translation.y -= delta * drop.fall;
+
+        if transform.translation.y < GROUND_TOP || transform.translation.x < WEATHER_LEFT - 8. {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
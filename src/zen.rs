@@ -0,0 +1,88 @@
+use bevy::prelude::*;
+
+use crate::{
+    crash_and_die,
+    modes::{mode_is, AddGameMode, GameMode, ModeInfo},
+    physics::ActivePhysics,
+    tutorials::{AddTutorial, Tutorial, BETWEEN_PIPES},
+    AppState, Atlas, Player, Score, SimSet, Velocity, SIM_HZ,
+};
+
+const ZEN: &str = "zen";
+// A zen run is over once this many pipes have gone by
+const ZEN_PIPES: u32 = 30;
+// How far up or down the bird can go before it's turned back
+const EDGE: f32 = 120.;
+
+struct Zen;
+
+impl GameMode for Zen {
+    fn info(&self) -> ModeInfo {
+        ModeInfo {
+            id: ZEN,
+            name: "Zen",
+            blurb: "No crashes, just flying",
+            preview: Atlas::Bird2,
+            color: Color::rgb(0.25, 0.4, 0.6),
+            seed: None,
+        }
+    }
+
+    fn setup(&self, app: &mut App) {
+        app.add_systems(
+            FixedUpdate,
+            (
+                stay_on_screen
+                    .in_set(SimSet::Collision)
+                    .before(crash_and_die),
+                finish_run.in_set(SimSet::Rules),
+            )
+                .run_if(in_state(AppState::Playing).and_then(mode_is(ZEN))),
+        );
+    }
+
+    fn crashes_end_run(&self) -> bool {
+        false
+    }
+
+    fn results(&self, score: u32, ticks: u64) -> String {
+        let seconds = ticks / SIM_HZ as u64;
+        format!("{score} pipes in {}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+pub struct ZenPlugin;
+
+impl Plugin for ZenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_game_mode(Zen).add_tutorial(Tutorial {
+            id: ZEN,
+            title: "Zen",
+            text: "Nothing ends the run. Pipes let you through and the ground bounces you back up",
+            art: Atlas::Bird2,
+            example: BETWEEN_PIPES,
+        });
+    }
+}
+
+// The ground bounces the bird back up and the sky holds it down, instead of
+// either of them ending the run
+fn stay_on_screen(
+    mut query: Query<(&mut Transform, &mut Velocity), With<Player>>,
+    physics: Res<ActivePhysics>,
+) {
+    let (mut transform, mut velocity) = query.single_mut();
+    if transform.translation.y < -EDGE {
+        transform.translation.y = -EDGE;
+        velocity.0 = physics.0.jump;
+    } else if transform.translation.y > EDGE {
+        transform.translation.y = EDGE;
+        velocity.0 = velocity.0.min(0.);
+    }
+}
+
+fn finish_run(score: Res<Score>, mut state: ResMut<NextState<AppState>>) {
+    if score.0 >= ZEN_PIPES {
+        state.set(AppState::GameOver);
+    }
+}